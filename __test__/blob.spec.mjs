@@ -0,0 +1,45 @@
+import { writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { Repository } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  const dir = initRepo("blob");
+  t.context = { dir, repo: new Repository(dir) };
+});
+
+test("blob should write a buffer to the odb", (t) => {
+  const { dir, repo } = t.context;
+  const oid = repo.blob(Buffer.from("hello\n"));
+  t.is(oid, git(dir, "hash-object --stdin", "hello\n"));
+  t.is(git(dir, `cat-file -p ${oid}`), "hello");
+});
+
+test("blobPath should apply the filters like git add", (t) => {
+  const { dir, repo } = t.context;
+  writeFileSync(join(dir, ".gitattributes"), "*.txt text eol=crlf\n");
+  writeFileSync(join(dir, "crlf.txt"), "a\r\nb\r\n");
+  const oid = repo.blobPath("crlf.txt");
+  git(dir, "add crlf.txt");
+  t.is(oid, git(dir, "rev-parse :crlf.txt"));
+  t.is(repo.findBlob(oid).size(), 4n);
+});
+
+test("blobWriter should write a blob in chunks", (t) => {
+  const { dir, repo } = t.context;
+  writeFileSync(join(dir, ".gitattributes"), "*.txt text eol=crlf\n");
+  const writer = repo.blobWriter("chunks.txt");
+  for (let i = 0; i < 100; i++) {
+    writer.write(Buffer.from(`line ${i}\r\n`));
+  }
+  const oid = writer.commit();
+  const expected = Array.from({ length: 100 }, (_, i) => `line ${i}\n`);
+  t.is(oid, git(dir, "hash-object --stdin", expected.join("")));
+  t.throws(() => writer.write(Buffer.from("more")), {
+    message: "The blob writer is already committed",
+  });
+});
@@ -0,0 +1,53 @@
+
+import test from "ava";
+
+import { BranchType, Repository } from "../index.js";
+
+import { git, initRepo, tempDir } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  const upstream = initRepo("branch");
+  git(upstream, "commit -q --allow-empty -m first");
+  const dir = tempDir("branch");
+  git(dir, `clone -q ${upstream} .`);
+  git(dir, "config user.email test@example.com");
+  git(dir, "config user.name test");
+  git(upstream, "commit -q --allow-empty -m upstream");
+  git(dir, "fetch -q");
+  git(dir, "commit -q --allow-empty -m local");
+  git(dir, "commit -q --allow-empty -m local");
+  t.context = { dir, repo: new Repository(dir) };
+});
+
+test("upstream and aheadBehind should follow the tracking branch", (t) => {
+  const { repo } = t.context;
+  const main = repo.findBranch("main", BranchType.Local);
+  t.true(main.isHead());
+  t.is(main.upstream().name(), "origin/main");
+  t.deepEqual(main.aheadBehind(), { ahead: 2, behind: 1 });
+  t.is(repo.branchUpstreamName("HEAD"), "refs/remotes/origin/main");
+  t.is(repo.branchRemoteName("refs/remotes/origin/main"), "origin");
+  t.is(repo.branchRemoteName("refs/heads/main"), null);
+  main.setUpstream(null);
+  t.is(main.upstream(), null);
+  t.is(main.aheadBehind(), null);
+  t.is(repo.branchUpstreamName("refs/heads/main"), null);
+  main.setUpstream("origin/main");
+  t.is(repo.branchUpstreamName("refs/heads/main"), "refs/remotes/origin/main");
+  t.is(repo.findBranch("missing", BranchType.Local), null);
+});
+
+test("upstream and aheadBehind should throw on an invalid configuration", (t) => {
+  const { dir, repo } = t.context;
+  git(dir, "config remote.origin.fetch +refs/heads/*");
+  const main = repo.findBranch("main", BranchType.Local);
+  const expectation = { message: /Find the upstream branch failed/ };
+  t.throws(() => main.upstream(), expectation);
+  t.throws(() => main.aheadBehind(), expectation);
+});
+
+test("branchUpstreamName should be null on a detached HEAD", (t) => {
+  const { dir, repo } = t.context;
+  git(dir, "checkout -q --detach");
+  t.is(repo.branchUpstreamName("HEAD"), null);
+});
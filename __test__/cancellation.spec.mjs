@@ -0,0 +1,57 @@
+import test from "ava";
+
+import { Repository } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+const COMMITS = 50000;
+
+// Build a long history where `root.txt` is only touched by the root commit,
+// so looking up its date walks every commit
+const syntheticHistory = () => {
+  const dir = initRepo("cancel");
+  const stream = [];
+  for (let i = 0; i < COMMITS; i++) {
+    const file = i === 0 ? "root.txt" : "churn.txt";
+    const content = `${i}\n`;
+    stream.push(
+      "commit refs/heads/main",
+      `mark :${i + 1}`,
+      `committer test <test@example.com> ${1600000000 + i} +0000`,
+      "data 1",
+      "c",
+      ...(i === 0 ? [] : [`from :${i}`]),
+      `M 100644 inline ${file}`,
+      `data ${content.length}`,
+      content,
+    );
+  }
+  git(dir, "fast-import --quiet", stream.join("\n"));
+  return dir;
+};
+
+test.before((t) => {
+  t.context.dir = syntheticHistory();
+});
+
+test("aborting should reject a running task promptly with code Aborted", async (t) => {
+  const repo = new Repository(t.context.dir);
+  const controller = new AbortController();
+  const pending = repo.getFileLatestModifiedDateAsync(
+    "root.txt",
+    controller.signal,
+  );
+  await new Promise((resolve) => setTimeout(resolve, 20));
+  const abortedAt = Date.now();
+  controller.abort();
+  await t.throwsAsync(pending, { code: "Aborted" });
+  t.true(Date.now() - abortedAt < 1000);
+});
+
+test("an already aborted signal should reject with code Aborted", async (t) => {
+  const repo = new Repository(t.context.dir);
+  await t.throwsAsync(
+    repo.getFileLatestModifiedDateAsync("root.txt", AbortSignal.abort()),
+    { code: "Aborted" },
+  );
+});
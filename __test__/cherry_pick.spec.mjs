@@ -0,0 +1,90 @@
+import { join } from "node:path";
+
+import test from "ava";
+
+import { Repository, Signature } from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+// A bare repository, so that no working directory could be touched, whose
+// release branch changed `releaseFiles`
+const setup = (releaseFiles) => {
+  const { dir } = buildRepo("cherry-pick", (builder) =>
+    builder
+      .commit({ message: "init", files: { "a.txt": "a\n", "b.txt": "b\n" } })
+      .branch("release")
+      .commit({ message: "release", files: releaseFiles })
+      .checkout("main")
+      .commit({ message: "fix 1", files: { "c.txt": "c\n" } })
+      .commit({ message: "fix 2", files: { "a.txt": "a2\n" } })
+      .commit({ message: "fix 3", files: { "d.txt": "d\n" } }),
+  );
+  const bare = join(dir, "bare.git");
+  git(dir, `clone -q --bare . ${bare}`);
+  return { dir: bare, repo: new Repository(bare) };
+};
+
+const committer = Signature.now("bot", "bot@example.com");
+
+test("cherryPickRange should pick a clean range onto a branch", (t) => {
+  const { dir, repo } = setup({ "r.txt": "r\n" });
+  const sources = git(dir, "rev-list --reverse main~3..main").split("\n");
+  const result = repo.cherryPickRange("main~3..main", "release", {
+    committer,
+    recordOrigin: true,
+  });
+  t.is(result.stoppedAt, undefined);
+  t.deepEqual(result.applied.map(({ sourceOid }) => sourceOid), sources);
+  t.is(git(dir, "rev-parse release"), result.applied[2].newOid);
+  t.is(git(dir, "show release:a.txt"), "a2");
+  t.is(git(dir, "show release:r.txt"), "r");
+  t.is(git(dir, "log -1 --format=%an/%cn release"), "Test/bot");
+  t.is(
+    git(dir, "log -1 --format=%B release"),
+    `fix 3\n\n(cherry picked from commit ${sources[2]})`,
+  );
+});
+
+test("cherryPickRange should stop at a conflicting commit", (t) => {
+  const { dir, repo } = setup({ "a.txt": "release\n" });
+  const release = git(dir, "rev-parse release");
+  const range = "main~3..main";
+  const conflicting = git(dir, "rev-parse main~1");
+
+  const atomic = repo.cherryPickRange(range, "release", { committer });
+  t.is(atomic.applied.length, 1);
+  t.is(atomic.stoppedAt.sourceOid, conflicting);
+  t.deepEqual(atomic.stoppedAt.conflicts, ["a.txt"]);
+  t.is(git(dir, "rev-parse release"), release);
+
+  const incremental = repo.cherryPickRange(range, "release", {
+    committer,
+    stopOnConflict: true,
+  });
+  t.is(incremental.stoppedAt.sourceOid, conflicting);
+  t.is(git(dir, "rev-parse release"), incremental.applied[0].newOid);
+});
+
+test("cherryPickRange should leave the branch untouched at a conflict in the middle of the range", (t) => {
+  const { dir, repo } = setup({ "a.txt": "release\n" });
+  const release = git(dir, "rev-parse release");
+  const refs = git(dir, "for-each-ref");
+  const result = repo.cherryPickRange("main~3..main", "release", {
+    committer,
+    stopOnConflict: false,
+  });
+  t.deepEqual(
+    result.applied.map(({ sourceOid }) => sourceOid),
+    [git(dir, "rev-parse main~2")],
+  );
+  t.is(result.stoppedAt.sourceOid, git(dir, "rev-parse main~1"));
+  t.deepEqual(result.stoppedAt.conflicts, ["a.txt"]);
+  // The commit after the conflict is not picked, and the picked commit is
+  // only written to the object database
+  t.is(git(dir, "rev-parse release"), release);
+  t.is(git(dir, "for-each-ref"), refs);
+  const picked = result.applied[0].newOid;
+  t.is(git(dir, `cat-file -t ${picked}`), "commit");
+  t.is(git(dir, `rev-parse ${picked}~1`), release);
+  t.is(git(dir, `for-each-ref --contains ${picked}`), "");
+});
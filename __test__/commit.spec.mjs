@@ -0,0 +1,283 @@
+import test from "ava";
+
+import {
+  ObjectType,
+  Repository,
+  Signature,
+  TestRepoBuilder,
+} from "../index.js";
+
+import { buildRepo, git, initRepo, tempDir } from "./helpers.mjs";
+
+test("commitWithParents should create a merge commit", (t) => {
+  const { dir, repo } = buildRepo("commit", (builder) =>
+    builder
+      .commit({ message: "init", files: { "a.txt": "a\n" } })
+      .branch("feature")
+      .commit({ message: "feature", files: { "b.txt": "b\n" } })
+      .checkout("main"),
+  );
+  const main = git(dir, "rev-parse main");
+  const feature = git(dir, "rev-parse feature");
+  const signature = Signature.now("test", "test@example.com");
+  const oid = repo.commitWithParents(
+    "HEAD",
+    signature,
+    signature,
+    "merge feature",
+    repo.findCommit(feature).tree(),
+    [main, feature],
+  );
+  t.is(git(dir, "rev-parse main"), oid);
+  t.is(git(dir, "rev-parse main^1"), main);
+  t.is(git(dir, "rev-parse main^2"), feature);
+});
+
+test("parents should iterate the parents of a merge commit", (t) => {
+  const { dir, repo } = buildRepo("commit", (builder) =>
+    builder
+      .commit({ message: "init", files: { "a.txt": "a\n" } })
+      .branch("feature")
+      .commit({ message: "feature", files: { "b.txt": "b\n" } })
+      .checkout("main"),
+  );
+  const main = git(dir, "rev-parse main");
+  const feature = git(dir, "rev-parse feature");
+  const signature = Signature.now("test", "test@example.com");
+  const oid = repo.commitWithParents(
+    "HEAD",
+    signature,
+    signature,
+    "merge feature",
+    repo.findCommit(feature).tree(),
+    [main, feature],
+  );
+  const merge = repo.findCommit(oid);
+  t.deepEqual(
+    [...merge.parents()].map((parent) => parent.id()),
+    [main, feature],
+  );
+  t.deepEqual([...merge.parentIdsIter()], [main, feature]);
+  t.deepEqual([...repo.findCommit(main).parentIdsIter()], []);
+});
+
+test("commitSigned should round trip through extractSignature", (t) => {
+  const { dir, repo } = buildRepo("commit", (builder) =>
+    builder.commit({ message: "init", files: { "a.txt": "a\n" } }),
+  );
+  const content = `${git(dir, "cat-file commit HEAD")}\n`;
+  const signature =
+    "-----BEGIN SSH SIGNATURE-----\nU1NIU0lH\n-----END SSH SIGNATURE-----";
+  const oid = repo.commitSigned(content, signature);
+  t.true(git(dir, `cat-file commit ${oid}`).includes("gpgsig -----BEGIN"));
+  const extracted = repo.findCommit(oid).extractSignature();
+  t.is(extracted.signature.toString(), signature);
+  t.is(extracted.signedData.toString(), content);
+  t.throws(() => repo.findCommit(git(dir, "rev-parse HEAD")).extractSignature());
+});
+
+test("conventionalCommits should parse the commit messages of a range", (t) => {
+  const { dir, repo } = buildRepo("commit", (builder) =>
+    builder
+      .commit({ message: "chore: init", files: { "a.txt": "a\n" } })
+      .tag("v1")
+      .commit({ message: "feat: add b", files: { "b.txt": "b\n" } })
+      .commit({
+        message: "fix(parser): handle empty input\n\nDetails.\n\nRefs: #12",
+        files: { "c.txt": "c\n" },
+      })
+      .commit({ message: "feat(api)!: drop v1", files: { "d.txt": "d\n" } })
+      .commit({ message: "Update README", files: { "e.txt": "e\n" } }),
+  );
+  const commits = repo.conventionalCommits("v1..main", { includeBody: true });
+  t.deepEqual(
+    commits.map(({ oid, ...commit }) => commit),
+    [
+      { breaking: false, description: "Update README", footers: [] },
+      {
+        type: "feat",
+        scope: "api",
+        breaking: true,
+        description: "drop v1",
+        footers: [],
+      },
+      {
+        type: "fix",
+        scope: "parser",
+        breaking: false,
+        description: "handle empty input",
+        body: "Details.",
+        footers: [{ key: "Refs", value: "#12" }],
+      },
+      { type: "feat", breaking: false, description: "add b", footers: [] },
+    ],
+  );
+  t.is(commits[0].oid, git(dir, "rev-parse main"));
+});
+
+test("validateCommitMessage should list the broken rules", (t) => {
+  const { repo } = buildRepo("commit");
+  const rules = {
+    maxSubjectLength: 20,
+    requireConventional: true,
+    subjectRegex: "^[a-z]",
+    bodyLineLength: 10,
+  };
+  t.deepEqual(repo.validateCommitMessage("fix: x\n\nshort\n", { rules }), {
+    valid: true,
+    problems: [],
+  });
+  const message = "Update parser for empty input\n\nshort\nway too long\n";
+  const { valid, problems } = repo.validateCommitMessage(message, { rules });
+  t.false(valid);
+  t.deepEqual(
+    problems.map(({ rule, line }) => [rule, line]),
+    [
+      ["maxSubjectLength", 1],
+      ["requireConventional", 1],
+      ["subjectRegex", 1],
+      ["bodyLineLength", 4],
+    ],
+  );
+  t.is(problems[0].message, "The subject has 29 characters, more than 20");
+  t.true(repo.validateCommitMessage(message).valid);
+  const invalid = { rules: { subjectRegex: "(" } };
+  t.throws(() => repo.validateCommitMessage("fix: x", invalid), {
+    code: "InvalidArg",
+  });
+});
+
+test("Signature.withOffset should keep the time zone of the commit", (t) => {
+  const { dir, repo } = buildRepo("commit", (builder) =>
+    builder.commit({ message: "init", files: { "a.txt": "a\n" } }),
+  );
+  const signature = Signature.withOffset(
+    "test",
+    "test@example.com",
+    1700000000,
+    120,
+  );
+  t.is(signature.offset(), 120);
+  t.deepEqual(signature.whenWithOffset(), {
+    seconds: 1700000000,
+    offsetMinutes: 120,
+  });
+  const oid = repo.commitWithParents(
+    "HEAD",
+    signature,
+    signature,
+    "second",
+    repo.head().peelToTree(),
+    [git(dir, "rev-parse HEAD")],
+  );
+  t.is(git(dir, `log -1 --format=%ad --date=iso ${oid}`), "2023-11-15 00:13:20 +0200");
+  t.is(repo.findCommit(oid).author().offset(), 120);
+});
+
+test("signature should read the configured user", (t) => {
+  const { dir, repo } = buildRepo("commit");
+  git(dir, "config user.name configured");
+  git(dir, "config user.email configured@example.com");
+  const signature = repo.signature();
+  t.is(signature.name(), "configured");
+  t.is(signature.email(), "configured@example.com");
+  const fromConfig = Signature.fromConfig(repo.config());
+  t.is(fromConfig.name(), "configured");
+  t.is(repo.config().getString("user.missing"), null);
+});
+
+test("raw objects should round trip without normalization", (t) => {
+  const { dir, repo } = buildRepo("commit", (builder) =>
+    builder.commit({ message: "init", files: { "a.txt": "a\n" } }),
+  );
+  const commit = repo.findCommit(git(dir, "rev-parse HEAD"));
+  const raw = repo.readRawObject(commit.id());
+  t.is(raw.type, ObjectType.Commit);
+  t.deepEqual(raw.data, commit.rawBytes());
+  const data = raw.data
+    .toString("utf8")
+    .replace(/^committer .*$/m, "committer Migrated <m@example.com> 0 +0000");
+  const oid = repo.writeRawObject(ObjectType.Commit, Buffer.from(data));
+  t.is(git(dir, `cat-file -p ${oid}`), data.trim());
+  const migrated = repo.findCommit(oid).committer();
+  t.is(migrated.name(), "Migrated");
+  t.is(migrated.when(), 0);
+  t.throws(() => repo.writeRawObject(ObjectType.Any, Buffer.from(data)));
+});
+
+test("headCommit and headTree should resolve HEAD", (t) => {
+  const dir = tempDir("commit");
+  git(dir, "init -q");
+  const empty = new Repository(dir);
+  t.throws(() => empty.headCommit(), { code: "UnbornHead" });
+  t.throws(() => empty.headTree(), { code: "UnbornHead" });
+
+  const repo = new TestRepoBuilder(dir)
+    .commit({ message: "init", files: { "a.txt": "a\n" } })
+    .build();
+  t.is(repo.headCommit().id(), git(dir, "rev-parse HEAD"));
+  t.is(repo.headTree().id(), git(dir, "rev-parse HEAD^{tree}"));
+});
+
+const PGP_SIGNATURE = `-----BEGIN PGP SIGNATURE-----
+
+iHUEABYIAB0WIQT81Fp3dOoSMi7dANVWe0J5ePUz7AUCatJfEAAKCRBWe0J5ePUz
+7EnaAQC+fsLNrADxcI8cSObReF+NNxO92ZYtIXnIkCA18Aen6QD/drBQKfwyVPxH
+zigYWswulgJkjRjFnZi0LeCJRFR/Uw4=
+=f3d6
+-----END PGP SIGNATURE-----`;
+
+const SSH_SIGNATURE = `-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAADMAAAALc3NoLWVkMjU1MTkAAAAgwDHlvcY/rteFDy52SsQuApx9Fz
+GP/eYtD2m6cYlIKmsAAAADZ2l0AAAAAAAAAAZzaGE1MTIAAABTAAAAC3NzaC1lZDI1NTE5
+AAAAQIcpcqEZpq5Vx+Y2FXyg2Lh2LgoBrcmUluXpn9Ov8xi5/nc/HDb6o+o+kXs5eF6ldK
+1YaHMeZ2LOF7MYHpz+nwA=
+-----END SSH SIGNATURE-----`;
+
+const SMIME_SIGNATURE = `-----BEGIN SIGNED MESSAGE-----
+MIAGCSqGSIb3DQEHAqCAMIACAQExDzANBglghkgBZQMEAgEFADCABgkqhkiG9w0B
+-----END SIGNED MESSAGE-----`;
+
+test("signatureSummaries should describe the commit signatures", (t) => {
+  const dir = initRepo("commit");
+  const tree = git(dir, "mktree", "");
+  const identity = "test <test@example.com> 1600000000 +0000";
+  const signatures = [null, PGP_SIGNATURE, SSH_SIGNATURE, SMIME_SIGNATURE];
+  let parent = null;
+  // Store the signatures as they are, they are not verified
+  for (const signature of signatures) {
+    const headers = [
+      `tree ${tree}`,
+      ...(parent ? [`parent ${parent}`] : []),
+      `author ${identity}`,
+      `committer ${identity}`,
+      ...(signature ? [`gpgsig ${signature.replaceAll("\n", "\n ")}`] : []),
+    ];
+    parent = git(
+      dir,
+      "hash-object -t commit -w --stdin",
+      `${headers.join("\n")}\n\ncommit\n`,
+    );
+  }
+  const repo = new Repository(dir);
+  const summaries = repo.signatureSummaries(parent);
+  t.deepEqual(
+    summaries.map(({ oid, ...summary }) => summary),
+    [
+      { signed: true, kind: "smime" },
+      {
+        signed: true,
+        kind: "ssh",
+        keyId: "SHA256:Oc50yY5gQg9DmlijNI1HjUU8vVbC6/qlX+9g7wxVtts",
+      },
+      {
+        signed: true,
+        kind: "openpgp",
+        keyId: "567B427978F533EC",
+        fingerprint: "FCD45A7774EA12322EDD00D5567B427978F533EC",
+      },
+      { signed: false },
+    ],
+  );
+  t.is(summaries[0].oid, parent);
+});
@@ -0,0 +1,91 @@
+import { spawnSync } from "node:child_process";
+import { existsSync, mkdirSync, writeFileSync } from "node:fs";
+import { join, resolve } from "node:path";
+
+import test from "ava";
+
+import { Config, ConfigLevel } from "../index.js";
+
+import { tempDir } from "./helpers.mjs";
+
+test("Config should open configuration files outside repositories", (t) => {
+  const dir = tempDir("config");
+  const path = join(dir, "config");
+  writeFileSync(path, "[user]\n\tname = Tester\n");
+  t.is(Config.openPath(path).getString("user.name"), "Tester");
+  t.is(Config.openOndisk(path).getString("core.missing"), null);
+  t.notThrows(() => Config.openDefault());
+});
+
+test("Config.entries should list the variables with their level", (t) => {
+  const dir = tempDir("config");
+  const path = join(dir, "config");
+  writeFileSync(path, "[user]\n\tname = Tester\n\temail = t@example.com\n");
+  writeFileSync(join(dir, "other"), "[core]\n\tbare\n");
+  const config = Config.openPath(path);
+  t.deepEqual(
+    [...config.entries()].map(({ name, value, level }) => [name, value, level]),
+    [
+      ["user.name", "Tester", ConfigLevel.Local],
+      ["user.email", "t@example.com", ConfigLevel.Local],
+    ],
+  );
+  writeFileSync(path, `[include]\n\tpath = ${join(dir, "other")}\n`);
+  const included = [...Config.openPath(path).entries("^core\\.")];
+  t.deepEqual(included, [
+    { name: "core.bare", includeDepth: 1, level: ConfigLevel.Local },
+  ]);
+});
+
+// Open the global config in a child process whose home directory is `home`,
+// reporting the `user.name` it sets
+const openGlobal = (home, env = {}) => {
+  const script = `
+    const { Config } = require(${JSON.stringify(resolve("index.js"))})
+    console.log(JSON.stringify(Config.openGlobal().getString("user.name")))
+  `;
+  const { stdout } = spawnSync(process.execPath, ["-e", script], {
+    encoding: "utf8",
+    env: {
+      ...process.env,
+      HOME: home,
+      USERPROFILE: home,
+      XDG_CONFIG_HOME: join(home, ".config"),
+      ...env,
+    },
+  });
+  return JSON.parse(stdout);
+};
+
+test("Config.openGlobal should open the global configuration file", (t) => {
+  const home = tempDir("config-home");
+  t.is(openGlobal(home), null);
+  writeFileSync(join(home, ".gitconfig"), "[user]\n\tname = Global\n");
+  t.is(openGlobal(home), "Global");
+  // The XDG configuration file takes precedence
+  const xdg = join(home, ".config", "git");
+  mkdirSync(xdg, { recursive: true });
+  writeFileSync(join(xdg, "config"), "[user]\n\tname = Xdg\n");
+  t.is(openGlobal(home), "Xdg");
+  t.is(openGlobal(home, { XDG_CONFIG_HOME: join(home, "none") }), "Global");
+});
+
+test("Config.openSystem should open the system configuration file", (t) => {
+  if (process.platform === "win32") {
+    t.notThrows(() => Config.openDefault());
+    return;
+  }
+  if (!existsSync("/etc/gitconfig")) {
+    t.throws(() => Config.openSystem(), {
+      message: /^Open the system config failed/,
+    });
+    return;
+  }
+  const system = Config.openSystem();
+  const entries = (config) =>
+    [...config.entries()].map(({ name, value }) => [name, value]);
+  t.deepEqual(entries(system), entries(Config.openPath("/etc/gitconfig")));
+  t.true(
+    [...system.entries()].every(({ level }) => level === ConfigLevel.System),
+  );
+});
@@ -0,0 +1,40 @@
+import { writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { DescribeFormatOptions, DescribeOptions } from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  t.context = buildRepo("describe", (builder) =>
+    builder.commit({ message: "init", files: { "a.txt": "a\n" } }),
+  );
+  git(t.context.dir, "tag -a v1.0.0 -m release");
+});
+
+test("describe should name the tag of a tagged commit", (t) => {
+  const { dir, repo } = t.context;
+  t.is(repo.describe().formatSimple(), "v1.0.0");
+  t.is(repo.describe().formatSimple(), git(dir, "describe"));
+  const long = new DescribeFormatOptions().alwaysUseLongFormat(true);
+  t.is(repo.describe().format(long), git(dir, "describe --long"));
+});
+
+test("describe should count the commits after the tag", (t) => {
+  const { dir, repo } = t.context;
+  git(dir, "commit -q --allow-empty -m next");
+  git(dir, "tag lightweight");
+  t.regex(repo.describe().formatSimple(), /^v1\.0\.0-1-g[0-9a-f]{7}$/);
+  t.is(repo.describe().formatSimple(), git(dir, "describe"));
+  const abbrev = new DescribeFormatOptions().abbreviatedSize(12);
+  t.is(repo.describe().format(abbrev), git(dir, "describe --abbrev=12"));
+  const tags = new DescribeOptions().describeTags();
+  t.is(repo.describe(tags).formatSimple(), git(dir, "describe --tags"));
+
+  writeFileSync(join(dir, "a.txt"), "changed\n");
+  const dirty = new DescribeFormatOptions().dirtySuffix("-dirty");
+  t.is(repo.describe().format(dirty), git(dir, "describe --dirty"));
+  t.true(repo.describe().format(dirty).endsWith("-dirty"));
+});
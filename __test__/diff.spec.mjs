@@ -0,0 +1,510 @@
+import { chmodSync, rmSync, symlinkSync, writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import {
+  ApplyLocation,
+  ApplyOptions,
+  Delta,
+  Diff,
+  DiffFlags,
+  DiffFormat,
+  DiffStatsFormat,
+  Repository,
+} from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+const hexdump = (path, content) =>
+  path.endsWith(".bin")
+    ? content.toString("hex").replace(/(.{2})/g, "$1\n")
+    : null;
+
+test.beforeEach((t) => {
+  const dir = initRepo("diff");
+  writeFileSync(join(dir, "data.bin"), Buffer.from([0, 1, 2, 3]));
+  writeFileSync(join(dir, "text.txt"), "base\n");
+  git(dir, "add -A");
+  git(dir, "commit -q -m base");
+  writeFileSync(join(dir, "data.bin"), Buffer.from([0, 1, 0xff, 3]));
+  t.context.dir = dir;
+  t.context.repo = new Repository(dir);
+});
+
+test("binary deltas should be rendered as binary by default", (t) => {
+  const { repo } = t.context;
+  const diff = repo.diffTreeToWorkdir(repo.head().peelToTree());
+  t.true(diff.toBuffer().toString().includes("Binary files"));
+  t.deepEqual(
+    [...diff.deltas()].map((delta) => delta.converted),
+    [false],
+  );
+});
+
+test("textconv should render hunks of binary deltas", (t) => {
+  const { repo } = t.context;
+  const diff = repo.diffTreeToWorkdir(repo.head().peelToTree(), {
+    textconv: hexdump,
+  });
+  const patch = diff.toBuffer().toString();
+  t.false(patch.includes("Binary files"));
+  t.true(patch.includes("@@"));
+  t.true(patch.includes("-02\n+ff\n"));
+  t.deepEqual(
+    [...diff.deltas()].map((delta) => delta.converted),
+    [true],
+  );
+});
+
+test("textconv should render hunks with the options of the diff", (t) => {
+  const { repo } = t.context;
+  const diff = repo.diffTreeToWorkdir(repo.head().peelToTree(), {
+    textconv: hexdump,
+    contextLines: 0,
+  });
+  t.true(diff.toBuffer().toString().endsWith("@@ -3 +3 @@\n-02\n+ff\n"));
+});
+
+test("textconv should skip files larger than textconvMaxBytes", (t) => {
+  const { repo } = t.context;
+  const diff = repo.diffTreeToWorkdir(repo.head().peelToTree(), {
+    textconv: hexdump,
+    textconvMaxBytes: 2,
+  });
+  t.true(diff.toBuffer().toString().includes("Binary files"));
+});
+
+test("the binary detection policy should classify and render deltas", (t) => {
+  const { dir, repo } = t.context;
+  writeFileSync(join(dir, ".gitattributes"), "*.bin diff\n");
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  repo.setBinaryDetection({ treatAsBinaryExtensions: [".TXT"] });
+  t.false(repo.isBinaryPath("data.bin", Buffer.from([0])));
+  t.true(repo.isBinaryPath("notes.txt"));
+  t.true(repo.isBinaryPath("data.dat", Buffer.from([1, 0])));
+  const diff = repo.diffTreeToWorkdir(repo.head().peelToTree());
+  const patch = diff.toBuffer().toString();
+  t.true(patch.includes("Binary files a/text.txt and b/text.txt differ"));
+  t.false(patch.includes("Binary files a/data.bin"));
+  t.deepEqual(
+    [...diff.deltas()].map((delta) => delta.isBinary()),
+    [false, true],
+  );
+  t.deepEqual(
+    [...diff.deltas()].map(
+      (delta) => delta.flags() & (DiffFlags.Binary | DiffFlags.NotBinary),
+    ),
+    [DiffFlags.NotBinary, DiffFlags.Binary],
+  );
+});
+
+test("reclassified deltas should be rendered with the options of the diff", (t) => {
+  const { dir, repo } = t.context;
+  const content = (line) => `a\nb\nc\0\n${line}\ne\n`;
+  writeFileSync(join(dir, "data.dat"), content("d"));
+  git(dir, "add -A");
+  git(dir, "commit -q -m dat");
+  writeFileSync(join(dir, "data.dat"), content("x"));
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  repo.setBinaryDetection({
+    treatAsTextExtensions: ["dat"],
+    treatAsBinaryExtensions: ["txt"],
+  });
+  const diff = repo.diffTreeToWorkdir(repo.head().peelToTree(), {
+    contextLines: 0,
+  });
+  const patch = diff.toBuffer().toString();
+  t.true(patch.includes("@@ -4 +4 @@"));
+  t.true(patch.includes("\n-d\n+x\ndiff"));
+  t.false(patch.includes("\n e\n"));
+  t.deepEqual(
+    diff.numstat().map(({ path, isBinary }) => [path, isBinary]),
+    [
+      ["data.dat", false],
+      ["text.txt", true],
+    ],
+  );
+  t.is(diff.patch(1), null);
+});
+
+test("DiffFlags should be reported as a bitmask", (t) => {
+  const { dir, repo } = t.context;
+  const base = repo.head().peelToTree();
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  writeFileSync(join(dir, "added.txt"), "added\n");
+  git(dir, "add -A");
+  git(dir, "commit -q -m change");
+  const diff = repo.diffTreeToTree(base, repo.head().peelToTree());
+  // The content is only inspected for binary data once the patch is built
+  diff.toBuffer();
+  const [added, data, text] = [...diff.deltas()];
+  const textFile = DiffFlags.NotBinary | DiffFlags.ValidId | DiffFlags.Exists;
+  t.is(text.oldFile().flags(), textFile);
+  t.is(text.newFile().flags(), textFile);
+  t.is(added.oldFile().flags() & DiffFlags.Exists, 0);
+  t.is(added.newFile().flags(), textFile);
+  t.is(text.flags() & DiffFlags.NotBinary, DiffFlags.NotBinary);
+  t.true(data.isBinary());
+  t.is(data.flags() & DiffFlags.Binary, DiffFlags.Binary);
+  t.is(data.flags() & DiffFlags.NotBinary, 0);
+});
+
+test("hunk headers should name the enclosing function like git diff", (t) => {
+  const { dir, repo } = t.context;
+  const source = (value) =>
+    [
+      "pub struct Parser {}",
+      "",
+      "impl Parser {",
+      "  pub fn new() -> Self {",
+      "    Self {}",
+      "  }",
+      "",
+      "  pub fn parse(&self) -> usize {",
+      "    let a = 1;",
+      "    let b = 2;",
+      "    let c = 3;",
+      `    a + b + c + ${value}`,
+      "  }",
+      "}",
+      "",
+    ].join("\n");
+  writeFileSync(join(dir, ".gitattributes"), "*.rs diff=rust\n");
+  writeFileSync(join(dir, "lib.rs"), source(1));
+  git(dir, "checkout -q -- data.bin");
+  git(dir, "add -A");
+  git(dir, "commit -q -m rust");
+  writeFileSync(join(dir, "lib.rs"), source(2));
+  const tree = repo.head().peelToTree();
+  const patch = repo.diffTreeToWorkdir(tree).toBuffer().toString();
+  t.true(patch.includes("@@ pub fn parse(&self) -> usize {\n"));
+  t.is(patch, `${git(dir, "diff")}\n`);
+  const whole = repo
+    .diffTreeToWorkdir(tree, { functionContext: true })
+    .toBuffer()
+    .toString();
+  t.is(whole, `${git(dir, "diff -W")}\n`);
+});
+
+test("merge should keep the rendering of the deltas it leaves unchanged", (t) => {
+  const { dir, repo } = t.context;
+  const source = (value) =>
+    [
+      "fn answer() -> u32 {",
+      "  let a = 1;",
+      "  let b = 2;",
+      "  let c = 3;",
+      "  let d = 4;",
+      `  a + b + c + d + ${value}`,
+      "}",
+      "",
+    ].join("\n");
+  writeFileSync(join(dir, ".gitattributes"), "*.rs diff=rust\n");
+  writeFileSync(join(dir, "z.rs"), source(1));
+  git(dir, "checkout -q -- data.bin");
+  git(dir, "add -A");
+  git(dir, "commit -q -m rust");
+  const base = repo.head().peelToTree();
+  writeFileSync(join(dir, "z.rs"), source(2));
+  git(dir, "commit -q -am z");
+  const rust = repo.head().peelToTree();
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  git(dir, "commit -q -am text");
+  const diff = repo.diffTreeToTree(base, rust);
+  diff.merge(repo.diffTreeToTree(rust, repo.head().peelToTree()));
+  const patch = diff.toBuffer().toString();
+  t.true(patch.includes("@@ fn answer() -> u32 {\n"));
+  t.is(patch, `${git(dir, "diff HEAD~2 HEAD")}\n`);
+});
+
+test("apply should write the diff to the index", (t) => {
+  const { dir, repo } = t.context;
+  git(dir, "checkout -q -- data.bin");
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  const diff = repo.diffTreeToWorkdir(repo.head().peelToTree());
+  repo.apply(diff, ApplyLocation.Index);
+  t.is(git(dir, "show :text.txt"), "changed");
+});
+
+test("apply should skip deltas rejected by the delta callback", (t) => {
+  const { dir, repo } = t.context;
+  git(dir, "checkout -q -- data.bin");
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  const diff = repo.diffTreeToWorkdir(repo.head().peelToTree());
+  const options = new ApplyOptions().deltaCallback(
+    (delta) => delta.newFile().path() !== "text.txt",
+  );
+  const tree = repo.head().peelToTree();
+  const index = repo.applyToTree(tree, diff, options);
+  t.is(index.writeTreeTo(repo), tree.id());
+});
+
+test("diffs between trees, the index and the workdir", (t) => {
+  const { dir, repo } = t.context;
+  const paths = (diff) => [...diff.deltas()].map((d) => d.newFile().path());
+  git(dir, "add data.bin");
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  const head = repo.head().peelToTree();
+  t.deepEqual(paths(repo.diffTreeToIndex(head)), ["data.bin"]);
+  t.deepEqual(paths(repo.diffIndexToWorkdir()), ["text.txt"]);
+  git(dir, "commit -q -am next");
+  t.deepEqual(paths(repo.diffTreeToTree(head, repo.head().peelToTree())), [
+    "data.bin",
+    "text.txt",
+  ]);
+  t.deepEqual(paths(repo.diffTreeToTree(null, head)), [
+    "data.bin",
+    "text.txt",
+  ]);
+  const diff = repo.diffCommits("HEAD~1", "HEAD");
+  t.deepEqual(paths(diff), ["data.bin", "text.txt"]);
+  t.is(
+    diff.numDeltasOfType(Delta.Modified),
+    repo.diffCommits("HEAD~1", "HEAD^{tree}").len(),
+  );
+  t.throws(() => repo.diffCommits("HEAD", "missing"), {
+    message: /^Resolve \[missing\] to a tree failed/,
+  });
+});
+
+test("DiffFile should expose paths which are not UTF-8", (t) => {
+  const { dir, repo } = t.context;
+  const name = Buffer.from([0x61, 0xff, 0x2e, 0x74, 0x78, 0x74]);
+  const path = Buffer.concat([Buffer.from(`${dir}/`), name]);
+  writeFileSync(path, "a\n");
+  git(dir, "add -A");
+  git(dir, "commit -q -m raw");
+  writeFileSync(path, "b\n");
+  const [file] = [...repo.diffIndexToWorkdir().deltas()]
+    .map((delta) => delta.newFile())
+    .filter((file) => file.path() !== "data.bin");
+  t.is(file.path(), null);
+  t.deepEqual(Buffer.from(file.pathRaw()), name);
+  t.is(file.pathLossy(), "a\ufffd.txt");
+});
+
+test("stageHunks and unstageHunks should move single hunks", (t) => {
+  const { dir, repo } = t.context;
+  const lines = Array.from({ length: 20 }, (_, i) => `line ${i}`);
+  writeFileSync(join(dir, "text.txt"), `${lines.join("\n")}\n`);
+  git(dir, "commit -q -am lines");
+  lines[1] = "first";
+  lines[18] = "second";
+  writeFileSync(join(dir, "text.txt"), `${lines.join("\n")}`);
+  const hunks = (diff) =>
+    [...diff.deltas()]
+      .filter((delta) => delta.newFile().path() === "text.txt")
+      .map((delta) => delta.hunkCount());
+  const [delta] = [...repo.diffIndexToWorkdir().deltas()].filter(
+    (delta) => delta.newFile().path() === "text.txt",
+  );
+  t.is(delta.hunkCount(), 2);
+  repo.stageHunks("text.txt", [1, 1], delta.newFile().id());
+  const head = () => repo.head().peelToTree();
+  t.deepEqual(hunks(repo.diffTreeToIndex(head())), [1]);
+  t.deepEqual(hunks(repo.diffIndexToWorkdir()), [1]);
+  t.regex(git(dir, "diff --cached"), /\+second\n\+line 19\n\\ No newline/);
+  t.regex(git(dir, "diff"), /\+first/);
+  lines[1] = "first again";
+  writeFileSync(join(dir, "text.txt"), `${lines.join("\n")}`);
+  const error = t.throws(() =>
+    repo.stageHunks("text.txt", [0], delta.newFile().id()),
+  );
+  t.is(error.code, "StaleHunks");
+  t.throws(() => repo.stageHunks("text.txt", [1]), {
+    message: /has no hunk at index 1, it has 1 hunks/,
+  });
+  repo.unstageHunks("text.txt", [0]);
+  t.deepEqual(hunks(repo.diffTreeToIndex(head())), []);
+  t.deepEqual(hunks(repo.diffIndexToWorkdir()), [2]);
+});
+
+test("DiffOptions should select paths and shape hunks", (t) => {
+  const { dir, repo } = t.context;
+  const paths = (diff) => [...diff.deltas()].map((d) => d.newFile().path());
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  writeFileSync(join(dir, "new.txt"), "new\n");
+  const tree = repo.head().peelToTree();
+  t.deepEqual(paths(repo.diffTreeToWorkdir(tree)), ["data.bin", "text.txt"]);
+  t.deepEqual(paths(repo.diffTreeToWorkdir(tree, { pathspec: ["*.txt"] })), [
+    "text.txt",
+  ]);
+  t.deepEqual(
+    paths(repo.diffIndexToWorkdir(null, { includeUntracked: true })),
+    ["data.bin", "new.txt", "text.txt"],
+  );
+  const patch = repo
+    .diffTreeToWorkdir(tree, { pathspec: ["text.txt"], idAbbrev: 12 })
+    .toBuffer()
+    .toString();
+  t.regex(patch, /^index [0-9a-f]{12}\.\.[0-9a-f]{12} /m);
+  git(dir, "mv text.txt moved.txt");
+  const renamed = repo.diffTreeToIndex(tree, null, { findRenames: true });
+  const [delta] = [...renamed.deltas()].filter(
+    (delta) => delta.status() === Delta.Renamed,
+  );
+  t.is(delta.oldFile().path(), "text.txt");
+  t.is(delta.newFile().path(), "moved.txt");
+});
+
+test("stats, statsBuffer and numstat should count the changes", (t) => {
+  const { dir, repo } = t.context;
+  writeFileSync(join(dir, "text.txt"), "changed\nadded\n");
+  const diff = repo.diffIndexToWorkdir();
+  t.deepEqual(diff.stats(), { filesChanged: 2, insertions: 2, deletions: 1 });
+  t.is(
+    diff.statsBuffer(DiffStatsFormat.Full, 80).toString(),
+    " data.bin | Bin 4 -> 4 bytes\n text.txt | 3 ++-\n" +
+      " 2 files changed, 2 insertions(+), 1 deletion(-)\n",
+  );
+  t.is(
+    diff.statsBuffer(DiffStatsFormat.Short, 80).toString(),
+    ` ${git(dir, "diff --shortstat")}\n`,
+  );
+  t.deepEqual(diff.numstat(), [
+    { path: "data.bin", insertions: 0, deletions: 0, isBinary: true },
+    { path: "text.txt", insertions: 2, deletions: 1, isBinary: false },
+  ]);
+});
+
+test("Diff.fromBuffer should parse a patch to apply", (t) => {
+  const { dir, repo } = t.context;
+  git(dir, "checkout -q -- data.bin");
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  const diff = Diff.fromBuffer(`${git(dir, "diff")}\n`);
+  t.is(diff.len(), 1);
+  git(dir, "checkout -q -- text.txt");
+  repo.apply(diff, ApplyLocation.Both);
+  t.is(git(dir, "show :text.txt"), "changed");
+  t.is(git(dir, "status --porcelain"), "M  text.txt");
+  const error = t.throws(() => repo.apply(diff, ApplyLocation.Workdir));
+  t.regex(error.message, /^Apply diff failed at \[text\.txt\]: /);
+});
+
+test("deltas should report mode and symlink target changes", (t) => {
+  const { dir, repo } = t.context;
+  writeFileSync(join(dir, "run.sh"), "echo\n");
+  writeFileSync(join(dir, "config"), "a\n");
+  symlinkSync("text.txt", join(dir, "link"));
+  git(dir, "add -A");
+  git(dir, "commit -q -m links");
+  chmodSync(join(dir, "run.sh"), 0o755);
+  rmSync(join(dir, "link"));
+  symlinkSync("data.bin", join(dir, "link"));
+  rmSync(join(dir, "config"));
+  symlinkSync("text.txt", join(dir, "config"));
+  const deltas = new Map(
+    [
+      ...repo
+        .diffTreeToWorkdir(repo.head().peelToTree(), {
+          includeTypechange: true,
+        })
+        .deltas(),
+    ].map((delta) => [delta.newFile().path(), delta]),
+  );
+  const run = deltas.get("run.sh");
+  t.true(run.modeChanged());
+  t.deepEqual(run.modes(), { oldMode: 0o100644, newMode: 0o100755 });
+  t.is(run.symlinkTargetChange(), null);
+  const link = deltas.get("link");
+  t.false(link.modeChanged());
+  t.deepEqual(link.symlinkTargetChange(), {
+    old: "text.txt",
+    new: "data.bin",
+  });
+  const config = deltas.get("config");
+  t.is(config.status(), Delta.Typechange);
+  t.true(config.modeChanged());
+  t.deepEqual(config.modes(), { oldMode: 0o100644, newMode: 0o120000 });
+  t.is(config.symlinkTargetChange(), null);
+});
+
+test("len, numDeltasOfType and hunkCount should count deltas", (t) => {
+  const { dir, repo } = t.context;
+  const lines = Array.from({ length: 20 }, (_, i) => `${i}\n`).join("");
+  writeFileSync(join(dir, "text.txt"), `base\n${lines}`);
+  git(dir, "commit -q -am lines");
+  writeFileSync(join(dir, "data.bin"), Buffer.from([0, 0xff]));
+  writeFileSync(join(dir, "text.txt"), `one\nbase\n${lines}two\n`);
+  const diff = repo.diffTreeToWorkdir(repo.head().peelToTree());
+  t.is(diff.len(), 2);
+  t.false(diff.isEmpty());
+  t.is(diff.numDeltasOfType(Delta.Modified), 2);
+  t.is(diff.numDeltasOfType(Delta.Added), 0);
+  const hunks = Object.fromEntries(
+    [...diff.deltas()].map((delta) => [
+      delta.newFile().path(),
+      delta.hunkCount(),
+    ]),
+  );
+  t.deepEqual(hunks, { "data.bin": 0, "text.txt": 2 });
+});
+
+test("toBuffer should render the formats of git diff", (t) => {
+  const { dir, repo } = t.context;
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  const diff = repo.diffIndexToWorkdir();
+  t.is(diff.toBuffer(DiffFormat.Patch).toString(), `${git(dir, "diff")}\n`);
+  t.is(
+    diff.toBuffer(DiffFormat.NameOnly).toString(),
+    `${git(dir, "diff --name-only")}\n`,
+  );
+  t.is(
+    diff.toBuffer(DiffFormat.NameStatus).toString(),
+    `${git(dir, "diff --name-status")}\n`,
+  );
+  t.regex(
+    diff.toBuffer(DiffFormat.Raw).toString(),
+    /^:100644 100644 df967b9\.\.\. [0-9a-f]{7}\.\.\. M\ttext\.txt$/m,
+  );
+  const headers = diff.toBuffer(DiffFormat.PatchHeader).toString();
+  t.true(headers.endsWith("--- a/text.txt\n+++ b/text.txt\n"));
+  t.false(headers.includes("@@"));
+  const binary = repo.diffIndexToWorkdir(null, { showBinary: true }).toBuffer();
+  t.regex(binary.toString(), /^GIT binary patch$/m);
+});
+
+test("foreach should stream the deltas, hunks and lines", (t) => {
+  const { dir, repo } = t.context;
+  writeFileSync(join(dir, "text.txt"), "changed\n");
+  const diff = repo.diffIndexToWorkdir(null, { showBinary: true });
+  const events = [];
+  diff.foreach(
+    (delta, progress) => {
+      events.push(["file", delta.newFile().path(), typeof progress]);
+    },
+    (delta, binary) => {
+      events.push(["binary", delta.newFile().path(), binary.containsData]);
+    },
+    (delta, hunk) => {
+      events.push(["hunk", delta.newFile().path(), hunk.header()]);
+    },
+    (delta, hunk, line) => {
+      events.push(["line", line.origin(), line.content().toString()]);
+    },
+  );
+  t.deepEqual(events, [
+    ["file", "data.bin", "number"],
+    ["binary", "data.bin", true],
+    ["file", "text.txt", "number"],
+    ["hunk", "text.txt", "@@ -1 +1 @@\n"],
+    ["line", "-", "base\n"],
+    ["line", "+", "changed\n"],
+  ]);
+  let files = 0;
+  diff.foreach(() => {
+    files += 1;
+    return false;
+  });
+  t.is(files, 1);
+  const error = new Error("stop");
+  t.is(
+    t.throws(() =>
+      diff.foreach(() => {
+        throw error;
+      }),
+    ),
+    error,
+  );
+});
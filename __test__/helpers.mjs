@@ -0,0 +1,36 @@
+import { execSync } from "node:child_process";
+import { mkdtempSync } from "node:fs";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+
+import { TestRepoBuilder } from "../index.js";
+
+// Run a git command in `cwd`, returning its trimmed output
+export const git = (cwd, command, input) =>
+  execSync(`git ${command}`, { cwd, input, stdio: "pipe" })
+    .toString("utf8")
+    .trim();
+
+// Create an empty temporary directory named after `name`
+export const tempDir = (name) =>
+  mkdtempSync(join(tmpdir(), `simple-git-${name}-`));
+
+// Initialize a repository on `main` with a committer configured for the git
+// CLI, passing `args` to `git init`, e.g. `--bare`
+export const initRepo = (name, args = "") => {
+  const dir = tempDir(name);
+  git(dir, `init -q -b main ${args}`);
+  git(dir, "config user.name test");
+  git(dir, "config user.email test@example.com");
+  return dir;
+};
+
+// Build a repository with the steps of `build` applied to a `TestRepoBuilder`,
+// with a committer configured for the git CLI
+export const buildRepo = (name, build = (builder) => builder) => {
+  const dir = tempDir(name);
+  const repo = build(new TestRepoBuilder(dir)).build();
+  git(dir, "config user.name test");
+  git(dir, "config user.email test@example.com");
+  return { dir, repo };
+};
@@ -0,0 +1,67 @@
+import { writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { Mailmap, Repository, Signature } from "../index.js";
+
+import { buildRepo, initRepo } from "./helpers.mjs";
+
+test("Repository.mailmap should read the .mailmap file", (t) => {
+  const dir = initRepo("mailmap");
+  writeFileSync(
+    join(dir, ".mailmap"),
+    "Jane Doe <jane@example.com> <jane@old.example.com>\n",
+  );
+  const mailmap = new Repository(dir).mailmap();
+  const old = new Signature("jane", "jane@old.example.com", 1700000000);
+  const resolved = mailmap.resolveSignature(old);
+  t.is(resolved.name(), "Jane Doe");
+  t.is(resolved.email(), "jane@example.com");
+  t.is(resolved.when(), 1700000000);
+  const other = Signature.now("joe", "joe@example.com");
+  t.is(mailmap.resolveSignature(other).name(), "joe");
+});
+
+test("Mailmap should be built from a buffer and entries", (t) => {
+  const mailmap = Mailmap.fromBuf(
+    Buffer.from("<joe@example.com> <joe@old.example.com>\n"),
+  );
+  mailmap.addEntry("Jane Doe", null, "jane", "jane@example.com");
+  const joe = Signature.now("Joe", "joe@old.example.com");
+  t.is(mailmap.resolveSignature(joe).email(), "joe@example.com");
+  t.is(mailmap.resolveSignature(joe).name(), "Joe");
+  const jane = Signature.now("jane", "jane@example.com");
+  t.is(mailmap.resolveSignature(jane).name(), "Jane Doe");
+  const janet = Signature.now("janet", "jane@example.com");
+  t.is(mailmap.resolveSignature(janet).name(), "janet");
+});
+
+test("Commit.allAuthors should merge co-authors through the mailmap", (t) => {
+  const message = [
+    "Add the parser",
+    "",
+    "Co-authored-by: Joe <Joe@Example.com>",
+    "Co-authored-by: Jane Old <jane@old.example.com>",
+    "Co-authored-by: nobody",
+  ].join("\n");
+  const { repo } = buildRepo("mailmap", (builder) =>
+    builder.commit({
+      message,
+      files: { "a.txt": "a\n" },
+      author: { name: "Jane Doe", email: " Jane@Example.com" },
+    }),
+  );
+  const commit = repo.head().peelToCommit();
+  const mailmap = Mailmap.fromBuf(
+    Buffer.from("<jane@example.com> <jane@old.example.com>\n"),
+  );
+  t.deepEqual(commit.allAuthors(mailmap), [
+    { name: "Jane Doe", email: "jane@example.com", role: "author" },
+    { name: "Joe", email: "joe@example.com", role: "coauthor" },
+  ]);
+  t.deepEqual(
+    commit.allAuthors().map(({ email }) => email),
+    ["jane@example.com", "joe@example.com", "jane@old.example.com"],
+  );
+});
@@ -0,0 +1,40 @@
+import test from "ava";
+
+import { Repository } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  const dir = initRepo("maintenance");
+  git(dir, "commit -q --allow-empty -m first");
+  t.context.dir = dir;
+  t.context.repo = new Repository(dir);
+});
+
+test("maintenanceReport should recommend packing loose refs", async (t) => {
+  const { dir, repo } = t.context;
+  for (let i = 1; i <= 20; i++) {
+    git(dir, `branch b${i}`);
+  }
+  const options = { looseRefsThreshold: 10 };
+  const report = await repo.maintenanceReport(options);
+  // The commit and its empty tree
+  t.is(report.looseObjects, 2);
+  t.is(typeof report.looseObjectOldestMs, "number");
+  t.is(report.packs, 0);
+  t.false(report.packedRefs);
+  t.is(report.looseRefs, 21);
+  t.true(report.reflogEnabled);
+  t.deepEqual(report.recommendations, ["pack-refs: 21 loose refs"]);
+
+  const repack = await repo.maintenanceReport({ looseObjectsThreshold: 1 });
+  t.deepEqual(repack.recommendations, ["repack: 2 loose objects"]);
+
+  git(dir, "pack-refs --all");
+  git(dir, "repack -q -a -d");
+  const packed = await repo.maintenanceReport(options);
+  t.true(packed.packedRefs);
+  t.is(packed.looseRefs, 0);
+  t.is(packed.packs, 1);
+  t.deepEqual(packed.recommendations, []);
+});
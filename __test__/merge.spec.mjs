@@ -0,0 +1,267 @@
+import {
+  appendFileSync,
+  existsSync,
+  readFileSync,
+  writeFileSync,
+} from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import {
+  FileFavor,
+  Index,
+  MergeFileOptions,
+  Repository,
+  RepositoryState,
+} from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  // Append a line to both merged files
+  const side = (message) => {
+    const content = `base\n${message}\n`;
+    return { message, files: { "union.txt": content, "driver.txt": content } };
+  };
+  const { dir, repo } = buildRepo("merge", (builder) =>
+    builder
+      .commit({
+        message: "base",
+        files: {
+          ".gitattributes":
+            "union.txt merge=union\ndriver.txt merge=npm-merge-driver\n",
+          "union.txt": "base\n",
+          "driver.txt": "base\n",
+        },
+      })
+      .branch("feature")
+      .commit(side("theirs"))
+      .checkout("main")
+      .commit(side("ours")),
+  );
+  t.context.dir = dir;
+  t.context.index = repo.mergeCommits(
+    repo.findCommit(git(dir, "rev-parse main")),
+    repo.findCommit(git(dir, "rev-parse feature")),
+  );
+});
+
+test("merge=union should merge additions on both sides cleanly", (t) => {
+  const { index } = t.context;
+  t.false(index.mergeConflicts().some(({ path }) => path === "union.txt"));
+});
+
+test("custom merge driver should be reported on the conflict", (t) => {
+  const { index } = t.context;
+  t.deepEqual(index.mergeConflicts(), [
+    { path: "driver.txt", driver: "npm-merge-driver" },
+  ]);
+});
+
+test("merge should write the result to the workdir and the index", (t) => {
+  const { dir } = t.context;
+  const repo = new Repository(dir);
+  repo.merge([git(dir, "rev-parse feature")]);
+  t.is(repo.state(), RepositoryState.Merge);
+  t.is(readFileSync(join(dir, "union.txt"), "utf8"), "base\nours\ntheirs\n");
+  const index = repo.index();
+  t.true(index.hasConflicts());
+  t.is(index.getPath("union.txt", 0).id, git(dir, "hash-object union.txt"));
+  t.deepEqual(
+    [...index.conflicts()].map(({ our }) => our.path),
+    ["driver.txt"],
+  );
+  t.regex(git(dir, "status --porcelain"), /^UU driver\.txt$/m);
+  repo.cleanupState();
+  t.is(repo.state(), RepositoryState.Clean);
+});
+
+test("conflicts should list the sides of each conflicted path", (t) => {
+  const { index } = t.context;
+  const conflicts = [...index.conflicts()];
+  t.is(conflicts.length, 1);
+  const [{ ancestor, our, their }] = conflicts;
+  t.is(ancestor.path, "driver.txt");
+  t.is(our.path, "driver.txt");
+  t.is(their.path, "driver.txt");
+  t.notDeepEqual(our.id, their.id);
+  t.deepEqual(index.getPath("driver.txt", 2), our);
+  t.is(index.getPath("driver.txt", 0), null);
+});
+
+test("conflicts can be added and cleaned up", (t) => {
+  const { index } = t.context;
+  const [{ our, their }] = [...index.conflicts()];
+  const other = new Index();
+  other.conflictAdd(
+    null,
+    { ...our, path: "a.txt" },
+    { ...their, path: "a.txt" },
+  );
+  t.true(other.hasConflicts());
+  t.is(other.get(0).path, "a.txt");
+  t.is(other.getPath("a.txt", 1), null);
+  t.is(other.getPath("a.txt", 3).id, their.id);
+  other.conflictCleanup();
+  t.false(other.hasConflicts());
+  t.is(other.len(), 0);
+});
+
+test("annotated commits should record how they were looked up", (t) => {
+  const { dir } = t.context;
+  const repo = new Repository(dir);
+  const feature = git(dir, "rev-parse feature");
+  const byId = repo.findAnnotatedCommit(feature);
+  t.is(byId.id(), feature);
+  t.is(byId.refname(), null);
+  const byRef = repo.annotatedCommitFromRef(
+    repo.findReference("refs/heads/feature"),
+  );
+  t.is(byRef.id(), feature);
+  t.is(byRef.refname(), "refs/heads/feature");
+  const fetched = repo.annotatedCommitFromFetchhead(
+    "feature",
+    "https://example.com/repo.git",
+    feature,
+  );
+  t.is(fetched.id(), feature);
+  t.is(fetched.refname(), "feature");
+});
+
+test("mergeTrees should merge trees in memory", (t) => {
+  const { dir } = t.context;
+  const repo = new Repository(dir);
+  const tree = (rev) => repo.findCommit(git(dir, `rev-parse ${rev}`)).tree();
+  const index = repo.mergeTrees(tree("main~1"), tree("main"), tree("feature"));
+  t.deepEqual(index.mergeConflicts(), [
+    { path: "driver.txt", driver: "npm-merge-driver" },
+  ]);
+  t.is(git(dir, "status --porcelain"), "");
+  // Without an ancestor both sides added every path, which merge=union
+  // merges cleanly
+  const unrelated = repo.mergeTrees(null, tree("main"), tree("feature"));
+  const [{ ancestor, our }] = [...unrelated.conflicts()];
+  t.is(ancestor, undefined);
+  t.is(our.path, "driver.txt");
+  t.not(unrelated.getPath("union.txt", 0), null);
+  // The empty tree standing for the ancestor is not written
+  const emptyTree = git(dir, "hash-object -t tree /dev/null");
+  const loose = join(dir, ".git/objects", emptyTree.slice(0, 2));
+  t.false(existsSync(join(loose, emptyTree.slice(2))));
+});
+
+test("merge attributes should be read from the merged trees", (t) => {
+  const { dir } = t.context;
+  writeFileSync(join(dir, "ours.txt"), "base\n");
+  git(dir, "add -A");
+  git(dir, "commit -q -m ours");
+  git(dir, "checkout -q -b rule");
+  appendFileSync(join(dir, ".gitattributes"), "ours.txt merge=ours\n");
+  writeFileSync(join(dir, "ours.txt"), "theirs\n");
+  git(dir, "commit -q -am rule");
+  git(dir, "checkout -q main");
+  writeFileSync(join(dir, "ours.txt"), "ours\n");
+  git(dir, "commit -q -am main");
+  const resolved = (repo) => {
+    const tree = (rev) => repo.findCommit(git(dir, `rev-parse ${rev}`)).tree();
+    const index = repo.mergeTrees(tree("main~1"), tree("main"), tree("rule"));
+    t.deepEqual(index.mergeConflicts(), []);
+    t.is(index.getPath("ours.txt", 0).id, git(dir, "rev-parse main:ours.txt"));
+  };
+  // The rule is only committed on the merged branch, not in the workdir
+  resolved(new Repository(dir));
+  const bare = join(dir, "bare.git");
+  git(dir, `clone -q --bare . ${bare}`);
+  resolved(new Repository(bare));
+});
+
+test("merge=union from the merged trees should resolve in bare repositories", (t) => {
+  const { dir } = t.context;
+  writeFileSync(join(dir, "list.txt"), "base\n");
+  git(dir, "add -A");
+  git(dir, "commit -q -m list");
+  git(dir, "checkout -q -b rule");
+  appendFileSync(join(dir, ".gitattributes"), "*.txt merge=union\n");
+  appendFileSync(join(dir, "list.txt"), "theirs\n");
+  git(dir, "commit -q -am rule");
+  git(dir, "checkout -q main");
+  appendFileSync(join(dir, "list.txt"), "ours\n");
+  git(dir, "commit -q -am main");
+  const bare = join(dir, "bare.git");
+  git(dir, `clone -q --bare . ${bare}`);
+  const repo = new Repository(bare);
+  const commit = (rev) => repo.findCommit(git(bare, `rev-parse ${rev}`));
+  const merged = (index) => {
+    t.false(index.mergeConflicts().some(({ path }) => path === "list.txt"));
+    const { id } = index.getPath("list.txt", 0);
+    t.is(git(bare, `cat-file -p ${id}`), "base\nours\ntheirs");
+  };
+  merged(repo.mergeCommits(commit("main"), commit("rule")));
+  const tree = (rev) => commit(rev).tree();
+  merged(repo.mergeTrees(tree("main~1"), tree("main"), tree("rule")));
+});
+
+test("merge=binary should keep ours and stay conflicted", (t) => {
+  const { dir } = t.context;
+  writeFileSync(join(dir, "data.bin"), "a\nb\nc\n");
+  git(dir, "add -A");
+  git(dir, "commit -q -m data");
+  git(dir, "checkout -q -b edit");
+  appendFileSync(join(dir, ".gitattributes"), "*.bin merge=binary\n");
+  writeFileSync(join(dir, "data.bin"), "a\nb\nC\n");
+  git(dir, "commit -q -am theirs");
+  git(dir, "checkout -q main");
+  writeFileSync(join(dir, "data.bin"), "A\nb\nc\n");
+  git(dir, "commit -q -am ours");
+  const bare = join(dir, "bare.git");
+  git(dir, `clone -q --bare . ${bare}`);
+  const repo = new Repository(bare);
+  const tree = (rev) => repo.findCommit(git(bare, `rev-parse ${rev}`)).tree();
+  // The changes do not overlap, which the text driver would merge cleanly
+  const index = repo.mergeTrees(tree("main~1"), tree("main"), tree("edit"));
+  t.true(index.mergeConflicts().some(({ path }) => path === "data.bin"));
+  const side = (rev) => git(bare, `rev-parse ${rev}:data.bin`);
+  t.is(index.getPath("data.bin", 0), null);
+  t.is(index.getPath("data.bin", 1).id, side("main~1"));
+  t.is(index.getPath("data.bin", 2).id, side("main"));
+  t.is(index.getPath("data.bin", 3).id, side("edit"));
+});
+
+test("mergeFile should merge the contents of a single file", (t) => {
+  const repo = new Repository(t.context.dir);
+  const input = (content) => ({
+    path: "a.txt",
+    mode: 0o100644,
+    content: Buffer.from(content),
+  });
+  const ancestor = input("a\nb\nc\n");
+  const clean = repo.mergeFile(
+    ancestor,
+    input("A\nb\nc\n"),
+    input("a\nb\nC\n"),
+  );
+  t.true(clean.automergeable);
+  t.is(clean.path, "a.txt");
+  t.is(clean.mode, 0o100644);
+  t.is(clean.content.toString(), "A\nb\nC\n");
+
+  const ours = input("a\nours\nc\n");
+  const theirs = input("a\ntheirs\nc\n");
+  const conflict = repo.mergeFile(
+    ancestor,
+    ours,
+    theirs,
+    new MergeFileOptions().ourLabel("mine").theirLabel("yours"),
+  );
+  t.false(conflict.automergeable);
+  t.regex(conflict.content.toString(), /^<<<<<<< mine\nours\n=======\n/m);
+  t.regex(conflict.content.toString(), /^>>>>>>> yours$/m);
+  const favored = repo.mergeFile(
+    ancestor,
+    ours,
+    theirs,
+    new MergeFileOptions().favor(FileFavor.Theirs),
+  );
+  t.is(favored.content.toString(), "a\ntheirs\nc\n");
+});
@@ -0,0 +1,141 @@
+import test from "ava";
+
+import { Signature } from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+const author = new Signature("author", "author@example.com", 1700000000);
+const committer = new Signature("bot", "bot@example.com", 1700000060);
+
+test.beforeEach((t) => {
+  t.context = buildRepo("note", (builder) =>
+    builder
+      .commit({ message: "first", files: { "a.txt": "a\n" } })
+      .commit({ message: "second", files: { "b.txt": "b\n" } }),
+  );
+  const { dir } = t.context;
+  t.context.first = git(dir, "rev-parse HEAD~1");
+  t.context.second = git(dir, "rev-parse HEAD");
+});
+
+test("notes should round-trip through create, read, iterate and delete", (t) => {
+  const { dir, first, repo, second } = t.context;
+  t.is(repo.noteDefaultRef(), "refs/notes/commits");
+  const create = (id, note, force = false) =>
+    repo.noteCreate(null, author, committer, id, note, force);
+  const blob = create(first, "reviewed\n");
+  t.is(git(dir, `notes show ${first}`), "reviewed");
+  create(second, "pending\n");
+  t.throws(() => create(second, "again\n"));
+  create(second, "shipped\n", true);
+
+  const note = repo.note(null, first);
+  t.is(note.id(), blob);
+  t.is(note.message(), "reviewed\n");
+  t.is(note.author().name(), "author");
+  t.is(note.author().when(), 1700000000);
+  t.is(note.committer().email(), "bot@example.com");
+  t.is(repo.note(null, second).message(), "shipped\n");
+  const notes = new Map(
+    [...repo.notes()].map((entry) => [entry.annotatedId, entry.noteId]),
+  );
+  t.deepEqual(
+    notes,
+    new Map([
+      [first, blob],
+      [second, git(dir, `notes list ${second}`)],
+    ]),
+  );
+
+  repo.noteDelete(null, author, committer, first);
+  t.throws(() => repo.note(null, first));
+  t.throws(() => repo.noteDelete(null, author, committer, first));
+  t.deepEqual(
+    [...repo.notes()].map((entry) => entry.annotatedId),
+    [second],
+  );
+  t.is(
+    git(dir, "log -1 --format=%s refs/notes/commits"),
+    "Notes removed by 'git_note_remove' from libgit2",
+  );
+});
+
+test("notes should be written to a custom notes reference", (t) => {
+  const { dir, first, repo } = t.context;
+  const notesRef = "refs/notes/review";
+  repo.noteCreate(notesRef, author, committer, first, "lgtm\n", false);
+  t.is(git(dir, `notes --ref review show ${first}`), "lgtm");
+  t.throws(() => repo.note(null, first));
+  t.is(repo.note(notesRef, first).message(), "lgtm\n");
+  t.deepEqual(
+    [...repo.notes(notesRef)].map((entry) => entry.annotatedId),
+    [first],
+  );
+});
+
+test("Guard should see the notes commit before the notes reference moves", (t) => {
+  const { dir, first, repo, second } = t.context;
+  const updates = [];
+  let reject = true;
+  repo.setRefUpdateGuard((update) => {
+    updates.push(update);
+    return !reject;
+  });
+  t.throws(
+    () => repo.noteCreate(null, author, committer, first, "guarded\n", false),
+    { message: /rejected by the ref update guard$/ },
+  );
+  t.throws(() => git(dir, "rev-parse --verify -q refs/notes/commits"));
+  t.is(updates.length, 1);
+  t.is(updates[0].refname, "refs/notes/commits");
+  t.is(updates[0].kind, "create");
+  // The notes commit was not written
+  t.throws(() => git(dir, `cat-file -e ${updates[0].newOid}`));
+
+  reject = false;
+  repo.noteCreate(null, author, committer, first, "guarded\n", false);
+  t.is(git(dir, "rev-parse refs/notes/commits"), updates.at(-1).newOid);
+  repo.noteCreate(null, author, committer, second, "too\n", false);
+  t.is(git(dir, "rev-parse refs/notes/commits"), updates.at(-1).newOid);
+
+  const old = git(dir, "rev-parse refs/notes/commits");
+  reject = true;
+  t.throws(() => repo.noteDelete(null, author, committer, first), {
+    message: /rejected by the ref update guard$/,
+  });
+  t.is(git(dir, "rev-parse refs/notes/commits"), old);
+  t.is(updates.at(-1).oldOid, old);
+  t.is(updates.at(-1).kind, "update");
+  reject = false;
+  repo.noteDelete(null, author, committer, first);
+  t.is(git(dir, "rev-parse refs/notes/commits"), updates.at(-1).newOid);
+});
+
+test("Guard should see the notes commit of a fanned out notes tree", (t) => {
+  const { dir, first, repo, second } = t.context;
+  // Keep the note of `first` under a fanout directory, as git does for
+  // large notes trees
+  const blob = git(dir, "hash-object -w --stdin", "fanned out\n");
+  const [prefix, rest] = [first.slice(0, 2), first.slice(2)];
+  const fanout = git(dir, "mktree", `100644 blob ${blob}\t${rest}\n`);
+  const tree = git(dir, "mktree", `040000 tree ${fanout}\t${prefix}\n`);
+  const notes = git(dir, `commit-tree ${tree} -m notes`);
+  git(dir, `update-ref refs/notes/commits ${notes}`);
+  t.is(repo.note(null, first).message(), "fanned out\n");
+  const updates = [];
+  repo.setRefUpdateGuard((update) => {
+    updates.push(update);
+    return true;
+  });
+  repo.noteCreate(null, author, committer, first, "replaced\n", true);
+  t.is(git(dir, "rev-parse refs/notes/commits"), updates.at(-1).newOid);
+  t.is(
+    git(dir, "ls-tree -r --name-only refs/notes/commits"),
+    `${prefix}/${rest}`,
+  );
+  repo.noteCreate(null, author, committer, second, "top level\n", false);
+  t.is(git(dir, "rev-parse refs/notes/commits"), updates.at(-1).newOid);
+  repo.noteDelete(null, author, committer, first);
+  t.is(git(dir, "rev-parse refs/notes/commits"), updates.at(-1).newOid);
+  t.is(repo.note(null, second).message(), "top level\n");
+});
@@ -0,0 +1,30 @@
+import test from "ava";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+test("GitObject should downcast to the wrapper of its kind", (t) => {
+  const { dir, repo } = buildRepo("object", (builder) =>
+    builder.commit({ message: "init", files: { "a.txt": "a\n" } }),
+  );
+  git(dir, "config user.name tagger");
+  git(dir, "config user.email tagger@example.com");
+  git(dir, "tag -a v1.0.0 -m release");
+  const lookup = (revision) =>
+    repo.findObjectByPrefix(git(dir, `rev-parse ${revision}`));
+
+  const head = repo.findCommit(git(dir, "rev-parse HEAD"));
+  t.is(head.asObject().asCommit().id(), head.id());
+  const commit = lookup("HEAD");
+  t.is(commit.asTree(), null);
+  t.is(commit.asBlob(), null);
+  t.is(commit.asTag(), null);
+
+  const tree = lookup("HEAD^{tree}").asTree();
+  t.is(tree.getName("a.txt").id(), git(dir, "rev-parse HEAD:a.txt"));
+  const blob = lookup("HEAD:a.txt").asBlob();
+  t.is(Buffer.from(blob.content()).toString(), "a\n");
+  const tag = lookup("v1.0.0").asTag();
+  t.is(tag.name(), "v1.0.0");
+  t.is(tag.targetId(), head.id());
+  t.is(lookup("v1.0.0").asCommit(), null);
+});
@@ -0,0 +1,21 @@
+import test from "ava";
+
+import { ObjectType, Odb, Repository } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+test("Odb should hash, write, find and read objects", (t) => {
+  const dir = initRepo("odb");
+  const odb = new Repository(dir).odb();
+  const data = Buffer.from("hello\n");
+  const oid = Odb.hash(ObjectType.Blob, data);
+  t.is(oid, git(dir, "hash-object --stdin", "hello\n"));
+  t.false(odb.exists(oid));
+  t.is(odb.write(ObjectType.Blob, data), oid);
+  t.true(odb.exists(oid));
+  t.is(odb.existsPrefix(oid.slice(0, 7)), oid);
+  const object = odb.read(oid);
+  t.is(object.kind(), ObjectType.Blob);
+  t.is(Buffer.from(object.data()).toString(), "hello\n");
+  t.is(object.size(), 6n);
+});
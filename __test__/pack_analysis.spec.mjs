@@ -0,0 +1,37 @@
+import { statSync, writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { Repository } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+test("packAnalysis should match git verify-pack", async (t) => {
+  const dir = initRepo("pack");
+  let content = "";
+  for (let i = 0; i < 20; i++) {
+    content += `line ${i} ${"x".repeat(200)}\n`;
+    writeFileSync(join(dir, "file.txt"), content);
+    git(dir, "add file.txt");
+    git(dir, `commit -q -m "change ${i}"`);
+  }
+  git(dir, "repack -adfq --depth=50");
+  const repo = new Repository(dir);
+  const [pack] = await repo.packAnalysis();
+  const index = pack.packFile.replace(/pack$/, "idx");
+  const verify = git(dir, `verify-pack -v ${index}`).split("\n");
+  const objects = verify.filter((line) => /^[0-9a-f]{40} /.test(line));
+  const depths = verify
+    .map((line) => /^chain length = (\d+):/.exec(line))
+    .filter(Boolean)
+    .map(([, depth]) => Number(depth));
+  t.is(pack.objects, objects.length);
+  t.is(pack.maxDeltaDepth, Math.max(...depths));
+  t.true(pack.maxDeltaDepth > 0);
+  t.is(pack.largestObject.size, content.length);
+  t.is(pack.totalSize, statSync(pack.packFile).size);
+  await t.throwsAsync(repo.packAnalysis(AbortSignal.abort()), {
+    code: "Aborted",
+  });
+});
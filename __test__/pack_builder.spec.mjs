@@ -0,0 +1,55 @@
+import { readdirSync } from "node:fs";
+
+import test from "ava";
+
+import { buildRepo, git, tempDir } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  t.context = buildRepo("pack-builder", (builder) =>
+    builder
+      .commit({ message: "a", files: { "a.txt": "a\n" } })
+      .commit({ message: "b", files: { "b.txt": "b\n" } }),
+  );
+});
+
+test("PackBuilder should pack the commits of a revwalk", (t) => {
+  const { repo } = t.context;
+  const walk = repo.revWalk().pushBetween("HEAD~1", "HEAD");
+  const builder = repo.packbuilder().insertWalk(walk);
+  t.deepEqual([...walk], []);
+  // The commit, its tree and the new blob
+  t.is(builder.objectCount(), 3);
+  t.is(builder.writeBuf().subarray(0, 4).toString(), "PACK");
+});
+
+test("PackBuilder should write a packfile with its index", (t) => {
+  const { dir, repo } = t.context;
+  const out = tempDir("pack-builder-out");
+  const builder = repo.packbuilder().insertCommit(git(dir, "rev-parse HEAD"));
+  builder.setThreads(1);
+  const stages = new Set();
+  const name = builder.writeFile(out, (stage, current, total) => {
+    stages.add(stage);
+    t.true(current <= total);
+  });
+  t.deepEqual(readdirSync(out).sort(), [
+    `pack-${name}.idx`,
+    `pack-${name}.pack`,
+  ]);
+  t.true(stages.has("deltafication"));
+  t.notThrows(() => git(out, `verify-pack pack-${name}.idx`));
+});
+
+test("PackBuilder.writeFile should rethrow the progress errors", (t) => {
+  const { dir, repo } = t.context;
+  const out = tempDir("pack-builder-out");
+  const tree = git(dir, "rev-parse HEAD^{tree}");
+  const builder = repo.packbuilder().insertTree(tree);
+  t.throws(
+    () =>
+      builder.writeFile(out, () => {
+        throw new Error("Stop");
+      }),
+    { message: "Stop" },
+  );
+});
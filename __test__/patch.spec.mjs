@@ -0,0 +1,69 @@
+import { writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { Patch } from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  t.context = buildRepo("patch", (builder) =>
+    builder.commit({
+      message: "base",
+      files: {
+        "main.js":
+          "const total = count + 1;\nconsole.log(total);\nexport default 1;\n",
+      },
+    }),
+  );
+});
+
+test("lineDiffRegions should highlight the changed words", (t) => {
+  const { dir, repo } = t.context;
+  writeFileSync(
+    join(dir, "main.js"),
+    "const total = amount + 1;\nconsole.log(total);\nimport { x } from 'y';\n",
+  );
+  const patch = repo.diffIndexToWorkdir().patch(0);
+  t.deepEqual(patch.lineDiffRegions(0), [
+    {
+      oldLineNo: 1,
+      newLineNo: 1,
+      regions: [{ oldStart: 14, oldLen: 5, newStart: 14, newLen: 6 }],
+    },
+    {
+      oldLineNo: 3,
+      newLineNo: 3,
+      regions: [{ oldStart: 0, oldLen: 17, newStart: 0, newLen: 22 }],
+    },
+  ]);
+  const [first] = patch.lineDiffRegions(0, "char");
+  t.deepEqual(first.regions, [
+    { oldStart: 14, oldLen: 1, newStart: 14, newLen: 2 },
+  ]);
+});
+
+test("Patch should expose the hunks and lines of a delta", (t) => {
+  const { dir } = t.context;
+  writeFileSync(
+    join(dir, "main.js"),
+    "const total = count + 2;\nconsole.log(total);\nexport default 1;\n",
+  );
+  const diff = t.context.repo.diffIndexToWorkdir();
+  const patch = Patch.fromDiff(diff, 0);
+  t.is(patch.numHunks(), 1);
+  const hunk = patch.hunk(0);
+  t.is(hunk.oldStart(), 1);
+  t.is(hunk.newLines(), 3);
+  t.is(hunk.header(), "@@ -1,3 +1,3 @@\n");
+  t.is(patch.numLinesInHunk(0), 4);
+  const lines = Array.from({ length: 4 }, (_, i) => patch.line(0, i));
+  t.deepEqual(lines.map((line) => line.origin()), ["-", "+", " ", " "]);
+  t.is(lines[0].content().toString(), "const total = count + 1;\n");
+  t.is(lines[0].newLineno(), null);
+  t.is(lines[1].newLineno(), 1);
+  t.is(lines[2].oldLineno(), 2);
+  t.is(patch.toBuffer().toString(), git(dir, "diff") + "\n");
+  t.throws(() => patch.hunk(1));
+});
@@ -0,0 +1,50 @@
+import { writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { Pathspec, PathspecFlags } from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  t.context = buildRepo("pathspec", (builder) =>
+    builder.commit({
+      message: "base",
+      files: {
+        "README.md": "base\n",
+        "src/lib.rs": "base\n",
+        "src/main.rs": "base\n",
+      },
+    }),
+  );
+});
+
+test("pathspec should match the workdir, the index and a diff", (t) => {
+  const { dir, repo } = t.context;
+  const pathspec = new Pathspec(["src/*.rs"]);
+  t.true(pathspec.matchesPath("src/lib.rs"));
+  t.false(pathspec.matchesPath("README.md"));
+  const sources = ["src/lib.rs", "src/main.rs"];
+  t.deepEqual([...pathspec.matchWorkdir(repo)], sources);
+  t.deepEqual([...pathspec.matchIndex(repo.index())], sources);
+  writeFileSync(join(dir, "src/main.rs"), "changed\n");
+  writeFileSync(join(dir, "README.md"), "changed\n");
+  const diff = repo.diffTreeToWorkdir(repo.head().peelToTree());
+  t.deepEqual([...pathspec.matchDiff(diff)], ["src/main.rs"]);
+});
+
+test("pathspec flags should be honored", (t) => {
+  const { repo } = t.context;
+  const pathspec = new Pathspec(["SRC/LIB.RS", "missing"]);
+  t.deepEqual([...pathspec.matchWorkdir(repo, PathspecFlags.UseCase)], []);
+  const list = pathspec.matchWorkdir(
+    repo,
+    PathspecFlags.IgnoreCase | PathspecFlags.FindFailures,
+  );
+  t.deepEqual([...list], ["src/lib.rs"]);
+  t.deepEqual(list.failedEntries(), ["missing"]);
+  t.throws(() =>
+    pathspec.matchIndex(repo.index(), PathspecFlags.NoMatchError),
+  );
+});
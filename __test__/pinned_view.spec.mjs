@@ -0,0 +1,43 @@
+import { writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { TreeWalkMode } from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  t.context = buildRepo("pinned-view", (builder) =>
+    builder.commit({ message: "first", files: { "a.txt": "a\n" } }),
+  );
+});
+
+test("pinnedView should keep reading the pinned commit", (t) => {
+  const { dir, repo } = t.context;
+  const first = git(dir, "rev-parse HEAD");
+  const view = repo.pinnedView();
+  writeFileSync(join(dir, "a.txt"), "changed\n");
+  writeFileSync(join(dir, "b.txt"), "b\n");
+  git(dir, "add -A");
+  git(dir, "commit -q -m second");
+
+  t.is(view.commitId(), first);
+  t.deepEqual(view.lsFiles(), ["a.txt"]);
+  t.is(view.fileAtRevision("a.txt").toString(), "a\n");
+  t.is(view.fileAtRevision("b.txt"), null);
+  const entries = [];
+  view.treeWalk(TreeWalkMode.PreOrder, (root, entry) => {
+    entries.push(entry.name());
+  });
+  t.deepEqual(entries, ["a.txt"]);
+
+  t.deepEqual(repo.pinnedView().lsFiles(), ["a.txt", "b.txt"]);
+  const second = repo.pinnedView("HEAD");
+  t.deepEqual(
+    second.commitRange(first).map(({ summary }) => summary),
+    ["second"],
+  );
+  t.is(second.diffCommits(first).len(), 2);
+  t.deepEqual(view.commitRange(first), []);
+});
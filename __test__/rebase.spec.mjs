@@ -0,0 +1,52 @@
+import test from "ava";
+
+import { RebaseOperationType, Signature } from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  t.context = buildRepo("rebase", (builder) =>
+    builder
+      .commit({ message: "init", files: { "a.txt": "a\n" } })
+      .branch("feature")
+      .commit({ message: "feature 1", files: { "b.txt": "b\n" } })
+      .commit({ message: "feature 2", files: { "c.txt": "c\n" } })
+      .checkout("main")
+      .commit({ message: "main", files: { "d.txt": "d\n" } }),
+  );
+});
+
+test("rebase should replay the commits of a branch onto another", (t) => {
+  const { dir, repo } = t.context;
+  const picked = [
+    git(dir, "rev-parse feature~1"),
+    git(dir, "rev-parse feature"),
+  ];
+  const committer = Signature.now("test", "test@example.com");
+  const rebase = repo.rebaseInit("feature", "main");
+  t.is(rebase.len(), 2);
+  t.is(rebase.operationCurrent(), null);
+  const operations = [];
+  for (let operation; (operation = rebase.next()); ) {
+    t.is(operation.kind, RebaseOperationType.Pick);
+    operations.push(operation.id);
+    rebase.commit(null, committer);
+  }
+  rebase.finish(committer);
+  t.deepEqual(operations, picked);
+  t.is(git(dir, "rev-parse feature~2"), git(dir, "rev-parse main"));
+  t.is(git(dir, "log -1 --format=%s feature"), "feature 2");
+  t.is(git(dir, "symbolic-ref HEAD"), "refs/heads/feature");
+});
+
+test("abort should restore the repository", (t) => {
+  const { dir, repo } = t.context;
+  const feature = git(dir, "rev-parse feature");
+  const rebase = repo.rebaseInit("feature", "main");
+  rebase.next();
+  t.is(rebase.operationCurrent(), 0);
+  repo.rebaseOpen().abort();
+  t.is(git(dir, "rev-parse feature"), feature);
+  t.is(git(dir, "symbolic-ref HEAD"), "refs/heads/feature");
+  t.is(git(dir, "status --porcelain"), "");
+});
@@ -0,0 +1,167 @@
+import { writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { Repository, Signature } from "../index.js";
+
+import { git, initRepo, tempDir } from "./helpers.mjs";
+
+const rejected = { message: /rejected by the ref update guard$/ };
+
+test.beforeEach((t) => {
+  const dir = initRepo("guard");
+  git(dir, "commit -q --allow-empty -m init");
+  const repo = new Repository(dir);
+  t.context = { dir, repo, updates: [], reject: () => false };
+  repo.setRefUpdateGuard((update) => {
+    t.context.updates.push(update);
+    if (update.kind === "delete" && update.refname.startsWith("refs/tags/")) {
+      return `Deleting ${update.refname} is not allowed`;
+    }
+    return !t.context.reject(update);
+  });
+});
+
+test("Guard should allow tag creation and reject tag deletion", (t) => {
+  const { dir, repo, updates } = t.context;
+  const head = repo.head().target();
+  repo.tagLightweight("v1.0.0", repo.findCommit(head).asObject(), false);
+  t.is(git(dir, "rev-parse v1.0.0"), head);
+  t.throws(() => repo.tagDelete("v1.0.0"), {
+    message: "Deleting refs/tags/v1.0.0 is not allowed",
+  });
+  t.is(git(dir, "rev-parse v1.0.0"), head);
+  t.deepEqual(updates, [
+    { refname: "refs/tags/v1.0.0", newOid: head, kind: "create" },
+    { refname: "refs/tags/v1.0.0", oldOid: head, kind: "delete" },
+  ]);
+});
+
+test("Guard should see the id of an annotated tag before it is written", (t) => {
+  const { dir, repo, updates } = t.context;
+  const target = repo.findCommit(repo.head().target()).asObject();
+  const tagger = new Signature("test", "test@example.com", 1700000000);
+  t.context.reject = (update) => update.refname === "refs/tags/v2.0.0";
+  t.throws(() => repo.tag("v2.0.0", target, tagger, "v2\n", false), rejected);
+  const { newOid } = updates.at(-1);
+  t.throws(() => git(dir, `cat-file -e ${newOid}`));
+  t.context.reject = () => false;
+  t.is(repo.tag("v2.0.0", target, tagger, "v2\n", false), newOid);
+  t.is(git(dir, "rev-parse v2.0.0"), newOid);
+});
+
+test("Guard should check the reference updated by amend", (t) => {
+  const { dir, repo, updates } = t.context;
+  const head = git(dir, "rev-parse HEAD");
+  const commit = repo.findCommit(head);
+  const amend = () => commit.amend("HEAD", null, null, "ISO-8859-1", "amend");
+  t.context.reject = (update) => update.refname === "refs/heads/main";
+  t.throws(amend, rejected);
+  t.is(git(dir, "rev-parse HEAD"), head);
+  const { newOid } = updates.at(-1);
+  t.throws(() => git(dir, `cat-file -e ${newOid}`));
+  t.context.reject = () => false;
+  t.is(amend(), newOid);
+  t.deepEqual(updates.at(-1), {
+    refname: "refs/heads/main",
+    oldOid: head,
+    newOid,
+    kind: "update",
+  });
+  t.is(git(dir, "rev-parse HEAD"), newOid);
+});
+
+test("Guard should check the references updated by a rebase", (t) => {
+  const { dir, repo, updates } = t.context;
+  git(dir, "checkout -q -b feature");
+  writeFileSync(join(dir, "a.txt"), "a\n");
+  git(dir, "add a.txt");
+  git(dir, "commit -q -m feature");
+  git(dir, "checkout -q main");
+  git(dir, "commit -q --allow-empty -m main");
+  const feature = git(dir, "rev-parse feature");
+  const committer = new Signature("test", "test@example.com", 1700000000);
+  const rebase = repo.rebaseInit("feature", "main");
+  rebase.next();
+  const detached = git(dir, "rev-parse HEAD");
+  t.context.reject = (update) => update.refname === "HEAD";
+  t.throws(() => rebase.commit(null, committer), rejected);
+  t.is(git(dir, "rev-parse HEAD"), detached);
+  const { newOid } = updates.at(-1);
+  t.context.reject = (update) => update.refname === "refs/heads/feature";
+  t.is(rebase.commit(null, committer), newOid);
+  t.is(git(dir, "rev-parse HEAD"), newOid);
+  t.throws(() => rebase.finish(committer), rejected);
+  t.deepEqual(updates.at(-1), {
+    refname: "refs/heads/feature",
+    oldOid: feature,
+    newOid,
+    kind: "update",
+  });
+  t.is(git(dir, "rev-parse feature"), feature);
+  t.context.reject = () => false;
+  rebase.finish(committer);
+  t.is(git(dir, "rev-parse feature"), newOid);
+});
+
+test("Guard should check the branch created by worktreeAdd", (t) => {
+  const { dir, repo, updates } = t.context;
+  const path = join(tempDir("guard-worktree"), "wt");
+  t.context.reject = (update) => update.refname === "refs/heads/wt";
+  t.throws(() => repo.worktreeAdd("wt", path), rejected);
+  t.deepEqual(updates.at(-1), {
+    refname: "refs/heads/wt",
+    newOid: git(dir, "rev-parse HEAD"),
+    kind: "create",
+  });
+  t.throws(() => git(dir, "rev-parse --verify -q refs/heads/wt"));
+  t.deepEqual(repo.worktrees(), []);
+});
+
+test("Guard should check the references kept by branch recovery", (t) => {
+  const { dir, repo } = t.context;
+  git(dir, "branch feature");
+  repo.enableBranchRecovery();
+  t.context.reject = (update) => update.refname.startsWith("refs/deleted/");
+  t.throws(() => repo.findReference("refs/heads/feature").delete(), rejected);
+  t.is(git(dir, "rev-parse feature"), git(dir, "rev-parse HEAD"));
+  t.context.reject = () => false;
+  repo.findReference("refs/heads/feature").delete();
+  const [deleted] = repo.recoverDeletedBranches();
+  t.context.reject = (update) =>
+    update.refname === deleted.entry && update.kind === "delete";
+  t.throws(() => repo.restoreDeletedBranch(deleted.entry), rejected);
+  t.throws(() => git(dir, "rev-parse --verify -q refs/heads/feature"));
+  t.is(repo.recoverDeletedBranches().length, 1);
+});
+
+test("Guard should check the staging references of finalizeFetched", (t) => {
+  const { dir, repo } = t.context;
+  const upstream = initRepo("guard-upstream");
+  git(upstream, "commit -q --allow-empty -m upstream");
+  const fetched = repo.fetchIntoOdb(upstream, [
+    "refs/heads/main:refs/remotes/upstream/main",
+  ]);
+  t.true(fetched.complete);
+  t.context.reject = (update) =>
+    update.refname.startsWith("refs/fetch-staging/");
+  t.throws(() => repo.finalizeFetched(fetched), rejected);
+  t.not(git(dir, "for-each-ref refs/fetch-staging"), "");
+});
+
+test("Guard should fail on a cycle of symbolic references", (t) => {
+  const { dir, repo } = t.context;
+  git(dir, "symbolic-ref refs/heads/ping refs/heads/pong");
+  git(dir, "symbolic-ref refs/heads/pong refs/heads/ping");
+  const signature = new Signature("test", "test@example.com", 1700000000);
+  const tree = repo.head().peelToTree();
+  t.throws(
+    () => repo.commit("refs/heads/ping", signature, signature, "ping", tree),
+    {
+      message:
+        "Symbolic reference cycle: " +
+        "[refs/heads/ping -> refs/heads/pong -> refs/heads/ping]",
+    },
+  );
+});
@@ -0,0 +1,127 @@
+import test from "ava";
+
+import { ObjectType, Repository } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  const dir = initRepo("reference");
+  git(dir, "commit -q --allow-empty -m first");
+  const first = git(dir, "rev-parse HEAD");
+  git(dir, "commit -q --allow-empty -m second");
+  const second = git(dir, "rev-parse HEAD");
+  t.context = { dir, first, second, repo: new Repository(dir) };
+});
+
+test("rename should report the renamed reference", (t) => {
+  const { dir, second, repo } = t.context;
+  const renamed = repo
+    .head()
+    .rename("refs/heads/renamed", false, "rename", second);
+  t.is(renamed.reference.name(), "refs/heads/renamed");
+  t.is(renamed.oldName, "refs/heads/main");
+  t.is(renamed.newName, "refs/heads/renamed");
+  t.is(renamed.target, second);
+  t.is(git(dir, "rev-parse renamed"), second);
+});
+
+test("rename should fail with Modified when the reference moved", (t) => {
+  const { dir, first, second, repo } = t.context;
+  const reference = repo.head();
+  git(dir, `update-ref refs/heads/main ${first}`);
+  const error = t.throws(() =>
+    reference.rename("refs/heads/renamed", false, "rename", second),
+  );
+  t.is(error.code, "Modified");
+  t.is(error.actual, first);
+  t.is(git(dir, "rev-parse main"), first);
+});
+
+test("created references should round trip through findReference", (t) => {
+  const { first, repo } = t.context;
+  repo.createReference("refs/heads/foo", first, false, "create foo");
+  const foo = repo.findReference("refs/heads/foo");
+  t.is(foo.target(), first);
+  t.is(repo.resolveReferenceFromShortName("foo").name(), "refs/heads/foo");
+  const symbolic = repo.createSymbolicReference(
+    "refs/heads/alias",
+    "refs/heads/foo",
+    false,
+    "create alias",
+  );
+  t.is(symbolic.symbolicTarget(), "refs/heads/foo");
+  t.throws(() =>
+    repo.createReference("refs/heads/foo", first, false, "create foo"),
+  );
+  symbolic.delete();
+  foo.delete();
+  t.is(repo.findReference("refs/heads/foo"), null);
+});
+
+test("deleting the checked out branch should fail", (t) => {
+  const { repo } = t.context;
+  t.throws(() => repo.findReference("refs/heads/main").delete(), {
+    message:
+      "Cannot delete branch [refs/heads/main] as it is the current HEAD of the repository",
+  });
+});
+
+test("references should iterate lazily and match globs", (t) => {
+  const { dir, first, repo } = t.context;
+  git(dir, `tag v1 ${first}`);
+  git(dir, "tag v2");
+  const names = [...repo.references()].map((reference) => reference.name());
+  t.deepEqual(names.sort(), [
+    "refs/heads/main",
+    "refs/tags/v1",
+    "refs/tags/v2",
+  ]);
+  const tags = repo.referencesGlob("refs/tags/*")[Symbol.iterator]();
+  const v1 = tags.next().value;
+  t.is(v1.name(), "refs/tags/v1");
+  t.is(v1.target(), first);
+  t.deepEqual(
+    [...repo.referenceNames("refs/tags/*")],
+    ["refs/tags/v1", "refs/tags/v2"],
+  );
+  t.deepEqual(repo.referenceNamesGlob("refs/tags/*"), [
+    "refs/tags/v1",
+    "refs/tags/v2",
+  ]);
+  t.is([...repo.referenceNames()].length, 3);
+});
+
+test("annotated tags should peel through to their target", (t) => {
+  const { dir, first, repo } = t.context;
+  git(dir, `tag -a -m release v1 ${first}`);
+  const blob = git(dir, "hash-object -w --stdin < /dev/null");
+  git(dir, `tag blob ${blob}`);
+  const tag = repo.findReference("refs/tags/v1");
+  t.is(tag.peelToCommit().id(), first);
+  t.is(tag.peelToTag().name(), "v1");
+  t.is(tag.peel(ObjectType.Commit).id(), first);
+  t.is(tag.peel(ObjectType.Tree).kind(), ObjectType.Tree);
+  t.is(repo.findReference("refs/tags/blob").peelToBlob().id(), blob);
+  t.throws(() => repo.head().peelToTag());
+});
+
+test("deleted branches should be recoverable", (t) => {
+  const { dir, first, repo } = t.context;
+  git(dir, `branch feature ${first}`);
+  repo.findReference("refs/heads/feature").delete();
+  t.deepEqual(repo.recoverDeletedBranches(), []);
+  git(dir, `branch feature ${first}`);
+  repo.enableBranchRecovery();
+  repo.findReference("refs/heads/feature").delete();
+  t.is(repo.findReference("refs/heads/feature"), null);
+  const [deleted, ...rest] = repo.recoverDeletedBranches();
+  t.is(rest.length, 0);
+  t.is(deleted.name, "feature");
+  t.is(deleted.oid, first);
+  t.is(deleted.entry, `refs/deleted/feature@${deleted.deletedAtMs}`);
+  t.true(Math.abs(Date.now() - deleted.deletedAtMs) < 60_000);
+  const restored = repo.restoreDeletedBranch(deleted.entry);
+  t.is(restored.name(), "refs/heads/feature");
+  t.is(git(dir, "rev-parse feature"), first);
+  t.deepEqual(repo.recoverDeletedBranches(), []);
+});
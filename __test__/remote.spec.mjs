@@ -0,0 +1,825 @@
+import { execFileSync, spawn } from "node:child_process";
+import { X509Certificate } from "node:crypto";
+import { getEventListeners } from "node:events";
+import { existsSync, readFileSync, writeFileSync } from "node:fs";
+import { createServer } from "node:http";
+import { connect } from "node:net";
+import { join } from "node:path";
+
+import test from "ava";
+
+import {
+  CertificateKind,
+  Cred,
+  CredentialType,
+  Direction,
+  FetchOptions,
+  getUserAgent,
+  ProxyOptions,
+  PushOptions,
+  RemoteCallbacks,
+  RepoBuilder,
+  Repository,
+  setUserAgent,
+} from "../index.js";
+
+import { git, initRepo, tempDir } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  const dir = tempDir("remote");
+  const local = initRepo("remote-local");
+  const origin = join(dir, "origin.git");
+  git(dir, `init -q --bare ${origin}`);
+  git(local, "commit -q --allow-empty -m init");
+  git(local, `remote add origin ${origin}`);
+  t.context = { dir, local, origin, repo: new Repository(local) };
+});
+
+test("pushAsync should push from the thread pool", async (t) => {
+  const { local, origin, repo } = t.context;
+  let progressCalls = 0;
+  const callbacks = new RemoteCallbacks().pushTransferProgress(() => {
+    progressCalls++;
+  });
+  await repo
+    .findRemote("origin")
+    .pushAsync(
+      ["refs/heads/main:refs/heads/main"],
+      new PushOptions().remoteCallback(callbacks),
+    );
+  t.is(git(origin, "rev-parse main"), git(local, "rev-parse main"));
+  t.true(progressCalls > 0);
+});
+
+test("Errors thrown by callbacks should reject the push", async (t) => {
+  const { repo } = t.context;
+  const callbacks = new RemoteCallbacks().pushTransferProgress(() => {
+    throw new Error("progress failed");
+  });
+  await t.throwsAsync(
+    () =>
+      repo
+        .findRemote("origin")
+        .pushAsync(
+          ["refs/heads/main:refs/heads/main"],
+          new PushOptions().remoteCallback(callbacks),
+        ),
+    { message: "progress failed" },
+  );
+});
+
+test("push callbacks should report the pushed refs", async (t) => {
+  const { local, origin, repo } = t.context;
+  const negotiated = [];
+  const updated = [];
+  const callbacks = new RemoteCallbacks()
+    .pushNegotiation((updates) => {
+      negotiated.push(...updates);
+    })
+    .updateReferences((refname, status) => {
+      updated.push([refname, status]);
+    });
+  await repo
+    .findRemote("origin")
+    .pushAsync(
+      ["refs/heads/main:refs/heads/main"],
+      new PushOptions().remoteCallback(callbacks),
+    );
+  t.deepEqual(negotiated, [
+    {
+      srcRefname: "refs/heads/main",
+      dstRefname: "refs/heads/main",
+      src: "0".repeat(40),
+      dst: git(local, "rev-parse main"),
+    },
+  ]);
+  t.deepEqual(updated, [["refs/heads/main", null]]);
+  t.is(git(origin, "rev-parse main"), git(local, "rev-parse main"));
+});
+
+test("pushNegotiation should be able to cancel the push", async (t) => {
+  const { origin, repo } = t.context;
+  const callbacks = new RemoteCallbacks().pushNegotiation(() => false);
+  await t.throwsAsync(() =>
+    repo
+      .findRemote("origin")
+      .pushAsync(
+        ["refs/heads/main:refs/heads/main"],
+        new PushOptions().remoteCallback(callbacks),
+      ),
+  );
+  t.throws(() => git(origin, "rev-parse --verify -q main"));
+});
+
+test("connectAndListRefs should list the advertised refs", (t) => {
+  const { local, repo } = t.context;
+  git(local, "push -q origin main");
+  const head = git(local, "rev-parse main");
+  const remote = repo.findRemote("origin");
+  const refs = [...remote.connectAndListRefs(Direction.Fetch)];
+  t.true(remote.connected());
+  t.deepEqual(
+    refs.map(({ name, oid, symrefTarget }) => ({ name, oid, symrefTarget })),
+    [{ name: "refs/heads/main", oid: head, symrefTarget: undefined }],
+  );
+  t.deepEqual([...remote.listRefs()], refs);
+  remote.disconnect();
+  const withOptions = remote.connectAndListRefs(
+    Direction.Fetch,
+    new FetchOptions(),
+  );
+  t.deepEqual([...withOptions], refs);
+});
+
+test("refspecs should describe the configured refspecs", (t) => {
+  const { local, repo } = t.context;
+  git(local, "config --add remote.origin.push refs/heads/main:refs/heads/rc");
+  const remote = repo.findRemote("origin");
+  t.deepEqual(remote.fetchRefspecs(), ["+refs/heads/*:refs/remotes/origin/*"]);
+  t.deepEqual(remote.pushRefspecs(), ["refs/heads/main:refs/heads/rc"]);
+  const refspecs = remote.refspecs();
+  t.deepEqual(
+    refspecs.map(({ direction, src, dst, force, str }) => ({
+      direction,
+      src,
+      dst,
+      force,
+      str,
+    })),
+    [
+      {
+        direction: Direction.Fetch,
+        src: "refs/heads/*",
+        dst: "refs/remotes/origin/*",
+        force: true,
+        str: "+refs/heads/*:refs/remotes/origin/*",
+      },
+      {
+        direction: Direction.Push,
+        src: "refs/heads/main",
+        dst: "refs/heads/rc",
+        force: false,
+        str: "refs/heads/main:refs/heads/rc",
+      },
+    ],
+  );
+  const [fetch, push] = refspecs;
+  t.true(fetch.srcMatches("refs/heads/main"));
+  t.false(fetch.srcMatches("refs/tags/main"));
+  t.true(fetch.dstMatches("refs/remotes/origin/main"));
+  t.false(fetch.dstMatches("refs/heads/main"));
+  t.true(push.srcMatches("refs/heads/main"));
+  t.false(push.srcMatches("refs/heads/feature"));
+  t.true(push.dstMatches("refs/heads/rc"));
+});
+
+test("updateTips should report the refs updated by fetch", (t) => {
+  const { local, origin, repo } = t.context;
+  git(local, `push -q ${origin} main`);
+  const updates = [];
+  const callbacks = new RemoteCallbacks().updateTips(
+    (refname, oldOid, newOid) => {
+      updates.push({ refname, oldOid, newOid });
+      return true;
+    },
+  );
+  repo
+    .findRemote("origin")
+    .fetch(
+      ["refs/heads/*:refs/remotes/origin/*"],
+      new FetchOptions().remoteCallback(callbacks),
+    );
+  t.deepEqual(updates, [
+    {
+      refname: "refs/remotes/origin/main",
+      oldOid: "0".repeat(40),
+      newOid: git(local, "rev-parse main"),
+    },
+  ]);
+});
+
+test("sidebandProgress should receive the progress of the remote", (t) => {
+  const { local, origin } = t.context;
+  git(local, `push -q ${origin} main`);
+  const fetch = (callback) => {
+    const dir = initRepo("remote-sideband");
+    git(dir, `remote add origin ${origin}`);
+    const callbacks = new RemoteCallbacks().sidebandProgress(callback);
+    new Repository(dir)
+      .findRemote("origin")
+      .fetch(["main"], new FetchOptions().remoteCallback(callbacks));
+    return dir;
+  };
+  let progress = "";
+  const dir = fetch((data) => {
+    progress += data.toString("utf8");
+    return true;
+  });
+  // The commit and its empty tree
+  const lines = progress.split(/[\r\n]+/);
+  t.true(lines.includes("Counting objects 2"));
+  t.true(progress.endsWith("Compressing objects: 100% (2/2), done\n"));
+  t.is(git(dir, "rev-parse FETCH_HEAD"), git(local, "rev-parse main"));
+  let calls = 0;
+  t.throws(() =>
+    fetch(() => {
+      calls++;
+      return false;
+    }),
+  );
+  t.is(calls, 1);
+});
+
+test("outgoingCommits and incomingCommits should list the upstream range", (t) => {
+  const { dir, local, origin } = t.context;
+  git(local, "push -q -u origin main");
+  const other = join(dir, "other");
+  git(dir, `clone -q -b main ${origin} ${other}`);
+  git(other, "config user.email other@example.com");
+  git(other, "config user.name other");
+  git(other, "commit -q --allow-empty -m incoming");
+  git(other, "push -q origin main");
+  git(local, "commit -q --allow-empty -m first");
+  git(local, "commit -q --allow-empty -m second");
+  git(local, "fetch -q origin");
+  const repo = new Repository(local);
+  t.deepEqual(
+    repo
+      .outgoingCommits()
+      .map(({ summary, authorName }) => [summary, authorName]),
+    [
+      ["second", "test"],
+      ["first", "test"],
+    ],
+  );
+  const incoming = repo.incomingCommits("main");
+  t.deepEqual(
+    incoming.map(({ oid, summary }) => [oid, summary]),
+    [[git(local, "rev-parse origin/main"), "incoming"]],
+  );
+  t.is(typeof incoming[0].timeMs, "number");
+});
+
+test("outgoingCommits should fail with NoUpstream without upstream", (t) => {
+  const { repo } = t.context;
+  t.throws(() => repo.outgoingCommits(), { code: "NoUpstream" });
+});
+
+test("fetchAll should fetch every remote and report failures", (t) => {
+  const { dir, local, origin, repo } = t.context;
+  git(local, `push -q ${origin} main`);
+  git(local, `remote add broken ${join(dir, "missing.git")}`);
+  const remotes = [];
+  const results = repo.fetchAll((remote) => {
+    remotes.push(remote);
+    return new FetchOptions();
+  });
+  t.deepEqual(remotes, ["broken", "origin"]);
+  const [broken, fetched] = results;
+  t.is(broken.remote, "broken");
+  t.false(broken.ok);
+  t.is(typeof broken.error, "string");
+  t.is(fetched.remote, "origin");
+  t.true(fetched.ok);
+  t.is(typeof fetched.receivedBytes, "number");
+  t.is(git(local, "rev-parse origin/main"), git(local, "rev-parse main"));
+});
+
+test("setUserAgent should round-trip", (t) => {
+  t.regex(getUserAgent(), /^libgit2 /);
+  setUserAgent("simple-git-test/1.0");
+  t.is(getUserAgent(), "simple-git-test/1.0");
+});
+
+test("fetch results should report the transport", (t) => {
+  const { local, origin, repo } = t.context;
+  git(local, `push -q ${origin} main`);
+  git(local, `remote set-url origin file://${origin}`);
+  const [fetched] = repo.fetchAll();
+  t.true(fetched.ok);
+  t.deepEqual(fetched.transport, { protocol: "file" });
+  const transport = (url) => {
+    git(local, `remote set-url origin ${url}`);
+    return repo.findRemote("origin").transport().protocol;
+  };
+  t.is(transport("https://example.com/repo.git"), "https");
+  t.is(transport("ssh://git@example.com/repo.git"), "ssh");
+  t.is(transport("git@example.com:repo.git"), "ssh");
+  t.is(transport("git://example.com/repo.git"), "git");
+  t.is(transport(origin), "file");
+});
+
+test("FetchOptions should be reusable to retry a failed clone", (t) => {
+  const { dir, local, origin } = t.context;
+  git(local, `push -q ${origin} main`);
+  let attempts = 0;
+  const callbacks = new RemoteCallbacks().transferProgress(() => {
+    if (attempts === 0) {
+      throw new Error("network down");
+    }
+  });
+  const builder = new RepoBuilder()
+    .branch("main")
+    .fetchOptions(new FetchOptions().remoteCallback(callbacks));
+  t.throws(() => builder.clone(`file://${origin}`, join(dir, "first")), {
+    message: "network down",
+  });
+  attempts++;
+  const cloned = builder.clone(`file://${origin}`, join(dir, "second"));
+  t.is(cloned.head().target(), git(local, "rev-parse main"));
+});
+
+test("cloneAsync and fetchAsync should run on the thread pool", async (t) => {
+  const { dir, local, origin } = t.context;
+  git(local, `push -q ${origin} main`);
+  git(origin, "symbolic-ref HEAD refs/heads/main");
+  const cloned = await Repository.cloneAsync(
+    `file://${origin}`,
+    join(dir, "cloned"),
+  );
+  t.is(cloned.head().target(), git(local, "rev-parse main"));
+  git(local, "commit -q --allow-empty -m second");
+  git(local, `push -q ${origin} main`);
+  await cloned
+    .findRemote("origin")
+    .fetchAsync(["refs/heads/*:refs/remotes/origin/*"]);
+  t.is(
+    git(join(dir, "cloned"), "rev-parse origin/main"),
+    git(local, "rev-parse main"),
+  );
+});
+
+test("aborting cloneAsync should interrupt the transfer", async (t) => {
+  const { dir, local, origin } = t.context;
+  for (let i = 0; i < 5; i++) {
+    writeFileSync(join(local, `file${i}.txt`), `${i}\n`);
+    git(local, "add .");
+    git(local, `commit -q -m "commit ${i}"`);
+  }
+  git(local, `push -q ${origin} main`);
+  const controller = new AbortController();
+  let progressCalls = 0;
+  const callbacks = new RemoteCallbacks().transferProgress(() => {
+    progressCalls++;
+    controller.abort();
+  });
+  const builder = new RepoBuilder().fetchOptions(
+    new FetchOptions().remoteCallback(callbacks),
+  );
+  const path = join(dir, "aborted");
+  await t.throwsAsync(
+    builder.cloneAsync(`file://${origin}`, path, controller.signal),
+    { code: "Aborted" },
+  );
+  t.is(progressCalls, 1);
+  await t.throwsAsync(
+    Repository.cloneAsync(`file://${origin}`, path, AbortSignal.abort()),
+    { code: "Aborted" },
+  );
+});
+
+test("aborting fetchAsync and pushAsync should remove the abort listener", async (t) => {
+  const { local, origin } = t.context;
+  writeFileSync(join(local, "a.txt"), "a\n");
+  git(local, "add .");
+  git(local, 'commit -q -m "add a"');
+  git(local, `push -q ${origin} main`);
+  const fetching = initRepo("remote-fetching");
+  const remote = new Repository(fetching).remoteAnonymous(`file://${origin}`);
+  const refspecs = ["refs/heads/*:refs/remotes/origin/*"];
+  const controller = new AbortController();
+  let progressCalls = 0;
+  const callbacks = new RemoteCallbacks().transferProgress(() => {
+    progressCalls++;
+    controller.abort();
+  });
+  await t.throwsAsync(
+    remote.fetchAsync(
+      refspecs,
+      new FetchOptions().remoteCallback(callbacks),
+      controller.signal,
+    ),
+    { code: "Aborted" },
+  );
+  t.is(progressCalls, 1);
+  t.is(getEventListeners(controller.signal, "abort").length, 0);
+
+  const fetched = new AbortController();
+  await remote.fetchAsync(refspecs, null, fetched.signal);
+  t.is(getEventListeners(fetched.signal, "abort").length, 0);
+  t.is(
+    git(fetching, "rev-parse origin/main"),
+    git(local, "rev-parse main"),
+  );
+
+  const pushing = new Repository(local).findRemote("origin");
+  await t.throwsAsync(
+    pushing.pushAsync(
+      ["refs/heads/main:refs/heads/aborted"],
+      null,
+      AbortSignal.abort(),
+    ),
+    { code: "Aborted" },
+  );
+  t.throws(() => git(origin, "rev-parse --verify -q aborted"));
+  const pushed = new AbortController();
+  await pushing.pushAsync(
+    ["refs/heads/main:refs/heads/pushed"],
+    null,
+    pushed.signal,
+  );
+  t.is(getEventListeners(pushed.signal, "abort").length, 0);
+  t.is(git(origin, "rev-parse pushed"), git(local, "rev-parse main"));
+});
+
+test("credentials should be asked again when the remote rejects them", async (t) => {
+  const { local, repo } = t.context;
+  const expected = `Basic ${Buffer.from("user:secret").toString("base64")}`;
+  let authorized = false;
+  const server = createServer((req, res) => {
+    if (req.headers.authorization === expected) {
+      authorized = true;
+      res.writeHead(404).end();
+      return;
+    }
+    res.writeHead(401, { "WWW-Authenticate": 'Basic realm="git"' }).end();
+  });
+  await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+  t.teardown(() => server.close());
+  git(local, `remote add http http://127.0.0.1:${server.address().port}/repo.git`);
+  const infos = [];
+  const callbacks = new RemoteCallbacks().credentials((info) => {
+    infos.push(info);
+    return info.attempt === 1
+      ? Cred.userpassPlaintext("user", "wrong")
+      : { type: "userpassPlaintext", username: "user", password: "secret" };
+  });
+  await t.throwsAsync(() =>
+    repo
+      .findRemote("http")
+      .pushAsync(
+        ["refs/heads/main:refs/heads/main"],
+        new PushOptions().remoteCallback(callbacks),
+      ),
+  );
+  t.true(authorized);
+  t.deepEqual(
+    infos.map((info) => info.attempt),
+    [1, 2],
+  );
+  t.truthy(infos[0].allowedTypes & CredentialType.UserPassPlaintext);
+});
+
+test("ProxyOptions.credentials should authenticate to the proxy", async (t) => {
+  const { local, repo } = t.context;
+  const expected = `Basic ${Buffer.from("proxy user:p@ss").toString("base64")}`;
+  const requests = [];
+  // libgit2 tunnels https remotes through the proxy with CONNECT
+  const proxy = createServer().on("connect", (req, socket) => {
+    const authorized = req.headers["proxy-authorization"] === expected;
+    requests.push([req.url, authorized]);
+    socket.end(
+      authorized
+        ? "HTTP/1.1 502 Bad Gateway\r\nConnection: close\r\n\r\n"
+        : 'HTTP/1.1 407 Proxy Authentication Required\r\nProxy-Authenticate: Basic realm="proxy"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n',
+    );
+  });
+  await new Promise((resolve) => proxy.listen(0, "127.0.0.1", resolve));
+  t.teardown(() => proxy.close());
+  git(local, "remote add https https://git.invalid/repo.git");
+  const proxyOptions = new ProxyOptions()
+    .url(`http://127.0.0.1:${proxy.address().port}`)
+    .credentials("proxy user", "p@ss");
+  await t.throwsAsync(() =>
+    repo
+      .findRemote("https")
+      .pushAsync(
+        ["refs/heads/main:refs/heads/main"],
+        new PushOptions().proxyOptions(proxyOptions),
+      ),
+  );
+  t.deepEqual(requests, [
+    ["git.invalid:443", false],
+    ["git.invalid:443", true],
+  ]);
+});
+
+// The local transport of libgit2 doesn't support shallow fetches, serve the
+// repositories of `dir` through `git daemon` instead.
+const serveGitDaemon = async (t, dir) => {
+  const port = 20000 + Math.floor(Math.random() * 20000);
+  const daemon = spawn("git", [
+    "daemon",
+    "--export-all",
+    "--reuseaddr",
+    "--listen=127.0.0.1",
+    `--port=${port}`,
+    `--base-path=${dir}`,
+    dir,
+  ]);
+  t.teardown(() => daemon.kill());
+  for (let i = 0; i < 100; i++) {
+    const listening = await new Promise((resolve) => {
+      const socket = connect(port, "127.0.0.1")
+        .on("connect", () => {
+          socket.end();
+          resolve(true);
+        })
+        .on("error", () => resolve(false));
+    });
+    if (listening) {
+      return `git://127.0.0.1:${port}`;
+    }
+    await new Promise((resolve) => setTimeout(resolve, 50));
+  }
+  throw new Error("git daemon did not start");
+};
+
+// Serve TLS with a self-signed certificate, returning its URL and the DER
+// encoded certificate
+const serveTls = async (t, dir) => {
+  const key = join(dir, "key.pem");
+  const cert = join(dir, "cert.pem");
+  execFileSync(
+    "openssl",
+    ["req", "-x509", "-newkey", "rsa:2048", "-nodes", "-days", "1"]
+      .concat(["-keyout", key, "-out", cert, "-subj", "/CN=127.0.0.1"]),
+    { stdio: "pipe" },
+  );
+  const port = 20000 + Math.floor(Math.random() * 20000);
+  const server = spawn("openssl", [
+    "s_server",
+    "-www",
+    ...["-accept", `127.0.0.1:${port}`, "-cert", cert, "-key", key],
+  ]);
+  t.teardown(() => server.kill());
+  // s_server serves one connection at a time, a connection probing the port
+  // would keep it busy
+  await new Promise((resolve, reject) => {
+    server.stdout.on("data", (data) => {
+      if (data.toString("utf8").includes("ACCEPT")) {
+        resolve();
+      }
+    });
+    server.on("exit", () => reject(new Error("s_server did not start")));
+  });
+  return {
+    url: `https://127.0.0.1:${port}/origin.git`,
+    der: new X509Certificate(readFileSync(cert)).raw,
+  };
+};
+
+const cloneChecked = (url, path, certificateCheck) =>
+  new RepoBuilder()
+    .fetchOptions(
+      new FetchOptions().remoteCallback(
+        new RemoteCallbacks().certificateCheck(certificateCheck),
+      ),
+    )
+    .clone(url, path);
+
+test("certificateCheck should be able to reject the certificate", async (t) => {
+  const { dir } = t.context;
+  const { url, der } = await serveTls(t, dir);
+  const checked = [];
+  t.throws(
+    () =>
+      cloneChecked(url, join(dir, "rejected"), (cert, host) => {
+        checked.push({ cert, host });
+        return false;
+      }),
+    {
+      message:
+        /Certificate of \[127\.0\.0\.1\] rejected by the certificate check/,
+    },
+  );
+  t.is(checked.length, 1);
+  t.is(checked[0].host, "127.0.0.1");
+  t.is(checked[0].cert.kind, CertificateKind.X509);
+  t.true(checked[0].cert.data.equals(der));
+  t.false(existsSync(join(dir, "rejected", ".git")));
+});
+
+test("Errors thrown by certificateCheck should fail the clone", async (t) => {
+  const { dir } = t.context;
+  const { url } = await serveTls(t, dir);
+  t.throws(
+    () =>
+      cloneChecked(url, join(dir, "thrown"), () => {
+        throw new Error("untrusted certificate");
+      }),
+    { message: "untrusted certificate" },
+  );
+});
+
+test("unshallow should fetch the whole history of a shallow clone", async (t) => {
+  const { dir, local } = t.context;
+  git(local, "commit -q --allow-empty -m second");
+  git(local, "commit -q --allow-empty -m third");
+  git(local, "push -q origin main");
+  const url = await serveGitDaemon(t, dir);
+  const path = join(dir, "shallow");
+  const repo = new RepoBuilder()
+    .branch("main")
+    .fetchOptions(new FetchOptions().depth(1))
+    .clone(`${url}/origin.git`, path);
+  const count = () => [...repo.revWalk().pushHead()].length;
+  t.true(repo.isShallow());
+  t.is(count(), 1);
+  repo.findRemote("origin").fetch([], new FetchOptions().depth(2));
+  t.true(repo.isShallow());
+  t.is(count(), 2);
+  repo.unshallow("origin");
+  t.false(repo.isShallow());
+  t.false(existsSync(join(path, ".git", "shallow")));
+  t.is(count(), 3);
+});
+
+test("fetchIntoOdb should resume an interrupted fetch", async (t) => {
+  const { dir, local, repo } = t.context;
+  const upstream = join(dir, "upstream");
+  git(dir, `init -q -b a ${upstream}`);
+  git(upstream, "config user.email test@example.com");
+  git(upstream, "config user.name test");
+  for (const branch of ["a", "b"]) {
+    if (branch === "b") {
+      git(upstream, "checkout -q --orphan b");
+    }
+    for (let i = 0; i < 5; i++) {
+      writeFileSync(join(upstream, `${branch}${i}`), `${branch}${i}\n`);
+      git(upstream, "add -A");
+      git(upstream, `commit -q -m ${branch}${i}`);
+    }
+  }
+  const url = `${await serveGitDaemon(t, dir)}/upstream`;
+  const refspecs = [
+    "refs/heads/a:refs/remotes/upstream/a",
+    "refs/heads/b:refs/remotes/upstream/b",
+  ];
+  let fetches = 0;
+  const interrupting = new RemoteCallbacks().transferProgress((progress) => {
+    if (progress.receivedObjects === 0) {
+      fetches++;
+    } else if (fetches === 2) {
+      throw new Error("connection reset");
+    }
+  });
+  const first = repo.fetchIntoOdb(
+    url,
+    refspecs,
+    null,
+    new FetchOptions().remoteCallback(interrupting),
+  );
+  t.false(first.complete);
+  t.true(first.error.includes("connection reset"));
+  t.deepEqual(
+    first.refs.map((ref) => ref.refname),
+    ["refs/remotes/upstream/a"],
+  );
+  t.throws(() => repo.finalizeFetched(first));
+  const second = repo.fetchIntoOdb(url, refspecs, {
+    resumeToken: first.resumeToken,
+  });
+  t.true(second.complete);
+  // Only the objects of the interrupted refspec are transferred again
+  t.is(
+    second.receivedObjects,
+    git(upstream, "rev-list --objects b").split("\n").length,
+  );
+  t.throws(() => git(local, "rev-parse --verify -q refs/remotes/upstream/b"));
+  repo.finalizeFetched(second);
+  t.is(
+    git(local, "rev-parse refs/remotes/upstream/b"),
+    git(upstream, "rev-parse b"),
+  );
+  t.is(git(local, "for-each-ref refs/fetch-staging"), "");
+});
+
+test("writeFetchHead should round trip through fetchheadForeach", (t) => {
+  const { local, repo } = t.context;
+  const oid = git(local, "rev-parse HEAD");
+  const remoteUrl = "https://example.com/repo.git";
+  const entries = [
+    { oid, isMerge: true, refname: "refs/heads/main", remoteUrl },
+    { oid, isMerge: false, refname: "refs/tags/v1.0", remoteUrl },
+    { oid, isMerge: false, refname: "refs/pull/1/head", remoteUrl },
+  ];
+  repo.writeFetchHead(entries);
+  t.is(
+    readFileSync(join(local, ".git", "FETCH_HEAD"), "utf8"),
+    [
+      `${oid}\t\tbranch 'main' of ${remoteUrl}`,
+      `${oid}\tnot-for-merge\ttag 'v1.0' of ${remoteUrl}`,
+      `${oid}\tnot-for-merge\t'refs/pull/1/head' of ${remoteUrl}`,
+      "",
+    ].join("\n"),
+  );
+  const read = [];
+  repo.fetchheadForeach((entry) => {
+    read.push(entry);
+  });
+  t.deepEqual(read, entries);
+  const first = [];
+  repo.fetchheadForeach((entry) => first.push(entry) && false);
+  t.is(first.length, 1);
+  t.is(git(local, "rev-parse FETCH_HEAD"), oid);
+
+  repo.clearFetchHead();
+  t.false(existsSync(join(local, ".git", "FETCH_HEAD")));
+  t.notThrows(() => repo.clearFetchHead());
+  t.throws(() => repo.fetchheadForeach(() => true));
+});
+
+test("dryRunFetch should list the wants without downloading", (t) => {
+  const { dir, local, origin, repo } = t.context;
+  git(local, "push -q origin main");
+  const other = join(dir, "other");
+  git(dir, `clone -q -b main ${origin} ${other}`);
+  git(other, "config user.email test@example.com");
+  git(other, "config user.name test");
+  for (const message of ["one", "two", "three"]) {
+    git(other, `commit -q --allow-empty -m ${message}`);
+  }
+  git(other, "push -q origin main");
+  git(other, "push -q origin main:refs/heads/copy");
+  const tip = git(other, "rev-parse main");
+
+  const result = repo
+    .findRemote("origin")
+    .dryRunFetch(["+refs/heads/main:refs/remotes/origin/main", "copy"]);
+  t.deepEqual(result.wants, [
+    { refname: "refs/heads/copy", oid: tip },
+    { refname: "refs/heads/main", oid: tip },
+  ]);
+  t.is(result.estimatedObjects, 1);
+  t.false(repo.odb().exists(tip));
+  t.is(git(local, "rev-parse origin/main"), git(local, "rev-parse main"));
+});
+
+test("dryRunFetch should walk the missing history up to maxWalk", (t) => {
+  const { dir, local, origin, repo } = t.context;
+  git(local, "push -q origin main");
+  const other = join(dir, "other");
+  git(dir, `clone -q -b main ${origin} ${other}`);
+  git(other, "config user.email test@example.com");
+  git(other, "config user.name test");
+  for (const message of ["one", "two", "three"]) {
+    git(other, `commit -q --allow-empty -m ${message}`);
+  }
+  git(other, "tag copy HEAD~1");
+  git(other, "push -q origin main main:refs/heads/copy copy");
+  const tip = git(other, "rev-parse main");
+  const refs = git(local, "for-each-ref");
+
+  const remote = repo.findRemote("origin");
+  const result = remote.dryRunFetch(["main", "copy"], null, 100);
+  t.deepEqual(result.wants, [
+    { refname: "refs/heads/copy", oid: tip },
+    { refname: "refs/heads/main", oid: tip },
+  ]);
+  t.is(result.estimatedObjects, 3);
+  t.is(remote.dryRunFetch(["main"], null, 2).estimatedObjects, 2);
+  t.is(remote.dryRunFetch(["refs/tags/copy"], null, 100).estimatedObjects, 2);
+  t.false(repo.odb().exists(tip));
+  t.is(git(local, "for-each-ref"), refs);
+
+  // Negative refspecs exclude references, and without refspecs the
+  // configured ones are used
+  t.deepEqual(
+    remote
+      .dryRunFetch(["refs/heads/*:refs/remotes/origin/*", "^refs/heads/copy"])
+      .wants.map((want) => want.refname),
+    ["refs/heads/main"],
+  );
+  t.deepEqual(
+    remote.dryRunFetch([]).wants.map((want) => want.refname),
+    ["refs/heads/copy", "refs/heads/main"],
+  );
+});
+
+test("dryRunFetch should download at most maxWalk commits of a network remote", async (t) => {
+  const { dir, local, origin, repo } = t.context;
+  git(local, "push -q origin main");
+  const other = join(dir, "other");
+  git(dir, `clone -q -b main ${origin} ${other}`);
+  git(other, "config user.email test@example.com");
+  git(other, "config user.name test");
+  for (let i = 0; i < 5; i++) {
+    git(other, `commit -q --allow-empty -m ${i}`);
+  }
+  git(other, "push -q origin main");
+  const remote = repo.remoteAnonymous(
+    `${await serveGitDaemon(t, dir)}/origin.git`,
+  );
+  let received = 0;
+  const options = new FetchOptions().remoteCallback(
+    new RemoteCallbacks().transferProgress((progress) => {
+      received = Math.max(received, progress.receivedObjects);
+    }),
+  );
+  t.is(remote.dryRunFetch(["main"], options, 2).estimatedObjects, 2);
+  // The two commits and their tree
+  t.true(received <= 3);
+  t.is(remote.dryRunFetch(["main"], null, 100).estimatedObjects, 5);
+  t.false(repo.odb().exists(git(other, "rev-parse main")));
+});
@@ -1,3 +1,4 @@
+import { realpathSync, writeFileSync } from "node:fs";
 import { readFile } from "node:fs/promises";
 import { execSync } from "node:child_process";
 import { join } from "node:path";
@@ -7,7 +8,9 @@ import test from "ava";
 
 const __dirname = join(fileURLToPath(import.meta.url), "..");
 
-import { Repository } from "../index.js";
+import { ObjectType, Repository } from "../index.js";
+
+import { git, initRepo, tempDir } from "./helpers.mjs";
 
 const workDir = join(__dirname, "..");
 
@@ -64,3 +67,99 @@ test("Should be able to get blob content", async (t) => {
     Buffer.from(blob.content()).toString("utf8"),
   );
 });
+
+test("Batch dates should match the single file lookups", async (t) => {
+  const { repo } = t.context;
+  const files = [join("src", "lib.rs"), "Cargo.toml", "not-exist.txt"];
+  t.deepEqual(await repo.getFilesLatestModifiedDateAsync(files), [
+    repo.getFileLatestModifiedDate(files[0]),
+    repo.getFileLatestModifiedDate(files[1]),
+    null,
+  ]);
+});
+
+test("setFsyncPolicy should write the config and keep writes working", (t) => {
+  const dir = tempDir("fsync");
+  const repo = Repository.init(dir);
+  repo.setFsyncPolicy({ objects: true, index: true });
+  const config = (key) => git(dir, `config ${key}`);
+  t.is(config("core.fsyncObjectFiles"), "true");
+  t.is(config("core.fsync"), "loose-object,index");
+  t.deepEqual(repo.fsyncPolicy(), { objects: true, refs: true, index: true });
+  repo.setFsyncPolicy({ refs: true });
+  t.is(config("core.fsync"), "loose-object,reference,index");
+  try {
+    const oid = repo.odb().write(ObjectType.Blob, Buffer.from("fsync\n"));
+    t.true(repo.odb().exists(oid));
+    t.notThrows(() => repo.index().write());
+  } finally {
+    repo.setFsyncPolicy({ refs: false });
+  }
+});
+
+test("find*ByPrefix should resolve short hashes", (t) => {
+  const { repo } = t.context;
+  const revParse = (rev) => git(workDir, `rev-parse ${rev}`);
+  const tree = revParse("HEAD^{tree}");
+  const blob = revParse("HEAD:Cargo.toml");
+  t.is(repo.findTreeByPrefix(tree.slice(0, 10)).id(), tree);
+  t.is(repo.findBlobByPrefix(blob.slice(0, 10)).id(), blob);
+  const object = repo.findObjectByPrefix(blob.slice(0, 10));
+  t.is(object.id(), blob);
+  t.is(object.kind(), ObjectType.Blob);
+  t.is(repo.findObjectByPrefix(blob.slice(0, 10), ObjectType.Tree), null);
+  t.is(repo.findTreeByPrefix(blob.slice(0, 10)), null);
+});
+
+test("findBlob and getBlobAt should look up blobs", (t) => {
+  const { repo } = t.context;
+  const revParse = (rev) => git(workDir, `rev-parse ${rev}`);
+  const oid = revParse("HEAD~1:Cargo.toml");
+  t.is(repo.findBlob(oid).id(), oid);
+  const blob = repo.getBlobAt("HEAD~1", "Cargo.toml");
+  t.is(blob.id(), oid);
+  t.is(
+    Buffer.from(blob.content()).toString("utf8"),
+    execSync("git show HEAD~1:Cargo.toml", { cwd: workDir }).toString("utf8"),
+  );
+  t.throws(() => repo.getBlobAt("HEAD", "missing.txt"));
+  t.throws(() => repo.getBlobAt("HEAD", "src"));
+  t.throws(() => repo.findBlob(revParse("HEAD^{tree}")));
+});
+
+test("indexPath and commonPath should follow linked worktrees", (t) => {
+  const dir = realpathSync(tempDir("paths"));
+  const main = join(dir, "main");
+  const linked = join(dir, "linked");
+  git(dir, `init -q ${main}`);
+  git(
+    main,
+    "-c user.name=test -c user.email=test@example.com " +
+      "commit -q --allow-empty -m init",
+  );
+  git(main, `worktree add -q ${linked}`);
+  const repo = new Repository(main);
+  const worktree = new Repository(linked);
+  const gitDir = join(main, ".git");
+  t.is(repo.indexPath(), join(gitDir, "index"));
+  t.is(repo.commonPath(), `${gitDir}/`);
+  t.is(worktree.indexPath(), join(gitDir, "worktrees/linked/index"));
+  t.is(worktree.commonPath(), `${gitDir}/`);
+});
+
+test("Ignore rules and attributes should be evaluated", (t) => {
+  const dir = initRepo("ignore");
+  writeFileSync(join(dir, ".gitignore"), "*.log\n");
+  writeFileSync(join(dir, ".gitattributes"), "*.bin -diff\n*.txt eol=lf\n");
+  const repo = new Repository(dir);
+  t.true(repo.isPathIgnored("debug.log"));
+  t.false(repo.isPathIgnored("build/out.tmp"));
+  repo.addIgnoreRule("build/\n*.tmp");
+  t.true(repo.isPathIgnored("build/out.tmp"));
+  repo.clearIgnoreRules();
+  t.false(repo.isPathIgnored("build/out.tmp"));
+  t.true(repo.isPathIgnored("debug.log"));
+  t.is(repo.checkAttribute("a.bin", "diff"), "unset");
+  t.is(repo.checkAttribute("a.txt", "eol"), "lf");
+  t.is(repo.checkAttribute("a.txt", "diff"), null);
+});
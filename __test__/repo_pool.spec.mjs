@@ -0,0 +1,64 @@
+import test from "ava";
+
+import { RepositoryPool } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+const createRepo = () => {
+  const dir = initRepo("pool");
+  git(dir, "commit -q --allow-empty -m init");
+  return dir;
+};
+
+test("Should evict the least recently used repository at maxOpen", (t) => {
+  const pool = new RepositoryPool({ maxOpen: 2 });
+  const [a, b, c] = [createRepo(), createRepo(), createRepo()];
+  const repoA = pool.get(a);
+  const repoB = pool.get(b);
+  t.is(pool.get(a), repoA);
+  pool.get(c);
+  t.is(pool.size, 2);
+  t.is(pool.get(a), repoA);
+  t.not(pool.with(b, (repo) => repo), repoB);
+  t.is(pool.size, 2);
+});
+
+test("Should reopen a repository modified by an external commit", (t) => {
+  const pool = new RepositoryPool();
+  const dir = createRepo();
+  const repo = pool.get(dir);
+  t.is(pool.get(dir), repo);
+  git(dir, "commit -q --allow-empty -m second");
+  const reopened = pool.get(dir);
+  t.not(reopened, repo);
+  t.is(reopened.head().target(), git(dir, "rev-parse HEAD"));
+});
+
+test("Should reopen a repository whose reference was updated", (t) => {
+  const pool = new RepositoryPool();
+  const dir = createRepo();
+  git(dir, "branch feature");
+  const repo = pool.get(dir);
+  // Like a push, update a branch without touching the git directory
+  const commit = git(dir, "commit-tree -p HEAD -m pushed HEAD^{tree}");
+  git(dir, `update-ref refs/heads/feature ${commit}`);
+  const reopened = pool.get(dir);
+  t.not(reopened, repo);
+  t.is(reopened.findReference("refs/heads/feature").target(), commit);
+  git(dir, "pack-refs --all");
+  t.not(pool.get(dir), reopened);
+});
+
+test("Should reopen a repository whose tag was created", (t) => {
+  const pool = new RepositoryPool();
+  const dir = createRepo();
+  const repo = pool.get(dir);
+  t.is(pool.get(dir), repo);
+  git(dir, "update-ref refs/tags/v1 HEAD");
+  const reopened = pool.get(dir);
+  t.not(reopened, repo);
+  t.is(
+    reopened.findReference("refs/tags/v1").target(),
+    git(dir, "rev-parse HEAD"),
+  );
+});
@@ -0,0 +1,72 @@
+import test from "ava";
+
+import { BranchType, Sort } from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+test("countCommits and collectOids should drain the walk", (t) => {
+  const { dir, repo } = buildRepo("revwalk", (builder) =>
+    builder
+      .commit({ message: "init", files: { "a.txt": "a\n" } })
+      .branch("feature")
+      .commit({ message: "one", files: { "b.txt": "b\n" } })
+      .commit({ message: "two", files: { "c.txt": "c\n" } })
+      .checkout("main"),
+  );
+  t.is(repo.revWalk().pushRange("main..feature").countCommits(), 2);
+  const walk = repo.revWalk().pushRange("main..feature");
+  walk.setSorting(Sort.Reverse);
+  t.deepEqual(
+    walk.collectOids(),
+    git(dir, "rev-list --reverse main..feature").split("\n"),
+  );
+  t.is(repo.openHandles().revwalks, 0);
+  t.is(repo.revWalk().pushHead().hideHead().countCommits(), 0);
+});
+
+test("pushBetween and hideRefsOfType should restrict the walk", (t) => {
+  const { dir, repo } = buildRepo("revwalk", (builder) =>
+    builder
+      .commit({ message: "init", files: { "a.txt": "a\n" } })
+      .branch("feature")
+      .commit({ message: "one", files: { "b.txt": "b\n" } })
+      .checkout("main"),
+  );
+  const main = git(dir, "rev-parse main");
+  const feature = git(dir, "rev-parse feature");
+  t.deepEqual(repo.revWalk().pushBetween(main, feature).collectOids(), [
+    feature,
+  ]);
+  git(dir, "checkout -q --detach feature");
+  git(dir, "commit -q --allow-empty -m detached");
+  t.deepEqual(
+    repo.revWalk().pushHead().hideRefsOfType(BranchType.Local).collectOids(),
+    [git(dir, "rev-parse HEAD")],
+  );
+});
+
+test("log should summarize the walked commits", async (t) => {
+  const { dir, repo } = buildRepo("revwalk", (builder) =>
+    builder
+      .commit({ message: "init", files: { "a.txt": "a\n" } })
+      .branch("feature")
+      .commit({ message: "one\n\nbody", files: { "b.txt": "b\n" } })
+      .commit({ message: "two", files: { "c.txt": "c\n" } })
+      .checkout("main"),
+  );
+  const log = await repo.log({
+    revs: ["main..feature"],
+    sorting: [Sort.Topological, Sort.Reverse],
+  });
+  t.deepEqual(
+    log.map(({ oid }) => oid),
+    git(dir, "rev-list --reverse main..feature").split("\n"),
+  );
+  t.is(log[0].summary, "one");
+  t.is(log[0].message, "one\n\nbody");
+  t.is(log[0].authorEmail, git(dir, "log -1 --format=%ae feature"));
+  t.deepEqual(log[1].parentIds, [log[0].oid]);
+  t.is((await repo.log({ revs: ["feature"], limit: 2 })).length, 2);
+  t.is((await repo.log({ revs: ["feature"], hide: ["main"] })).length, 2);
+  t.deepEqual((await repo.log()).map(({ summary }) => summary), ["init"]);
+});
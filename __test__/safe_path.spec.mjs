@@ -0,0 +1,115 @@
+import { existsSync, readFileSync, statSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { checkSafePath, Repository } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+// Names from git's path traversal CVEs and their variants
+const MALICIOUS = [
+  "",
+  "/etc/passwd",
+  "\\evil",
+  "../evil",
+  "..\\evil",
+  "a/../../evil",
+  "a/./b",
+  "a//b",
+  "C:\\Windows\\evil",
+  "C:evil",
+  ".git",
+  ".git/hooks/post-checkout",
+  ".GIT/config",
+  "sub/.Git/config",
+  ".git./config",
+  ".git /config",
+  ".git . . ./config",
+  "git~1/config",
+  "GIT~1/hooks/post-checkout",
+  ".git::$INDEX_ALLOCATION/config",
+  ".g\u200cit/config",
+  ".gi\u200dt/hooks/post-checkout",
+  "\ufeff.git/config",
+  ".git\u206f/config",
+  "evil.",
+  "evil ",
+  "dir./evil",
+  "a\u0000b",
+  "a\nb",
+  "aux",
+  "AUX.txt",
+  "con",
+  "nul.tar.gz",
+  "dir/COM1",
+  "lpt9.log",
+  "CONIN$",
+  'a"b',
+  "a|b",
+  "a?b",
+  "a*b",
+  "a<b>",
+];
+
+const SAFE = [
+  "src/lib.rs",
+  "文档/说明.md",
+  "émoji/😀.txt",
+  "Ελληνικά/αρχείο",
+  ".github/workflows/ci.yml",
+  ".gitignore",
+  ".gitmodules",
+  "git",
+  "console.log",
+  "auxiliary.txt",
+  "comics/com10.txt",
+  "a.b/c..d",
+  "...txt",
+];
+
+test("checkSafePath should reject known malicious names", (t) => {
+  for (const path of MALICIOUS) {
+    const error = t.throws(() => checkSafePath(path), { code: "UnsafePath" });
+    t.is(error.path, path, JSON.stringify(path));
+  }
+});
+
+test("checkSafePath should accept normal paths", (t) => {
+  for (const path of SAFE) {
+    t.notThrows(() => checkSafePath(path), JSON.stringify(path));
+  }
+});
+
+test("safePathCheckout should write the files of a tree", (t) => {
+  const dir = initRepo("safe-path");
+  const blob = git(dir, "hash-object -w --stdin", "hook\n");
+  const script = git(dir, "hash-object -w --stdin", "#!/bin/sh\n");
+  const hooks = git(dir, "mktree", `100644 blob ${blob}\tpost-checkout\n`);
+  const docs = git(
+    dir,
+    "mktree",
+    `100644 blob ${blob}\t说明.md\n100755 blob ${script}\trun.sh\n`,
+  );
+  const safe = git(dir, "mktree", `040000 tree ${docs}\tdocs\n`);
+  const repo = new Repository(dir);
+
+  const out = join(dir, "out");
+  t.is(repo.safePathCheckout(safe, out), 2);
+  t.is(readFileSync(join(out, "docs/说明.md"), "utf8"), "hook\n");
+  if (process.platform !== "win32") {
+    t.true((statSync(join(out, "docs/run.sh")).mode & 0o111) !== 0);
+  }
+
+  const hostile = git(
+    dir,
+    "mktree",
+    `100644 blob ${blob}\ta.txt\n040000 tree ${hooks}\t.GIT\n`,
+  );
+  const error = t.throws(
+    () => repo.safePathCheckout(hostile, join(dir, "hostile")),
+    { code: "UnsafePath" },
+  );
+  t.is(error.path, ".GIT");
+  t.false(existsSync(join(dir, "hostile")));
+});
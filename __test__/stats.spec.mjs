@@ -0,0 +1,53 @@
+import { setFlagsFromString } from "node:v8";
+import { runInNewContext } from "node:vm";
+
+import test from "ava";
+
+import { gitMemoryStats, Repository } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+setFlagsFromString("--expose-gc");
+const gc = runInNewContext("gc");
+
+// Collect the dropped wrappers, whose finalizers run in a later tick
+const collect = async () => {
+  for (let i = 0; i < 3; i++) {
+    gc();
+    await new Promise((resolve) => setImmediate(resolve));
+  }
+};
+
+test("gitMemoryStats should report the object cache", (t) => {
+  const stats = gitMemoryStats();
+  t.true(stats.cachedObjectsBytes >= 0);
+  t.true(stats.cachedObjectsLimitBytes > 0);
+  t.true(stats.mwindowMappedLimitBytes > 0);
+});
+
+test("openHandles should count the live wrappers", async (t) => {
+  const dir = initRepo("stats");
+  git(dir, "commit -q --allow-empty -m first");
+  git(dir, "remote add origin https://example.com/repo.git");
+  const repo = new Repository(dir);
+  const empty = { revwalks: 0, diffs: 0, trees: 0, remotes: 0 };
+  t.deepEqual(repo.openHandles(), empty);
+
+  const walk = repo.revWalk().pushHead();
+  (() => {
+    const tree = repo.head().peelToTree();
+    repo.findRemote("origin");
+    repo.diffTreeToWorkdir(tree);
+  })();
+  t.deepEqual(repo.openHandles(), {
+    revwalks: 1,
+    diffs: 1,
+    trees: 1,
+    remotes: 1,
+  });
+  t.is([...walk].length, 1);
+  t.is(repo.openHandles().revwalks, 0);
+
+  await collect();
+  t.deepEqual(repo.openHandles(), empty);
+});
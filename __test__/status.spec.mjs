@@ -0,0 +1,76 @@
+import { execSync } from "node:child_process";
+import { mkdirSync, writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+const write = (dir, path, content) => {
+  mkdirSync(join(dir, path, ".."), { recursive: true });
+  writeFileSync(join(dir, path), content);
+};
+
+// Group `git status` by the first `depth` components of the parent directory
+const groupStatuses = (dir, depth = Infinity) => {
+  const groups = new Map();
+  // Not trimmed, the first status column may be blank
+  const status = execSync("git status --porcelain -uall", { cwd: dir });
+  for (const line of status.toString("utf8").trimEnd().split("\n")) {
+    const [x, y, path] = [line[0], line[1], line.slice(3)];
+    const parent = path.split("/").slice(0, -1).slice(0, depth).join("/");
+    const group = groups.get(parent) ?? {
+      dir: parent,
+      staged: 0,
+      unstaged: 0,
+      untracked: 0,
+      conflicted: 0,
+    };
+    if (x === "?") group.untracked += 1;
+    else {
+      if (x !== " ") group.staged += 1;
+      if (y !== " ") group.unstaged += 1;
+    }
+    groups.set(parent, group);
+  }
+  return [...groups.values()].sort((a, b) => (a.dir < b.dir ? -1 : 1));
+};
+
+test.beforeEach((t) => {
+  const { dir, repo } = buildRepo("status", (builder) =>
+    builder.commit({
+      message: "base",
+      files: {
+        "root.txt": "base\n",
+        "src/a.js": "base\n",
+        "src/lib/b.js": "base\n",
+        "docs/c.md": "base\n",
+        ".gitignore": "*.log\n",
+      },
+    }),
+  );
+  write(dir, "root.txt", "changed\n");
+  write(dir, "src/a.js", "staged\n");
+  git(dir, "add src/a.js");
+  write(dir, "src/a.js", "staged and changed\n");
+  write(dir, "src/lib/b.js", "changed\n");
+  write(dir, "src/lib/new.js", "new\n");
+  write(dir, "src/lib/debug.log", "ignored\n");
+  write(dir, "docs/new/d.md", "new\n");
+  git(dir, "rm -q docs/c.md");
+  t.context = { dir, repo };
+});
+
+test("statusByDirectory should match grouped statuses", (t) => {
+  const { dir, repo } = t.context;
+  t.deepEqual(repo.statusByDirectory(), groupStatuses(dir));
+  t.deepEqual(repo.statusByDirectory({ depth: 1 }), groupStatuses(dir, 1));
+  t.deepEqual(repo.statusByDirectory({ depth: 0 }), groupStatuses(dir, 0));
+});
+
+test("statusByDirectory should respect pathspecs", (t) => {
+  const { repo } = t.context;
+  t.deepEqual(repo.statusByDirectory({ depth: 1, pathspec: ["src"] }), [
+    { dir: "src", staged: 1, unstaged: 2, untracked: 1, conflicted: 0 },
+  ]);
+});
@@ -0,0 +1,68 @@
+import { mkdirSync, writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { Repository } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+const commit = (dir, path, content) => {
+  writeFileSync(join(dir, path), content);
+  git(dir, "add -A");
+  git(dir, `commit -q -m "Update ${path}"`);
+};
+
+test.beforeEach((t) => {
+  const dir = initRepo("subtree-split");
+  mkdirSync(join(dir, "pkg"));
+  commit(dir, "root.txt", "root\n");
+  commit(dir, "pkg/a.txt", "a\n");
+  commit(dir, "root.txt", "root 2\n");
+  git(dir, "checkout -q -b feature");
+  commit(dir, "pkg/b.txt", "b\n");
+  git(dir, "checkout -q main");
+  commit(dir, "pkg/a.txt", "a 2\n");
+  git(dir, "merge -q --no-edit feature");
+  commit(dir, "root.txt", "root 3\n");
+  t.context.dir = dir;
+  t.context.repo = new Repository(dir);
+});
+
+test("subtreeSplit should rewrite the history of a directory", async (t) => {
+  const { dir, repo } = t.context;
+  const progress = [];
+  const { headOid, mapped } = await repo.subtreeSplit("pkg/", {
+    progress: (processed, total) => progress.push([processed, total]),
+  });
+  t.is(git(dir, `rev-parse ${headOid}^{tree}`), git(dir, "rev-parse HEAD:pkg"));
+  t.is(
+    git(dir, `rev-list --count --no-merges ${headOid}`),
+    git(dir, "rev-list --count --no-merges HEAD -- pkg"),
+  );
+  // The commits of both branches are joined by the merge
+  t.is(mapped, 4);
+  t.is(git(dir, `log -1 --format=%s ${headOid}^2`), "Update pkg/b.txt");
+  t.deepEqual(progress.at(-1), [7, 7]);
+});
+
+test("subtreeSplit should reuse the commits of onto", async (t) => {
+  const { dir, repo } = t.context;
+  const lib = initRepo("subtree-split-lib");
+  commit(lib, "a.txt", "a\n");
+  commit(lib, "b.txt", "b\n");
+  // What `git subtree add -P vendor` does
+  git(dir, `fetch -q ${lib} main:lib`);
+  git(dir, "merge -q -s ours --no-commit --allow-unrelated-histories lib");
+  git(dir, "read-tree --prefix=vendor/ -u lib");
+  git(dir, "commit -q -m 'Add vendor'");
+  commit(dir, "vendor/c.txt", "c\n");
+  const { headOid, mapped } = await repo.subtreeSplit("vendor", {
+    onto: "lib",
+  });
+  t.is(mapped, 1);
+  t.is(git(dir, `rev-parse ${headOid}^`), git(dir, "rev-parse lib"));
+  await t.throwsAsync(() => repo.subtreeSplit("missing"), {
+    message: /never contains the directory \[missing\]/,
+  });
+});
@@ -0,0 +1,81 @@
+import { writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { ObjectType } from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+test("tagger and target should describe an annotated tag", (t) => {
+  const { dir, repo } = buildRepo("tag", (builder) =>
+    builder.commit({ message: "init", files: { "a.txt": "a\n" } }),
+  );
+  git(dir, "config user.name tagger");
+  git(dir, "config user.email tagger@example.com");
+  git(dir, "tag -a v1.0.0 -m release");
+  const tag = repo.findTag(git(dir, "rev-parse v1.0.0"));
+  const head = git(dir, "rev-parse HEAD");
+  t.is(tag.tagger().name(), "tagger");
+  t.is(tag.tagger().email(), "tagger@example.com");
+  t.is(tag.targetId(), head);
+  t.is(tag.targetType(), ObjectType.Commit);
+  const target = tag.target();
+  t.is(target.id(), head);
+  t.is(target.kind(), ObjectType.Commit);
+});
+
+test("tagger should be null for a tag without tagger", (t) => {
+  const { dir, repo } = buildRepo("tag", (builder) =>
+    builder.commit({ message: "init", files: { "a.txt": "a\n" } }),
+  );
+  const head = git(dir, "rev-parse HEAD");
+  // Tags created by very old versions of git have no tagger
+  const oid = git(
+    dir,
+    "hash-object -t tag -w --stdin --literally",
+    `object ${head}\ntype commit\ntag legacy\n\nlegacy tag\n`,
+  );
+  const tag = repo.findTag(oid);
+  t.is(tag.tagger(), null);
+  t.is(tag.targetId(), head);
+  t.is(tag.name(), "legacy");
+});
+
+test("tagList should resolve lightweight and annotated tags", (t) => {
+  const { dir, repo } = buildRepo("tag", (builder) =>
+    builder.commit({ message: "init", files: { "a.txt": "a\n" } }),
+  );
+  git(dir, "config user.name tagger");
+  git(dir, "config user.email tagger@example.com");
+  git(dir, "tag v1.0.0");
+  git(dir, "tag -a v2.0.0 -m release");
+  git(dir, "-c advice.nestedTag=false tag -a nested -m nested v2.0.0");
+  const head = git(dir, "rev-parse HEAD");
+  // A tag name which is not valid UTF-8
+  const raw = Buffer.from([0x76, 0xff]);
+  writeFileSync(
+    Buffer.concat([Buffer.from(join(dir, ".git/refs/tags/")), raw]),
+    `${head}\n`,
+  );
+
+  const tags = repo.tagList();
+  t.deepEqual(
+    tags.map(({ name, isAnnotated }) => [name, isAnnotated]),
+    [
+      ["nested", true],
+      ["v1.0.0", false],
+      ["v2.0.0", true],
+      ["v�", false],
+    ],
+  );
+  t.true(tags.every(({ targetOid }) => targetOid === head));
+  t.is(tags[2].oid, git(dir, "rev-parse v2.0.0"));
+  t.is(tags[1].oid, head);
+  t.is(tags[0].nameBytes, undefined);
+
+  const matching = repo.tagList("v*", { rawNames: true });
+  const names = matching.map(({ name }) => name);
+  t.deepEqual(names, ["v1.0.0", "v2.0.0", "v�"]);
+  t.deepEqual(matching[2].nameBytes, raw);
+});
@@ -0,0 +1,45 @@
+import { existsSync, readFileSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { TestRepoBuilder } from "../index.js";
+
+import { git, tempDir } from "./helpers.mjs";
+
+const fixture = () => {
+  const dir = tempDir("fixture");
+  new TestRepoBuilder(dir)
+    .commit({ message: "init", files: { "README.md": "# fixture\n" } })
+    .branch("feature")
+    .commit({
+      message: "feature",
+      files: { "src/index.js": "export {}\n" },
+      author: { name: "Feature", email: "feature@example.com" },
+    })
+    .tag("v1")
+    .checkout("main")
+    .commit({ message: "remove readme", files: { "README.md": null } })
+    .build();
+  return dir;
+};
+
+test("should build a deterministic history", (t) => {
+  const first = fixture();
+  const second = fixture();
+  for (const rev of ["main", "feature", "v1"]) {
+    t.is(git(first, `rev-parse ${rev}`), git(second, `rev-parse ${rev}`));
+  }
+  t.is(git(first, "rev-list --count main"), "2");
+  t.is(git(first, "log -1 --format=%an feature"), "Feature");
+  t.is(git(first, "show feature:src/index.js"), "export {}");
+  t.is(git(first, "symbolic-ref HEAD"), "refs/heads/main");
+});
+
+test("should check out the current branch", (t) => {
+  const dir = fixture();
+  t.false(existsSync(join(dir, "README.md")));
+  t.is(git(dir, "status --porcelain"), "");
+  git(dir, "checkout -q feature");
+  t.is(readFileSync(join(dir, "README.md"), "utf8"), "# fixture\n");
+});
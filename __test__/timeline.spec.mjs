@@ -0,0 +1,98 @@
+import { execSync } from "node:child_process";
+
+import test from "ava";
+
+import { Repository } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+const EPOCH = 1700000000;
+
+// Run a git command at `time`, for the dates of the commits and the reflogs
+const gitAt = (cwd, command, time = EPOCH) =>
+  execSync(`git ${command}`, {
+    cwd,
+    env: {
+      ...process.env,
+      GIT_AUTHOR_DATE: `@${time} +0000`,
+      GIT_COMMITTER_DATE: `@${time} +0000`,
+    },
+  })
+    .toString("utf8")
+    .trim();
+
+test.beforeEach((t) => {
+  const dir = initRepo("timeline");
+  gitAt(dir, "commit -q --allow-empty -m first");
+  gitAt(dir, "tag -a v1.0 -m 'First release'", EPOCH + 100);
+  gitAt(dir, "branch feature", EPOCH + 200);
+  gitAt(dir, "commit -q --allow-empty -m second", EPOCH + 300);
+  t.context.dir = dir;
+  t.context.repo = new Repository(dir);
+});
+
+test("timeline should merge commits, tags and reflogs", (t) => {
+  const { dir, repo } = t.context;
+  const events = repo.timeline();
+  t.deepEqual(
+    events.map(({ kind, timeMs, refname, title }) => ({
+      kind,
+      timeMs,
+      refname,
+      title,
+    })),
+    [
+      {
+        kind: "commit",
+        timeMs: (EPOCH + 300) * 1000,
+        refname: "refs/heads/main",
+        title: "second",
+      },
+      {
+        kind: "reflog",
+        timeMs: (EPOCH + 200) * 1000,
+        refname: "refs/heads/feature",
+        title: "branch: Created from main",
+      },
+      {
+        kind: "tag",
+        timeMs: (EPOCH + 100) * 1000,
+        refname: "refs/tags/v1.0",
+        title: "First release",
+      },
+      {
+        kind: "commit",
+        timeMs: EPOCH * 1000,
+        refname: "refs/heads/main",
+        title: "first",
+      },
+    ],
+  );
+  t.is(events[0].oid, git(dir, "rev-parse main"));
+  t.is(events[1].oid, git(dir, "rev-parse feature"));
+  t.is(events[2].oid, git(dir, "rev-parse v1.0"));
+  t.is(events[0].actorName, "test");
+  t.is(events[0].actorEmail, "test@example.com");
+});
+
+test("timeline should filter the events", async (t) => {
+  const { repo } = t.context;
+  const kinds = (events) => events.map(({ kind }) => kind);
+  t.deepEqual(kinds(repo.timeline({ limit: 2 })), ["commit", "reflog"]);
+  t.deepEqual(kinds(repo.timeline({ sinceEpoch: EPOCH + 100 })), [
+    "commit",
+    "reflog",
+    "tag",
+  ]);
+  // Without the commit events, the reflog entries of the commits are listed
+  t.deepEqual(kinds(repo.timeline({ include: ["tags", "reflog"] })), [
+    "reflog",
+    "reflog",
+    "tag",
+    "reflog",
+  ]);
+  t.deepEqual(
+    kinds(await repo.timelineAsync({ include: ["commits"] })),
+    ["commit", "commit"],
+  );
+});
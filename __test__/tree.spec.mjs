@@ -0,0 +1,86 @@
+import test from "ava";
+
+import { FileMode, ObjectType, TreeWalkMode } from "../index.js";
+
+import { buildRepo, git } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  t.context = buildRepo("tree", (builder) =>
+    builder.commit({
+      message: "init",
+      files: {
+        "README.md": "# readme\n",
+        "src/lib.js": "lib\n",
+        "src/util/index.js": "util\n",
+      },
+    }),
+  );
+});
+
+test("walk should visit nested entries in pre and post order", (t) => {
+  const { repo } = t.context;
+  const tree = repo.head().peelToTree();
+  const visit = (mode) => {
+    const paths = [];
+    tree.walk(mode, (root, entry) => {
+      paths.push(root + entry.name());
+    });
+    return paths;
+  };
+  t.deepEqual(visit(TreeWalkMode.PreOrder), [
+    "README.md",
+    "src",
+    "src/lib.js",
+    "src/util",
+    "src/util/index.js",
+  ]);
+  t.deepEqual(visit(TreeWalkMode.PostOrder), [
+    "README.md",
+    "src/lib.js",
+    "src/util/index.js",
+    "src/util",
+    "src",
+  ]);
+});
+
+test("walk should skip subtrees and stop when asked", (t) => {
+  const { repo } = t.context;
+  const tree = repo.head().peelToTree();
+  const skipped = [];
+  tree.walk(TreeWalkMode.PreOrder, (root, entry) => {
+    skipped.push(root + entry.name());
+    return entry.name() === "util" ? 1 : 0;
+  });
+  t.false(skipped.includes("src/util/index.js"));
+  const stopped = [];
+  tree.walk(TreeWalkMode.PreOrder, (root, entry) => {
+    stopped.push(root + entry.name());
+    return -1;
+  });
+  t.deepEqual(stopped, ["README.md"]);
+  const error = new Error("callback failed");
+  t.is(
+    t.throws(() =>
+      tree.walk(TreeWalkMode.PreOrder, () => {
+        throw error;
+      }),
+    ),
+    error,
+  );
+});
+
+test("entriesRecursive should list every entry like git ls-tree", (t) => {
+  const { dir, repo } = t.context;
+  const entries = repo.head().peelToTree().entriesRecursive();
+  t.deepEqual(
+    entries.map(({ path }) => path),
+    ["README.md", "src", "src/lib.js", "src/util", "src/util/index.js"],
+  );
+  const lib = entries.find(({ path }) => path === "src/lib.js");
+  t.is(lib.oid, git(dir, "rev-parse HEAD:src/lib.js"));
+  t.is(lib.filemode, FileMode.Blob);
+  t.is(lib.type, ObjectType.Blob);
+  const util = entries.find(({ path }) => path === "src/util");
+  t.is(util.filemode, FileMode.Tree);
+  t.is(util.type, ObjectType.Tree);
+});
@@ -0,0 +1,42 @@
+import test from "ava";
+
+import { FileMode, Repository, Signature } from "../index.js";
+
+import { git, initRepo } from "./helpers.mjs";
+
+test("treebuilder should write commits in a bare repository", (t) => {
+  const dir = initRepo("treebuilder", "--bare");
+  const repo = new Repository(dir);
+  const readme = repo.blob(Buffer.from("# readme\n"));
+  const script = repo.blob(Buffer.from("#!/bin/sh\n"));
+
+  const src = repo.treebuilder();
+  src.insert("run.sh", script, FileMode.BlobExecutable);
+  const srcOid = src.write();
+  const root = repo.treebuilder();
+  t.true(root.isEmpty());
+  root.insert("README.md", readme, FileMode.Blob);
+  root.insert("src", srcOid, FileMode.Tree);
+  root.insert("stale.txt", readme, FileMode.Blob);
+  t.is(root.len(), 3);
+  root.remove("stale.txt");
+  t.is(root.get("README.md").id(), readme);
+  t.is(root.get("stale.txt"), null);
+  t.throws(() => root.insert("bad", readme, FileMode.Unreadable));
+
+  const signature = Signature.now("test", "test@example.com");
+  const tree = repo.findTree(root.write());
+  const oid = repo.commit("HEAD", signature, signature, "init", tree);
+  t.is(
+    git(dir, `ls-tree -r ${oid}`),
+    [
+      `100644 blob ${readme}\tREADME.md`,
+      `100755 blob ${script}\tsrc/run.sh`,
+    ].join("\n"),
+  );
+
+  const next = repo.treebuilder(tree);
+  t.is(next.len(), 2);
+  next.clear();
+  t.is(next.write(), git(dir, "hash-object -t tree /dev/null"));
+});
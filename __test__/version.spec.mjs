@@ -0,0 +1,58 @@
+import { spawnSync } from "node:child_process";
+import {
+  copyFileSync,
+  readdirSync,
+  readFileSync,
+  writeFileSync,
+} from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { API_LEVEL, assertApiLevel, nativeVersion } from "../index.js";
+
+import { tempDir } from "./helpers.mjs";
+
+test("nativeVersion should report the versions of the binding", (t) => {
+  const { version } = JSON.parse(readFileSync("package.json", "utf8"));
+  const native = nativeVersion();
+  t.is(native.crate, version);
+  t.regex(native.libgit2, /^\d+\.\d+\.\d+$/);
+  t.true(native.apiLevel >= 1);
+});
+
+test("assertApiLevel should throw on a mismatched level", (t) => {
+  const { apiLevel } = nativeVersion();
+  t.notThrows(() => assertApiLevel(apiLevel));
+  const error = t.throws(() => assertApiLevel(apiLevel + 1));
+  t.regex(error.message, new RegExp(`API level ${apiLevel} `));
+});
+
+test("index.js should check the API level of the binding when loaded", (t) => {
+  const { apiLevel } = nativeVersion();
+  t.is(API_LEVEL, apiLevel);
+
+  // A copy of index.js stamped with another level next to the binding
+  const dir = tempDir("version");
+  for (const file of readdirSync(".").filter((f) => f.endsWith(".node"))) {
+    copyFileSync(file, join(dir, file));
+  }
+  const index = readFileSync("index.js", "utf8");
+  const stamped = `const API_LEVEL = ${apiLevel}\n`;
+  t.true(index.includes(stamped));
+  writeFileSync(
+    join(dir, "index.js"),
+    index.replace(stamped, `const API_LEVEL = ${apiLevel + 1}\n`),
+  );
+  const load = (path) =>
+    spawnSync(process.execPath, ["-e", `require(${JSON.stringify(path)})`], {
+      encoding: "utf8",
+    });
+  t.is(load(join(process.cwd(), "index.js")).status, 0);
+  const mismatched = load(join(dir, "index.js"));
+  t.not(mismatched.status, 0);
+  t.regex(
+    mismatched.stderr,
+    new RegExp(`API level ${apiLevel} but the level ${apiLevel + 1} `),
+  );
+});
@@ -0,0 +1,79 @@
+import { existsSync, rmSync } from "node:fs";
+import { join } from "node:path";
+
+import test from "ava";
+
+import { WorktreeAddOptions, WorktreePruneOptions } from "../index.js";
+
+import { buildRepo, git, tempDir } from "./helpers.mjs";
+
+test.beforeEach((t) => {
+  t.context = buildRepo("worktree", (builder) =>
+    builder.commit({ message: "init", files: { "a.txt": "a\n" } }),
+  );
+  t.context.base = tempDir("worktree-linked");
+});
+
+test("worktrees should round-trip through add, lock and prune", (t) => {
+  const { base, dir, repo } = t.context;
+  const path = join(base, "feature");
+  const added = repo.worktreeAdd("feature", path);
+  t.is(added.name(), "feature");
+  t.is(added.path(), path);
+  t.deepEqual(repo.worktrees(), ["feature"]);
+  t.is(git(dir, "rev-parse feature"), git(dir, "rev-parse HEAD"));
+  t.true(existsSync(join(path, "a.txt")));
+
+  const linked = added.openRepository();
+  t.is(linked.head().name(), "refs/heads/feature");
+  t.is(linked.path(), join(dir, ".git/worktrees/feature/"));
+
+  const worktree = repo.findWorktree("feature");
+  t.false(worktree.isLocked());
+  t.is(worktree.lockReason(), null);
+  worktree.lock("in use");
+  t.true(worktree.isLocked());
+  t.is(worktree.lockReason(), "in use");
+  t.regex(git(dir, "worktree list --porcelain"), /^locked in use$/m);
+  const valid = new WorktreePruneOptions().valid(true).workingTree(true);
+  t.false(worktree.isPrunable(valid));
+  t.throws(() => worktree.prune(valid));
+
+  worktree.unlock();
+  t.false(worktree.isLocked());
+  t.false(worktree.isPrunable());
+  t.true(worktree.isPrunable(valid));
+  worktree.prune(valid);
+  t.deepEqual(repo.worktrees(), []);
+  t.false(existsSync(path));
+  t.throws(() => repo.findWorktree("feature"));
+});
+
+test("worktreeAdd should apply its options", (t) => {
+  const { base, dir, repo } = t.context;
+  git(dir, "branch existing");
+  git(dir, "branch other");
+  const locked = repo.worktreeAdd(
+    "existing",
+    join(base, "existing"),
+    new WorktreeAddOptions().lock(true).checkoutExisting(true),
+  );
+  t.true(locked.isLocked());
+  t.is(locked.openRepository().head().name(), "refs/heads/existing");
+
+  const reference = repo.findReference("refs/heads/other");
+  const other = repo.worktreeAdd(
+    "elsewhere",
+    join(base, "elsewhere"),
+    new WorktreeAddOptions().reference(reference),
+  );
+  t.is(other.openRepository().head().name(), "refs/heads/other");
+  t.throws(() => git(dir, "rev-parse --verify -q refs/heads/elsewhere"));
+  t.deepEqual(repo.worktrees().sort(), ["elsewhere", "existing"]);
+
+  // A worktree whose directory is gone can be pruned with the defaults
+  rmSync(join(base, "elsewhere"), { recursive: true });
+  t.true(repo.findWorktree("elsewhere").isPrunable());
+  repo.findWorktree("elsewhere").prune();
+  t.deepEqual(repo.worktrees(), ["existing"]);
+});
@@ -0,0 +1,50 @@
+// Stamp the API level of the native binding into the generated `index.js` and
+// `index.d.ts`, run after `napi build`.
+//
+// `index.js` then checks the level of the loaded binding when it is required,
+// failing with an error naming both versions when several versions of the
+// package are installed.
+import { readFileSync, writeFileSync } from "node:fs";
+
+const MARKER = "/* API level, stamped by api-level.mjs */";
+
+const read = (file) => readFileSync(new URL(file, import.meta.url), "utf8");
+
+const [, level] = read("./src/version.rs").match(
+  /const API_LEVEL: u32 = (\d+);/,
+);
+const { version } = JSON.parse(read("./package.json"));
+
+const stamp = (file, footer) => {
+  const generated = read(file);
+  const start = generated.indexOf(`\n${MARKER}`);
+  const content = start === -1 ? generated : generated.slice(0, start);
+  writeFileSync(
+    new URL(file, import.meta.url),
+    `${content}\n${MARKER}\n${footer}`,
+  );
+};
+
+stamp(
+  "./index.js",
+  `const API_LEVEL = ${level}
+
+if (typeof assertApiLevel !== 'function') {
+  throw new Error(
+    'The native binding does not report its API level but the level ${level} of @napi-rs/simple-git ${version} is expected, several versions of @napi-rs/simple-git may be installed',
+  )
+}
+assertApiLevel(API_LEVEL)
+
+module.exports.API_LEVEL = API_LEVEL
+`,
+);
+stamp(
+  "./index.d.ts",
+  `/**
+ * The API level \`index.js\` was generated for, checked against the loaded
+ * native binding, see \`nativeVersion\`.
+ */
+export const API_LEVEL: ${level}
+`,
+);
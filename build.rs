@@ -2,6 +2,7 @@ extern crate napi_build;
 
 fn main() {
   napi_build::setup();
+  stamp_package_version();
   let compile_target = std::env::var("TARGET").unwrap();
   match compile_target.as_str() {
     "x86_64-unknown-linux-gnu" => {
@@ -20,3 +21,18 @@ fn main() {
     _ => {}
   }
 }
+
+/// Expose the version of the npm package to the crate, which reports it in
+/// `nativeVersion` so that mismatched bindings can be told apart.
+fn stamp_package_version() {
+  println!("cargo:rerun-if-changed=package.json");
+  let version = std::fs::read_to_string("package.json")
+    .ok()
+    .and_then(|manifest| {
+      let (_, rest) = manifest.split_once("\"version\"")?;
+      let (_, rest) = rest.split_once('"')?;
+      rest.split_once('"').map(|(version, _)| version.to_owned())
+    })
+    .unwrap_or_else(|| std::env::var("CARGO_PKG_VERSION").unwrap());
+  println!("cargo:rustc-env=SIMPLE_GIT_PACKAGE_VERSION={version}");
+}
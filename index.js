@@ -310,30 +310,81 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { Blob, Commit, DiffFlags, FileMode, Deltas, DiffDelta, Delta, DiffFile, Diff, ObjectType, GitObject, Reference, ReferenceType, Direction, FetchPrune, AutotagOption, RemoteRedirect, CredentialType, RemoteUpdateFlags, Remote, RemoteCallbacks, FetchOptions, ProxyOptions, Cred, credTypeContains, RepositoryState, RepositoryOpenFlags, Repository, RepoBuilder, CloneLocal, Sort, RevWalk, Signature, Tag, Tree, TreeIter, TreeEntry } = nativeBinding
+const { AnnotatedCommit, ApplyLocation, ApplyOptions, Blob, BlobWriter, BranchType, Branch, CheckoutOptions, CommitAuthorRole, Commit, CommitParents, CommitParentIds, SignatureKind, ConfigLevel, ConfigEntries, Config, CommitMessageRule, DiffFlags, FileMode, Deltas, DiffDelta, Delta, DiffFile, DiffHunk, DiffBinaryKind, DiffLine, DescribeOptions, DescribeResult, DescribeFormatOptions, DiffStatsFormat, DiffFormat, Diff, Index, IndexConflicts, Mailmap, MergeOptions, FileFavor, MergeFileOptions, Note, Notes, ObjectType, GitObject, Odb, OdbObject, PackBuilderStage, PackBuilder, DiffGranularity, Patch, PathspecFlags, Pathspec, PathspecMatchList, PinnedView, RebaseOperationType, Rebase, Reference, ReferenceType, RefUpdateKind, References, ReferenceNames, Direction, Refspec, FetchPrune, AutotagOption, RemoteRedirect, CredentialType, CertificateKind, CredDescriptorType, RemoteUpdateFlags, Remote, RemoteCallbacks, FetchOptions, PushOptions, RemoteRefList, ProxyOptions, Cred, credTypeContains, RepositoryState, RepositoryOpenFlags, Repository, RepoBuilder, CloneLocal, RepositoryPool, Sort, RevWalk, checkSafePath, Signature, gitMemoryStats, Tag, TestRepoBuilder, TimelineSource, TimelineEventKind, TransportProtocol, setUserAgent, getUserAgent, TreeWalkMode, Tree, TreeIter, TreeEntry, TreeBuilder, nativeVersion, assertApiLevel, Worktree, WorktreeAddOptions, WorktreePruneOptions } = nativeBinding
 
+module.exports.AnnotatedCommit = AnnotatedCommit
+module.exports.ApplyLocation = ApplyLocation
+module.exports.ApplyOptions = ApplyOptions
 module.exports.Blob = Blob
+module.exports.BlobWriter = BlobWriter
+module.exports.BranchType = BranchType
+module.exports.Branch = Branch
+module.exports.CheckoutOptions = CheckoutOptions
+module.exports.CommitAuthorRole = CommitAuthorRole
 module.exports.Commit = Commit
+module.exports.CommitParents = CommitParents
+module.exports.CommitParentIds = CommitParentIds
+module.exports.SignatureKind = SignatureKind
+module.exports.ConfigLevel = ConfigLevel
+module.exports.ConfigEntries = ConfigEntries
+module.exports.Config = Config
+module.exports.CommitMessageRule = CommitMessageRule
 module.exports.DiffFlags = DiffFlags
 module.exports.FileMode = FileMode
 module.exports.Deltas = Deltas
 module.exports.DiffDelta = DiffDelta
 module.exports.Delta = Delta
 module.exports.DiffFile = DiffFile
+module.exports.DiffHunk = DiffHunk
+module.exports.DiffBinaryKind = DiffBinaryKind
+module.exports.DiffLine = DiffLine
+module.exports.DescribeOptions = DescribeOptions
+module.exports.DescribeResult = DescribeResult
+module.exports.DescribeFormatOptions = DescribeFormatOptions
+module.exports.DiffStatsFormat = DiffStatsFormat
+module.exports.DiffFormat = DiffFormat
 module.exports.Diff = Diff
+module.exports.Index = Index
+module.exports.IndexConflicts = IndexConflicts
+module.exports.Mailmap = Mailmap
+module.exports.MergeOptions = MergeOptions
+module.exports.FileFavor = FileFavor
+module.exports.MergeFileOptions = MergeFileOptions
+module.exports.Note = Note
+module.exports.Notes = Notes
 module.exports.ObjectType = ObjectType
 module.exports.GitObject = GitObject
+module.exports.Odb = Odb
+module.exports.OdbObject = OdbObject
+module.exports.PackBuilderStage = PackBuilderStage
+module.exports.PackBuilder = PackBuilder
+module.exports.DiffGranularity = DiffGranularity
+module.exports.Patch = Patch
+module.exports.PathspecFlags = PathspecFlags
+module.exports.Pathspec = Pathspec
+module.exports.PathspecMatchList = PathspecMatchList
+module.exports.PinnedView = PinnedView
+module.exports.RebaseOperationType = RebaseOperationType
+module.exports.Rebase = Rebase
 module.exports.Reference = Reference
 module.exports.ReferenceType = ReferenceType
+module.exports.RefUpdateKind = RefUpdateKind
+module.exports.References = References
+module.exports.ReferenceNames = ReferenceNames
 module.exports.Direction = Direction
+module.exports.Refspec = Refspec
 module.exports.FetchPrune = FetchPrune
 module.exports.AutotagOption = AutotagOption
 module.exports.RemoteRedirect = RemoteRedirect
 module.exports.CredentialType = CredentialType
+module.exports.CertificateKind = CertificateKind
+module.exports.CredDescriptorType = CredDescriptorType
 module.exports.RemoteUpdateFlags = RemoteUpdateFlags
 module.exports.Remote = Remote
 module.exports.RemoteCallbacks = RemoteCallbacks
 module.exports.FetchOptions = FetchOptions
+module.exports.PushOptions = PushOptions
+module.exports.RemoteRefList = RemoteRefList
 module.exports.ProxyOptions = ProxyOptions
 module.exports.Cred = Cred
 module.exports.credTypeContains = credTypeContains
@@ -342,10 +393,38 @@ module.exports.RepositoryOpenFlags = RepositoryOpenFlags
 module.exports.Repository = Repository
 module.exports.RepoBuilder = RepoBuilder
 module.exports.CloneLocal = CloneLocal
+module.exports.RepositoryPool = RepositoryPool
 module.exports.Sort = Sort
 module.exports.RevWalk = RevWalk
+module.exports.checkSafePath = checkSafePath
 module.exports.Signature = Signature
+module.exports.gitMemoryStats = gitMemoryStats
 module.exports.Tag = Tag
+module.exports.TestRepoBuilder = TestRepoBuilder
+module.exports.TimelineSource = TimelineSource
+module.exports.TimelineEventKind = TimelineEventKind
+module.exports.TransportProtocol = TransportProtocol
+module.exports.setUserAgent = setUserAgent
+module.exports.getUserAgent = getUserAgent
+module.exports.TreeWalkMode = TreeWalkMode
 module.exports.Tree = Tree
 module.exports.TreeIter = TreeIter
 module.exports.TreeEntry = TreeEntry
+module.exports.TreeBuilder = TreeBuilder
+module.exports.nativeVersion = nativeVersion
+module.exports.assertApiLevel = assertApiLevel
+module.exports.Worktree = Worktree
+module.exports.WorktreeAddOptions = WorktreeAddOptions
+module.exports.WorktreePruneOptions = WorktreePruneOptions
+
+/* API level, stamped by api-level.mjs */
+const API_LEVEL = 1
+
+if (typeof assertApiLevel !== 'function') {
+  throw new Error(
+    'The native binding does not report its API level but the level 1 of @napi-rs/simple-git 0.1.19 is expected, several versions of @napi-rs/simple-git may be installed',
+  )
+}
+assertApiLevel(API_LEVEL)
+
+module.exports.API_LEVEL = API_LEVEL
@@ -0,0 +1,32 @@
+use napi_derive::napi;
+
+use napi::bindgen_prelude::SharedReference;
+
+use crate::repo::Repository;
+
+#[napi]
+/// A commit along with how it was looked up, e.g. from a reference or from
+/// `FETCH_HEAD`, which merges and rebases record in their messages.
+pub struct AnnotatedCommit {
+  pub(crate) inner: SharedReference<Repository, git2::AnnotatedCommit<'static>>,
+  /// Not set for the commits looked up by id, whose reference name libgit2
+  /// reports as a null pointer.
+  pub(crate) refname: Option<String>,
+}
+
+#[napi]
+impl AnnotatedCommit {
+  #[napi]
+  /// Get the id of the commit.
+  pub fn id(&self) -> String {
+    self.inner.id().to_string()
+  }
+
+  #[napi]
+  /// Get the name of the reference the commit was looked up from.
+  ///
+  /// Returns `null` if it was looked up by id.
+  pub fn refname(&self) -> Option<String> {
+    self.refname.clone()
+  }
+}
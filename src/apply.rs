@@ -0,0 +1,148 @@
+use std::sync::{Arc, Mutex};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+  deltas::{DiffDelta, DiffHunk},
+  error::IntoNapiError,
+  util::CallbackError,
+};
+
+#[napi]
+/// Possible application locations for `apply`
+pub enum ApplyLocation {
+  /// Apply the patch to the workdir
+  Workdir,
+  /// Apply the patch to the index
+  Index,
+  /// Apply the patch to both the working directory and the index
+  Both,
+}
+
+impl From<ApplyLocation> for git2::ApplyLocation {
+  fn from(value: ApplyLocation) -> Self {
+    match value {
+      ApplyLocation::Workdir => git2::ApplyLocation::WorkDir,
+      ApplyLocation::Index => git2::ApplyLocation::Index,
+      ApplyLocation::Both => git2::ApplyLocation::Both,
+    }
+  }
+}
+
+#[napi]
+/// Options to specify when applying a diff.
+///
+/// An error thrown by a callback skips the delta or hunk and fails the apply
+/// with that error.
+pub struct ApplyOptions {
+  pub(crate) inner: git2::ApplyOptions<'static>,
+  pub(crate) callback_error: CallbackError,
+  /// The path of the delta being applied, named by the error when a delta
+  /// does not apply.
+  current_path: Arc<Mutex<Option<String>>>,
+  has_delta_callback: bool,
+}
+
+#[napi]
+impl ApplyOptions {
+  #[napi(constructor)]
+  #[allow(clippy::new_without_default)]
+  /// Creates a new set of empty options (zeroed).
+  pub fn new() -> ApplyOptions {
+    ApplyOptions {
+      inner: git2::ApplyOptions::new(),
+      callback_error: CallbackError::default(),
+      current_path: Arc::default(),
+      has_delta_callback: false,
+    }
+  }
+
+  #[napi]
+  /// Set a callback which is called for each delta of the diff, returning
+  /// `false` skips the delta.
+  pub fn delta_callback(&mut self, env: Env, callback: FunctionRef<DiffDelta, bool>) -> &Self {
+    let callback_error = self.callback_error.clone();
+    let current_path = self.current_path.clone();
+    self.has_delta_callback = true;
+    self.inner.delta_callback(move |delta| {
+      record_path(&current_path, delta.as_ref());
+      let Some(delta) = delta else {
+        return true;
+      };
+      // SAFETY: the delta points into the diff being applied, which outlives
+      // the apply operation just like the deltas returned by `Diff.deltas`
+      let delta: git2::DiffDelta<'static> = unsafe { std::mem::transmute(delta) };
+      callback
+        .borrow_back(&env)
+        .and_then(|cb| cb.call(delta.into()))
+        .unwrap_or_else(|err| {
+          callback_error.set(err);
+          false
+        })
+    });
+    self
+  }
+
+  #[napi]
+  /// Set a callback which is called for each hunk of the diff, returning
+  /// `false` skips the hunk.
+  pub fn hunk_callback(&mut self, env: Env, callback: FunctionRef<DiffHunk, bool>) -> &Self {
+    let callback_error = self.callback_error.clone();
+    self.inner.hunk_callback(move |hunk| {
+      let Some(hunk) = hunk else {
+        return true;
+      };
+      callback
+        .borrow_back(&env)
+        .and_then(|cb| cb.call(hunk.into()))
+        .unwrap_or_else(|err| {
+          callback_error.set(err);
+          false
+        })
+    });
+    self
+  }
+
+  #[napi]
+  /// Don't actually make changes, just test that the patch applies.
+  pub fn check(&mut self, check: bool) -> &Self {
+    self.inner.check(check);
+    self
+  }
+}
+
+impl ApplyOptions {
+  /// Apply `diff` with `apply`, naming the path of the delta which does not
+  /// apply in the error.
+  pub(crate) fn apply<T, F>(&mut self, msg: &str, apply: F) -> Result<T>
+  where
+    F: FnOnce(&mut git2::ApplyOptions<'static>) -> std::result::Result<T, git2::Error>,
+  {
+    if let Ok(mut path) = self.current_path.lock() {
+      path.take();
+    }
+    // Installed in place, libgit2 being handed the address of the options
+    if !self.has_delta_callback {
+      let current_path = self.current_path.clone();
+      self.has_delta_callback = true;
+      self.inner.delta_callback(move |delta| {
+        record_path(&current_path, delta.as_ref());
+        true
+      });
+    }
+    let result = apply(&mut self.inner);
+    let path = self.current_path.lock().ok().and_then(|path| path.clone());
+    self.callback_error.check(match path {
+      Some(path) => result.convert(format!("{msg} at [{path}]")),
+      None => result.convert(msg),
+    })
+  }
+}
+
+fn record_path(current_path: &Mutex<Option<String>>, delta: Option<&git2::DiffDelta>) {
+  let path = delta.and_then(|delta| delta.new_file().path().or(delta.old_file().path()));
+  if let Ok(mut current_path) = current_path.lock() {
+    *current_path = path.map(|path| path.to_string_lossy().into_owned());
+  }
+}
@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use napi_derive::napi;
+
+/// The number of bytes git looks for a NUL byte in to decide that a file is
+/// binary.
+const DEFAULT_NUL_CHECK_BYTES: usize = 8000;
+
+#[napi(object)]
+/// How `Repository.setBinaryDetection` decides whether a file is binary.
+pub struct BinaryDetectionOptions {
+  /// The number of leading bytes searched for a NUL byte, which marks the
+  /// content as binary.
+  ///
+  /// Defaults to 8000, like git.
+  pub nul_check_bytes: Option<u32>,
+  /// Extensions of the files always treated as text, e.g. `["svg"]`.
+  pub treat_as_text_extensions: Option<Vec<String>>,
+  /// Extensions of the files always treated as binary, e.g. `["bin"]`.
+  pub treat_as_binary_extensions: Option<Vec<String>>,
+  /// Let the `binary`, `text` and `diff` attributes of `.gitattributes` take
+  /// precedence over the extensions and the content.
+  ///
+  /// Defaults to `true`.
+  pub respect_gitattributes: Option<bool>,
+}
+
+#[derive(Clone)]
+/// The policy deciding whether a file is binary, shared by all the APIs
+/// processing file contents so a file is binary in all of them or none.
+pub(crate) struct BinaryDetection {
+  nul_check_bytes: usize,
+  text_extensions: Vec<String>,
+  binary_extensions: Vec<String>,
+  respect_gitattributes: bool,
+}
+
+impl Default for BinaryDetection {
+  fn default() -> Self {
+    Self {
+      nul_check_bytes: DEFAULT_NUL_CHECK_BYTES,
+      text_extensions: Vec::new(),
+      binary_extensions: Vec::new(),
+      respect_gitattributes: true,
+    }
+  }
+}
+
+impl From<BinaryDetectionOptions> for BinaryDetection {
+  fn from(options: BinaryDetectionOptions) -> Self {
+    let normalize = |extensions: Option<Vec<String>>| {
+      extensions
+        .unwrap_or_default()
+        .into_iter()
+        .map(|ext| ext.trim_start_matches('.').to_lowercase())
+        .collect()
+    };
+    Self {
+      nul_check_bytes: options
+        .nul_check_bytes
+        .map_or(DEFAULT_NUL_CHECK_BYTES, |bytes| bytes as usize),
+      text_extensions: normalize(options.treat_as_text_extensions),
+      binary_extensions: normalize(options.treat_as_binary_extensions),
+      respect_gitattributes: options.respect_gitattributes.unwrap_or(true),
+    }
+  }
+}
+
+impl BinaryDetection {
+  /// Decide whether the file at `path` with `content` is binary, checking the
+  /// attributes, then the extension, then the content. Without content, a
+  /// file which is not binary by its attributes or extension is text.
+  pub(crate) fn is_binary(
+    &self,
+    repo: &git2::Repository,
+    path: &str,
+    content: Option<&[u8]>,
+  ) -> Result<bool, git2::Error> {
+    if self.respect_gitattributes {
+      if let Some(binary) = binary_from_attributes(repo, path)? {
+        return Ok(binary);
+      }
+    }
+    if let Some(ext) = Path::new(path).extension() {
+      let ext = ext.to_string_lossy().to_lowercase();
+      if self.binary_extensions.contains(&ext) {
+        return Ok(true);
+      }
+      if self.text_extensions.contains(&ext) {
+        return Ok(false);
+      }
+    }
+    Ok(content.is_some_and(|content| {
+      content
+        .iter()
+        .take(self.nul_check_bytes)
+        .any(|byte| *byte == 0)
+    }))
+  }
+}
+
+/// The decision made by the attributes of `path`, `binary` and `-text` or
+/// `-diff` making it binary while `text` or `diff` make it text.
+fn binary_from_attributes(
+  repo: &git2::Repository,
+  path: &str,
+) -> Result<Option<bool>, git2::Error> {
+  let flags = git2::AttrCheckFlags::default();
+  if git2::AttrValue::from_string(repo.get_attr(Path::new(path), "binary", flags)?)
+    == git2::AttrValue::True
+  {
+    return Ok(Some(true));
+  }
+  for attr in ["text", "diff"] {
+    match git2::AttrValue::from_string(repo.get_attr(Path::new(path), attr, flags)?) {
+      git2::AttrValue::True => return Ok(Some(false)),
+      git2::AttrValue::False => return Ok(Some(true)),
+      _ => {}
+    }
+  }
+  Ok(None)
+}
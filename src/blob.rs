@@ -1,12 +1,13 @@
 use std::ops::Deref;
 
-use napi::bindgen_prelude::{SharedReference, Uint8Array};
+use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use crate::object::GitObject;
+use crate::{error::IntoNapiError, object::GitObject, repo::Repository};
 
 pub(crate) enum BlobParent {
   GitObject(SharedReference<GitObject, git2::Blob<'static>>),
+  Repository(SharedReference<Repository, git2::Blob<'static>>),
 }
 
 impl Deref for BlobParent {
@@ -15,6 +16,7 @@ impl Deref for BlobParent {
   fn deref(&self) -> &git2::Blob<'static> {
     match self {
       BlobParent::GitObject(parent) => parent.deref(),
+      BlobParent::Repository(parent) => parent.deref(),
     }
   }
 }
@@ -40,6 +42,8 @@ impl Blob {
 
   #[napi]
   /// Get the content of this blob.
+  ///
+  /// The content is copied into a new array on every call.
   pub fn content(&self) -> Uint8Array {
     self.inner.content().to_vec().into()
   }
@@ -50,3 +54,46 @@ impl Blob {
     self.inner.size() as u64
   }
 }
+
+#[napi]
+/// A stream writing the content of a blob in chunks, see
+/// `Repository.blobWriter`.
+pub struct BlobWriter {
+  pub(crate) inner: SharedReference<Repository, Option<git2::BlobWriter<'static>>>,
+}
+
+#[napi]
+impl BlobWriter {
+  #[napi]
+  /// Append `data` to the content of the blob.
+  pub fn write(&mut self, data: Buffer) -> Result<()> {
+    let writer = self.inner.as_mut().ok_or_else(already_committed)?;
+    std::io::Write::write_all(writer, &data).map_err(|err| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Write to blob failed: {err}"),
+      )
+    })
+  }
+
+  #[napi]
+  /// Write the blob to the object database, after the filters of the hint
+  /// path are applied to its content.
+  ///
+  /// Returns the OID of the written blob. The writer can not be used
+  /// afterwards.
+  pub fn commit(&mut self) -> Result<String> {
+    let writer = self.inner.take().ok_or_else(already_committed)?;
+    writer
+      .commit()
+      .map(|oid| oid.to_string())
+      .convert("Write blob failed")
+  }
+}
+
+fn already_committed() -> Error {
+  Error::new(
+    Status::GenericFailure,
+    "The blob writer is already committed".to_owned(),
+  )
+}
@@ -0,0 +1,122 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{error::IntoNapiError, repo::Repository};
+
+#[napi]
+/// An enumeration for the possible types of branches.
+pub enum BranchType {
+  /// A local branch not on a remote.
+  Local,
+  /// A branch for a remote.
+  Remote,
+}
+
+impl From<BranchType> for git2::BranchType {
+  fn from(value: BranchType) -> Self {
+    match value {
+      BranchType::Local => git2::BranchType::Local,
+      BranchType::Remote => git2::BranchType::Remote,
+    }
+  }
+}
+
+#[napi(object)]
+/// The number of commits a branch has that its upstream branch does not, and
+/// the other way around.
+pub struct AheadBehind {
+  pub ahead: u32,
+  pub behind: u32,
+}
+
+#[napi]
+/// A structure to represent a git [branch][1]
+///
+/// [1]: http://git-scm.com/book/en/Git-Branching-What-a-Branch-Is
+pub struct Branch {
+  pub(crate) inner: SharedReference<Repository, git2::Branch<'static>>,
+}
+
+#[napi]
+impl Branch {
+  #[napi]
+  /// Return the name of the given local or remote branch.
+  ///
+  /// May return `None` if the name is not valid utf-8.
+  pub fn name(&self) -> Result<Option<String>> {
+    self
+      .inner
+      .name()
+      .map(|name| name.map(ToOwned::to_owned))
+      .convert_without_message()
+  }
+
+  #[napi]
+  /// Determine if the current local branch is pointed at by HEAD.
+  pub fn is_head(&self) -> bool {
+    self.inner.is_head()
+  }
+
+  #[napi]
+  /// Return the branch tracked by this local branch.
+  ///
+  /// Returns `null` if it has no upstream branch.
+  pub fn upstream(&self, env: Env) -> Result<Option<Branch>> {
+    let Some(upstream) = self.find_upstream()? else {
+      return Ok(None);
+    };
+    Ok(Some(Branch {
+      inner: self
+        .inner
+        .clone_owner(env)?
+        .share_with(env, |_| Ok(upstream))?,
+    }))
+  }
+
+  #[napi]
+  /// Set the upstream branch of this local branch, e.g. `origin/main` for a
+  /// remote-tracking branch or the name of a local branch.
+  ///
+  /// Pass `null` to unset the upstream branch.
+  pub fn set_upstream(&mut self, upstream_name: Option<String>) -> Result<()> {
+    self
+      .inner
+      .set_upstream(upstream_name.as_deref())
+      .convert("Set the upstream branch failed")
+  }
+
+  #[napi]
+  /// Count the commits this local branch and its upstream branch have that
+  /// the other does not.
+  ///
+  /// Returns `null` if it has no upstream branch.
+  pub fn ahead_behind(&self, env: Env) -> Result<Option<AheadBehind>> {
+    let Some(upstream) = self.find_upstream()? else {
+      return Ok(None);
+    };
+    let (Some(local), Some(upstream)) = (self.inner.get().target(), upstream.get().target()) else {
+      return Ok(None);
+    };
+    let (ahead, behind) = self
+      .inner
+      .clone_owner(env)?
+      .inner
+      .graph_ahead_behind(local, upstream)
+      .convert("Count the commits ahead and behind the upstream branch failed")?;
+    Ok(Some(AheadBehind {
+      ahead: ahead as u32,
+      behind: behind as u32,
+    }))
+  }
+}
+
+impl Branch {
+  /// The branch tracked by this local branch, `None` if it has none.
+  fn find_upstream(&self) -> Result<Option<git2::Branch<'static>>> {
+    match self.inner.upstream() {
+      Ok(upstream) => Ok(Some(upstream)),
+      Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+      Err(err) => Err(err).convert("Find the upstream branch failed"),
+    }
+  }
+}
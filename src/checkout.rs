@@ -0,0 +1,247 @@
+use std::path::Path;
+
+use napi_derive::napi;
+
+#[napi]
+/// A builder struct used to configure the checkout performed by operations
+/// like `merge`.
+pub struct CheckoutOptions {
+  pub(crate) inner: git2::build::CheckoutBuilder<'static>,
+}
+
+#[napi]
+impl CheckoutOptions {
+  #[napi(constructor)]
+  #[allow(clippy::new_without_default)]
+  /// Creates a new builder for checkouts with all of its default
+  /// configuration.
+  pub fn new() -> CheckoutOptions {
+    CheckoutOptions {
+      inner: git2::build::CheckoutBuilder::new(),
+    }
+  }
+
+  #[napi]
+  /// Indicate that this checkout should perform a dry run by checking for
+  /// conflicts but not make any actual changes.
+  pub fn dry_run(&mut self) -> &Self {
+    self.inner.dry_run();
+    self
+  }
+
+  #[napi]
+  /// Take any action necessary to get the working directory to match the
+  /// target including potentially discarding modified files.
+  pub fn force(&mut self) -> &Self {
+    self.inner.force();
+    self
+  }
+
+  #[napi]
+  /// Indicate that the checkout should be performed safely, allowing new
+  /// files to be created but not overwriting existing files or changes.
+  ///
+  /// This is the default.
+  pub fn safe(&mut self) -> &Self {
+    self.inner.safe();
+    self
+  }
+
+  #[napi]
+  /// In safe mode, create files that don't exist.
+  ///
+  /// Defaults to false.
+  pub fn recreate_missing(&mut self, allow: bool) -> &Self {
+    self.inner.recreate_missing(allow);
+    self
+  }
+
+  #[napi]
+  /// In safe mode, apply safe file updates even when there are conflicts
+  /// instead of canceling the checkout.
+  ///
+  /// Defaults to false.
+  pub fn allow_conflicts(&mut self, allow: bool) -> &Self {
+    self.inner.allow_conflicts(allow);
+    self
+  }
+
+  #[napi]
+  /// Remove untracked files from the working dir.
+  ///
+  /// Defaults to false.
+  pub fn remove_untracked(&mut self, remove: bool) -> &Self {
+    self.inner.remove_untracked(remove);
+    self
+  }
+
+  #[napi]
+  /// Remove ignored files from the working dir.
+  ///
+  /// Defaults to false.
+  pub fn remove_ignored(&mut self, remove: bool) -> &Self {
+    self.inner.remove_ignored(remove);
+    self
+  }
+
+  #[napi]
+  /// Only update the contents of files that already exist.
+  ///
+  /// If set, files will not be created or deleted.
+  ///
+  /// Defaults to false.
+  pub fn update_only(&mut self, update: bool) -> &Self {
+    self.inner.update_only(update);
+    self
+  }
+
+  #[napi]
+  /// Prevents checkout from writing the updated files' information to the
+  /// index.
+  ///
+  /// Defaults to true.
+  pub fn update_index(&mut self, update: bool) -> &Self {
+    self.inner.update_index(update);
+    self
+  }
+
+  #[napi]
+  /// Indicate whether the index and git attributes should be refreshed from
+  /// disk before any operations.
+  ///
+  /// Defaults to true,
+  pub fn refresh(&mut self, refresh: bool) -> &Self {
+    self.inner.refresh(refresh);
+    self
+  }
+
+  #[napi]
+  /// Skip files with unmerged index entries.
+  ///
+  /// Defaults to false.
+  pub fn skip_unmerged(&mut self, skip: bool) -> &Self {
+    self.inner.skip_unmerged(skip);
+    self
+  }
+
+  #[napi]
+  /// Indicate whether the checkout should proceed on conflicts by using the
+  /// stage 2 version of the file ("ours").
+  ///
+  /// Defaults to false.
+  pub fn use_ours(&mut self, ours: bool) -> &Self {
+    self.inner.use_ours(ours);
+    self
+  }
+
+  #[napi]
+  /// Indicate whether the checkout should proceed on conflicts by using the
+  /// stage 3 version of the file ("theirs").
+  ///
+  /// Defaults to false.
+  pub fn use_theirs(&mut self, theirs: bool) -> &Self {
+    self.inner.use_theirs(theirs);
+    self
+  }
+
+  #[napi]
+  /// Indicate whether ignored files should be overwritten during the checkout.
+  ///
+  /// Defaults to true.
+  pub fn overwrite_ignored(&mut self, overwrite: bool) -> &Self {
+    self.inner.overwrite_ignored(overwrite);
+    self
+  }
+
+  #[napi]
+  /// Indicate whether a normal merge file should be written for conflicts.
+  ///
+  /// Defaults to false.
+  pub fn conflict_style_merge(&mut self, on: bool) -> &Self {
+    self.inner.conflict_style_merge(on);
+    self
+  }
+
+  #[napi]
+  /// Indicates whether to include common ancestor data in diff3 format files
+  /// for conflicts.
+  ///
+  /// Defaults to false.
+  pub fn conflict_style_diff3(&mut self, on: bool) -> &Self {
+    self.inner.conflict_style_diff3(on);
+    self
+  }
+
+  #[napi]
+  /// Treat paths specified in `path` as exact file paths instead of as
+  /// pathspecs.
+  pub fn disable_pathspec_match(&mut self, on: bool) -> &Self {
+    self.inner.disable_pathspec_match(on);
+    self
+  }
+
+  #[napi]
+  /// Indicate whether to apply filters like CRLF conversion.
+  pub fn disable_filters(&mut self, disable: bool) -> &Self {
+    self.inner.disable_filters(disable);
+    self
+  }
+
+  #[napi]
+  /// Set the mode with which new directories are created.
+  ///
+  /// Default is 0755
+  pub fn dir_perm(&mut self, perm: i32) -> &Self {
+    self.inner.dir_perm(perm);
+    self
+  }
+
+  #[napi]
+  /// Set the mode with which new files are created.
+  ///
+  /// The default is 0644 or 0755 as dictated by the blob.
+  pub fn file_perm(&mut self, perm: i32) -> &Self {
+    self.inner.file_perm(perm);
+    self
+  }
+
+  #[napi]
+  /// Add a path to be checked out.
+  ///
+  /// The path is a pathspec pattern, unless `disable_pathspec_match` is set.
+  ///
+  /// If no paths are specified, then all files are checked out. Otherwise
+  /// only these specified paths are checked out.
+  pub fn path(&mut self, path: String) -> &Self {
+    self.inner.path(path);
+    self
+  }
+
+  #[napi]
+  /// Set the directory to check out to
+  pub fn target_dir(&mut self, dst: String) -> &Self {
+    self.inner.target_dir(Path::new(&dst));
+    self
+  }
+
+  #[napi]
+  /// The name of the common ancestor side of conflicts
+  pub fn ancestor_label(&mut self, label: String) -> &Self {
+    self.inner.ancestor_label(&label);
+    self
+  }
+
+  #[napi]
+  /// The name of the common our side of conflicts
+  pub fn our_label(&mut self, label: String) -> &Self {
+    self.inner.our_label(&label);
+    self
+  }
+
+  #[napi]
+  /// The name of the common their side of conflicts
+  pub fn their_label(&mut self, label: String) -> &Self {
+    self.inner.their_label(&label);
+    self
+  }
+}
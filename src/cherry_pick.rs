@@ -0,0 +1,44 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::signature::Signature;
+
+#[napi(object, object_to_js = false)]
+pub struct CherryPickRangeOptions {
+  /// The committer of the picked commits, their authors are kept.
+  #[napi(ts_type = "Signature")]
+  pub committer: ClassInstance<Signature>,
+  /// Append `(cherry picked from commit <oid>)` to the messages, like
+  /// `git cherry-pick -x`.
+  pub record_origin: Option<bool>,
+  /// Move the branch after each picked commit, so that the commits picked
+  /// before a conflict are kept.
+  ///
+  /// By default the branch is only moved once the whole range is picked, and
+  /// is left untouched when a commit conflicts, the commits picked before it
+  /// being reachable from no reference.
+  pub stop_on_conflict: Option<bool>,
+}
+
+#[napi(object)]
+pub struct CherryPickedCommit {
+  pub source_oid: String,
+  pub new_oid: String,
+}
+
+#[napi(object)]
+/// The commit of the range which could not be picked cleanly.
+pub struct CherryPickStop {
+  pub source_oid: String,
+  /// The conflicted paths.
+  pub conflicts: Vec<String>,
+}
+
+#[napi(object)]
+/// The outcome of `Repository.cherryPickRange`.
+pub struct CherryPickRangeResult {
+  /// The picked commits, oldest first.
+  pub applied: Vec<CherryPickedCommit>,
+  /// Not set if the whole range was picked.
+  pub stopped_at: Option<CherryPickStop>,
+}
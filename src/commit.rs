@@ -1,4 +1,4 @@
-use std::ops::Deref;
+use std::{borrow::Cow, ops::Deref};
 
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
@@ -6,9 +6,14 @@ use napi_derive::napi;
 use chrono::{DateTime, Utc};
 
 use crate::{
+  conventional_commit::ConventionalCommit,
   error::IntoNapiError,
+  mailmap::Mailmap,
   object::ObjectParent,
+  reference::RefUpdate,
+  repo::commit_id,
   signature::{Signature, SignatureInner},
+  stats::{HandleKind, OpenHandle},
   tree::{Tree, TreeParent},
 };
 
@@ -28,6 +33,64 @@ impl Deref for CommitInner {
   }
 }
 
+#[napi(object)]
+/// A summary of a commit, as listed by `outgoingCommits`, `incomingCommits`
+/// and `log`.
+pub struct CommitSummary {
+  pub oid: String,
+  /// The first paragraph of the commit message.
+  pub summary: String,
+  /// The full commit message.
+  pub message: String,
+  pub author_name: String,
+  pub author_email: String,
+  /// The committer time, in milliseconds since the Unix epoch.
+  pub time_ms: i64,
+  pub parent_ids: Vec<String>,
+}
+
+impl From<&git2::Commit<'_>> for CommitSummary {
+  fn from(commit: &git2::Commit<'_>) -> Self {
+    let author = commit.author();
+    Self {
+      oid: commit.id().to_string(),
+      summary: String::from_utf8_lossy(commit.summary_bytes().unwrap_or_default()).into_owned(),
+      message: String::from_utf8_lossy(commit.message_bytes()).into_owned(),
+      author_name: String::from_utf8_lossy(author.name_bytes()).into_owned(),
+      author_email: String::from_utf8_lossy(author.email_bytes()).into_owned(),
+      time_ms: commit.time().seconds() * 1000,
+      parent_ids: commit.parent_ids().map(|oid| oid.to_string()).collect(),
+    }
+  }
+}
+
+#[napi(string_enum = "lowercase")]
+/// How a person listed by `Commit.allAuthors` took part in a commit.
+pub enum CommitAuthorRole {
+  Author,
+  Committer,
+  /// Named by a `Co-authored-by` trailer of the message.
+  Coauthor,
+}
+
+#[napi(object)]
+/// A person who took part in a commit, see `Commit.allAuthors`.
+pub struct CommitAuthor {
+  pub name: String,
+  /// The email, trimmed and lowercased, e.g. to look up an avatar.
+  pub email: String,
+  pub role: CommitAuthorRole,
+}
+
+#[napi(object)]
+/// The signature of a signed commit.
+pub struct ExtractedSignature {
+  pub signature: Buffer,
+  /// The content of the commit without the signature, which is the data the
+  /// signature was made for.
+  pub signed_data: Buffer,
+}
+
 #[napi]
 pub struct Commit {
   pub(crate) inner: CommitInner,
@@ -56,8 +119,16 @@ impl Commit {
       let tree = commit.inner.tree().convert("Find tree on commit failed")?;
       Ok(tree)
     })?;
+    let handle = match &self.inner {
+      CommitInner::Repository(commit) => Some(OpenHandle::new(
+        &commit.clone_owner(env)?.handles,
+        HandleKind::Tree,
+      )),
+      CommitInner::Commit(_) => None,
+    };
     Ok(Tree {
       inner: TreeParent::Commit(tree),
+      _handle: handle,
     })
   }
 
@@ -112,6 +183,16 @@ impl Commit {
     self.inner.raw_header()
   }
 
+  #[napi]
+  /// Get the raw content of the commit object, its header followed by its
+  /// raw message, as read by `Repository.readRawObject`.
+  pub fn raw_bytes(&self) -> Buffer {
+    let mut raw = self.inner.raw_header_bytes().to_vec();
+    raw.push(b'\n');
+    raw.extend_from_slice(self.inner.message_raw_bytes());
+    raw.into()
+  }
+
   #[napi]
   /// Get an arbitrary header field.
   pub fn header_field_bytes(&self, field: String) -> Result<Buffer> {
@@ -198,6 +279,56 @@ impl Commit {
     })
   }
 
+  #[napi]
+  /// List the author, the committer and the co-authors named by the
+  /// `Co-authored-by` trailers of the message, once per email.
+  ///
+  /// The identities are resolved through `mailmap` when given, and the first
+  /// role of a person is kept. Malformed trailers are skipped.
+  pub fn all_authors(&self, mailmap: Option<&Mailmap>) -> Result<Vec<CommitAuthor>> {
+    let message = String::from_utf8_lossy(self.inner.message_bytes());
+    let coauthors = ConventionalCommit::parse(String::new(), &message, false)
+      .footers
+      .into_iter()
+      .filter(|footer| footer.key.eq_ignore_ascii_case("Co-authored-by"))
+      .filter_map(|footer| {
+        // A malformed trailer has no `<email>` or takes the following lines
+        let (name, email) = footer.value.trim().strip_suffix('>')?.split_once('<')?;
+        let (name, email) = (name.trim(), email.trim());
+        (!name.is_empty() && !email.is_empty() && !email.contains(['<', '\n']))
+          .then(|| git2::Signature::new(name, email, &git2::Time::new(0, 0)).ok())
+          .flatten()
+      });
+    let people = [
+      (self.inner.author(), CommitAuthorRole::Author),
+      (self.inner.committer(), CommitAuthorRole::Committer),
+    ]
+    .into_iter()
+    .chain(coauthors.map(|signature| (signature, CommitAuthorRole::Coauthor)));
+    let mut authors: Vec<CommitAuthor> = Vec::new();
+    for (signature, role) in people {
+      let signature = match mailmap {
+        Some(mailmap) => mailmap
+          .inner
+          .resolve_signature(&signature)
+          .convert("Resolve signature failed")?,
+        None => signature,
+      };
+      let email = String::from_utf8_lossy(signature.email_bytes())
+        .trim()
+        .to_lowercase();
+      if authors.iter().any(|author| author.email == email) {
+        continue;
+      }
+      authors.push(CommitAuthor {
+        name: String::from_utf8_lossy(signature.name_bytes()).into_owned(),
+        email,
+        role,
+      });
+    }
+    Ok(authors)
+  }
+
   #[napi]
   /// Get the committer of this commit.
   pub fn committer(&self, this_ref: Reference<Commit>, env: Env) -> Result<Signature> {
@@ -217,8 +348,10 @@ impl Commit {
   /// For information about `update_ref`, see [`Repository::commit`].
   ///
   /// [`Repository::commit`]: struct.Repository.html#method.commit
+  #[allow(clippy::too_many_arguments)]
   pub fn amend(
     &self,
+    env: Env,
     update_ref: Option<&str>,
     author: Option<&Signature>,
     committer: Option<&Signature>,
@@ -226,6 +359,35 @@ impl Commit {
     message: Option<&str>,
     tree: Option<&Tree>,
   ) -> Result<String> {
+    if let (Some(update_ref), CommitInner::Repository(commit)) = (update_ref, &self.inner) {
+      let repo = commit.clone_owner(env)?;
+      if repo.ref_update_guard.is_some() {
+        // The amended commit as `git_commit_amend` writes it
+        let original_tree;
+        let tree = match tree {
+          Some(tree) => tree.inner(),
+          None => {
+            original_tree = commit.tree().convert("Find tree on commit failed")?;
+            &original_tree
+          }
+        };
+        let parents = commit.parents().collect::<Vec<_>>();
+        let oid = commit_id(
+          &repo.inner,
+          author.map_or(&commit.author(), |s| &*s.inner),
+          committer.map_or(&commit.committer(), |s| &*s.inner),
+          message_encoding.or(commit.message_encoding()),
+          &message.map_or_else(
+            || String::from_utf8_lossy(commit.message_bytes()),
+            Cow::Borrowed,
+          ),
+          tree,
+          &parents.iter().collect::<Vec<_>>(),
+        )?;
+        let (refname, old_oid) = repo.resolve_update_ref(update_ref)?;
+        repo.check_ref_update(&env, RefUpdate::new(&refname, old_oid, Some(oid)))?;
+      }
+    }
     self
       .inner
       .amend(
@@ -234,12 +396,46 @@ impl Commit {
         committer.map(|s| &*s.inner),
         message_encoding,
         message,
-        tree.map(|s| &*s.inner()),
+        tree.map(|s| s.inner()),
       )
       .map(|oid| oid.to_string())
       .convert("Amend commit failed")
   }
 
+  #[napi]
+  /// Extract the signature stored in the `signatureField` header of the
+  /// commit, `gpgsig` by default, to verify it against the signed data.
+  ///
+  /// Fails if the commit is not signed, or if it was not looked up from a
+  /// repository, e.g. a commit returned by `parent`.
+  pub fn extract_signature(
+    &self,
+    env: Env,
+    signature_field: Option<String>,
+  ) -> Result<ExtractedSignature> {
+    let CommitInner::Repository(commit) = &self.inner else {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!(
+          "Commit [{}] is not attached to a repository, find it with Repository.findCommit",
+          self.inner.id()
+        ),
+      ));
+    };
+    let repo = commit.clone_owner(env)?;
+    let (signature, signed_data) = repo
+      .inner
+      .extract_signature(&commit.id(), signature_field.as_deref())
+      .convert(format!(
+        "Extract signature of commit [{}] failed",
+        commit.id()
+      ))?;
+    Ok(ExtractedSignature {
+      signature: signature.to_vec().into(),
+      signed_data: signed_data.to_vec().into(),
+    })
+  }
+
   #[napi]
   /// Get the number of parents of this commit.
   ///
@@ -263,13 +459,33 @@ impl Commit {
     })
   }
 
+  #[napi]
+  /// Get an iterator over the parents of the commit.
+  pub fn parents(&self, this_ref: Reference<Commit>, env: Env) -> Result<CommitParents> {
+    Ok(CommitParents {
+      inner: this_ref.share_with(env, |commit| Ok(commit.inner.deref().clone()))?,
+      index: 0,
+    })
+  }
+
+  #[napi]
+  /// Get an iterator over the parent ids of the commit, without loading the
+  /// parent commits from the ODB.
+  pub fn parent_ids_iter(&self) -> Result<CommitParentIds> {
+    Ok(CommitParentIds {
+      inner: self.inner.deref().clone(),
+      index: 0,
+    })
+  }
+
   #[napi]
   /// Get the specified parent id of the commit.
   ///
   /// This is different from `parent`, which will attempt to load the
   /// parent commit from the ODB.
   ///
-  /// Use the `parent_ids` iterator to return an iterator over all parents.
+  /// Use the `parentIdsIter` iterator to return an iterator over all parent
+  /// ids.
   pub fn parent_id(&self, i: u32) -> Result<String> {
     Ok(
       self
@@ -288,3 +504,45 @@ impl Commit {
     }
   }
 }
+
+#[napi(iterator)]
+/// An iterator over the parents of a commit.
+pub struct CommitParents {
+  pub(crate) inner: SharedReference<Commit, git2::Commit<'static>>,
+  pub(crate) index: usize,
+}
+
+#[napi]
+impl Generator for CommitParents {
+  type Yield = Commit;
+  type Return = ();
+  type Next = ();
+
+  fn next(&mut self, _value: Option<Self::Next>) -> Option<Self::Yield> {
+    let parent = self.inner.parent(self.index).ok()?;
+    self.index += 1;
+    Some(Commit {
+      inner: CommitInner::Commit(parent),
+    })
+  }
+}
+
+#[napi(iterator)]
+/// An iterator over the parent ids of a commit.
+pub struct CommitParentIds {
+  pub(crate) inner: git2::Commit<'static>,
+  pub(crate) index: usize,
+}
+
+#[napi]
+impl Generator for CommitParentIds {
+  type Yield = String;
+  type Return = ();
+  type Next = ();
+
+  fn next(&mut self, _value: Option<Self::Next>) -> Option<Self::Yield> {
+    let id = self.inner.parent_id(self.index).ok()?;
+    self.index += 1;
+    Some(id.to_string())
+  }
+}
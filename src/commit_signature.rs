@@ -0,0 +1,215 @@
+use base64::{engine::general_purpose, Engine};
+use napi_derive::napi;
+use sha2::{Digest, Sha256};
+
+/// The OpenPGP packet tag of signatures.
+const SIGNATURE_PACKET: u8 = 2;
+/// The OpenPGP signature subpacket types naming the key which made it.
+const ISSUER_KEY_ID: u8 = 16;
+const ISSUER_FINGERPRINT: u8 = 33;
+
+#[napi(string_enum = "lowercase")]
+/// The format of a commit signature, inferred from its armor.
+pub enum SignatureKind {
+  /// An OpenPGP signature, made by `gpg`.
+  Openpgp,
+  /// An SSHSIG signature, made by `ssh-keygen -Y sign`.
+  Ssh,
+  /// An S/MIME signature, made by `gpgsm` for `gpg.format=x509`.
+  Smime,
+}
+
+#[napi(object)]
+/// The signature of a commit listed by `Repository.signatureSummaries`.
+///
+/// The signature is only parsed, not verified.
+pub struct SignatureSummary {
+  pub oid: String,
+  pub signed: bool,
+  /// Not set if the commit is not signed, or its armor is unknown.
+  pub kind: Option<SignatureKind>,
+  /// The identifier of the signing key, the 64-bit key ID in hexadecimal of
+  /// an OpenPGP key, or the `SHA256:` fingerprint of an SSH key.
+  ///
+  /// Not set if the signature does not name its key, e.g. for S/MIME.
+  pub key_id: Option<String>,
+  /// The fingerprint in hexadecimal of an OpenPGP key.
+  ///
+  /// Not set if the signature only has the key ID.
+  pub fingerprint: Option<String>,
+}
+
+impl SignatureSummary {
+  /// Summarize the `gpgsig` header of the commit `oid`.
+  pub(crate) fn parse(oid: String, signature: Option<&[u8]>) -> Self {
+    let mut summary = Self {
+      oid,
+      signed: signature.is_some(),
+      kind: None,
+      key_id: None,
+      fingerprint: None,
+    };
+    let Some(signature) = signature else {
+      return summary;
+    };
+    let signature = String::from_utf8_lossy(signature);
+    let Some((label, data)) = dearmor(&signature) else {
+      return summary;
+    };
+    match label {
+      "PGP SIGNATURE" => {
+        summary.kind = Some(SignatureKind::Openpgp);
+        if let Some((key_id, fingerprint)) = data.as_deref().and_then(openpgp_issuer) {
+          summary.key_id = key_id;
+          summary.fingerprint = fingerprint;
+        }
+      }
+      "SSH SIGNATURE" => {
+        summary.kind = Some(SignatureKind::Ssh);
+        summary.key_id = data.as_deref().and_then(ssh_fingerprint);
+      }
+      "SIGNED MESSAGE" | "PKCS7" | "CMS" => summary.kind = Some(SignatureKind::Smime),
+      _ => {}
+    }
+    summary
+  }
+}
+
+/// The label of the armor of `text` and its decoded data, if it is valid
+/// base64.
+fn dearmor(text: &str) -> Option<(&str, Option<Vec<u8>>)> {
+  let mut lines = text.lines().map(str::trim_end);
+  let label = lines
+    .find_map(|line| line.strip_prefix("-----BEGIN "))?
+    .strip_suffix("-----")?;
+  let body = lines
+    .take_while(|line| !line.starts_with("-----END "))
+    .collect::<Vec<_>>();
+  // The OpenPGP armor headers end with a blank line, and its checksum starts
+  // with a `=` which never starts a line of base64
+  let body = match body.iter().position(|line| line.is_empty()) {
+    Some(blank) => &body[blank + 1..],
+    None => &body[..],
+  };
+  let encoded = body
+    .iter()
+    .filter(|line| !line.starts_with('='))
+    .copied()
+    .collect::<String>();
+  Some((label, general_purpose::STANDARD.decode(encoded).ok()))
+}
+
+/// The key ID and fingerprint of the issuer of the OpenPGP signature packet
+/// `data`.
+fn openpgp_issuer(data: &[u8]) -> Option<(Option<String>, Option<String>)> {
+  let (tag, body) = openpgp_packet(data)?;
+  if tag != SIGNATURE_PACKET {
+    return None;
+  }
+  match *body.first()? {
+    // version, length of the hashed material, type, creation time, key ID
+    3 => Some((Some(hex(body.get(7..15)?)), None)),
+    version @ 4..=6 => {
+      let count_len = if version == 6 { 4 } else { 2 };
+      // version, type, public key and hash algorithms
+      let mut rest = body.get(4..)?;
+      let mut key_id = None;
+      let mut fingerprint = None;
+      // The hashed then the unhashed subpackets
+      for _ in 0..2 {
+        let count = be_uint(rest.get(..count_len)?);
+        let subpackets = rest.get(count_len..count_len + count)?;
+        rest = &rest[count_len + count..];
+        for (kind, data) in Subpackets(subpackets) {
+          match kind {
+            ISSUER_KEY_ID if data.len() == 8 => key_id = Some(hex(data)),
+            ISSUER_FINGERPRINT if data.len() > 1 => fingerprint = Some(&data[1..]),
+            _ => {}
+          }
+        }
+      }
+      // The key ID is the tail of a v4 fingerprint, the head of the newer ones
+      let key_id = key_id.or_else(|| match fingerprint? {
+        fingerprint if fingerprint.len() == 20 => Some(hex(&fingerprint[12..])),
+        fingerprint => Some(hex(fingerprint.get(..8)?)),
+      });
+      Some((key_id, fingerprint.map(hex)))
+    }
+    _ => None,
+  }
+}
+
+/// The tag and body of the first OpenPGP packet of `data`.
+fn openpgp_packet(data: &[u8]) -> Option<(u8, &[u8])> {
+  let header = *data.first()?;
+  if header & 0x80 == 0 {
+    return None;
+  }
+  let (tag, len, offset) = if header & 0x40 != 0 {
+    let first = *data.get(1)? as usize;
+    let (len, offset) = match first {
+      0..=191 => (first, 2),
+      192..=223 => (((first - 192) << 8) + *data.get(2)? as usize + 192, 3),
+      255 => (be_uint(data.get(2..6)?), 6),
+      // A partial body length, only the first part is read
+      _ => (1 << (first & 0x1f), 2),
+    };
+    (header & 0x3f, len, offset)
+  } else {
+    let (len, offset) = match header & 0x3 {
+      0 => (*data.get(1)? as usize, 2),
+      1 => (be_uint(data.get(1..3)?), 3),
+      2 => (be_uint(data.get(1..5)?), 5),
+      _ => (data.len() - 1, 1),
+    };
+    ((header >> 2) & 0xf, len, offset)
+  };
+  Some((tag, data.get(offset..offset + len)?))
+}
+
+/// The type and data of the signature subpackets of an OpenPGP signature.
+struct Subpackets<'a>(&'a [u8]);
+
+impl<'a> Iterator for Subpackets<'a> {
+  type Item = (u8, &'a [u8]);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let data = self.0;
+    let first = *data.first()? as usize;
+    let (len, offset) = match first {
+      0..=191 => (first, 1),
+      192..=254 => (((first - 192) << 8) + *data.get(1)? as usize + 192, 2),
+      _ => (be_uint(data.get(1..5)?), 5),
+    };
+    let subpacket = data.get(offset..offset + len).filter(|s| !s.is_empty());
+    let Some(subpacket) = subpacket else {
+      self.0 = &[];
+      return None;
+    };
+    self.0 = &data[offset + len..];
+    // The high bit flags critical subpackets
+    Some((subpacket[0] & 0x7f, &subpacket[1..]))
+  }
+}
+
+/// The `SHA256:` fingerprint of the public key of the SSHSIG blob `data`.
+fn ssh_fingerprint(data: &[u8]) -> Option<String> {
+  // The magic preamble and the version
+  let rest = data.strip_prefix(b"SSHSIG")?.get(4..)?;
+  let len = be_uint(rest.get(..4)?);
+  let public_key = rest.get(4..4 + len)?;
+  Some(format!(
+    "SHA256:{}",
+    general_purpose::STANDARD_NO_PAD.encode(Sha256::digest(public_key))
+  ))
+}
+
+fn be_uint(bytes: &[u8]) -> usize {
+  bytes
+    .iter()
+    .fold(0, |value, byte| (value << 8) | *byte as usize)
+}
+
+fn hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{byte:02X}")).collect()
+}
@@ -0,0 +1,176 @@
+use std::path::Path;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::error::IntoNapiError;
+
+#[napi]
+#[allow(clippy::upper_case_acronyms)]
+/// The levels of the configuration files, from the lowest to the highest
+/// priority.
+pub enum ConfigLevel {
+  /// System-wide on Windows, for compatibility with portable git
+  ProgramData = 1,
+  /// System-wide configuration file, e.g. /etc/gitconfig
+  System,
+  /// XDG-compatible configuration file, e.g. ~/.config/git/config
+  XDG,
+  /// User-specific configuration, e.g. ~/.gitconfig
+  Global,
+  /// Repository specific config, e.g. $PWD/.git/config
+  Local,
+  /// Worktree specific configuration file, e.g. $GIT_DIR/config.worktree
+  Worktree,
+  /// Application specific configuration file
+  App,
+  /// Highest level available
+  Highest = -1,
+}
+
+impl From<git2::ConfigLevel> for ConfigLevel {
+  fn from(value: git2::ConfigLevel) -> Self {
+    match value {
+      git2::ConfigLevel::ProgramData => ConfigLevel::ProgramData,
+      git2::ConfigLevel::System => ConfigLevel::System,
+      git2::ConfigLevel::XDG => ConfigLevel::XDG,
+      git2::ConfigLevel::Global => ConfigLevel::Global,
+      git2::ConfigLevel::Local => ConfigLevel::Local,
+      git2::ConfigLevel::Worktree => ConfigLevel::Worktree,
+      git2::ConfigLevel::App => ConfigLevel::App,
+      git2::ConfigLevel::Highest => ConfigLevel::Highest,
+    }
+  }
+}
+
+#[napi(object)]
+/// A variable of a configuration file, see `Config.entries`.
+pub struct ConfigEntry {
+  pub name: String,
+  /// Not set if the variable has no value, a shorthand for `true`.
+  pub value: Option<String>,
+  /// How many `include` directives lead to the file of the variable, `0` if
+  /// it was not included.
+  pub include_depth: u32,
+  pub level: ConfigLevel,
+}
+
+#[napi(iterator)]
+/// An iterator over the variables of a configuration.
+pub struct ConfigEntries {
+  pub(crate) inner: SharedReference<Config, git2::ConfigEntries<'static>>,
+}
+
+#[napi]
+impl Generator for ConfigEntries {
+  type Yield = ConfigEntry;
+  type Return = ();
+  type Next = ();
+
+  fn next(&mut self, _value: Option<Self::Next>) -> Option<Self::Yield> {
+    let entry = self.inner.next()?.ok()?;
+    Some(ConfigEntry {
+      name: String::from_utf8_lossy(entry.name_bytes()).into_owned(),
+      value: entry
+        .has_value()
+        .then(|| String::from_utf8_lossy(entry.value_bytes()).into_owned()),
+      include_depth: entry.include_depth(),
+      level: entry.level().into(),
+    })
+  }
+}
+
+#[napi]
+/// A structure representing a git configuration key/value store
+pub struct Config {
+  pub(crate) inner: git2::Config,
+}
+
+#[napi]
+impl Config {
+  #[napi(factory)]
+  /// Open the global configuration file, `$XDG_CONFIG_HOME/git/config` if it
+  /// exists, `~/.gitconfig` otherwise.
+  ///
+  /// Without any global configuration file, the returned config is empty.
+  pub fn open_global() -> Result<Config> {
+    Ok(Config {
+      inner: git2::Config::open_default()
+        .and_then(|mut config| config.open_global())
+        .convert("Open the global config failed")?,
+    })
+  }
+
+  #[napi(factory)]
+  /// Open the system configuration file, e.g. `/etc/gitconfig`.
+  ///
+  /// Throws an error if there is no system configuration file.
+  pub fn open_system() -> Result<Config> {
+    Ok(Config {
+      inner: git2::Config::open_default()
+        .and_then(|config| config.open_level(git2::ConfigLevel::System))
+        .convert("Open the system config failed")?,
+    })
+  }
+
+  #[napi(factory)]
+  /// Open the global, XDG and system configuration files, the values of the
+  /// global ones taking precedence, like the config of a repository without
+  /// its own file.
+  pub fn open_default() -> Result<Config> {
+    Ok(Config {
+      inner: git2::Config::open_default().convert("Open the default config failed")?,
+    })
+  }
+
+  #[napi(factory)]
+  /// Open the configuration file at `path`.
+  pub fn open_path(path: String) -> Result<Config> {
+    Ok(Config {
+      inner: git2::Config::open(Path::new(&path))
+        .convert(format!("Open the config [{path}] failed"))?,
+    })
+  }
+
+  #[napi(factory)]
+  /// Open the configuration file at `path`, like `openPath`, under the name
+  /// of `git_config_open_ondisk` in libgit2.
+  pub fn open_ondisk(path: String) -> Result<Config> {
+    Config::open_path(path)
+  }
+
+  #[napi]
+  /// Iterate over the variables of every level of the configuration, from
+  /// the lowest to the highest level, a variable set at several levels being
+  /// yielded once per level.
+  ///
+  /// Only the variables whose name matches the regular expression `glob` are
+  /// yielded if set.
+  pub fn entries(
+    &self,
+    env: Env,
+    this_ref: Reference<Config>,
+    glob: Option<String>,
+  ) -> Result<ConfigEntries> {
+    Ok(ConfigEntries {
+      inner: this_ref.share_with(env, |config| {
+        config
+          .inner
+          .entries(glob.as_deref())
+          .convert("List the config entries failed")
+      })?,
+    })
+  }
+
+  #[napi]
+  /// Get the value of a string config variable.
+  ///
+  /// Returns `null` if the variable is not set.
+  pub fn get_string(&self, name: String) -> Result<Option<String>> {
+    match self.inner.get_string(&name) {
+      Ok(value) => Ok(Some(value)),
+      Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+      Err(err) => Err(err).convert(format!("Get config [{name}] failed")),
+    }
+  }
+}
@@ -0,0 +1,261 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use regex::Regex;
+
+#[napi(object)]
+pub struct ConventionalCommitsOptions {
+  /// Include the body of the commit messages, without the footers.
+  ///
+  /// Defaults to `false`.
+  pub include_body: Option<bool>,
+}
+
+#[napi(object)]
+/// A footer of a commit message, such as `Refs: #123` or
+/// `BREAKING CHANGE: drop node 16`.
+pub struct CommitFooter {
+  pub key: String,
+  pub value: String,
+}
+
+#[napi(object)]
+/// A commit message parsed according to the
+/// [Conventional Commits](https://www.conventionalcommits.org) specification.
+pub struct ConventionalCommit {
+  pub oid: String,
+  /// The type of the commit, e.g. `feat` or `fix`.
+  ///
+  /// Not set if the summary does not follow the specification.
+  #[napi(js_name = "type")]
+  pub kind: Option<String>,
+  pub scope: Option<String>,
+  /// Whether the summary is marked with `!` or a `BREAKING CHANGE` footer is
+  /// present.
+  pub breaking: bool,
+  /// The description following the type, or the whole summary if it does not
+  /// follow the specification.
+  pub description: String,
+  /// Only set with `includeBody`, and if the message has a body.
+  pub body: Option<String>,
+  pub footers: Vec<CommitFooter>,
+}
+
+impl ConventionalCommit {
+  pub(crate) fn parse(oid: String, message: &str, include_body: bool) -> Self {
+    let message = message.trim();
+    let (summary, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let mut paragraphs = rest
+      .trim()
+      .split("\n\n")
+      .filter(|p| !p.trim().is_empty())
+      .collect::<Vec<_>>();
+    let footers = match paragraphs.last().and_then(|p| parse_footers(p)) {
+      Some(footers) => {
+        paragraphs.pop();
+        footers
+      }
+      None => Vec::new(),
+    };
+    let body = (include_body && !paragraphs.is_empty()).then(|| paragraphs.join("\n\n"));
+    let breaking_footer = footers
+      .iter()
+      .any(|footer| footer.key == "BREAKING CHANGE" || footer.key == "BREAKING-CHANGE");
+    match parse_summary(summary.trim()) {
+      Some((kind, scope, breaking, description)) => Self {
+        oid,
+        kind: Some(kind.to_owned()),
+        scope: scope.map(str::to_owned),
+        breaking: breaking || breaking_footer,
+        description: description.to_owned(),
+        body,
+        footers,
+      },
+      None => Self {
+        oid,
+        kind: None,
+        scope: None,
+        breaking: breaking_footer,
+        description: summary.trim().to_owned(),
+        body,
+        footers,
+      },
+    }
+  }
+}
+
+#[napi(object)]
+#[derive(Default)]
+/// The rules `Repository.validateCommitMessage` checks, every rule not set
+/// being skipped.
+pub struct CommitMessageRules {
+  /// The maximum number of characters of the subject line.
+  pub max_subject_length: Option<u32>,
+  /// Whether the subject must follow the
+  /// [Conventional Commits](https://www.conventionalcommits.org) form
+  /// `type(scope)!: description`.
+  pub require_conventional: Option<bool>,
+  /// A regular expression the subject line must match.
+  pub subject_regex: Option<String>,
+  /// The maximum number of characters of the lines of the body.
+  pub body_line_length: Option<u32>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct ValidateCommitMessageOptions {
+  /// Not set to accept any message.
+  pub rules: Option<CommitMessageRules>,
+}
+
+#[napi(string_enum = "camelCase")]
+/// The rules of `CommitMessageRules`.
+pub enum CommitMessageRule {
+  MaxSubjectLength,
+  RequireConventional,
+  SubjectRegex,
+  BodyLineLength,
+}
+
+#[napi(object)]
+/// A rule a commit message breaks, see `Repository.validateCommitMessage`.
+pub struct CommitMessageProblem {
+  pub rule: CommitMessageRule,
+  pub message: String,
+  /// The line breaking the rule, starting at `1` for the subject.
+  ///
+  /// Not set if the rule applies to the whole message.
+  pub line: Option<u32>,
+}
+
+#[napi(object)]
+/// The result of `Repository.validateCommitMessage`.
+pub struct CommitMessageValidation {
+  /// Whether the message breaks no rule.
+  pub valid: bool,
+  pub problems: Vec<CommitMessageProblem>,
+}
+
+/// Check `message` against `rules`, listing the problems in the order of the
+/// lines breaking them.
+pub(crate) fn validate_commit_message(
+  message: &str,
+  rules: &CommitMessageRules,
+) -> Result<CommitMessageValidation> {
+  let subject_regex = rules
+    .subject_regex
+    .as_deref()
+    .map(|pattern| {
+      Regex::new(pattern).map_err(|err| {
+        Error::new(
+          Status::InvalidArg,
+          format!("Invalid subject regex [{pattern}]: {err}"),
+        )
+      })
+    })
+    .transpose()?;
+  let mut lines = message.trim_end().lines();
+  let subject = lines.next().unwrap_or_default();
+  let mut problems = Vec::new();
+  let subject_length = subject.chars().count();
+  if let Some(max) = rules.max_subject_length {
+    if subject_length > max as usize {
+      problems.push(CommitMessageProblem {
+        rule: CommitMessageRule::MaxSubjectLength,
+        message: format!("The subject has {subject_length} characters, more than {max}"),
+        line: Some(1),
+      });
+    }
+  }
+  if rules.require_conventional.unwrap_or(false) && parse_summary(subject.trim()).is_none() {
+    problems.push(CommitMessageProblem {
+      rule: CommitMessageRule::RequireConventional,
+      message: "The subject does not follow the form `type(scope): description`".to_owned(),
+      line: Some(1),
+    });
+  }
+  if let Some(regex) = subject_regex {
+    if !regex.is_match(subject) {
+      problems.push(CommitMessageProblem {
+        rule: CommitMessageRule::SubjectRegex,
+        message: format!("The subject does not match [{}]", regex.as_str()),
+        line: Some(1),
+      });
+    }
+  }
+  if let Some(max) = rules.body_line_length {
+    for (index, line) in lines.enumerate() {
+      let length = line.chars().count();
+      if length > max as usize {
+        problems.push(CommitMessageProblem {
+          rule: CommitMessageRule::BodyLineLength,
+          message: format!("The line has {length} characters, more than {max}"),
+          line: Some(index as u32 + 2),
+        });
+      }
+    }
+  }
+  Ok(CommitMessageValidation {
+    valid: problems.is_empty(),
+    problems,
+  })
+}
+
+/// Split `type(scope)!: description`, returning `None` if the summary does
+/// not follow that form.
+fn parse_summary(summary: &str) -> Option<(&str, Option<&str>, bool, &str)> {
+  let (prefix, description) = summary.split_once(": ")?;
+  let (prefix, breaking) = match prefix.strip_suffix('!') {
+    Some(prefix) => (prefix, true),
+    None => (prefix, false),
+  };
+  let (kind, scope) = match prefix.split_once('(') {
+    Some((kind, scope)) => {
+      let scope = scope.strip_suffix(')')?;
+      if scope.is_empty() || scope.contains(['(', ')']) {
+        return None;
+      }
+      (kind, Some(scope))
+    }
+    None => (prefix, None),
+  };
+  if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+    return None;
+  }
+  let description = description.trim();
+  if description.is_empty() {
+    return None;
+  }
+  Some((kind, scope, breaking, description))
+}
+
+/// Parse a paragraph of `token: value` or `token #value` lines, a line which
+/// does not start a footer continuing the value of the previous one. Returns
+/// `None` if the paragraph does not start with a footer.
+fn parse_footers(paragraph: &str) -> Option<Vec<CommitFooter>> {
+  let mut footers: Vec<CommitFooter> = Vec::new();
+  for line in paragraph.lines() {
+    match parse_footer(line) {
+      Some(footer) => footers.push(footer),
+      None => footers.last_mut()?.value.push_str(&format!("\n{line}")),
+    }
+  }
+  Some(footers)
+}
+
+fn parse_footer(line: &str) -> Option<CommitFooter> {
+  let (key, value) = match line.split_once(": ") {
+    Some((key, value)) => (key, value.to_owned()),
+    None => {
+      let (key, value) = line.split_once(" #")?;
+      (key, format!("#{value}"))
+    }
+  };
+  let is_token = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+  if !is_token && key != "BREAKING CHANGE" {
+    return None;
+  }
+  Some(CommitFooter {
+    key: key.to_owned(),
+    value,
+  })
+}
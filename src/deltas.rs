@@ -1,7 +1,9 @@
-use napi::{bindgen_prelude::*, JsString};
+use std::collections::{HashMap, HashSet};
+
+use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use crate::util::path_to_javascript_string;
+use crate::error::IntoNapiError;
 
 #[napi]
 #[repr(u32)]
@@ -31,18 +33,6 @@ impl From<DiffFlags> for git2::DiffFlags {
   }
 }
 
-impl From<git2::DiffFlags> for DiffFlags {
-  fn from(value: git2::DiffFlags) -> Self {
-    match value {
-      git2::DiffFlags::BINARY => DiffFlags::Binary,
-      git2::DiffFlags::NOT_BINARY => DiffFlags::NotBinary,
-      git2::DiffFlags::VALID_ID => DiffFlags::ValidId,
-      git2::DiffFlags::EXISTS => DiffFlags::Exists,
-      _ => DiffFlags::Binary,
-    }
-  }
-}
-
 #[napi]
 /// Valid modes for index and tree entries.
 pub enum FileMode {
@@ -76,10 +66,53 @@ impl From<git2::FileMode> for FileMode {
   }
 }
 
+impl From<FileMode> for git2::FileMode {
+  fn from(value: FileMode) -> Self {
+    match value {
+      FileMode::Unreadable => git2::FileMode::Unreadable,
+      FileMode::Tree => git2::FileMode::Tree,
+      FileMode::Blob => git2::FileMode::Blob,
+      FileMode::BlobGroupWritable => git2::FileMode::BlobGroupWritable,
+      FileMode::BlobExecutable => git2::FileMode::BlobExecutable,
+      FileMode::Link => git2::FileMode::Link,
+      FileMode::Commit => git2::FileMode::Commit,
+    }
+  }
+}
+
+impl From<FileMode> for i32 {
+  fn from(value: FileMode) -> Self {
+    git2::FileMode::from(value).into()
+  }
+}
+
+impl From<i32> for FileMode {
+  fn from(value: i32) -> Self {
+    match value {
+      0o040000 => FileMode::Tree,
+      0o100644 => FileMode::Blob,
+      0o100664 => FileMode::BlobGroupWritable,
+      0o100755 => FileMode::BlobExecutable,
+      0o120000 => FileMode::Link,
+      0o160000 => FileMode::Commit,
+      _ => FileMode::Unreadable,
+    }
+  }
+}
+
 #[napi(iterator)]
 /// An iterator over the diffs in a delta
 pub struct Deltas {
   pub(crate) inner: SharedReference<crate::diff::Diff, git2::Deltas<'static>>,
+  /// Indexes of the deltas converted by `textconv`.
+  pub(crate) converted: HashSet<usize>,
+  /// Whether the deltas reclassified by the binary detection policy of the
+  /// repository are binary.
+  pub(crate) binary: HashMap<usize, bool>,
+  /// The targets of the deltas between two symlinks.
+  pub(crate) symlink_targets: HashMap<usize, SymlinkTargetChange>,
+  pub(crate) index: usize,
+  pub(crate) env: Env,
 }
 
 #[napi]
@@ -89,23 +122,164 @@ impl Generator for Deltas {
   type Return = ();
 
   fn next(&mut self, _value: Option<()>) -> Option<Self::Yield> {
-    self.inner.next().map(|delta| DiffDelta { inner: delta })
+    let delta = self.inner.next()?;
+    let converted = self.converted.contains(&self.index);
+    let binary = self.binary.get(&self.index).copied();
+    let symlink_target_change = self.symlink_targets.get(&self.index).cloned();
+    let diff = self
+      .inner
+      .clone_owner(self.env)
+      .ok()
+      .map(|diff| (diff, self.index));
+    self.index += 1;
+    Some(DiffDelta {
+      inner: delta,
+      converted,
+      binary,
+      symlink_target_change,
+      diff,
+    })
   }
 }
 
 #[napi]
 pub struct DiffDelta {
   pub(crate) inner: git2::DiffDelta<'static>,
+  converted: bool,
+  binary: Option<bool>,
+  symlink_target_change: Option<SymlinkTargetChange>,
+  /// The diff the delta belongs to, and its index in the diff.
+  diff: Option<(Reference<crate::diff::Diff>, usize)>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+/// The targets of both sides of a delta between two symlinks.
+pub struct SymlinkTargetChange {
+  pub old: String,
+  pub new: String,
+}
+
+#[napi(object)]
+/// The raw modes of both sides of a delta, e.g. `0o100755`, `0` for a
+/// missing side.
+pub struct DeltaModes {
+  pub old_mode: u32,
+  pub new_mode: u32,
+}
+
+impl DiffDelta {
+  /// The delta of `diff` at `index`, like the ones listed by `Diff.deltas`.
+  pub(crate) fn of_diff(
+    env: Env,
+    diff: &Reference<crate::diff::Diff>,
+    index: usize,
+  ) -> Result<Self> {
+    let owner = diff.clone(env)?;
+    let inner = diff.inner.get_delta(index).ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("The diff has no delta at index {index}"),
+      )
+    })?;
+    Ok(Self {
+      // SAFETY: the delta points into the diff, which `owner` keeps alive
+      inner: unsafe { std::mem::transmute::<git2::DiffDelta<'_>, git2::DiffDelta<'static>>(inner) },
+      converted: diff.converted.contains_key(&index),
+      binary: diff
+        .reclassified(env, index)?
+        .map(|reclassified| reclassified.binary),
+      symlink_target_change: diff.symlink_targets.get(&index).cloned(),
+      diff: Some((owner, index)),
+    })
+  }
+}
+
+impl From<git2::DiffDelta<'static>> for DiffDelta {
+  fn from(inner: git2::DiffDelta<'static>) -> Self {
+    Self {
+      inner,
+      converted: false,
+      binary: None,
+      symlink_target_change: None,
+      diff: None,
+    }
+  }
 }
 
 #[napi]
 impl DiffDelta {
+  #[napi(getter)]
+  /// Whether this binary delta was converted to text by the `textconv`
+  /// option of the diff.
+  pub fn converted(&self) -> bool {
+    self.converted
+  }
+
   #[napi]
-  /// Returns the flags on the delta.
+  /// Returns the flags on the delta, a bitmask of `DiffFlags`, e.g.
+  /// `delta.flags() & DiffFlags.Binary`.
   ///
-  /// For more information, see `DiffFlags`'s documentation.
-  pub fn flags(&self) -> DiffFlags {
-    self.inner.flags().into()
+  /// The binary detection policy of the repository overrides the binary
+  /// flags.
+  pub fn flags(&self) -> u32 {
+    let mut flags = self.inner.flags();
+    if let Some(binary) = self.binary {
+      flags.remove(git2::DiffFlags::BINARY | git2::DiffFlags::NOT_BINARY);
+      flags.insert(if binary {
+        git2::DiffFlags::BINARY
+      } else {
+        git2::DiffFlags::NOT_BINARY
+      });
+    }
+    flags.bits()
+  }
+
+  #[napi]
+  /// Returns `true` if the files of the delta are treated as binary data,
+  /// according to the binary detection policy of the repository if set.
+  pub fn is_binary(&self) -> bool {
+    self
+      .binary
+      .unwrap_or_else(|| self.inner.flags().is_binary())
+  }
+
+  #[napi]
+  /// Returns whether the mode of the entry changed, e.g. by `chmod +x` or
+  /// from a file to a symlink, both sides existing.
+  pub fn mode_changed(&self) -> bool {
+    let (old_file, new_file) = (self.inner.old_file(), self.inner.new_file());
+    old_file.exists() && new_file.exists() && old_file.mode() != new_file.mode()
+  }
+
+  #[napi]
+  /// Returns the raw modes of both sides of this delta.
+  pub fn modes(&self) -> DeltaModes {
+    DeltaModes {
+      old_mode: i32::from(self.inner.old_file().mode()) as u32,
+      new_mode: i32::from(self.inner.new_file().mode()) as u32,
+    }
+  }
+
+  #[napi]
+  /// Returns the targets of both sides of a delta between two symlinks.
+  ///
+  /// Returns `null` unless both sides are symlinks.
+  pub fn symlink_target_change(&self) -> Option<SymlinkTargetChange> {
+    self.symlink_target_change.clone()
+  }
+
+  #[napi]
+  /// Returns the number of hunks of this delta, computed by libgit2 without
+  /// the `textconv` conversion.
+  ///
+  /// Returns `null` for the deltas passed to the callbacks of `ApplyOptions`.
+  pub fn hunk_count(&self) -> Result<Option<u32>> {
+    let Some((diff, index)) = &self.diff else {
+      return Ok(None);
+    };
+    let patch = git2::Patch::from_diff(&diff.inner, *index).convert("Create the patch failed")?;
+    Ok(Some(patch.map_or(0, |patch| patch.num_hunks() as u32)))
   }
 
   #[napi]
@@ -187,6 +361,24 @@ impl From<git2::Delta> for Delta {
   }
 }
 
+impl From<Delta> for git2::Delta {
+  fn from(delta: Delta) -> Self {
+    match delta {
+      Delta::Unmodified => git2::Delta::Unmodified,
+      Delta::Added => git2::Delta::Added,
+      Delta::Deleted => git2::Delta::Deleted,
+      Delta::Modified => git2::Delta::Modified,
+      Delta::Renamed => git2::Delta::Renamed,
+      Delta::Copied => git2::Delta::Copied,
+      Delta::Ignored => git2::Delta::Ignored,
+      Delta::Untracked => git2::Delta::Untracked,
+      Delta::Typechange => git2::Delta::Typechange,
+      Delta::Unreadable => git2::Delta::Unreadable,
+      Delta::Conflicted => git2::Delta::Conflicted,
+    }
+  }
+}
+
 #[napi]
 pub struct DiffFile {
   pub(crate) inner: git2::DiffFile<'static>,
@@ -203,14 +395,34 @@ impl DiffFile {
     self.inner.id().to_string()
   }
 
+  #[napi]
+  /// Returns the path of the entry relative to the working directory of the
+  /// repository.
+  ///
+  /// Returns `null` if the path is not valid UTF-8, see `pathRaw` and
+  /// `pathLossy`.
+  pub fn path(&self) -> Option<&str> {
+    self
+      .inner
+      .path_bytes()
+      .and_then(|path| std::str::from_utf8(path).ok())
+  }
+
   #[napi]
   /// Returns the path, in bytes, of the entry relative to the working
   /// directory of the repository.
-  pub fn path(&self, env: Env) -> Option<JsString> {
+  pub fn path_raw(&self) -> Option<Uint8Array> {
+    self.inner.path_bytes().map(|path| path.to_vec().into())
+  }
+
+  #[napi]
+  /// Returns the path of the entry relative to the working directory of the
+  /// repository, with its invalid UTF-8 sequences replaced by `U+FFFD`.
+  pub fn path_lossy(&self) -> Option<String> {
     self
       .inner
-      .path()
-      .and_then(|p| path_to_javascript_string(&env, p).ok())
+      .path_bytes()
+      .map(|path| String::from_utf8_lossy(path).into_owned())
   }
 
   #[napi]
@@ -219,6 +431,20 @@ impl DiffFile {
     self.inner.size()
   }
 
+  #[napi]
+  /// Returns the flags of the file, a bitmask of `DiffFlags`.
+  pub fn flags(&self) -> u32 {
+    [
+      (self.inner.is_binary(), DiffFlags::Binary),
+      (self.inner.is_not_binary(), DiffFlags::NotBinary),
+      (self.inner.is_valid_id(), DiffFlags::ValidId),
+      (self.inner.exists(), DiffFlags::Exists),
+    ]
+    .into_iter()
+    .filter(|(set, _)| *set)
+    .fold(0, |flags, (_, flag)| flags | flag as u32)
+  }
+
   #[napi]
   /// Returns `true` if file(s) are treated as binary data.
   pub fn is_binary(&self) -> bool {
@@ -249,3 +475,170 @@ impl DiffFile {
     self.inner.mode().into()
   }
 }
+
+#[napi]
+/// A structure describing a hunk of a diff.
+pub struct DiffHunk {
+  old_start: u32,
+  old_lines: u32,
+  new_start: u32,
+  new_lines: u32,
+  header: Vec<u8>,
+}
+
+impl From<git2::DiffHunk<'_>> for DiffHunk {
+  fn from(hunk: git2::DiffHunk<'_>) -> Self {
+    Self {
+      old_start: hunk.old_start(),
+      old_lines: hunk.old_lines(),
+      new_start: hunk.new_start(),
+      new_lines: hunk.new_lines(),
+      header: hunk.header().to_vec(),
+    }
+  }
+}
+
+#[napi]
+impl DiffHunk {
+  #[napi]
+  /// Starting line number in old_file
+  pub fn old_start(&self) -> u32 {
+    self.old_start
+  }
+
+  #[napi]
+  /// Number of lines in old_file
+  pub fn old_lines(&self) -> u32 {
+    self.old_lines
+  }
+
+  #[napi]
+  /// Starting line number in new_file
+  pub fn new_start(&self) -> u32 {
+    self.new_start
+  }
+
+  #[napi]
+  /// Number of lines in new_file
+  pub fn new_lines(&self) -> u32 {
+    self.new_lines
+  }
+
+  #[napi]
+  /// Header text
+  pub fn header(&self) -> String {
+    String::from_utf8_lossy(&self.header).into_owned()
+  }
+}
+
+#[napi]
+/// The kinds of the data of a side of a binary delta.
+pub enum DiffBinaryKind {
+  /// There is no data
+  None,
+  /// The data is the literal content of the file
+  Literal,
+  /// The data is the delta from the other side
+  Delta,
+}
+
+impl From<git2::DiffBinaryKind> for DiffBinaryKind {
+  fn from(value: git2::DiffBinaryKind) -> Self {
+    match value {
+      git2::DiffBinaryKind::None => DiffBinaryKind::None,
+      git2::DiffBinaryKind::Literal => DiffBinaryKind::Literal,
+      git2::DiffBinaryKind::Delta => DiffBinaryKind::Delta,
+    }
+  }
+}
+
+#[napi(object)]
+/// The data of a side of a binary delta.
+pub struct DiffBinaryFile {
+  pub kind: DiffBinaryKind,
+  /// The deflated data.
+  pub data: Buffer,
+  /// The length of the data once inflated.
+  pub inflated_len: u32,
+}
+
+impl From<git2::DiffBinaryFile<'_>> for DiffBinaryFile {
+  fn from(file: git2::DiffBinaryFile<'_>) -> Self {
+    Self {
+      kind: file.kind().into(),
+      data: file.data().to_vec().into(),
+      inflated_len: file.inflated_len() as u32,
+    }
+  }
+}
+
+#[napi(object)]
+/// The contents of a binary delta, see `Diff.foreach`.
+pub struct DiffBinary {
+  /// Whether the data of the sides is included, which needs the
+  /// `showBinary` option of the diff.
+  pub contains_data: bool,
+  pub old_file: DiffBinaryFile,
+  pub new_file: DiffBinaryFile,
+}
+
+impl From<git2::DiffBinary<'_>> for DiffBinary {
+  fn from(binary: git2::DiffBinary<'_>) -> Self {
+    Self {
+      contains_data: binary.contains_data(),
+      old_file: binary.old_file().into(),
+      new_file: binary.new_file().into(),
+    }
+  }
+}
+
+#[napi]
+/// A line of a hunk of a diff.
+pub struct DiffLine {
+  origin: char,
+  content: Vec<u8>,
+  old_lineno: Option<u32>,
+  new_lineno: Option<u32>,
+}
+
+impl From<git2::DiffLine<'_>> for DiffLine {
+  fn from(line: git2::DiffLine<'_>) -> Self {
+    Self {
+      origin: line.origin(),
+      content: line.content().to_vec(),
+      old_lineno: line.old_lineno(),
+      new_lineno: line.new_lineno(),
+    }
+  }
+}
+
+#[napi]
+impl DiffLine {
+  #[napi]
+  /// The origin of the line, `+` for additions, `-` for deletions and a space
+  /// for context lines.
+  ///
+  /// The lines noting a missing newline at the end of the file have the
+  /// origins `=`, `>` and `<`.
+  pub fn origin(&self) -> String {
+    self.origin.to_string()
+  }
+
+  #[napi]
+  /// Content of the line, with its line break
+  pub fn content(&self) -> Buffer {
+    self.content.clone().into()
+  }
+
+  #[napi]
+  /// Line number in old_file, `null` for added lines
+  pub fn old_lineno(&self) -> Option<u32> {
+    self.old_lineno
+  }
+
+  #[napi]
+  /// Line number in new_file, `null` for deleted lines
+  pub fn new_lineno(&self) -> Option<u32> {
+    self.new_lineno
+  }
+}
@@ -0,0 +1,125 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{error::IntoNapiError, repo::Repository};
+
+#[napi]
+/// Options which can be used to customize how a description is created.
+pub struct DescribeOptions {
+  pub(crate) inner: git2::DescribeOptions,
+}
+
+#[napi]
+impl DescribeOptions {
+  #[napi(constructor)]
+  #[allow(clippy::new_without_default)]
+  /// Creates a new set of options with default values.
+  pub fn new() -> DescribeOptions {
+    DescribeOptions {
+      inner: git2::DescribeOptions::new(),
+    }
+  }
+
+  #[napi]
+  /// Sets the reference lookup strategy
+  ///
+  /// This behaves like the `--tags` option to git-describe.
+  pub fn describe_tags(&mut self) -> &Self {
+    self.inner.describe_tags();
+    self
+  }
+
+  #[napi]
+  /// Sets the reference lookup strategy
+  ///
+  /// This behaves like the `--all` option to git-describe.
+  pub fn describe_all(&mut self) -> &Self {
+    self.inner.describe_all();
+    self
+  }
+
+  #[napi]
+  /// Decide whether only the first parent of a commit should be followed when
+  /// looking for a reference.
+  pub fn only_follow_first_parent(&mut self, follow: bool) -> &Self {
+    self.inner.only_follow_first_parent(follow);
+    self
+  }
+
+  #[napi]
+  /// If no matching tag or reference is found whether a describe option would
+  /// normally fail. This option indicates, however, that it will instead fall
+  /// back to showing the full id of the commit.
+  pub fn show_commit_oid_as_fallback(&mut self, show: bool) -> &Self {
+    self.inner.show_commit_oid_as_fallback(show);
+    self
+  }
+}
+
+#[napi]
+/// The result of a `describe` operation on a `Repository`.
+pub struct DescribeResult {
+  pub(crate) inner: SharedReference<Repository, git2::Describe<'static>>,
+}
+
+#[napi]
+impl DescribeResult {
+  #[napi]
+  /// Prints this describe result, returning the result as a string.
+  pub fn format(&self, opts: Option<&DescribeFormatOptions>) -> Result<String> {
+    self
+      .inner
+      .format(opts.map(|o| &o.inner))
+      .convert("Format describe result failed")
+  }
+
+  #[napi]
+  /// Prints this describe result with the default format options.
+  pub fn format_simple(&self) -> Result<String> {
+    self.format(None)
+  }
+}
+
+#[napi]
+/// Options which can be used to customize how a description is formatted.
+pub struct DescribeFormatOptions {
+  pub(crate) inner: git2::DescribeFormatOptions,
+}
+
+#[napi]
+impl DescribeFormatOptions {
+  #[napi(constructor)]
+  #[allow(clippy::new_without_default)]
+  /// Creates a new set of options with default values.
+  pub fn new() -> DescribeFormatOptions {
+    DescribeFormatOptions {
+      inner: git2::DescribeFormatOptions::new(),
+    }
+  }
+
+  #[napi]
+  /// Sets the size of the abbreviated commit id to use.
+  ///
+  /// The value is the lower bound for the length of the abbreviated string,
+  /// and the default is 7.
+  pub fn abbreviated_size(&mut self, size: u32) -> &Self {
+    self.inner.abbreviated_size(size);
+    self
+  }
+
+  #[napi]
+  /// Sets whether or not the long format is used even when a shorter name
+  /// could be used.
+  pub fn always_use_long_format(&mut self, long: bool) -> &Self {
+    self.inner.always_use_long_format(long);
+    self
+  }
+
+  #[napi]
+  /// If the workdir is dirty and this is set, this string will be appended to
+  /// the description string.
+  pub fn dirty_suffix(&mut self, suffix: String) -> &Self {
+    self.inner.dirty_suffix(&suffix);
+    self
+  }
+}
@@ -1,28 +1,567 @@
-use std::ops::Deref;
+use std::{
+  cell::{Cell, Ref, RefCell},
+  collections::HashMap,
+  fs,
+  ops::{Deref, DerefMut},
+};
 
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use crate::deltas::Deltas;
+use crate::binary::BinaryDetection;
+use crate::deltas::{
+  Delta, Deltas, DiffBinary, DiffDelta, DiffHunk, DiffLine, SymlinkTargetChange,
+};
+use crate::diff_driver::{render_patch, Funcname};
 use crate::error::IntoNapiError;
+use crate::patch::Patch;
+use crate::stats::{HandleKind, OpenHandle};
+use crate::util::{CallbackError, JsCallback};
 
-#[napi(object)]
-#[derive(Debug, Default)]
+#[napi(object, object_to_js = false)]
+#[derive(Default)]
 pub struct DiffOptions {
   /// When generating output, include the names of unmodified files if they
   /// are included in the `Diff`. Normally these are skipped in the formats
   /// that list files (e.g. name-only, name-status, raw). Even with this these
   /// will not be included in the patch format.
   pub show_unmodified: Option<bool>,
+  /// Convert the content of binary files to text before computing their
+  /// hunks, like the `textconv` drivers of `git diff --textconv`.
+  ///
+  /// Called with the path and the content of each side of a binary delta,
+  /// returning `null` leaves the delta as binary.
+  #[napi(ts_type = "(path: string, content: Buffer) => string | null")]
+  pub textconv: Option<FunctionRef<(String, Buffer), Option<String>>>,
+  /// Binary files larger than this many bytes are not converted by
+  /// `textconv`.
+  ///
+  /// Defaults to no limit.
+  pub textconv_max_bytes: Option<u32>,
+  /// Include the whole functions around the changes as context, like
+  /// `git diff -W`.
+  ///
+  /// The functions are found with the `xfuncname` of the diff driver of each
+  /// path, set by the `diff` attribute.
+  pub function_context: Option<bool>,
+  /// Report the entries whose type changed, e.g. from a file to a symlink,
+  /// as a single `Typechange` delta instead of splitting them into a deleted
+  /// and an added delta.
+  pub include_typechange: Option<bool>,
+  /// Only diff the paths matching these pathspecs, e.g. `src/*.rs`.
+  ///
+  /// Not set to diff every path.
+  pub pathspec: Option<Vec<String>>,
+  /// The number of unchanged lines around the changes of the hunks.
+  ///
+  /// Defaults to 3.
+  pub context_lines: Option<u32>,
+  /// The maximum number of unchanged lines between two changes before they
+  /// are split into separate hunks.
+  ///
+  /// Defaults to 0.
+  pub interhunk_lines: Option<u32>,
+  /// Ignore all whitespace, like `git diff -w`.
+  pub ignore_whitespace: Option<bool>,
+  /// Ignore changes in the amount of whitespace, like `git diff -b`.
+  pub ignore_whitespace_change: Option<bool>,
+  /// Include the untracked files in diffs against the working directory.
+  pub include_untracked: Option<bool>,
+  /// Include the content of untracked directories rather than the
+  /// directories themselves, with `includeUntracked`.
+  pub recurse_untracked_dirs: Option<bool>,
+  /// Files larger than this many bytes are treated as binary.
+  ///
+  /// Defaults to 512MB, a negative value disables the limit.
+  pub max_size: Option<i64>,
+  /// The number of hexadecimal digits the ids of the `index` lines of the
+  /// patches are abbreviated to.
+  ///
+  /// Defaults to `core.abbrev`, or 7.
+  pub id_abbrev: Option<u32>,
+  /// Detect the renamed files, like `git diff -M`, reporting them as
+  /// `Renamed` deltas.
+  pub find_renames: Option<bool>,
+  /// Render the binary deltas as binary patches which `git apply` can apply,
+  /// like `git diff --binary`.
+  ///
+  /// Not set to only note that the binary files differ.
+  pub show_binary: Option<bool>,
+}
+
+impl DiffOptions {
+  pub(crate) fn to_git2(&self) -> git2::DiffOptions {
+    let mut options = git2::DiffOptions::default();
+    if let Some(show_unmodified) = self.show_unmodified {
+      options.show_unmodified(show_unmodified);
+    }
+    if let Some(include_typechange) = self.include_typechange {
+      options.include_typechange(include_typechange);
+    }
+    for pathspec in self.pathspec.iter().flatten() {
+      options.pathspec(pathspec);
+    }
+    if let Some(context_lines) = self.context_lines {
+      options.context_lines(context_lines);
+    }
+    if let Some(interhunk_lines) = self.interhunk_lines {
+      options.interhunk_lines(interhunk_lines);
+    }
+    if let Some(ignore_whitespace) = self.ignore_whitespace {
+      options.ignore_whitespace(ignore_whitespace);
+    }
+    if let Some(ignore_whitespace_change) = self.ignore_whitespace_change {
+      options.ignore_whitespace_change(ignore_whitespace_change);
+    }
+    if let Some(include_untracked) = self.include_untracked {
+      options.include_untracked(include_untracked);
+    }
+    if let Some(recurse_untracked_dirs) = self.recurse_untracked_dirs {
+      options.recurse_untracked_dirs(recurse_untracked_dirs);
+    }
+    if let Some(max_size) = self.max_size {
+      options.max_size(max_size);
+    }
+    if let Some(id_abbrev) = self.id_abbrev {
+      options.id_abbrev(id_abbrev.min(u16::MAX as u32) as u16);
+    }
+    if let Some(show_binary) = self.show_binary {
+      options.show_binary(show_binary);
+    }
+    options
+  }
+}
+
+#[napi(object)]
+/// The totals of the changes of a diff, see `Diff.stats`.
+pub struct DiffStats {
+  pub files_changed: u32,
+  pub insertions: u32,
+  pub deletions: u32,
+}
+
+#[napi]
+/// The formats `Diff.statsBuffer` renders the totals of a diff in.
+pub enum DiffStatsFormat {
+  /// A line per file with a histogram, like `git diff --stat`.
+  Full,
+  /// Only the totals, like `git diff --shortstat`.
+  Short,
+  /// A line per file with the numbers of changed lines, like
+  /// `git diff --numstat`.
+  Number,
+}
+
+impl From<DiffStatsFormat> for git2::DiffStatsFormat {
+  fn from(value: DiffStatsFormat) -> Self {
+    match value {
+      DiffStatsFormat::Full => git2::DiffStatsFormat::FULL,
+      DiffStatsFormat::Short => git2::DiffStatsFormat::SHORT,
+      DiffStatsFormat::Number => git2::DiffStatsFormat::NUMBER,
+    }
+  }
+}
+
+#[napi]
+/// The formats `Diff.toBuffer` renders a diff in.
+pub enum DiffFormat {
+  /// The full patch, like `git diff`.
+  Patch,
+  /// The file headers of the patch, without the hunks.
+  PatchHeader,
+  /// A line per file with the modes and ids of both sides, like
+  /// `git diff --raw`.
+  Raw,
+  /// The paths of the files, like `git diff --name-only`.
+  NameOnly,
+  /// The statuses and paths of the files, like `git diff --name-status`.
+  NameStatus,
+}
+
+impl From<DiffFormat> for git2::DiffFormat {
+  fn from(value: DiffFormat) -> Self {
+    match value {
+      DiffFormat::Patch => git2::DiffFormat::Patch,
+      DiffFormat::PatchHeader => git2::DiffFormat::PatchHeader,
+      DiffFormat::Raw => git2::DiffFormat::Raw,
+      DiffFormat::NameOnly => git2::DiffFormat::NameOnly,
+      DiffFormat::NameStatus => git2::DiffFormat::NameStatus,
+    }
+  }
+}
+
+#[napi(object)]
+/// The numbers of changed lines of a delta, see `Diff.numstat`.
+pub struct FileNumstat {
+  /// The path of the new side of the delta, or of the old side if the file
+  /// was deleted.
+  pub path: String,
+  pub insertions: u32,
+  pub deletions: u32,
+  /// Binary deltas have no line counted.
+  pub is_binary: bool,
+}
+
+pub(crate) enum DiffInner {
+  Repository(SharedReference<crate::repo::Repository, git2::Diff<'static>>),
+  /// A diff parsed by `Diff.fromBuffer`, which belongs to no repository.
+  Buffer(git2::Diff<'static>),
+}
+
+impl Deref for DiffInner {
+  type Target = git2::Diff<'static>;
+
+  fn deref(&self) -> &git2::Diff<'static> {
+    match self {
+      DiffInner::Repository(parent) => parent.deref(),
+      DiffInner::Buffer(diff) => diff,
+    }
+  }
+}
+
+impl DerefMut for DiffInner {
+  fn deref_mut(&mut self) -> &mut git2::Diff<'static> {
+    match self {
+      DiffInner::Repository(parent) => parent.deref_mut(),
+      DiffInner::Buffer(diff) => diff,
+    }
+  }
 }
 
 #[napi]
 pub struct Diff {
-  pub(crate) inner: SharedReference<crate::repo::Repository, git2::Diff<'static>>,
+  pub(crate) inner: DiffInner,
+  /// The patches of the binary deltas converted by `textconv`, keyed by the
+  /// index of the delta.
+  pub(crate) converted: HashMap<usize, Vec<u8>>,
+  /// The deltas classified by the binary detection policy of the repository
+  /// so far, keyed by the index of the delta, with whether they are binary
+  /// and their patch rendered accordingly if libgit2 classified them
+  /// differently.
+  reclassified: RefCell<HashMap<usize, Option<Reclassified>>>,
+  /// The patches of the text deltas rendered with the function names found
+  /// by their diff driver, keyed by the index of the delta.
+  pub(crate) function_names: HashMap<usize, Vec<u8>>,
+  /// The targets of both sides of the deltas between two symlinks, keyed by
+  /// the index of the delta.
+  pub(crate) symlink_targets: HashMap<usize, SymlinkTargetChange>,
+  /// The binary detection policy of the repository when the diff was made.
+  binary_detection: Option<BinaryDetection>,
+  /// The options the diff was made with, to render the reclassified deltas.
+  options: DiffOptions,
+  /// Not set for the diffs parsed by `Diff.fromBuffer`.
+  pub(crate) _handle: Option<OpenHandle>,
+}
+
+impl Diff {
+  pub(crate) fn new(
+    env: Env,
+    mut inner: SharedReference<crate::repo::Repository, git2::Diff<'static>>,
+    repo: &crate::repo::Repository,
+    options: Option<DiffOptions>,
+  ) -> Result<Self> {
+    let mut options = options.unwrap_or_default();
+    if options.find_renames.unwrap_or(false) {
+      inner
+        .find_similar(Some(git2::DiffFindOptions::new().renames(true)))
+        .convert("Find the renamed files failed")?;
+    }
+    let function_context = options.function_context.unwrap_or(false);
+    let function_names = function_name_patches(&repo.inner, &inner, function_context)?;
+    let symlink_targets = symlink_targets(&repo.inner, &inner)?;
+    let textconv = options.textconv.take();
+    let mut diff = Self {
+      inner: DiffInner::Repository(inner),
+      converted: HashMap::new(),
+      reclassified: RefCell::default(),
+      function_names,
+      symlink_targets,
+      binary_detection: repo.binary_detection.clone(),
+      options,
+      _handle: Some(OpenHandle::new(&repo.handles, HandleKind::Diff)),
+    };
+    if let Some(textconv) = textconv {
+      diff.converted = textconv_patches(env, &repo.inner, &diff, &JsCallback::new(env, textconv)?)?;
+    }
+    Ok(diff)
+  }
+
+  /// Classify the delta at `index` with the binary detection policy of the
+  /// repository, returning whether it is binary and its patch rendered
+  /// accordingly if libgit2 classified it differently.
+  pub(crate) fn reclassified(
+    &self,
+    env: Env,
+    index: usize,
+  ) -> Result<Option<Ref<'_, Reclassified>>> {
+    let (Some(policy), DiffInner::Repository(inner)) = (&self.binary_detection, &self.inner) else {
+      return Ok(None);
+    };
+    if !self.reclassified.borrow().contains_key(&index) {
+      let repo = inner.clone_owner(env)?;
+      let reclassified = reclassify_delta(&repo.inner, &self.inner, index, policy, &self.options)?;
+      self.reclassified.borrow_mut().insert(index, reclassified);
+    }
+    Ok(
+      Ref::filter_map(self.reclassified.borrow(), |reclassified| {
+        reclassified.get(&index).and_then(Option::as_ref)
+      })
+      .ok(),
+    )
+  }
+
+  /// Whether the delta at `index` is binary according to the binary detection
+  /// policy of the repository, not set without a policy.
+  pub(crate) fn is_binary(&self, env: Env, index: usize) -> Result<Option<bool>> {
+    if self.binary_detection.is_none() {
+      return Ok(None);
+    }
+    let reclassified = self
+      .reclassified(env, index)?
+      .map(|reclassified| reclassified.binary);
+    Ok(Some(reclassified.unwrap_or_else(|| {
+      self
+        .inner
+        .get_delta(index)
+        .is_some_and(|delta| delta.flags().is_binary())
+    })))
+  }
+}
+
+/// A delta the binary detection policy of the repository classifies
+/// differently from libgit2.
+#[derive(Clone)]
+pub(crate) struct Reclassified {
+  pub(crate) binary: bool,
+  /// The patch of the delta, rendered as binary or text accordingly.
+  pub(crate) patch: Vec<u8>,
+}
+
+/// Classify the delta at `index` of `diff` with `policy`, rendering it as
+/// binary or text with `options` if libgit2 classified it differently.
+fn reclassify_delta(
+  repo: &git2::Repository,
+  diff: &git2::Diff,
+  index: usize,
+  policy: &BinaryDetection,
+  options: &DiffOptions,
+) -> Result<Option<Reclassified>> {
+  let Some(patch) = git2::Patch::from_diff(diff, index).convert_without_message()? else {
+    return Ok(None);
+  };
+  let delta = patch.delta();
+  let old_content = read_diff_file(repo, &delta.old_file())?;
+  let new_content = read_diff_file(repo, &delta.new_file())?;
+  let mut binary = false;
+  for (file, content) in [
+    (delta.old_file(), &old_content),
+    (delta.new_file(), &new_content),
+  ] {
+    let (Some(path), Some(content)) = (file.path(), content) else {
+      continue;
+    };
+    binary |= policy
+      .is_binary(repo, &path.to_string_lossy(), Some(content))
+      .convert("Detect binary file failed")?;
+  }
+  if binary == delta.flags().is_binary() {
+    return Ok(None);
+  }
+  let mut options = options.to_git2();
+  options.force_binary(binary).force_text(!binary);
+  let mut patch = git2::Patch::from_buffers(
+    old_content.as_deref().unwrap_or_default(),
+    delta.old_file().path(),
+    new_content.as_deref().unwrap_or_default(),
+    delta.new_file().path(),
+    Some(&mut options),
+  )
+  .convert("Render reclassified delta failed")?;
+  let buf = patch.to_buf().convert("Render reclassified delta failed")?;
+  Ok(Some(Reclassified {
+    binary,
+    patch: buf.to_vec(),
+  }))
+}
+
+/// Render the text deltas of `diff` whose path has a diff driver with a
+/// function name pattern, or all of them with `function_context`.
+fn function_name_patches(
+  repo: &git2::Repository,
+  diff: &git2::Diff,
+  function_context: bool,
+) -> Result<HashMap<usize, Vec<u8>>> {
+  let mut rendered = HashMap::new();
+  for (index, delta) in diff.deltas().enumerate() {
+    let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+      continue;
+    };
+    let funcname = match Funcname::for_path(repo, path)? {
+      Some(funcname) => funcname,
+      None if function_context => Funcname::default(),
+      None => continue,
+    };
+    let Some(mut patch) = git2::Patch::from_diff(diff, index).convert_without_message()? else {
+      continue;
+    };
+    if patch.delta().flags().is_binary() {
+      continue;
+    }
+    let old = read_diff_file(repo, &delta.old_file())?.unwrap_or_default();
+    let new = match function_context {
+      true => read_diff_file(repo, &delta.new_file())?.unwrap_or_default(),
+      false => Vec::new(),
+    };
+    rendered.insert(
+      index,
+      render_patch(&mut patch, &funcname, &old, &new, function_context)?,
+    );
+  }
+  Ok(rendered)
+}
+
+/// Render the binary deltas of `diff` which `textconv` converts to text, with
+/// the hunk `options` of the diff.
+fn textconv_patches(
+  env: Env,
+  repo: &git2::Repository,
+  diff: &Diff,
+  textconv: &JsCallback<(String, Buffer), Option<String>>,
+) -> Result<HashMap<usize, Vec<u8>>> {
+  let max_bytes = diff.options.textconv_max_bytes.map(|max| max as usize);
+  let mut options = diff.options.to_git2();
+  let mut converted = HashMap::new();
+  for (index, delta) in diff.inner.deltas().enumerate() {
+    let Some(patch) = git2::Patch::from_diff(&diff.inner, index).convert_without_message()? else {
+      continue;
+    };
+    let binary = diff
+      .is_binary(env, index)?
+      .unwrap_or_else(|| patch.delta().flags().is_binary());
+    if !binary {
+      continue;
+    }
+    let old_content = read_diff_file(repo, &delta.old_file())?;
+    let new_content = read_diff_file(repo, &delta.new_file())?;
+    let too_large = |content: &Option<Vec<u8>>| matches!((content, max_bytes), (Some(content), Some(max)) if content.len() > max);
+    if too_large(&old_content) || too_large(&new_content) {
+      continue;
+    }
+    let convert = |file: git2::DiffFile, content: Option<Vec<u8>>| -> Result<Option<String>> {
+      match content {
+        Some(content) => {
+          let path = file
+            .path()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+          textconv.call((path, content.into()), Ok)
+        }
+        None => Ok(Some(String::new())),
+      }
+    };
+    let (Some(old_text), Some(new_text)) = (
+      convert(delta.old_file(), old_content)?,
+      convert(delta.new_file(), new_content)?,
+    ) else {
+      continue;
+    };
+    let mut patch = git2::Patch::from_buffers(
+      old_text.as_bytes(),
+      delta.old_file().path(),
+      new_text.as_bytes(),
+      delta.new_file().path(),
+      Some(&mut options),
+    )
+    .convert("Render converted delta failed")?;
+    let buf = patch.to_buf().convert("Render converted delta failed")?;
+    converted.insert(index, buf.to_vec());
+  }
+  Ok(converted)
+}
+
+/// Read the content of one side of a delta, from the object database or from
+/// the working directory if it was not written to it.
+fn read_diff_file(repo: &git2::Repository, file: &git2::DiffFile) -> Result<Option<Vec<u8>>> {
+  if !file.exists() {
+    return Ok(None);
+  }
+  if let Ok(blob) = repo.find_blob(file.id()) {
+    return Ok(Some(blob.content().to_vec()));
+  }
+  match (repo.workdir(), file.path()) {
+    (Some(workdir), Some(path)) => fs::read(workdir.join(path)).map(Some).map_err(|err| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Read [{}] failed: {err}", path.display()),
+      )
+    }),
+    _ => Ok(None),
+  }
+}
+
+/// Read the targets of both sides of the deltas between two symlinks.
+fn symlink_targets(
+  repo: &git2::Repository,
+  diff: &git2::Diff,
+) -> Result<HashMap<usize, SymlinkTargetChange>> {
+  let mut targets = HashMap::new();
+  for (index, delta) in diff.deltas().enumerate() {
+    let (old_file, new_file) = (delta.old_file(), delta.new_file());
+    if old_file.mode() != git2::FileMode::Link || new_file.mode() != git2::FileMode::Link {
+      continue;
+    }
+    if let (Some(old), Some(new)) = (
+      read_symlink_target(repo, &old_file)?,
+      read_symlink_target(repo, &new_file)?,
+    ) {
+      targets.insert(index, SymlinkTargetChange { old, new });
+    }
+  }
+  Ok(targets)
+}
+
+/// Read the target of the symlink `file`, from its blob or from the working
+/// directory if it is not hashed yet.
+fn read_symlink_target(repo: &git2::Repository, file: &git2::DiffFile) -> Result<Option<String>> {
+  if let Ok(blob) = repo.find_blob(file.id()) {
+    return Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()));
+  }
+  match (repo.workdir(), file.path()) {
+    (Some(workdir), Some(path)) => fs::read_link(workdir.join(path))
+      .map(|target| Some(target.to_string_lossy().replace('\\', "/")))
+      .map_err(|err| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Read the symlink [{}] failed: {err}", path.display()),
+        )
+      }),
+    _ => Ok(None),
+  }
 }
 
 #[napi]
 impl Diff {
+  #[napi]
+  /// Parse a patch in the unified diff format, e.g. the output of `git diff`
+  /// or `Diff.toBuffer`, into a diff which can be applied with
+  /// `Repository.apply`.
+  ///
+  /// The diff belongs to no repository, so its deltas only carry the paths,
+  /// the modes and the abbreviated ids of the patch.
+  pub fn from_buffer(patch: String) -> Result<Diff> {
+    let inner = git2::Diff::from_buffer(patch.as_bytes()).convert("Parse the patch failed")?;
+    Ok(Diff {
+      inner: DiffInner::Buffer(inner),
+      converted: HashMap::new(),
+      reclassified: RefCell::default(),
+      function_names: HashMap::new(),
+      symlink_targets: HashMap::new(),
+      binary_detection: None,
+      options: DiffOptions::default(),
+      _handle: None,
+    })
+  }
+
   #[napi]
   /// Merge one diff into another.
   ///
@@ -32,24 +571,405 @@ impl Diff {
   /// as if the old version was from the "onto" list and the new version
   /// is from the "from" list (with the exception that if the item has a
   /// pending DELETE in the middle, then it will show as deleted).
+  ///
+  /// The deltas rendered by `textconv`, the binary detection policy or the
+  /// diff drivers keep their rendering when they are left unchanged by the
+  /// merge, the deltas merged from both lists are rendered by libgit2.
   pub fn merge(&mut self, diff: &Diff) -> Result<()> {
+    let ours = delta_indexes(&self.inner);
+    let theirs = delta_indexes(&diff.inner);
     self
       .inner
       .merge(diff.inner.deref())
-      .convert_without_message()
+      .convert_without_message()?;
+    let merged = self
+      .inner
+      .deltas()
+      .map(|delta| DeltaKey::from(&delta))
+      .collect::<Vec<_>>();
+    let merged = MergedDeltas {
+      merged,
+      ours,
+      theirs,
+    };
+    self.converted = merged.rekey(&self.converted, &diff.converted);
+    *self.reclassified.get_mut() =
+      merged.rekey(self.reclassified.get_mut(), &diff.reclassified.borrow());
+    self.function_names = merged.rekey(&self.function_names, &diff.function_names);
+    self.symlink_targets = merged.rekey(&self.symlink_targets, &diff.symlink_targets);
+    Ok(())
+  }
+
+  #[napi]
+  /// Returns the number of deltas in this diff.
+  pub fn len(&self) -> u32 {
+    self.inner.deltas().len() as u32
+  }
+
+  #[napi]
+  /// Returns `true` if this diff has no delta.
+  pub fn is_empty(&self) -> bool {
+    self.inner.deltas().len() == 0
+  }
+
+  #[napi]
+  /// Returns the number of deltas of the status `delta` in this diff.
+  pub fn num_deltas_of_type(&self, delta: Delta) -> u32 {
+    let delta = git2::Delta::from(delta);
+    self
+      .inner
+      .deltas()
+      .filter(|candidate| candidate.status() == delta)
+      .count() as u32
+  }
+
+  #[napi]
+  /// Get the patch of the delta at `index`, with its hunks and lines.
+  ///
+  /// Returns `null` if the delta is binary, according to the binary
+  /// detection policy of the repository if set, or unchanged.
+  pub fn patch(&self, env: Env, self_ref: Reference<Diff>, index: u32) -> Result<Option<Patch>> {
+    let index = index as usize;
+    if index >= self.inner.deltas().len() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("The diff has no delta at index {index}"),
+      ));
+    }
+    if git2::Patch::from_diff(&self.inner, index)
+      .convert_without_message()?
+      .is_none()
+      || self.is_binary(env, index)? == Some(true)
+    {
+      return Ok(None);
+    }
+    Ok(Some(Patch {
+      inner: self_ref.share_with(env, |diff| {
+        git2::Patch::from_diff(&diff.inner, index)
+          .convert_without_message()?
+          .ok_or_else(|| Error::from_reason("The delta has no patch"))
+      })?,
+    }))
   }
 
   #[napi]
   /// Returns an iterator over the deltas in this diff.
   pub fn deltas(&self, env: Env, self_ref: Reference<Diff>) -> Result<Deltas> {
+    let mut binary = HashMap::new();
+    for index in 0..self.inner.deltas().len() {
+      if let Some(reclassified) = self.reclassified(env, index)? {
+        binary.insert(index, reclassified.binary);
+      }
+    }
     Ok(Deltas {
+      converted: self.converted.keys().copied().collect(),
+      binary,
+      symlink_targets: self.symlink_targets.clone(),
+      index: 0,
+      env,
       inner: self_ref.share_with(env, |diff| Ok(diff.inner.deltas()))?,
     })
   }
 
+  #[napi]
+  /// Render this diff in `format`, the patch format like `git diff` if not
+  /// set.
+  ///
+  /// In the patch format, binary deltas converted by the `textconv` option
+  /// are rendered with the converted text, and deltas are rendered as binary
+  /// or text according to the binary detection policy of the repository. The
+  /// hunk headers name the enclosing function found by the diff driver of the
+  /// path.
+  pub fn to_buffer(&self, env: Env, format: Option<DiffFormat>) -> Result<Buffer> {
+    let mut buf = Vec::new();
+    if let Some(format) = format.filter(|format| !matches!(format, DiffFormat::Patch)) {
+      self
+        .inner
+        .print(format.into(), |_, _, line| {
+          // The content lines do not include their origin
+          if matches!(line.origin(), '+' | '-' | ' ') {
+            buf.push(line.origin() as u8);
+          }
+          buf.extend_from_slice(line.content());
+          true
+        })
+        .convert("Render the diff failed")?;
+      return Ok(buf.into());
+    }
+    for index in 0..self.inner.deltas().len() {
+      if let Some(converted) = self.converted.get(&index) {
+        buf.extend_from_slice(converted);
+      } else if let Some(reclassified) = self.reclassified(env, index)? {
+        buf.extend_from_slice(&reclassified.patch);
+      } else if let Some(rendered) = self.function_names.get(&index) {
+        buf.extend_from_slice(rendered);
+      } else if let Some(mut patch) =
+        git2::Patch::from_diff(&self.inner, index).convert_without_message()?
+      {
+        buf.extend_from_slice(&patch.to_buf().convert_without_message()?);
+      }
+    }
+    Ok(buf.into())
+  }
+
+  #[napi(
+    ts_args_type = "fileCb: (delta: DiffDelta, progress: number) => boolean | void, binaryCb?: ((delta: DiffDelta, binary: DiffBinary) => boolean | void) | undefined | null, hunkCb?: ((delta: DiffDelta, hunk: DiffHunk) => boolean | void) | undefined | null, lineCb?: ((delta: DiffDelta, hunk: DiffHunk | null, line: DiffLine) => boolean | void) | undefined | null"
+  )]
+  /// Iterate over the deltas of the diff, calling `fileCb` for each delta
+  /// with the progress of the iteration between 0 and 1, `binaryCb` for the
+  /// binary deltas, `hunkCb` for each hunk and `lineCb` for each line.
+  ///
+  /// Returning `false` from a callback stops the iteration, an error thrown
+  /// by a callback stops it and is rethrown.
+  pub fn foreach(
+    &self,
+    env: Env,
+    self_ref: Reference<Diff>,
+    file_cb: ForeachCallback<(DiffDelta, f64)>,
+    binary_cb: Option<ForeachCallback<(DiffDelta, DiffBinary)>>,
+    hunk_cb: Option<ForeachCallback<(DiffDelta, DiffHunk)>>,
+    line_cb: Option<ForeachCallback<(DiffDelta, Option<DiffHunk>, DiffLine)>>,
+  ) -> Result<()> {
+    let callback_error = &CallbackError::default();
+    // libgit2 skips some deltas, the index of the current one is found by
+    // looking for it from the previous one
+    let current = Cell::new(0);
+    let delta = || DiffDelta::of_diff(env, &self_ref, current.get());
+    let mut on_file = |file: git2::DiffDelta<'_>, progress: f32| {
+      let deltas = self.inner.deltas().len();
+      if let Some(index) = (current.get()..deltas).find(|index| {
+        self
+          .inner
+          .get_delta(*index)
+          .is_some_and(|candidate| same_delta(&candidate, &file))
+      }) {
+        current.set(index);
+      }
+      call_foreach_callback(&env, &file_cb, callback_error, || {
+        Ok((delta()?, progress as f64))
+      })
+    };
+    let mut on_binary = binary_cb.map(|binary_cb| {
+      move |_: git2::DiffDelta<'_>, binary: git2::DiffBinary<'_>| {
+        call_foreach_callback(&env, &binary_cb, callback_error, || {
+          Ok((delta()?, binary.into()))
+        })
+      }
+    });
+    let mut on_hunk = hunk_cb.map(|hunk_cb| {
+      move |_: git2::DiffDelta<'_>, hunk: git2::DiffHunk<'_>| {
+        call_foreach_callback(&env, &hunk_cb, callback_error, || {
+          Ok((delta()?, hunk.into()))
+        })
+      }
+    });
+    let mut on_line = line_cb.map(|line_cb| {
+      move |_: git2::DiffDelta<'_>, hunk: Option<git2::DiffHunk<'_>>, line: git2::DiffLine<'_>| {
+        call_foreach_callback(&env, &line_cb, callback_error, || {
+          Ok((delta()?, hunk.map(DiffHunk::from), line.into()))
+        })
+      }
+    });
+    let result = self.inner.foreach(
+      &mut on_file,
+      on_binary
+        .as_mut()
+        .map(|cb| cb as &mut dyn FnMut(git2::DiffDelta<'_>, git2::DiffBinary<'_>) -> bool),
+      on_hunk
+        .as_mut()
+        .map(|cb| cb as &mut dyn FnMut(git2::DiffDelta<'_>, git2::DiffHunk<'_>) -> bool),
+      on_line.as_mut().map(|cb| {
+        cb as &mut dyn FnMut(
+          git2::DiffDelta<'_>,
+          Option<git2::DiffHunk<'_>>,
+          git2::DiffLine<'_>,
+        ) -> bool
+      }),
+    );
+    let result = match result {
+      // A callback stopped the iteration
+      Err(err) if err.code() == git2::ErrorCode::User => Ok(()),
+      result => result,
+    };
+    callback_error.check(result.convert("Iterate over the diff failed"))
+  }
+
+  #[napi]
+  /// Get the numbers of changed files, inserted lines and deleted lines of
+  /// the diff.
+  pub fn stats(&self) -> Result<DiffStats> {
+    let stats = self
+      .inner
+      .stats()
+      .convert("Compute the diff stats failed")?;
+    Ok(DiffStats {
+      files_changed: stats.files_changed() as u32,
+      insertions: stats.insertions() as u32,
+      deletions: stats.deletions() as u32,
+    })
+  }
+
+  #[napi]
+  /// Render the stats of the diff as text in `format`, fitting the
+  /// histograms of the `Full` format in `width` columns.
+  ///
+  /// `includeSummary` appends the created, deleted and renamed files and
+  /// their mode changes, like `git diff --summary`.
+  pub fn stats_buffer(
+    &self,
+    format: DiffStatsFormat,
+    width: u32,
+    include_summary: Option<bool>,
+  ) -> Result<Buffer> {
+    let mut format = git2::DiffStatsFormat::from(format);
+    if include_summary.unwrap_or(false) {
+      format |= git2::DiffStatsFormat::INCLUDE_SUMMARY;
+    }
+    let buf = self
+      .inner
+      .stats()
+      .and_then(|stats| stats.to_buf(format, width as usize))
+      .convert("Render the diff stats failed")?;
+    Ok(buf.to_vec().into())
+  }
+
+  #[napi]
+  /// Count the inserted and deleted lines of every delta, like
+  /// `git diff --numstat`.
+  ///
+  /// Deltas are binary according to the binary detection policy of the
+  /// repository.
+  pub fn numstat(&self, env: Env) -> Result<Vec<FileNumstat>> {
+    let mut numstat = Vec::with_capacity(self.inner.deltas().len());
+    for index in 0..self.inner.deltas().len() {
+      let patch = git2::Patch::from_diff(&self.inner, index).convert_without_message()?;
+      // Loading the patch flags the delta as binary or not
+      let Some(delta) = self.inner.get_delta(index) else {
+        continue;
+      };
+      let path = delta
+        .new_file()
+        .path_bytes()
+        .or(delta.old_file().path_bytes())
+        .unwrap_or_default();
+      let is_binary = self
+        .is_binary(env, index)?
+        .unwrap_or_else(|| delta.flags().is_binary());
+      let (insertions, deletions) = match patch {
+        Some(patch) if !is_binary => {
+          let (_, insertions, deletions) = patch.line_stats().convert_without_message()?;
+          (insertions as u32, deletions as u32)
+        }
+        _ => (0, 0),
+      };
+      numstat.push(FileNumstat {
+        path: String::from_utf8_lossy(path).into_owned(),
+        insertions,
+        deletions,
+        is_binary,
+      });
+    }
+    Ok(numstat)
+  }
+
   #[napi]
   /// Check if deltas are sorted case sensitively or insensitively.
   pub fn is_sorted_icase(&self) -> bool {
     self.inner.is_sorted_icase()
   }
 }
+
+/// Whether `a` and `b` are the same delta of a diff.
+fn same_delta(a: &git2::DiffDelta<'_>, b: &git2::DiffDelta<'_>) -> bool {
+  a.status() == b.status()
+    && a.old_file().id() == b.old_file().id()
+    && a.new_file().id() == b.new_file().id()
+    && a.old_file().path_bytes() == b.old_file().path_bytes()
+    && a.new_file().path_bytes() == b.new_file().path_bytes()
+}
+
+/// The identity of a delta, to find it again after its diff was merged.
+#[derive(PartialEq, Eq, Hash)]
+struct DeltaKey {
+  status: u32,
+  old_id: git2::Oid,
+  new_id: git2::Oid,
+  old_path: Option<Vec<u8>>,
+  new_path: Option<Vec<u8>>,
+}
+
+impl From<&git2::DiffDelta<'_>> for DeltaKey {
+  fn from(delta: &git2::DiffDelta<'_>) -> Self {
+    Self {
+      status: delta.status() as u32,
+      old_id: delta.old_file().id(),
+      new_id: delta.new_file().id(),
+      old_path: delta.old_file().path_bytes().map(<[u8]>::to_vec),
+      new_path: delta.new_file().path_bytes().map(<[u8]>::to_vec),
+    }
+  }
+}
+
+/// The indexes of the deltas of `diff`, keyed by their identity.
+fn delta_indexes(diff: &git2::Diff) -> HashMap<DeltaKey, usize> {
+  diff
+    .deltas()
+    .enumerate()
+    .map(|(index, delta)| (DeltaKey::from(&delta), index))
+    .collect()
+}
+
+/// The deltas of a merged diff, with the indexes of the deltas of the two
+/// diffs it was merged from.
+struct MergedDeltas {
+  merged: Vec<DeltaKey>,
+  ours: HashMap<DeltaKey, usize>,
+  theirs: HashMap<DeltaKey, usize>,
+}
+
+impl MergedDeltas {
+  /// Key the entries cached for the deltas of the two diffs by the index of
+  /// the same delta in the merged diff, dropping the entries of the deltas
+  /// merged from both diffs.
+  fn rekey<T: Clone>(
+    &self,
+    our_cache: &HashMap<usize, T>,
+    their_cache: &HashMap<usize, T>,
+  ) -> HashMap<usize, T> {
+    self
+      .merged
+      .iter()
+      .enumerate()
+      .filter_map(|(index, key)| {
+        let ours = self.ours.get(key).and_then(|index| our_cache.get(index));
+        let theirs = || {
+          self
+            .theirs
+            .get(key)
+            .and_then(|index| their_cache.get(index))
+        };
+        ours.or_else(theirs).map(|entry| (index, entry.clone()))
+      })
+      .collect()
+  }
+}
+
+/// A callback of `Diff.foreach`, returning `false` to stop the iteration.
+type ForeachCallback<Args> = FunctionRef<Args, Option<bool>>;
+
+/// Call a callback of `Diff.foreach` with the arguments made by `args`,
+/// returning whether to keep iterating.
+fn call_foreach_callback<Args: JsValuesTupleIntoVec>(
+  env: &Env,
+  callback: &ForeachCallback<Args>,
+  callback_error: &CallbackError,
+  args: impl FnOnce() -> Result<Args>,
+) -> bool {
+  args()
+    .and_then(|args| callback.borrow_back(env)?.call(args))
+    .map(|keep_going| keep_going != Some(false))
+    .unwrap_or_else(|err| {
+      callback_error.set(err);
+      false
+    })
+}
@@ -0,0 +1,356 @@
+use std::path::Path;
+
+use napi::bindgen_prelude::*;
+use regex::Regex;
+
+use crate::error::IntoNapiError;
+
+/// The `xfuncname` patterns of the diff drivers built into git, one regex per
+/// line, a leading `!` rejecting the matching lines.
+const BUILTIN_DRIVERS: &[(&str, &str)] = &[
+  (
+    "cpp",
+    "!^[ \t]*[A-Za-z_][A-Za-z_0-9]*:[[:space:]]*($|/[/*])\n\
+     ^((::[[:space:]]*)?[A-Za-z_].*)$",
+  ),
+  (
+    "golang",
+    "^[ \t]*(func[ \t]*.*(\\{[ \t]*)?)\n\
+     ^[ \t]*(type[ \t].*(struct|interface)[ \t]*(\\{[ \t]*)?)",
+  ),
+  (
+    "java",
+    "!^[ \t]*(catch|do|for|if|instanceof|new|return|switch|throw|while)\n\
+     ^[ \t]*(([a-z]+[ \t]+)*(class|enum|interface|record)[ \t]+[A-Za-z][A-Za-z0-9_$]*[ \t]+.*)$\n\
+     ^[ \t]*(([A-Za-z_<>&][\\]\\[?&<>.,A-Za-z_0-9]*[ \t]+)+[A-Za-z_][A-Za-z_0-9]*[ \t]*\\([^;]*)$",
+  ),
+  (
+    "javascript",
+    "([a-zA-Z_$][a-zA-Z0-9_$]*(\\.[a-zA-Z0-9_$]+)*[ \t]*=[ \t]*function([ \t][a-zA-Z_$][a-zA-Z0-9_$]*)?[^\\{]*)\n\
+     ([a-zA-Z_$][a-zA-Z0-9_$]*[ \t]*:[ \t]*function([ \t][a-zA-Z_$][a-zA-Z0-9_$]*)?[^\\{]*)\n\
+     [^a-zA-Z0-9_\\$](function([ \t][a-zA-Z_$][a-zA-Z0-9_$]*)?[^\\{]*)",
+  ),
+  (
+    "php",
+    "^[ \t]*(((public|private|protected|static|final)[ \t]+)*((class|function)[ \t].*))$",
+  ),
+  ("python", "^[ \t]*((class|(async[ \t]+)?def)[ \t].*)$"),
+  ("ruby", "^[ \t]*((class|module|def)[ \t].*)$"),
+  (
+    "rust",
+    "^[\t ]*((pub(\\([^\\)]+\\))?[\t ]+)?((async|const|unsafe|extern([\t ]+\"[^\"]+\"))[\t ]+)?(struct|enum|union|mod|trait|fn|impl|macro_rules!)[< \t]+[^;]*)$",
+  ),
+];
+
+/// git truncates the function names of the hunk headers to this many bytes.
+const MAX_FUNCNAME_BYTES: usize = 80;
+
+/// Finds the line naming the function enclosing a hunk, like the `xfuncname`
+/// of a git diff driver.
+pub(crate) struct Funcname {
+  patterns: Vec<(bool, Regex)>,
+}
+
+impl Default for Funcname {
+  /// The default of git, a line starting with a letter, `_` or `$`.
+  fn default() -> Self {
+    Self {
+      patterns: vec![(false, Regex::new("^[A-Za-z_$].*").unwrap())],
+    }
+  }
+}
+
+impl Funcname {
+  fn parse(patterns: &str) -> std::result::Result<Self, regex::Error> {
+    let patterns = patterns
+      .lines()
+      .map(|pattern| {
+        let (negate, pattern) = match pattern.strip_prefix('!') {
+          Some(pattern) => (true, pattern),
+          None => (false, pattern),
+        };
+        Regex::new(pattern).map(|regex| (negate, regex))
+      })
+      .collect::<std::result::Result<_, _>>()?;
+    Ok(Self { patterns })
+  }
+
+  /// The function name pattern of the diff driver set by the `diff` attribute
+  /// of `path`, read from `diff.<driver>.xfuncname` or built into git.
+  ///
+  /// Returns `None` if the path has no diff driver with such a pattern.
+  pub(crate) fn for_path(repo: &git2::Repository, path: &Path) -> Result<Option<Self>> {
+    let driver = repo
+      .get_attr(path, "diff", git2::AttrCheckFlags::default())
+      .convert(format!(
+        "Get the diff attribute of [{}] failed",
+        path.display()
+      ))?;
+    let Some(driver) = driver.filter(|driver| !matches!(*driver, "set" | "unset")) else {
+      return Ok(None);
+    };
+    let config = repo
+      .config()
+      .convert("Get the config of Repository failed")?;
+    let configured = ["xfuncname", "funcname"]
+      .into_iter()
+      .find_map(|key| config.get_string(&format!("diff.{driver}.{key}")).ok());
+    let patterns = match configured {
+      Some(patterns) => patterns,
+      None => {
+        let driver = if driver == "c" { "cpp" } else { driver };
+        match BUILTIN_DRIVERS.iter().find(|(name, _)| *name == driver) {
+          Some((_, patterns)) => patterns.to_string(),
+          None => return Ok(None),
+        }
+      }
+    };
+    Self::parse(&patterns).map(Some).map_err(|err| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Invalid xfuncname of [{driver}]: {err}"),
+      )
+    })
+  }
+
+  /// The function name found in `line`, if it starts a function.
+  pub(crate) fn find(&self, line: &str) -> Option<String> {
+    let line = line.trim_end_matches(['\n', '\r']);
+    for (negate, regex) in &self.patterns {
+      let Some(captures) = regex.captures(line) else {
+        continue;
+      };
+      if *negate {
+        return None;
+      }
+      let name = captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map_or("", |m| m.as_str())
+        .trim_end();
+      let mut end = name.len().min(MAX_FUNCNAME_BYTES);
+      while !name.is_char_boundary(end) {
+        end -= 1;
+      }
+      return Some(name[..end].to_owned());
+    }
+    None
+  }
+
+  /// The name of the function enclosing the pre-image `lines` before the
+  /// 0-based line `before`.
+  fn enclosing(&self, lines: &[&str], before: usize) -> Option<String> {
+    lines[..before.min(lines.len())]
+      .iter()
+      .rev()
+      .find_map(|line| self.find(line))
+  }
+}
+
+/// A line of a patch rendered with the whole files as context.
+struct PatchLine {
+  origin: char,
+  old_lineno: Option<u32>,
+  new_lineno: Option<u32>,
+  /// The rendered line, along with the `\ No newline at end of file` marker
+  /// following it.
+  text: Vec<u8>,
+}
+
+/// Render `patch` with the name of the function enclosing each hunk in its
+/// header, and with the whole functions around the changes as context when
+/// `function_context` is set, like `git diff -W`.
+///
+/// `old` and `new` are the contents of the sides of the delta, which only
+/// need to be read for the function context.
+pub(crate) fn render_patch(
+  patch: &mut git2::Patch,
+  funcname: &Funcname,
+  old: &[u8],
+  new: &[u8],
+  function_context: bool,
+) -> Result<Vec<u8>> {
+  let buf = patch.to_buf().convert("Render patch failed")?;
+  let buf: &[u8] = &buf;
+  let header_len = buf
+    .split_inclusive(|b| *b == b'\n')
+    .take_while(|line| !line.starts_with(b"@@ -"))
+    .map(<[u8]>::len)
+    .sum::<usize>();
+  if header_len == buf.len() {
+    return Ok(buf.to_vec());
+  }
+  let old_text = String::from_utf8_lossy(old);
+  let old_lines = old_text.lines().collect::<Vec<_>>();
+  let mut out = buf[..header_len].to_vec();
+  if function_context {
+    let lines = full_context_lines(patch, old, new)?;
+    for (start, end) in function_ranges(&lines, funcname, &old_lines) {
+      write_hunk(
+        &mut out,
+        &lines[start..end],
+        funcname,
+        &old_lines,
+        &lines[..start],
+      );
+    }
+    return Ok(out);
+  }
+  let mut hunk_index = 0;
+  for line in buf[header_len..].split_inclusive(|b| *b == b'\n') {
+    if !line.starts_with(b"@@ -") {
+      out.extend_from_slice(line);
+      continue;
+    }
+    let (hunk, _) = patch.hunk(hunk_index).convert("Get hunk failed")?;
+    hunk_index += 1;
+    write_hunk_header(
+      &mut out,
+      (hunk.old_start(), hunk.old_lines()),
+      (hunk.new_start(), hunk.new_lines()),
+      funcname,
+      &old_lines,
+    );
+  }
+  Ok(out)
+}
+
+/// The lines of the delta of `patch` diffed with the whole files as context.
+fn full_context_lines(patch: &git2::Patch, old: &[u8], new: &[u8]) -> Result<Vec<PatchLine>> {
+  let delta = patch.delta();
+  let mut options = git2::DiffOptions::new();
+  options.context_lines(u32::MAX / 2);
+  let full = git2::Patch::from_buffers(
+    old,
+    delta.old_file().path(),
+    new,
+    delta.new_file().path(),
+    Some(&mut options),
+  )
+  .convert("Render patch failed")?;
+  let mut lines: Vec<PatchLine> = Vec::new();
+  for hunk_index in 0..full.num_hunks() {
+    let count = full
+      .num_lines_in_hunk(hunk_index)
+      .convert("Get hunk failed")?;
+    for line_index in 0..count {
+      let line = full
+        .line_in_hunk(hunk_index, line_index)
+        .convert("Get hunk line failed")?;
+      match (line.origin(), lines.last_mut()) {
+        ('=' | '>' | '<', Some(previous)) => previous.text.extend_from_slice(line.content()),
+        (origin, _) => {
+          let mut text = vec![origin as u8];
+          text.extend_from_slice(line.content());
+          lines.push(PatchLine {
+            origin,
+            old_lineno: line.old_lineno(),
+            new_lineno: line.new_lineno(),
+            text,
+          });
+        }
+      }
+    }
+  }
+  Ok(lines)
+}
+
+/// The ranges of `lines` covering the functions which enclose changes, along
+/// with the usual 3 lines of context, merged when they touch.
+fn function_ranges(
+  lines: &[PatchLine],
+  funcname: &Funcname,
+  old_lines: &[&str],
+) -> Vec<(usize, usize)> {
+  const CONTEXT_LINES: usize = 3;
+  let starts_function = |line: &PatchLine| {
+    line
+      .old_lineno
+      .and_then(|lineno| old_lines.get(lineno as usize - 1))
+      .is_some_and(|text| funcname.find(text).is_some())
+  };
+  let mut ranges: Vec<(usize, usize)> = Vec::new();
+  for (index, line) in lines.iter().enumerate() {
+    if line.origin == ' ' {
+      continue;
+    }
+    let function_start = lines[..=index]
+      .iter()
+      .rposition(|line| line.origin != '+' && starts_function(line))
+      .unwrap_or(0);
+    let mut function_end = lines[index + 1..]
+      .iter()
+      .position(|line| line.origin == ' ' && starts_function(line))
+      .map_or(lines.len(), |offset| index + 1 + offset);
+    while function_end > index + 1
+      && lines[function_end - 1].origin == ' '
+      && lines[function_end - 1].text[1..].trim_ascii().is_empty()
+    {
+      function_end -= 1;
+    }
+    let start = function_start.min(index.saturating_sub(CONTEXT_LINES));
+    let end = function_end.max((index + 1 + CONTEXT_LINES).min(lines.len()));
+    match ranges.last_mut() {
+      Some((_, last_end)) if start <= *last_end => *last_end = (*last_end).max(end),
+      _ => ranges.push((start, end)),
+    }
+  }
+  ranges
+}
+
+/// Write a hunk made of `lines`, `preceding` being the lines before it.
+fn write_hunk(
+  out: &mut Vec<u8>,
+  lines: &[PatchLine],
+  funcname: &Funcname,
+  old_lines: &[&str],
+  preceding: &[PatchLine],
+) {
+  let side = |lineno: fn(&PatchLine) -> Option<u32>| {
+    let count = lines.iter().filter(|line| lineno(line).is_some()).count() as u32;
+    let start = match lines.iter().find_map(lineno) {
+      Some(start) => start,
+      None => preceding.iter().rev().find_map(lineno).unwrap_or(0),
+    };
+    (start, count)
+  };
+  write_hunk_header(
+    out,
+    side(|line| line.old_lineno),
+    side(|line| line.new_lineno),
+    funcname,
+    old_lines,
+  );
+  for line in lines {
+    out.extend_from_slice(&line.text);
+  }
+}
+
+/// Write the `@@ -start,count +start,count @@ funcname` header of a hunk.
+fn write_hunk_header(
+  out: &mut Vec<u8>,
+  (old_start, old_count): (u32, u32),
+  (new_start, new_count): (u32, u32),
+  funcname: &Funcname,
+  old_lines: &[&str],
+) {
+  let range = |start: u32, count: u32| match count {
+    1 => format!("{start}"),
+    _ => format!("{start},{count}"),
+  };
+  let before = match old_count {
+    0 => old_start as usize,
+    _ => old_start.saturating_sub(1) as usize,
+  };
+  let mut header = format!(
+    "@@ -{} +{} @@",
+    range(old_start, old_count),
+    range(new_start, new_count)
+  );
+  if let Some(name) = funcname.enclosing(old_lines, before) {
+    header.push(' ');
+    header.push_str(&name);
+  }
+  header.push('\n');
+  out.extend_from_slice(header.as_bytes());
+}
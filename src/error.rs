@@ -1,3 +1,5 @@
+use napi::NapiValue;
+
 pub(crate) trait IntoNapiError: Sized {
   type Associate;
 
@@ -44,3 +46,31 @@ impl<T> NotNullError for Option<T> {
     self.ok_or_else(|| napi::Error::new(napi::Status::GenericFailure, msg))
   }
 }
+
+/// Create an error whose JavaScript `code` is `code` rather than one of the
+/// napi statuses, so callers can tell specific failures apart.
+pub(crate) fn coded_error<S: Into<String>>(env: &napi::Env, code: &str, reason: S) -> napi::Error {
+  coded_error_with_properties(env, code, reason, &[])
+}
+
+/// Like `coded_error`, additionally setting the string `properties` on the
+/// JavaScript error to give callers the details of the failure.
+pub(crate) fn coded_error_with_properties<S: Into<String>>(
+  env: &napi::Env,
+  code: &str,
+  reason: S,
+  properties: &[(&str, &str)],
+) -> napi::Error {
+  let error = napi::Error::new(code, reason.into());
+  let value = unsafe { napi::JsError::from(error).into_value(env.raw()) };
+  let mut object = unsafe { napi::JsObject::from_raw_unchecked(env.raw(), value) };
+  for (name, property) in properties {
+    if let Err(err) = env
+      .create_string(property)
+      .and_then(|property| object.set_named_property(name, property))
+    {
+      return err;
+    }
+  }
+  unsafe { napi::JsUnknown::from_raw_unchecked(env.raw(), value) }.into()
+}
@@ -0,0 +1,436 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use napi::{bindgen_prelude::*, JsString};
+use napi_derive::napi;
+
+use crate::{error::IntoNapiError, repo::Repository, util::path_to_javascript_string};
+
+#[napi]
+/// A structure to represent a git [index][1]
+///
+/// [1]: http://git-scm.com/book/en/Git-Internals-Git-Objects
+pub struct Index {
+  pub(crate) inner: git2::Index,
+  /// Custom merge drivers, keyed by path, which were configured through the
+  /// `merge` attribute of paths left conflicted by a merge.
+  pub(crate) merge_drivers: HashMap<String, String>,
+  /// Whether `write` flushes the index file to disk, see
+  /// `Repository.setFsyncPolicy`.
+  pub(crate) fsync: bool,
+}
+
+impl From<git2::Index> for Index {
+  fn from(inner: git2::Index) -> Self {
+    Self {
+      inner,
+      merge_drivers: HashMap::new(),
+      fsync: false,
+    }
+  }
+}
+
+#[napi(object)]
+/// A path left conflicted by a merge.
+pub struct MergeConflict {
+  pub path: String,
+  /// The name of the custom merge driver configured for this path through the
+  /// `merge` attribute, which needs to be run by the caller to resolve the
+  /// conflict.
+  ///
+  /// Not set if the path is merged by one of the built-in drivers.
+  pub driver: Option<String>,
+}
+
+/// The bits of `IndexEntry.flags` holding the stage of the entry.
+const INDEX_ENTRY_STAGE_MASK: u16 = 0x3000;
+const INDEX_ENTRY_STAGE_SHIFT: u16 = 12;
+
+#[napi(object)]
+/// An entry of the index, a file of the working directory.
+pub struct IndexEntry {
+  pub id: String,
+  pub path: String,
+  pub mode: u32,
+  pub uid: u32,
+  pub gid: u32,
+  pub file_size: u32,
+  /// Seconds since the Unix epoch.
+  pub mtime: i64,
+  /// Seconds since the Unix epoch.
+  pub ctime: i64,
+  pub flags: u16,
+  pub flags_extended: u16,
+}
+
+impl From<git2::IndexEntry> for IndexEntry {
+  fn from(entry: git2::IndexEntry) -> Self {
+    Self {
+      id: entry.id.to_string(),
+      path: String::from_utf8_lossy(&entry.path).into_owned(),
+      mode: entry.mode,
+      uid: entry.uid,
+      gid: entry.gid,
+      file_size: entry.file_size,
+      mtime: entry.mtime.seconds() as i64,
+      ctime: entry.ctime.seconds() as i64,
+      flags: entry.flags,
+      flags_extended: entry.flags_extended,
+    }
+  }
+}
+
+impl TryFrom<IndexEntry> for git2::IndexEntry {
+  type Error = Error;
+
+  fn try_from(entry: IndexEntry) -> Result<Self> {
+    Ok(Self {
+      ctime: git2::IndexTime::new(entry.ctime as i32, 0),
+      mtime: git2::IndexTime::new(entry.mtime as i32, 0),
+      dev: 0,
+      ino: 0,
+      mode: entry.mode,
+      uid: entry.uid,
+      gid: entry.gid,
+      file_size: entry.file_size,
+      id: git2::Oid::from_str(&entry.id).convert(format!("Invalid OID [{}]", entry.id))?,
+      flags: entry.flags,
+      flags_extended: entry.flags_extended,
+      path: entry.path.into_bytes(),
+    })
+  }
+}
+
+#[napi(object)]
+/// The sides of a path in conflict in the index.
+///
+/// A side is not set if the path does not exist on it.
+pub struct IndexConflict {
+  pub ancestor: Option<IndexEntry>,
+  pub our: Option<IndexEntry>,
+  pub their: Option<IndexEntry>,
+}
+
+#[napi(iterator)]
+/// An iterator over the conflicts of an index.
+pub struct IndexConflicts {
+  pub(crate) inner: SharedReference<Index, git2::IndexConflicts<'static>>,
+}
+
+#[napi]
+impl Generator for IndexConflicts {
+  type Yield = IndexConflict;
+  type Return = ();
+  type Next = ();
+
+  fn next(&mut self, _value: Option<Self::Next>) -> Option<Self::Yield> {
+    self.inner.next().and_then(|conflict| {
+      conflict.ok().map(|conflict| IndexConflict {
+        ancestor: conflict.ancestor.map(IndexEntry::from),
+        our: conflict.our.map(IndexEntry::from),
+        their: conflict.their.map(IndexEntry::from),
+      })
+    })
+  }
+}
+
+#[napi]
+impl Index {
+  #[napi(constructor)]
+  /// Creates a new in-memory index.
+  ///
+  /// This index object cannot be read/written to the filesystem, but may be
+  /// used to perform in-memory index operations.
+  pub fn new() -> Result<Self> {
+    Ok(
+      git2::Index::new()
+        .convert("Create in-memory index failed")?
+        .into(),
+    )
+  }
+
+  #[napi(factory)]
+  /// Create a new bare Git index object as a memory representation of the Git
+  /// index file in 'index_path', without a repository to back it.
+  ///
+  /// Since there is no ODB or working directory behind this index, any Index
+  /// methods which rely on these (e.g. add_path) will fail.
+  ///
+  /// If you need an index attached to a repository, use the `index()` method
+  /// on `Repository`.
+  pub fn open(index_path: String) -> Result<Self> {
+    Ok(
+      git2::Index::open(Path::new(&index_path))
+        .convert(format!("Open index [{index_path}] failed"))?
+        .into(),
+    )
+  }
+
+  #[napi]
+  /// Get the count of entries currently in the index
+  pub fn len(&self) -> u32 {
+    self.inner.len() as u32
+  }
+
+  #[napi]
+  /// Return `true` is there is no entry in the index
+  pub fn is_empty(&self) -> bool {
+    self.inner.is_empty()
+  }
+
+  #[napi]
+  /// Get one of the entries in the index by its position.
+  pub fn get(&self, n: u32) -> Option<IndexEntry> {
+    self.inner.get(n as usize).map(IndexEntry::from)
+  }
+
+  #[napi]
+  /// Get one of the entries in the index by its path and stage, `0` for the
+  /// entries which are not in conflict.
+  pub fn get_path(&self, path: String, stage: i32) -> Option<IndexEntry> {
+    self
+      .inner
+      .get_path(Path::new(&path), stage)
+      .map(IndexEntry::from)
+  }
+
+  #[napi]
+  /// Does this index have conflicts?
+  ///
+  /// Returns `true` if the index contains conflicts, `false` if it does not.
+  pub fn has_conflicts(&self) -> bool {
+    self.inner.has_conflicts()
+  }
+
+  #[napi]
+  /// Add or update index entries to represent a conflict. Any staged entries
+  /// that exist at the given paths will be removed.
+  ///
+  /// The entries are the entries from the tree included in the merge. Any
+  /// entry may be `null` to indicate that that file was not present in the
+  /// trees during the merge. For example, the ancestor entry may be `null` to
+  /// indicate that a file was added in both branches and must be resolved.
+  pub fn conflict_add(
+    &mut self,
+    ancestor: Option<IndexEntry>,
+    our: Option<IndexEntry>,
+    their: Option<IndexEntry>,
+  ) -> Result<()> {
+    let entries = [ancestor, our, their]
+      .into_iter()
+      .enumerate()
+      .filter_map(|(side, entry)| entry.map(|entry| (side as u16 + 1, entry)))
+      .map(|(stage, entry)| {
+        let mut entry = git2::IndexEntry::try_from(entry)?;
+        entry.flags = (entry.flags & !INDEX_ENTRY_STAGE_MASK) | (stage << INDEX_ENTRY_STAGE_SHIFT);
+        Ok(entry)
+      })
+      .collect::<Result<Vec<_>>>()?;
+    for entry in &entries {
+      let path = String::from_utf8_lossy(&entry.path).into_owned();
+      let stage = (entry.flags >> INDEX_ENTRY_STAGE_SHIFT) as i32;
+      // The file is no longer staged once it is in conflict
+      for stage in [0, stage] {
+        if self.inner.get_path(Path::new(&path), stage).is_some() {
+          self
+            .inner
+            .remove(Path::new(&path), stage)
+            .convert("Add conflict to index failed")?;
+        }
+      }
+    }
+    for entry in &entries {
+      self
+        .inner
+        .add(entry)
+        .convert("Add conflict to index failed")?;
+    }
+    Ok(())
+  }
+
+  #[napi]
+  /// Removes the index entries that represent a conflict of a single file.
+  pub fn conflict_remove(&mut self, path: String) -> Result<()> {
+    self
+      .inner
+      .conflict_remove(Path::new(&path))
+      .convert(format!("Remove conflict of [{path}] from index failed"))
+  }
+
+  #[napi]
+  /// Remove all conflicts in the index (entries with a stage greater than 0).
+  pub fn conflict_cleanup(&mut self) -> Result<()> {
+    let conflicted = self
+      .inner
+      .iter()
+      .filter_map(|entry| {
+        let stage = (entry.flags & INDEX_ENTRY_STAGE_MASK) >> INDEX_ENTRY_STAGE_SHIFT;
+        (stage > 0).then(|| (String::from_utf8_lossy(&entry.path).into_owned(), stage))
+      })
+      .collect::<Vec<_>>();
+    for (path, stage) in conflicted {
+      self
+        .inner
+        .remove(Path::new(&path), stage as i32)
+        .convert("Clean up index conflicts failed")?;
+    }
+    Ok(())
+  }
+
+  #[napi]
+  /// Get an iterator over the conflicts in this index.
+  pub fn conflicts(&self, env: Env, self_ref: Reference<Index>) -> Result<IndexConflicts> {
+    Ok(IndexConflicts {
+      inner: self_ref.share_with(env, |index| {
+        index
+          .inner
+          .conflicts()
+          .convert("Read index conflicts failed")
+      })?,
+    })
+  }
+
+  #[napi]
+  /// Get the paths left conflicted by the merge which produced this index.
+  pub fn merge_conflicts(&self) -> Result<Vec<MergeConflict>> {
+    let mut conflicts = Vec::new();
+    for conflict in self
+      .inner
+      .conflicts()
+      .convert("Read index conflicts failed")?
+    {
+      let conflict = conflict.convert("Read index conflict failed")?;
+      if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+        let path = String::from_utf8_lossy(&entry.path).into_owned();
+        conflicts.push(MergeConflict {
+          driver: self.merge_drivers.get(&path).cloned(),
+          path,
+        });
+      }
+    }
+    Ok(conflicts)
+  }
+
+  #[napi]
+  /// Get the full path to the index file on disk.
+  ///
+  /// Returns `None` if this is an in-memory index.
+  pub fn path(&self, env: Env) -> Option<JsString> {
+    self
+      .inner
+      .path()
+      .and_then(|p| path_to_javascript_string(&env, p).ok())
+  }
+
+  #[napi]
+  /// Update the contents of an existing index object in memory by reading
+  /// from the hard disk.
+  ///
+  /// If force is true, this performs a "hard" read that discards in-memory
+  /// changes and always reloads the on-disk index data. If there is no
+  /// on-disk version, the index will be cleared.
+  ///
+  /// If force is false, this does a "soft" read that reloads the index data
+  /// from disk only if it has changed since the last time it was loaded.
+  /// Purely in-memory index data will be untouched. Be aware: if there are
+  /// changes on disk, unwritten in-memory changes are discarded.
+  pub fn read(&mut self, force: bool) -> Result<()> {
+    self.inner.read(force).convert("Read index failed")
+  }
+
+  #[napi]
+  /// Write an existing index object from memory back to disk using an atomic
+  /// file lock.
+  pub fn write(&mut self) -> Result<()> {
+    self.inner.write().convert("Write index failed")?;
+    match self.inner.path() {
+      Some(path) if self.fsync => fs::File::open(path)
+        .and_then(|file| file.sync_all())
+        .map_err(|err| {
+          Error::new(
+            Status::GenericFailure,
+            format!("Fsync [{}] failed: {err}", path.display()),
+          )
+        }),
+      _ => Ok(()),
+    }
+  }
+
+  #[napi]
+  /// Write the index as a tree.
+  ///
+  /// This method will scan the index and write a representation of its
+  /// current state back to disk; it recursively creates tree objects for each
+  /// of the subtrees stored in the index, but only returns the OID of the root
+  /// tree. This is the OID that can be used e.g. to create a commit.
+  ///
+  /// The index instance cannot be bare, and needs to be associated to an
+  /// existing repository.
+  ///
+  /// The index must not contain any file in conflict.
+  pub fn write_tree(&mut self) -> Result<String> {
+    self
+      .inner
+      .write_tree()
+      .map(|oid| oid.to_string())
+      .convert("Write tree from index failed")
+  }
+
+  #[napi]
+  /// Write the index as a tree to the given repository
+  ///
+  /// This is the same as `write_tree` except that the destination repository
+  /// can be chosen.
+  pub fn write_tree_to(&mut self, repo: &Repository) -> Result<String> {
+    self
+      .inner
+      .write_tree_to(&repo.inner)
+      .map(|oid| oid.to_string())
+      .convert("Write tree from index failed")
+  }
+
+  #[napi]
+  /// Add or update an index entry from a file on disk
+  ///
+  /// The file path must be relative to the repository's working folder and
+  /// must be readable.
+  ///
+  /// This method will fail in bare index instances.
+  ///
+  /// This forces the file to be added to the index, not looking at gitignore
+  /// rules.
+  ///
+  /// If this file currently is the result of a merge conflict, this file will
+  /// no longer be marked as conflicting. The data about the conflict will be
+  /// moved to the "resolve undo" (REUC) section.
+  pub fn add_path(&mut self, path: String) -> Result<()> {
+    self
+      .inner
+      .add_path(Path::new(&path))
+      .convert(format!("Add [{path}] to index failed"))
+  }
+
+  #[napi]
+  /// Remove an index entry corresponding to a file on disk.
+  ///
+  /// The file path must be relative to the repository's working folder. It
+  /// may exist.
+  ///
+  /// If this file currently is the result of a merge conflict, this file will
+  /// no longer be marked as conflicting. The data about the conflict will be
+  /// moved to the "resolve undo" (REUC) section.
+  pub fn remove_path(&mut self, path: String) -> Result<()> {
+    self
+      .inner
+      .remove_path(Path::new(&path))
+      .convert(format!("Remove [{path}] from index failed"))
+  }
+
+  #[napi]
+  /// Clear the contents (all the entries) of an index object.
+  ///
+  /// This clears the index object in memory; changes must be explicitly
+  /// written to disk for them to take effect persistently via `write`.
+  pub fn clear(&mut self) -> Result<()> {
+    self.inner.clear().convert("Clear index failed")
+  }
+}
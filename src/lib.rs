@@ -1,17 +1,52 @@
 #![deny(clippy::all)]
 
+pub mod annotated_commit;
+pub mod apply;
+pub mod binary;
 pub mod blob;
+pub mod branch;
+pub mod checkout;
+pub mod cherry_pick;
 pub mod commit;
+pub mod commit_signature;
+pub mod config;
+pub mod conventional_commit;
 pub mod deltas;
+pub mod describe;
 pub mod diff;
+mod diff_driver;
 mod error;
+pub mod index;
+pub mod mailmap;
+pub mod maintenance;
+pub mod merge;
+pub mod note;
 pub mod object;
+pub mod odb;
+pub mod pack_analysis;
+pub mod pack_builder;
+pub mod patch;
+pub mod pathspec;
+pub mod pinned_view;
+pub mod rebase;
 pub mod reference;
 pub mod remote;
 pub mod repo;
 pub mod repo_builder;
+pub mod repo_pool;
+pub mod resumable_fetch;
 pub mod rev_walk;
+pub mod safe_path;
 pub mod signature;
+pub mod stats;
+pub mod status;
+pub mod subtree_split;
 pub mod tag;
+pub mod test_repo_builder;
+pub mod timeline;
+pub mod transport;
 pub mod tree;
+pub mod tree_builder;
 pub(crate) mod util;
+pub mod version;
+pub mod worktree;
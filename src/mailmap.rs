@@ -0,0 +1,72 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+  error::IntoNapiError,
+  signature::{Signature, SignatureInner},
+};
+
+#[napi]
+/// A set of rules mapping the names and emails of signatures to canonical
+/// ones, as written in `.mailmap` files.
+pub struct Mailmap {
+  pub(crate) inner: git2::Mailmap,
+}
+
+#[napi]
+impl Mailmap {
+  #[napi(constructor)]
+  /// Creates an empty mailmap.
+  pub fn new() -> Result<Self> {
+    Ok(Self {
+      inner: git2::Mailmap::new().convert("Create mailmap failed")?,
+    })
+  }
+
+  #[napi(factory)]
+  /// Creates a mailmap from the content of a `.mailmap` file.
+  pub fn from_buf(buf: Buffer) -> Result<Self> {
+    let buf = std::str::from_utf8(&buf)
+      .map_err(|err| Error::new(Status::InvalidArg, format!("Invalid mailmap: {err}")))?;
+    Ok(Self {
+      inner: git2::Mailmap::from_buffer(buf).convert("Parse mailmap failed")?,
+    })
+  }
+
+  #[napi]
+  /// Add a rule mapping the signatures with `replaceEmail`, and also
+  /// `replaceName` if set, to `realName` and `realEmail`.
+  ///
+  /// The name, or the email, is kept if `realName`, or `realEmail`, is not
+  /// set.
+  pub fn add_entry(
+    &mut self,
+    real_name: Option<String>,
+    real_email: Option<String>,
+    replace_name: Option<String>,
+    replace_email: String,
+  ) -> Result<()> {
+    self
+      .inner
+      .add_entry(
+        real_name.as_deref(),
+        real_email.as_deref(),
+        replace_name.as_deref(),
+        &replace_email,
+      )
+      .convert("Add mailmap entry failed")
+  }
+
+  #[napi]
+  /// Resolve a signature to its canonical name and email, keeping its time.
+  pub fn resolve_signature(&self, signature: &Signature) -> Result<Signature> {
+    Ok(Signature {
+      inner: SignatureInner::Signature(
+        self
+          .inner
+          .resolve_signature(&signature.inner)
+          .convert("Resolve signature failed")?,
+      ),
+    })
+  }
+}
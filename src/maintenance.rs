@@ -0,0 +1,189 @@
+use std::{
+  path::Path,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{error::IntoNapiError, repo::Repository, util::CancellationToken};
+
+/// The default `gc.auto` of git.
+const DEFAULT_LOOSE_OBJECTS_THRESHOLD: u32 = 6700;
+/// The default `gc.autoPackLimit` of git.
+const DEFAULT_PACKS_THRESHOLD: u32 = 50;
+const DEFAULT_LOOSE_REFS_THRESHOLD: u32 = 1000;
+
+#[napi(object)]
+#[derive(Default)]
+pub struct MaintenanceReportOptions {
+  /// Recommend a repack above this many loose objects.
+  ///
+  /// Not set to use 6700, the default `gc.auto` of git.
+  pub loose_objects_threshold: Option<u32>,
+  /// Recommend a repack above this many packs.
+  ///
+  /// Not set to use 50, the default `gc.autoPackLimit` of git.
+  pub packs_threshold: Option<u32>,
+  /// Recommend packing the references above this many loose references.
+  ///
+  /// Not set to use 1000.
+  pub loose_refs_threshold: Option<u32>,
+}
+
+#[napi(object)]
+/// The state of the object and reference storage of a repository, see
+/// `Repository.maintenanceReport`.
+pub struct MaintenanceReport {
+  pub loose_objects: u32,
+  /// The modification time of the oldest loose object in milliseconds since
+  /// the Unix epoch.
+  ///
+  /// Not set if there is no loose object.
+  pub loose_object_oldest_ms: Option<i64>,
+  pub packs: u32,
+  /// Whether the repository has a `packed-refs` file.
+  pub packed_refs: bool,
+  pub loose_refs: u32,
+  /// Whether the updates of the references are recorded in their reflogs,
+  /// see `core.logAllRefUpdates`.
+  pub reflog_enabled: bool,
+  /// The maintenance tasks worth running, e.g.
+  /// `pack-refs: 5000 loose refs` or `repack: 30000 loose objects`.
+  pub recommendations: Vec<String>,
+}
+
+pub struct MaintenanceReportTask {
+  pub(crate) repo: Reference<Repository>,
+  pub(crate) options: MaintenanceReportOptions,
+  pub(crate) cancellation: CancellationToken,
+}
+
+unsafe impl Send for MaintenanceReportTask {}
+
+#[napi]
+impl Task for MaintenanceReportTask {
+  type Output = MaintenanceReport;
+  type JsValue = MaintenanceReport;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let repo = &self.repo.inner;
+    let objects = repo.commondir().join("objects");
+    let mut loose_objects = 0;
+    let mut oldest = None::<SystemTime>;
+    for entry in read_dir(&objects)? {
+      self.cancellation.check()?;
+      let name = entry.file_name();
+      let name = name.to_string_lossy();
+      if name.len() != 2 || !name.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        continue;
+      }
+      for object in read_dir(&entry.path())? {
+        // Skip the temporary files of objects being written
+        if !object
+          .file_name()
+          .to_string_lossy()
+          .bytes()
+          .all(|byte| byte.is_ascii_hexdigit())
+        {
+          continue;
+        }
+        loose_objects += 1;
+        if let Ok(modified) = object.metadata().and_then(|metadata| metadata.modified()) {
+          oldest = Some(oldest.map_or(modified, |oldest| oldest.min(modified)));
+        }
+      }
+    }
+    let packs = read_dir(&objects.join("pack"))?
+      .iter()
+      .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "pack"))
+      .count() as u32;
+    let packed_refs = repo.commondir().join("packed-refs").is_file();
+    let loose_refs = count_files(&repo.commondir().join("refs"), &self.cancellation)?;
+    let config = repo
+      .config()
+      .convert("Get the config of Repository failed")?;
+    let reflog_enabled = match config.get_string("core.logAllRefUpdates") {
+      Ok(value) if value.eq_ignore_ascii_case("always") => true,
+      Ok(_) => config
+        .get_bool("core.logAllRefUpdates")
+        .convert("Read core.logAllRefUpdates failed")?,
+      // git enables the reflogs of repositories with a working directory
+      Err(_) => !repo.is_bare(),
+    };
+    let options = &self.options;
+    let mut recommendations = Vec::new();
+    if loose_refs
+      > options
+        .loose_refs_threshold
+        .unwrap_or(DEFAULT_LOOSE_REFS_THRESHOLD)
+    {
+      recommendations.push(format!("pack-refs: {loose_refs} loose refs"));
+    }
+    if loose_objects
+      > options
+        .loose_objects_threshold
+        .unwrap_or(DEFAULT_LOOSE_OBJECTS_THRESHOLD)
+    {
+      recommendations.push(format!("repack: {loose_objects} loose objects"));
+    }
+    if packs > options.packs_threshold.unwrap_or(DEFAULT_PACKS_THRESHOLD) {
+      recommendations.push(format!("repack: {packs} packs"));
+    }
+    Ok(MaintenanceReport {
+      loose_objects,
+      loose_object_oldest_ms: oldest.map(|oldest| {
+        oldest
+          .duration_since(UNIX_EPOCH)
+          .map_or(0, |since| since.as_millis() as i64)
+      }),
+      packs,
+      packed_refs,
+      loose_refs,
+      reflog_enabled,
+      recommendations,
+    })
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    self.cancellation.detach(env)?;
+    Ok(output)
+  }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    self.cancellation.reject(env, err)
+  }
+}
+
+/// The entries of the directory at `path`, none if it is missing.
+fn read_dir(path: &Path) -> Result<Vec<std::fs::DirEntry>> {
+  match std::fs::read_dir(path) {
+    Ok(entries) => entries
+      .collect::<std::io::Result<Vec<_>>>()
+      .map_err(|err| io_error(path, err)),
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+    Err(err) => Err(io_error(path, err)),
+  }
+}
+
+/// The number of files in the directory at `path` and its subdirectories.
+fn count_files(path: &Path, cancellation: &CancellationToken) -> Result<u32> {
+  let mut count = 0;
+  for entry in read_dir(path)? {
+    cancellation.check()?;
+    let file_type = entry.file_type().map_err(|err| io_error(path, err))?;
+    if file_type.is_dir() {
+      count += count_files(&entry.path(), cancellation)?;
+    } else {
+      count += 1;
+    }
+  }
+  Ok(count)
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> Error {
+  Error::new(
+    Status::GenericFailure,
+    format!("Read [{}] failed: {err}", path.display()),
+  )
+}
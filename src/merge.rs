@@ -0,0 +1,351 @@
+use std::{collections::HashMap, path::Path};
+
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+
+#[napi]
+/// Options to specify when merging.
+pub struct MergeOptions {
+  pub(crate) inner: git2::MergeOptions,
+}
+
+#[napi]
+impl MergeOptions {
+  #[napi(constructor)]
+  #[allow(clippy::new_without_default)]
+  /// Creates a default set of merge options.
+  pub fn new() -> MergeOptions {
+    MergeOptions {
+      inner: git2::MergeOptions::new(),
+    }
+  }
+
+  #[napi]
+  /// Detect file renames
+  pub fn find_renames(&mut self, find: bool) -> &Self {
+    self.inner.find_renames(find);
+    self
+  }
+
+  #[napi]
+  /// If a conflict occurs, exit immediately instead of attempting to continue
+  /// resolving conflicts
+  pub fn fail_on_conflict(&mut self, fail: bool) -> &Self {
+    self.inner.fail_on_conflict(fail);
+    self
+  }
+
+  #[napi]
+  /// Enable or disable skipping the REUC extension on the index.
+  pub fn skip_reuc(&mut self, skip: bool) -> &Self {
+    self.inner.skip_reuc(skip);
+    self
+  }
+
+  #[napi]
+  /// If the commits being merged have multiple merge bases, do not build a
+  /// recursive merge base (by merging the multiple merge bases), instead
+  /// simply use the first base.
+  pub fn no_recursive(&mut self, disable: bool) -> &Self {
+    self.inner.no_recursive(disable);
+    self
+  }
+
+  #[napi]
+  /// Similarity to consider a file renamed (default 50)
+  pub fn rename_threshold(&mut self, thresh: u32) -> &Self {
+    self.inner.rename_threshold(thresh);
+    self
+  }
+
+  #[napi]
+  /// Maximum similarity sources to examine for renames (default 200).
+  /// If the number of rename candidates (add / delete pairs) is greater than
+  /// this value, inexact rename detection is aborted. This setting overrides
+  /// the `merge.renameLimit` configuration value.
+  pub fn target_limit(&mut self, limit: u32) -> &Self {
+    self.inner.target_limit(limit);
+    self
+  }
+
+  #[napi]
+  /// Maximum number of times to merge common ancestors to build a
+  /// virtual merge base when faced with criss-cross merges.  When
+  /// this limit is reached, the next ancestor will simply be used
+  /// instead of attempting to merge it.  The default is unlimited.
+  pub fn recursion_limit(&mut self, limit: u32) -> &Self {
+    self.inner.recursion_limit(limit);
+    self
+  }
+}
+
+#[napi]
+/// The side whose changes are kept for the conflicting regions of a file.
+pub enum FileFavor {
+  /// Keep both sides between conflict markers, the default.
+  Normal,
+  /// Keep our side.
+  Ours,
+  /// Keep their side.
+  Theirs,
+  /// Keep the unique lines of both sides.
+  Union,
+}
+
+impl From<FileFavor> for git2::FileFavor {
+  fn from(value: FileFavor) -> Self {
+    match value {
+      FileFavor::Normal => git2::FileFavor::Normal,
+      FileFavor::Ours => git2::FileFavor::Ours,
+      FileFavor::Theirs => git2::FileFavor::Theirs,
+      FileFavor::Union => git2::FileFavor::Union,
+    }
+  }
+}
+
+#[napi]
+/// Options to specify when merging a single file.
+pub struct MergeFileOptions {
+  pub(crate) inner: git2::MergeFileOptions,
+}
+
+#[napi]
+impl MergeFileOptions {
+  #[napi(constructor)]
+  #[allow(clippy::new_without_default)]
+  /// Creates a default set of merge file options.
+  pub fn new() -> MergeFileOptions {
+    MergeFileOptions {
+      inner: git2::MergeFileOptions::new(),
+    }
+  }
+
+  #[napi]
+  /// Label for the ancestor side of the conflicts, shown in diff3-style merge
+  /// files.
+  pub fn ancestor_label(&mut self, label: String) -> &Self {
+    self.inner.ancestor_label(label);
+    self
+  }
+
+  #[napi]
+  /// Label for our side of the conflicts
+  pub fn our_label(&mut self, label: String) -> &Self {
+    self.inner.our_label(label);
+    self
+  }
+
+  #[napi]
+  /// Label for their side of the conflicts
+  pub fn their_label(&mut self, label: String) -> &Self {
+    self.inner.their_label(label);
+    self
+  }
+
+  #[napi]
+  /// Specify a side to favor for resolving conflicts
+  pub fn favor(&mut self, favor: FileFavor) -> &Self {
+    self.inner.favor(favor.into());
+    self
+  }
+}
+
+#[napi(object)]
+/// A side of the merge of `Repository.mergeFile`.
+pub struct MergeFileInput {
+  /// Not set if the file has no path, the result then has none either.
+  pub path: Option<String>,
+  pub mode: u32,
+  pub content: Buffer,
+}
+
+#[napi(object)]
+/// The result of `Repository.mergeFile`.
+pub struct MergeFileResult {
+  /// Whether the sides merged without conflicts, the content having conflict
+  /// markers otherwise.
+  pub automergeable: bool,
+  /// Not set if the sides renamed the file differently or have no path.
+  pub path: Option<String>,
+  pub mode: u32,
+  pub content: Buffer,
+}
+
+/// The trees merged into an index, see `apply_merge_attributes`.
+pub(crate) struct MergeSides<'a> {
+  /// Not set if the sides have no common ancestor.
+  pub ancestor: Option<&'a git2::Tree<'a>>,
+  pub ours: &'a git2::Tree<'a>,
+  pub theirs: &'a git2::Tree<'a>,
+}
+
+/// Apply the `merge` attribute of the paths changed on both sides of an
+/// in-memory merge, which libgit2 only reads from the working directory.
+///
+/// - Conflicted paths using `merge=ours` are resolved to our side.
+/// - Conflicted paths using `merge=union` are resolved to the unique lines of
+///   both sides, like `FileFavor.Union`.
+/// - Paths using `merge=binary` keep our side and stay conflicted, even if
+///   libgit2 merged their contents cleanly.
+/// - Paths using `merge=text` are merged by libgit2.
+///
+/// The names of custom drivers configured for paths that stay conflicted are
+/// returned, keyed by path.
+///
+/// The attributes are read from the `.gitattributes` files of the merge
+/// result, not from the working directory, so rules added on either side
+/// apply and bare repositories are supported.
+pub(crate) fn apply_merge_attributes(
+  repo: &git2::Repository,
+  index: &mut git2::Index,
+  sides: MergeSides<'_>,
+) -> Result<HashMap<String, String>, git2::Error> {
+  let mut drivers = HashMap::new();
+  let mut conflicts = HashMap::new();
+  for conflict in index.conflicts()? {
+    let conflict = conflict?;
+    if let Some(entry) = conflict
+      .our
+      .as_ref()
+      .or(conflict.their.as_ref())
+      .or(conflict.ancestor.as_ref())
+    {
+      conflicts.insert(String::from_utf8_lossy(&entry.path).into_owned(), conflict);
+    }
+  }
+  // The paths libgit2 merged cleanly although both sides changed them
+  let mut merged = Vec::new();
+  repo
+    .diff_tree_to_tree(Some(sides.ours), Some(sides.theirs), None)?
+    .foreach(
+      &mut |delta, _| {
+        if let Some(path) = delta.new_file().path() {
+          merged.push(path.to_owned());
+        }
+        true
+      },
+      None,
+      None,
+      None,
+    )?;
+  merged.retain(|path| {
+    index.get_path(path, 0).is_some_and(|entry| {
+      let side = |tree: &git2::Tree| tree.get_path(path).ok().map(|entry| entry.id());
+      Some(entry.id) != side(sides.ours) && Some(entry.id) != side(sides.theirs)
+    })
+  });
+  if conflicts.is_empty() && merged.is_empty() {
+    return Ok(drivers);
+  }
+  // A private handle using the merge result as its index, leaving the index
+  // of `repo` untouched
+  let attributes = git2::Repository::open(repo.path())?;
+  attributes.set_index(index)?;
+  let merge_attribute = |path: &Path| {
+    attributes
+      .get_attr(path, "merge", git2::AttrCheckFlags::INDEX_ONLY)
+      .map(|driver| driver.map(str::to_owned))
+  };
+  let conflicts = conflicts
+    .into_iter()
+    .map(|(path, conflict)| Ok((merge_attribute(Path::new(&path))?, path, conflict)))
+    .collect::<Result<Vec<_>, git2::Error>>()?;
+  let merged = merged
+    .into_iter()
+    .map(|path| Ok((merge_attribute(&path)?, path)))
+    .collect::<Result<Vec<_>, git2::Error>>()?;
+  drop(attributes);
+  for (driver, path, conflict) in conflicts {
+    match git2::AttrValue::from_string(driver.as_deref()) {
+      git2::AttrValue::String("ours") => {
+        index.conflict_remove(Path::new(&path))?;
+        if let Some(our) = conflict.our {
+          index.add(&with_stage(our, 0))?;
+        }
+      }
+      git2::AttrValue::String("union") => {
+        let (Some(our), Some(their)) = (conflict.our, conflict.their) else {
+          continue;
+        };
+        let ancestor = match conflict.ancestor {
+          Some(ancestor) => ancestor,
+          None => index_entry(our.path.clone(), our.mode, repo.blob(b"")?, 1),
+        };
+        let mut options = git2::MergeFileOptions::new();
+        options.favor(git2::FileFavor::Union);
+        let result = repo.merge_file_from_index(&ancestor, &our, &their, Some(&mut options))?;
+        let resolved = git2::IndexEntry {
+          file_size: result.content().len() as u32,
+          ..index_entry(our.path, result.mode(), repo.blob(result.content())?, 0)
+        };
+        index.conflict_remove(Path::new(&path))?;
+        index.add(&resolved)?;
+      }
+      git2::AttrValue::String("text" | "binary") => {}
+      git2::AttrValue::String(custom) => {
+        drivers.insert(path, custom.to_owned());
+      }
+      _ => {}
+    }
+  }
+  for (driver, path) in merged {
+    if driver.as_deref() != Some("binary") {
+      continue;
+    }
+    // Restore the sides of the path as conflict entries, keeping ours
+    let stages = [
+      (sides.ancestor, 1),
+      (Some(sides.ours), 2),
+      (Some(sides.theirs), 3),
+    ];
+    let entries = stages
+      .into_iter()
+      .filter_map(|(tree, stage)| {
+        let entry = tree?.get_path(&path).ok()?;
+        let path = path.to_string_lossy().into_owned().into_bytes();
+        Some(index_entry(
+          path,
+          entry.filemode() as u32,
+          entry.id(),
+          stage,
+        ))
+      })
+      .collect::<Vec<_>>();
+    index.remove(&path, 0)?;
+    for entry in entries {
+      index.add(&entry)?;
+    }
+  }
+  Ok(drivers)
+}
+
+/// `entry` moved to `stage`, 0 for a resolved entry.
+fn with_stage(mut entry: git2::IndexEntry, stage: u16) -> git2::IndexEntry {
+  entry.flags &= !libgit2_sys::GIT_INDEX_ENTRY_STAGEMASK;
+  entry.flags |= stage << libgit2_sys::GIT_INDEX_ENTRY_STAGESHIFT;
+  entry
+}
+
+/// An index entry at `stage` for the object `id`, without file stats.
+fn index_entry(path: Vec<u8>, mode: u32, id: git2::Oid, stage: u16) -> git2::IndexEntry {
+  let flags = path
+    .len()
+    .min(libgit2_sys::GIT_INDEX_ENTRY_NAMEMASK as usize) as u16;
+  with_stage(
+    git2::IndexEntry {
+      ctime: git2::IndexTime::new(0, 0),
+      mtime: git2::IndexTime::new(0, 0),
+      dev: 0,
+      ino: 0,
+      mode,
+      uid: 0,
+      gid: 0,
+      file_size: 0,
+      id,
+      flags,
+      flags_extended: 0,
+      path,
+    },
+    stage,
+  )
+}
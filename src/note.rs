@@ -0,0 +1,184 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+  error::IntoNapiError,
+  repo::{commit_id, Repository},
+  signature::{Signature, SignatureInner},
+};
+
+/// The message of the notes commits written by `git_note_create`.
+const NOTES_ADDED: &str = "Notes added by 'git_note_create' from libgit2";
+
+/// The message of the notes commits written by `git_note_remove`.
+const NOTES_REMOVED: &str = "Notes removed by 'git_note_remove' from libgit2";
+
+#[napi]
+/// A structure representing a [note][note] in git.
+///
+/// [note]: http://alblue.bandlem.com/2011/11/git-tip-of-week-git-notes.html
+pub struct Note {
+  pub(crate) inner: SharedReference<Repository, git2::Note<'static>>,
+}
+
+#[napi]
+impl Note {
+  #[napi]
+  /// Get the note object's id
+  pub fn id(&self) -> String {
+    self.inner.id().to_string()
+  }
+
+  #[napi]
+  /// Get the note message as a string, returning `None` if it is not UTF-8.
+  pub fn message(&self) -> Option<&str> {
+    self.inner.message()
+  }
+
+  #[napi]
+  /// Get the note author
+  pub fn author(&self) -> Signature {
+    Signature {
+      inner: SignatureInner::Signature(self.inner.author().to_owned()),
+    }
+  }
+
+  #[napi]
+  /// Get the note committer
+  pub fn committer(&self) -> Signature {
+    Signature {
+      inner: SignatureInner::Signature(self.inner.committer().to_owned()),
+    }
+  }
+}
+
+#[napi(object)]
+pub struct NoteEntry {
+  /// The id of the object the note is attached to.
+  pub annotated_id: String,
+  /// The id of the note blob.
+  pub note_id: String,
+}
+
+#[napi(iterator)]
+/// An iterator over all of the notes within a repository.
+pub struct Notes {
+  pub(crate) inner: SharedReference<Repository, git2::Notes<'static>>,
+}
+
+#[napi]
+impl Generator for Notes {
+  type Yield = NoteEntry;
+  type Return = ();
+  type Next = ();
+
+  fn next(&mut self, _value: Option<Self::Next>) -> Option<Self::Yield> {
+    self.inner.next().and_then(|note| {
+      note.ok().map(|(note_id, annotated_id)| NoteEntry {
+        annotated_id: annotated_id.to_string(),
+        note_id: note_id.to_string(),
+      })
+    })
+  }
+}
+
+/// Compute the notes commit libgit2 writes on top of the notes commit `parent`
+/// to add the note `note` to `target`, or to remove its note.
+///
+/// The trees are written to an in-memory object database so that the ref
+/// update guard can be consulted before anything reaches the disk. Returns
+/// `None` when libgit2 fails anyway, e.g. as the note already exists.
+pub(crate) fn notes_commit_id(
+  repo: &git2::Repository,
+  parent: Option<git2::Oid>,
+  author: &git2::Signature,
+  committer: &git2::Signature,
+  target: git2::Oid,
+  note: Option<&str>,
+  force: bool,
+) -> Result<Option<git2::Oid>> {
+  let scratch = git2::Repository::open(repo.path()).convert("Open repository failed")?;
+  let odb = scratch.odb().convert_without_message()?;
+  let _mempack = odb
+    .add_new_mempack_backend(1000)
+    .convert_without_message()?;
+  let parent = parent
+    .map(|oid| scratch.find_commit(oid))
+    .transpose()
+    .convert("Find the notes commit failed")?;
+  let tree = parent
+    .as_ref()
+    .map(|parent| parent.tree())
+    .transpose()
+    .convert_without_message()?;
+  let blob = note
+    .map(|note| scratch.blob(note.as_bytes()))
+    .transpose()
+    .convert_without_message()?;
+  let target = target.to_string();
+  let Some(tree) = write_note_in_tree(&scratch, tree.as_ref(), &target, 0, blob, force)? else {
+    return Ok(None);
+  };
+  let tree = scratch.find_tree(tree).convert_without_message()?;
+  let message = if note.is_some() {
+    NOTES_ADDED
+  } else {
+    NOTES_REMOVED
+  };
+  let parents = parent.iter().collect::<Vec<_>>();
+  commit_id(&scratch, author, committer, None, message, &tree, &parents).map(Some)
+}
+
+/// Insert the note `blob` of `target` in the notes tree `tree`, or remove it
+/// if `None`, following the fanout of the existing notes like libgit2.
+fn write_note_in_tree(
+  repo: &git2::Repository,
+  tree: Option<&git2::Tree>,
+  target: &str,
+  fanout: usize,
+  blob: Option<git2::Oid>,
+  force: bool,
+) -> Result<Option<git2::Oid>> {
+  let name = &target[fanout..];
+  let mut subtree = None;
+  let mut exists = false;
+  for entry in tree.into_iter().flat_map(|tree| tree.iter()) {
+    let Some(entry_name) = entry
+      .name()
+      .filter(|entry_name| entry_name.bytes().all(|byte| byte.is_ascii_hexdigit()))
+    else {
+      continue;
+    };
+    if entry.kind() == Some(git2::ObjectType::Tree)
+      && entry_name.len() == 2
+      && name.starts_with(entry_name)
+    {
+      subtree = Some(entry.id());
+      break;
+    }
+    if entry_name == name {
+      exists = true;
+      break;
+    }
+  }
+  let (entry, id, mode) = match (subtree, exists, blob) {
+    (Some(subtree), _, _) => {
+      let subtree = repo.find_tree(subtree).convert_without_message()?;
+      let Some(id) = write_note_in_tree(repo, Some(&subtree), target, fanout + 2, blob, force)?
+      else {
+        return Ok(None);
+      };
+      (&name[..2], Some(id), i32::from(git2::FileMode::Tree))
+    }
+    (None, true, Some(_)) if !force => return Ok(None),
+    (None, false, None) => return Ok(None),
+    (None, _, blob) => (name, blob, i32::from(git2::FileMode::Blob)),
+  };
+  let mut builder = repo.treebuilder(tree).convert_without_message()?;
+  match id {
+    Some(id) => builder.insert(entry, id, mode).map(drop),
+    None => builder.remove(entry),
+  }
+  .convert_without_message()?;
+  builder.write().map(Some).convert_without_message()
+}
@@ -5,8 +5,11 @@ use napi_derive::napi;
 
 use crate::{
   blob::{Blob, BlobParent},
+  commit::{Commit, CommitInner},
   error::IntoNapiError,
   repo::Repository,
+  tag::{Tag, TagInner},
+  tree::{Tree, TreeParent},
 };
 
 #[napi]
@@ -50,6 +53,7 @@ impl From<ObjectType> for git2::ObjectType {
 pub(crate) enum ObjectParent {
   Repository(SharedReference<Repository, git2::Object<'static>>),
   Object(git2::Object<'static>),
+  Tag(SharedReference<Tag, git2::Object<'static>>),
 }
 
 impl Deref for ObjectParent {
@@ -58,7 +62,8 @@ impl Deref for ObjectParent {
   fn deref(&self) -> &git2::Object<'static> {
     match self {
       ObjectParent::Repository(parent) => parent.deref(),
-      ObjectParent::Object(parent) => &parent,
+      ObjectParent::Object(parent) => parent,
+      ObjectParent::Tag(parent) => parent.deref(),
     }
   }
 }
@@ -94,6 +99,69 @@ impl GitObject {
     })
   }
 
+  #[napi]
+  /// Get this object as a commit, `null` if it is not a commit.
+  pub fn as_commit(&self) -> Option<Commit> {
+    Some(Commit {
+      inner: CommitInner::Commit(self.inner.as_commit()?.clone()),
+    })
+  }
+
+  #[napi]
+  /// Get this object as a tree, `null` if it is not a tree.
+  pub fn as_tree(&self, env: Env, self_ref: Reference<GitObject>) -> Result<Option<Tree>> {
+    if self.inner.kind() != Some(git2::ObjectType::Tree) {
+      return Ok(None);
+    }
+    let tree = self_ref.share_with(env, |obj| {
+      obj
+        .inner
+        .as_tree()
+        .cloned()
+        .ok_or_else(|| Error::from_reason("Object is not a tree"))
+    })?;
+    Ok(Some(Tree {
+      inner: TreeParent::GitObject(tree),
+      _handle: None,
+    }))
+  }
+
+  #[napi]
+  /// Get this object as a blob, `null` if it is not a blob.
+  pub fn as_blob(&self, env: Env, self_ref: Reference<GitObject>) -> Result<Option<Blob>> {
+    if self.inner.kind() != Some(git2::ObjectType::Blob) {
+      return Ok(None);
+    }
+    let blob = self_ref.share_with(env, |obj| {
+      obj
+        .inner
+        .as_blob()
+        .cloned()
+        .ok_or_else(|| Error::from_reason("Object is not a blob"))
+    })?;
+    Ok(Some(Blob {
+      inner: BlobParent::GitObject(blob),
+    }))
+  }
+
+  #[napi]
+  /// Get this object as an annotated tag, `null` if it is not a tag.
+  pub fn as_tag(&self, env: Env, self_ref: Reference<GitObject>) -> Result<Option<Tag>> {
+    if self.inner.kind() != Some(git2::ObjectType::Tag) {
+      return Ok(None);
+    }
+    let tag = self_ref.share_with(env, |obj| {
+      obj
+        .inner
+        .as_tag()
+        .cloned()
+        .ok_or_else(|| Error::from_reason("Object is not a tag"))
+    })?;
+    Ok(Some(Tag {
+      inner: TagInner::GitObject(tag),
+    }))
+  }
+
   #[napi]
   /// Recursively peel an object until a blob is found
   pub fn peel_to_blob(&self, env: Env, self_ref: Reference<GitObject>) -> Result<Blob> {
@@ -0,0 +1,124 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{error::IntoNapiError, object::ObjectType, repo::Repository};
+
+#[napi]
+/// A structure to represent a git object database
+pub struct Odb {
+  pub(crate) inner: SharedReference<Repository, git2::Odb<'static>>,
+}
+
+#[napi]
+impl Odb {
+  #[napi]
+  /// Reads an object from the object database.
+  pub fn read(&self, env: Env, self_ref: Reference<Odb>, oid: String) -> Result<OdbObject> {
+    let oid = git2::Oid::from_str(oid.as_str()).convert(format!("Invalid OID [{oid}]"))?;
+    Ok(OdbObject {
+      inner: self_ref.share_with(env, |odb| {
+        odb
+          .inner
+          .read(oid)
+          .convert(format!("Read object [{oid}] from odb failed"))
+      })?,
+    })
+  }
+
+  #[napi]
+  /// Write an object to the database.
+  ///
+  /// Returns the OID of the written object.
+  pub fn write(&self, kind: ObjectType, data: Buffer) -> Result<String> {
+    self
+      .inner
+      .write(kind.into(), data.as_ref())
+      .map(|oid| oid.to_string())
+      .convert("Write object to odb failed")
+  }
+
+  #[napi]
+  /// Checks if the object database has an object.
+  pub fn exists(&self, oid: String) -> bool {
+    git2::Oid::from_str(oid.as_str())
+      .map(|oid| self.inner.exists(oid))
+      .unwrap_or(false)
+  }
+
+  #[napi]
+  /// Checks if the object database has an object, with a shortened OID.
+  ///
+  /// Returns the full OID of the object if it was found.
+  ///
+  /// `len` defaults to the length of `short_oid`.
+  pub fn exists_prefix(&self, short_oid: String, len: Option<u32>) -> Result<String> {
+    let oid =
+      git2::Oid::from_str(short_oid.as_str()).convert(format!("Invalid OID [{short_oid}]"))?;
+    self
+      .inner
+      .exists_prefix(oid, len.map_or(short_oid.len(), |len| len as usize))
+      .map(|oid| oid.to_string())
+      .convert(format!("Find object by prefix [{short_oid}] failed"))
+  }
+
+  #[napi]
+  /// Hashes the given data as an object of the given type, without writing it
+  /// to the database.
+  pub fn hash(&self, kind: ObjectType, data: Buffer) -> Result<String> {
+    Odb::hash_object(kind, data)
+  }
+
+  #[napi(js_name = "hash")]
+  /// Hashes the given data as an object of the given type, without a
+  /// repository.
+  pub fn hash_object(kind: ObjectType, data: Buffer) -> Result<String> {
+    git2::Oid::hash_object(kind.into(), data.as_ref())
+      .map(|oid| oid.to_string())
+      .convert("Hash object failed")
+  }
+}
+
+#[napi]
+/// An object from the Object Database.
+pub struct OdbObject {
+  pub(crate) inner: SharedReference<Odb, git2::OdbObject<'static>>,
+}
+
+#[napi]
+impl OdbObject {
+  #[napi]
+  /// Get the object data.
+  ///
+  /// The data is copied into a new array on every call, keep the array
+  /// rather than calling this repeatedly on large objects.
+  pub fn data(&self) -> Uint8Array {
+    self.inner.data().to_vec().into()
+  }
+
+  #[napi]
+  /// Get the object size.
+  pub fn size(&self) -> u64 {
+    self.inner.len() as u64
+  }
+
+  #[napi]
+  /// Get the object type.
+  pub fn kind(&self) -> ObjectType {
+    self.inner.kind().into()
+  }
+
+  #[napi]
+  /// Get the object id.
+  pub fn id(&self) -> String {
+    self.inner.id().to_string()
+  }
+}
+
+#[napi(object)]
+/// The type and the undecoded content of an object, see
+/// `Repository.readRawObject`.
+pub struct RawObject {
+  #[napi(js_name = "type")]
+  pub kind: ObjectType,
+  pub data: Buffer,
+}
@@ -0,0 +1,260 @@
+use std::{
+  collections::HashMap,
+  fs::File,
+  io::{Read, Seek, SeekFrom},
+  path::Path,
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{error::IntoNapiError, repo::Repository, util::CancellationToken};
+
+const IDX_MAGIC: &[u8] = b"\xfftOc";
+const IDX_HEADER_LEN: usize = 8 + 256 * 4;
+const OID_LEN: usize = 20;
+const OBJ_OFS_DELTA: u8 = 6;
+const OBJ_REF_DELTA: u8 = 7;
+
+#[napi(object)]
+pub struct PackedObject {
+  pub oid: String,
+  /// The inflated size of the object, its delta applied.
+  pub size: i64,
+}
+
+#[napi(object)]
+/// The objects of a pack file, see `Repository.packAnalysis`.
+pub struct PackAnalysis {
+  /// The path of the `.pack` file.
+  pub pack_file: String,
+  /// The number of objects in the pack.
+  pub objects: u32,
+  /// The object with the largest inflated size.
+  ///
+  /// Not set if the pack is empty.
+  pub largest_object: Option<PackedObject>,
+  /// The length of the longest delta chain, `0` if no object is a delta.
+  pub max_delta_depth: u32,
+  /// The size in bytes of the `.pack` file.
+  pub total_size: i64,
+}
+
+pub struct PackAnalysisTask {
+  pub(crate) repo: Reference<Repository>,
+  pub(crate) cancellation: CancellationToken,
+}
+
+unsafe impl Send for PackAnalysisTask {}
+
+#[napi]
+impl Task for PackAnalysisTask {
+  type Output = Vec<PackAnalysis>;
+  type JsValue = Vec<PackAnalysis>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let repo = &self.repo.inner;
+    let odb = repo.odb().convert("Get the odb of Repository failed")?;
+    let mut indexes = match std::fs::read_dir(repo.path().join("objects").join("pack")) {
+      Ok(entries) => entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "idx"))
+        .collect::<Vec<_>>(),
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+      Err(err) => return Err(io_error("Read the pack directory failed", err)),
+    };
+    indexes.sort();
+    indexes
+      .iter()
+      .map(|index| analyze_pack(&odb, index, &self.cancellation))
+      .collect()
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    self.cancellation.detach(env)?;
+    Ok(output)
+  }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    self.cancellation.reject(env, err)
+  }
+}
+
+fn analyze_pack(
+  odb: &git2::Odb,
+  index_path: &Path,
+  cancellation: &CancellationToken,
+) -> Result<PackAnalysis> {
+  let pack_path = index_path.with_extension("pack");
+  let entries = read_index(index_path)?;
+  let mut pack = File::open(&pack_path)
+    .map_err(|err| io_error(&format!("Open pack [{}] failed", pack_path.display()), err))?;
+  let total_size = pack
+    .metadata()
+    .map_err(|err| io_error("Read the size of the pack failed", err))?
+    .len();
+  let offsets = entries.iter().copied().collect::<HashMap<_, _>>();
+  let mut bases = HashMap::with_capacity(entries.len());
+  let mut largest: Option<PackedObject> = None;
+  for (oid, offset) in &entries {
+    cancellation.check()?;
+    let base = match read_base(&mut pack, *offset)
+      .map_err(|err| io_error(&format!("Read object [{oid}] failed"), err))?
+    {
+      Base::None => None,
+      Base::Offset(base) => Some(base),
+      Base::Oid(base) => offsets.get(&base).copied(),
+    };
+    bases.insert(*offset, base);
+    let (size, _) = odb
+      .read_header(*oid)
+      .convert(format!("Read the header of object [{oid}] failed"))?;
+    if largest
+      .as_ref()
+      .is_none_or(|largest| size as i64 > largest.size)
+    {
+      largest = Some(PackedObject {
+        oid: oid.to_string(),
+        size: size as i64,
+      });
+    }
+  }
+  Ok(PackAnalysis {
+    pack_file: pack_path.to_string_lossy().into_owned(),
+    objects: entries.len() as u32,
+    largest_object: largest,
+    max_delta_depth: max_delta_depth(&bases),
+    total_size: total_size as i64,
+  })
+}
+
+/// The length of the longest chain of `bases`, which map the offset of each
+/// object to the offset of its delta base.
+fn max_delta_depth(bases: &HashMap<u64, Option<u64>>) -> u32 {
+  let mut depths: HashMap<u64, u32> = HashMap::with_capacity(bases.len());
+  let mut max = 0;
+  for &offset in bases.keys() {
+    let mut chain = Vec::new();
+    let mut current = offset;
+    let mut depth = loop {
+      if let Some(depth) = depths.get(&current) {
+        break *depth;
+      }
+      match bases.get(&current).copied().flatten() {
+        // A chain longer than the pack can only be a corrupted cycle.
+        Some(base) if chain.len() <= bases.len() => {
+          chain.push(current);
+          current = base;
+        }
+        _ => {
+          depths.insert(current, 0);
+          break 0;
+        }
+      }
+    };
+    for offset in chain.into_iter().rev() {
+      depth += 1;
+      depths.insert(offset, depth);
+    }
+    max = max.max(depths[&offset]);
+  }
+  max
+}
+
+/// Read the objects listed by a version 2 pack index, along with their
+/// offsets in the pack.
+fn read_index(path: &Path) -> Result<Vec<(git2::Oid, u64)>> {
+  let data = std::fs::read(path)
+    .map_err(|err| io_error(&format!("Read [{}] failed", path.display()), err))?;
+  let corrupted = || {
+    Error::new(
+      Status::GenericFailure,
+      format!("Unsupported or corrupted pack index [{}]", path.display()),
+    )
+  };
+  if data.len() < IDX_HEADER_LEN || &data[..4] != IDX_MAGIC || be_u32(&data[4..]) != 2 {
+    return Err(corrupted());
+  }
+  let count = be_u32(&data[IDX_HEADER_LEN - 4..]) as usize;
+  let offsets_start = IDX_HEADER_LEN + count * (OID_LEN + 4);
+  let large_offsets_start = offsets_start + count * 4;
+  if data.len() < large_offsets_start {
+    return Err(corrupted());
+  }
+  (0..count)
+    .map(|n| {
+      let oid_start = IDX_HEADER_LEN + n * OID_LEN;
+      let oid =
+        git2::Oid::from_bytes(&data[oid_start..oid_start + OID_LEN]).map_err(|_| corrupted())?;
+      let offset = be_u32(&data[offsets_start + n * 4..]);
+      if offset & 0x8000_0000 == 0 {
+        return Ok((oid, offset as u64));
+      }
+      let large = large_offsets_start + (offset & 0x7fff_ffff) as usize * 8;
+      let bytes = data.get(large..large + 8).ok_or_else(corrupted)?;
+      Ok((oid, u64::from_be_bytes(bytes.try_into().unwrap())))
+    })
+    .collect()
+}
+
+enum Base {
+  None,
+  Offset(u64),
+  Oid(git2::Oid),
+}
+
+/// Read the delta base of the object stored at `offset` in `pack`.
+fn read_base(pack: &mut File, offset: u64) -> std::io::Result<Base> {
+  let mut header = [0u8; 48];
+  pack.seek(SeekFrom::Start(offset))?;
+  let mut len = 0;
+  while len < header.len() {
+    match pack.read(&mut header[len..])? {
+      0 => break,
+      read => len += read,
+    }
+  }
+  let mut bytes = header[..len].iter().copied();
+  let mut next = || {
+    bytes
+      .next()
+      .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))
+  };
+  let first = next()?;
+  let kind = (first >> 4) & 0x7;
+  let mut byte = first;
+  while byte & 0x80 != 0 {
+    byte = next()?;
+  }
+  match kind {
+    OBJ_OFS_DELTA => {
+      let mut byte = next()?;
+      let mut distance = (byte & 0x7f) as u64;
+      while byte & 0x80 != 0 {
+        byte = next()?;
+        distance = ((distance + 1) << 7) | (byte & 0x7f) as u64;
+      }
+      offset
+        .checked_sub(distance)
+        .map(Base::Offset)
+        .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::InvalidData))
+    }
+    OBJ_REF_DELTA => {
+      let oid = (0..OID_LEN)
+        .map(|_| next())
+        .collect::<std::io::Result<Vec<_>>>()?;
+      git2::Oid::from_bytes(&oid)
+        .map(Base::Oid)
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidData))
+    }
+    _ => Ok(Base::None),
+  }
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+  u32::from_be_bytes(bytes[..4].try_into().unwrap())
+}
+
+fn io_error(message: &str, err: std::io::Error) -> Error {
+  Error::new(Status::GenericFailure, format!("{message}: {err}"))
+}
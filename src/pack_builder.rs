@@ -0,0 +1,138 @@
+use std::{path::Path, thread};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{error::IntoNapiError, repo::Repository, rev_walk::RevWalk, util::CallbackError};
+
+#[napi(string_enum = "camelCase")]
+/// The stages reported to the progress callback of `PackBuilder.writeFile`.
+pub enum PackBuilderStage {
+  AddingObjects,
+  Deltafication,
+}
+
+impl From<git2::PackBuilderStage> for PackBuilderStage {
+  fn from(value: git2::PackBuilderStage) -> Self {
+    match value {
+      git2::PackBuilderStage::AddingObjects => PackBuilderStage::AddingObjects,
+      git2::PackBuilderStage::Deltafication => PackBuilderStage::Deltafication,
+    }
+  }
+}
+
+#[napi]
+/// A builder of packfiles, see `Repository.packbuilder`.
+pub struct PackBuilder {
+  pub(crate) inner: SharedReference<Repository, git2::PackBuilder<'static>>,
+}
+
+#[napi]
+impl PackBuilder {
+  #[napi]
+  /// Insert a commit, along with its tree and the objects it references.
+  ///
+  /// The parents of the commit are not inserted.
+  pub fn insert_commit(&mut self, oid: String) -> Result<&Self> {
+    let oid = git2::Oid::from_str(&oid).convert(format!("Invalid OID [{oid}]"))?;
+    self
+      .inner
+      .insert_commit(oid)
+      .convert(format!("Insert commit [{oid}] failed"))?;
+    Ok(self)
+  }
+
+  #[napi]
+  /// Insert a tree, along with the objects it references.
+  pub fn insert_tree(&mut self, oid: String) -> Result<&Self> {
+    let oid = git2::Oid::from_str(&oid).convert(format!("Invalid OID [{oid}]"))?;
+    self
+      .inner
+      .insert_tree(oid)
+      .convert(format!("Insert tree [{oid}] failed"))?;
+    Ok(self)
+  }
+
+  #[napi]
+  /// Insert the commits yielded by `walk` along with the objects they
+  /// reference, e.g. the commits of a `from..to` range.
+  ///
+  /// The walk is drained, as if iterated to its end.
+  pub fn insert_walk(&mut self, walk: &mut RevWalk) -> Result<&Self> {
+    let result = self.inner.insert_walk(&mut walk.inner);
+    walk._handle = None;
+    result.convert("Insert the commits of the revwalk failed")?;
+    Ok(self)
+  }
+
+  #[napi]
+  /// Set the number of threads used to search for deltas, `0` to detect the
+  /// number of CPUs.
+  ///
+  /// Returns the number of threads which will be used.
+  pub fn set_threads(&mut self, threads: u32) -> u32 {
+    self.inner.set_threads(threads)
+  }
+
+  #[napi]
+  /// Get the number of objects inserted so far.
+  pub fn object_count(&self) -> u32 {
+    self.inner.object_count() as u32
+  }
+
+  #[napi]
+  /// Write the packfile of the inserted objects to a buffer.
+  pub fn write_buf(&mut self) -> Result<Buffer> {
+    let mut buf = git2::Buf::new();
+    self
+      .inner
+      .write_buf(&mut buf)
+      .convert("Write the packfile failed")?;
+    Ok(buf.to_vec().into())
+  }
+
+  #[napi]
+  /// Write the packfile of the inserted objects and its index into the
+  /// directory `path`, e.g. `.git/objects/pack`.
+  ///
+  /// `progress` is called with the current stage and the number of objects
+  /// processed out of the total. Deltas searched by other threads, see
+  /// `setThreads`, are only reported once the search completes.
+  ///
+  /// Returns the name of the packfile, its files being `pack-<name>.pack`
+  /// and `pack-<name>.idx`.
+  pub fn write_file(
+    &mut self,
+    env: Env,
+    path: String,
+    #[napi(ts_arg_type = "(stage: PackBuilderStage, current: number, total: number) => void")]
+    progress: Option<FunctionRef<(PackBuilderStage, u32, u32), ()>>,
+  ) -> Result<String> {
+    let callback_error = CallbackError::default();
+    if let Some(progress) = progress {
+      let callback_error = callback_error.clone();
+      let main_thread = thread::current().id();
+      self
+        .inner
+        .set_progress_callback(move |stage, current, total| {
+          // The main thread is blocked until the write completes
+          if thread::current().id() != main_thread {
+            return true;
+          }
+          progress
+            .borrow_back(&env)
+            .and_then(|cb| cb.call((stage.into(), current, total)))
+            .map_err(|err| callback_error.set(err))
+            .is_ok()
+        })
+        .convert_without_message()?;
+    }
+    let result = self.inner.write(Path::new(&path), 0);
+    self
+      .inner
+      .unset_progress_callback()
+      .convert_without_message()?;
+    callback_error.check(result.convert(format!("Write the packfile into [{path}] failed")))?;
+    Ok(self.inner.name().unwrap_or_default().to_owned())
+  }
+}
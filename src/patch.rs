@@ -0,0 +1,374 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+  deltas::{DiffHunk, DiffLine},
+  diff::Diff,
+  error::IntoNapiError,
+};
+
+/// Lines whose tokens would need a larger table to be compared are reported
+/// as fully changed.
+const MAX_LCS_CELLS: usize = 4 * 1024 * 1024;
+
+/// The lowest similarity of two lines of removed and added runs of different
+/// lengths to be compared.
+const MIN_PAIR_SIMILARITY: f64 = 0.5;
+
+#[napi(string_enum = "lowercase")]
+#[derive(Default)]
+/// The units `Patch.lineDiffRegions` compares lines by.
+pub enum DiffGranularity {
+  /// Runs of letters, digits and underscores, runs of whitespace and single
+  /// punctuation characters.
+  #[default]
+  Word,
+  /// Single characters.
+  Char,
+}
+
+#[napi(object)]
+/// A changed region of a pair of lines, in bytes of their content.
+pub struct DiffRegion {
+  pub old_start: u32,
+  pub old_len: u32,
+  pub new_start: u32,
+  pub new_len: u32,
+}
+
+#[napi(object)]
+/// The changed regions of a removed line and of the added line replacing it.
+pub struct LineDiffRegions {
+  pub old_line_no: u32,
+  pub new_line_no: u32,
+  pub regions: Vec<DiffRegion>,
+}
+
+#[napi]
+/// The changes of a single delta of a diff, see `Diff.patch`.
+pub struct Patch {
+  pub(crate) inner: SharedReference<Diff, git2::Patch<'static>>,
+}
+
+#[napi]
+impl Patch {
+  #[napi]
+  /// Get the patch of the delta of `diff` at `index`, like `Diff.patch`.
+  ///
+  /// Returns `null` if the delta is binary or unchanged.
+  pub fn from_diff(env: Env, diff: Reference<Diff>, index: u32) -> Result<Option<Patch>> {
+    let this = diff.clone(env)?;
+    diff.patch(env, this, index)
+  }
+
+  #[napi]
+  /// The number of hunks of the patch.
+  pub fn num_hunks(&self) -> u32 {
+    self.inner.num_hunks() as u32
+  }
+
+  #[napi]
+  /// Get the hunk at `hunkIndex`.
+  pub fn hunk(&self, hunk_index: u32) -> Result<DiffHunk> {
+    let (hunk, _) = self
+      .inner
+      .hunk(hunk_index as usize)
+      .convert(format!("Get the hunk [{hunk_index}] failed"))?;
+    Ok(hunk.into())
+  }
+
+  #[napi]
+  /// The number of lines of the hunk at `hunkIndex`, its context lines
+  /// included.
+  pub fn num_lines_in_hunk(&self, hunk_index: u32) -> Result<u32> {
+    Ok(
+      self
+        .inner
+        .num_lines_in_hunk(hunk_index as usize)
+        .convert(format!("Get the hunk [{hunk_index}] failed"))? as u32,
+    )
+  }
+
+  #[napi]
+  /// Get the line at `lineIndex` of the hunk at `hunkIndex`.
+  pub fn line(&self, hunk_index: u32, line_index: u32) -> Result<DiffLine> {
+    let line = self
+      .inner
+      .line_in_hunk(hunk_index as usize, line_index as usize)
+      .convert(format!(
+        "Get the line [{line_index}] of the hunk [{hunk_index}] failed"
+      ))?;
+    Ok(line.into())
+  }
+
+  #[napi]
+  /// Render the patch in the unified diff format, its file headers included.
+  pub fn to_buffer(&mut self) -> Result<Buffer> {
+    let buf = self.inner.to_buf().convert("Render the patch failed")?;
+    Ok(buf.to_vec().into())
+  }
+
+  #[napi]
+  /// Pair the removed lines of the hunk at `hunkIndex` with the added lines
+  /// replacing them and compute the regions which changed within them, e.g.
+  /// to highlight the changed words of modified lines.
+  ///
+  /// Runs of removed lines followed by as many added lines are paired in
+  /// order. Runs of different lengths pair the most similar lines, keeping
+  /// their order, and leave the others unpaired. Lines sharing no letter or
+  /// digit are reported as a single region spanning both lines.
+  pub fn line_diff_regions(
+    &self,
+    hunk_index: u32,
+    granularity: Option<DiffGranularity>,
+  ) -> Result<Vec<LineDiffRegions>> {
+    let hunk_index = hunk_index as usize;
+    let granularity = granularity.unwrap_or_default();
+    let line_count = self
+      .inner
+      .num_lines_in_hunk(hunk_index)
+      .convert(format!("Get the hunk [{hunk_index}] failed"))?;
+    let mut lines = Vec::with_capacity(line_count);
+    for index in 0..line_count {
+      let line = self
+        .inner
+        .line_in_hunk(hunk_index, index)
+        .convert_without_message()?;
+      let content = line.content();
+      lines.push((
+        line.origin_value(),
+        content.strip_suffix(b"\n").unwrap_or(content).to_vec(),
+        line.old_lineno().unwrap_or_default(),
+        line.new_lineno().unwrap_or_default(),
+      ));
+    }
+    let mut result = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+      if lines[index].0 != git2::DiffLineType::Deletion {
+        index += 1;
+        continue;
+      }
+      let removed_start = index;
+      while index < lines.len() && lines[index].0 == git2::DiffLineType::Deletion {
+        index += 1;
+      }
+      let added_start = index;
+      while index < lines.len() && lines[index].0 == git2::DiffLineType::Addition {
+        index += 1;
+      }
+      let removed = &lines[removed_start..added_start];
+      let added = &lines[added_start..index];
+      for (old, new) in pair_lines(removed, added, granularity) {
+        let (_, old_content, old_line_no, _) = &removed[old];
+        let (_, new_content, _, new_line_no) = &added[new];
+        result.push(LineDiffRegions {
+          old_line_no: *old_line_no,
+          new_line_no: *new_line_no,
+          regions: diff_regions(old_content, new_content, granularity),
+        });
+      }
+    }
+    Ok(result)
+  }
+}
+
+type PatchLine = (git2::DiffLineType, Vec<u8>, u32, u32);
+
+/// Pair the indexes of the `removed` lines with the ones of the `added` lines
+/// replacing them.
+fn pair_lines(
+  removed: &[PatchLine],
+  added: &[PatchLine],
+  granularity: DiffGranularity,
+) -> Vec<(usize, usize)> {
+  if removed.len() == added.len() {
+    return (0..removed.len()).map(|index| (index, index)).collect();
+  }
+  let mut pairs = Vec::new();
+  let mut next_added = 0;
+  for (old, (_, old_content, _, _)) in removed.iter().enumerate() {
+    let old_tokens = tokenize(old_content, granularity);
+    let best = (next_added..added.len())
+      .map(|new| {
+        let new_tokens = tokenize(&added[new].1, granularity);
+        (
+          new,
+          similarity(old_content, &old_tokens, &added[new].1, &new_tokens),
+        )
+      })
+      .fold(None, |best: Option<(usize, f64)>, candidate| match best {
+        Some(best) if best.1 >= candidate.1 => Some(best),
+        _ => Some(candidate),
+      });
+    if let Some((new, score)) = best {
+      if score >= MIN_PAIR_SIMILARITY {
+        pairs.push((old, new));
+        next_added = new + 1;
+      }
+    }
+  }
+  pairs
+}
+
+/// Split `line` into the byte ranges of its tokens.
+fn tokenize(line: &[u8], granularity: DiffGranularity) -> Vec<(usize, usize)> {
+  let mut tokens = Vec::new();
+  let mut start = 0;
+  while start < line.len() {
+    let end = match granularity {
+      // UTF-8 continuation bytes belong to the character before them
+      DiffGranularity::Char => {
+        start
+          + 1
+          + line[start + 1..]
+            .iter()
+            .take_while(|byte| **byte & 0xc0 == 0x80)
+            .count()
+      }
+      DiffGranularity::Word => {
+        let class = byte_class(line[start]);
+        if class == ByteClass::Punctuation {
+          start + 1
+        } else {
+          start
+            + 1
+            + line[start + 1..]
+              .iter()
+              .take_while(|byte| byte_class(**byte) == class)
+              .count()
+        }
+      }
+    };
+    tokens.push((start, end));
+    start = end;
+  }
+  tokens
+}
+
+#[derive(PartialEq, Eq)]
+enum ByteClass {
+  Word,
+  Whitespace,
+  Punctuation,
+}
+
+fn byte_class(byte: u8) -> ByteClass {
+  if byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80 {
+    ByteClass::Word
+  } else if byte.is_ascii_whitespace() {
+    ByteClass::Whitespace
+  } else {
+    ByteClass::Punctuation
+  }
+}
+
+/// The pairs of indexes of the tokens of the longest common subsequence of
+/// `old` and `new`.
+///
+/// Returns `None` if the lines are too long to be compared.
+fn common_tokens(
+  old: &[u8],
+  old_tokens: &[(usize, usize)],
+  new: &[u8],
+  new_tokens: &[(usize, usize)],
+) -> Option<Vec<(usize, usize)>> {
+  let (rows, columns) = (old_tokens.len() + 1, new_tokens.len() + 1);
+  if rows * columns > MAX_LCS_CELLS {
+    return None;
+  }
+  let token_eq = |i: usize, j: usize| {
+    let (old_start, old_end) = old_tokens[i];
+    let (new_start, new_end) = new_tokens[j];
+    old[old_start..old_end] == new[new_start..new_end]
+  };
+  // The lengths of the common subsequences of the suffixes
+  let mut lengths = vec![0u32; rows * columns];
+  for i in (0..old_tokens.len()).rev() {
+    for j in (0..new_tokens.len()).rev() {
+      lengths[i * columns + j] = if token_eq(i, j) {
+        lengths[(i + 1) * columns + j + 1] + 1
+      } else {
+        lengths[(i + 1) * columns + j].max(lengths[i * columns + j + 1])
+      };
+    }
+  }
+  let mut pairs = Vec::new();
+  let (mut i, mut j) = (0, 0);
+  while i < old_tokens.len() && j < new_tokens.len() {
+    if token_eq(i, j) {
+      pairs.push((i, j));
+      i += 1;
+      j += 1;
+    } else if lengths[(i + 1) * columns + j] >= lengths[i * columns + j + 1] {
+      i += 1;
+    } else {
+      j += 1;
+    }
+  }
+  Some(pairs)
+}
+
+/// The share of the bytes of both lines which are common to them.
+fn similarity(
+  old: &[u8],
+  old_tokens: &[(usize, usize)],
+  new: &[u8],
+  new_tokens: &[(usize, usize)],
+) -> f64 {
+  if old.is_empty() && new.is_empty() {
+    return 1.0;
+  }
+  let common = common_tokens(old, old_tokens, new, new_tokens)
+    .unwrap_or_default()
+    .iter()
+    .map(|(i, _)| old_tokens[*i].1 - old_tokens[*i].0)
+    .sum::<usize>();
+  (2 * common) as f64 / (old.len() + new.len()) as f64
+}
+
+/// The regions which differ between the `old` and `new` lines.
+fn diff_regions(old: &[u8], new: &[u8], granularity: DiffGranularity) -> Vec<DiffRegion> {
+  let old_tokens = tokenize(old, granularity);
+  let new_tokens = tokenize(new, granularity);
+  let whole = || {
+    vec![DiffRegion {
+      old_start: 0,
+      old_len: old.len() as u32,
+      new_start: 0,
+      new_len: new.len() as u32,
+    }]
+  };
+  let Some(common) = common_tokens(old, &old_tokens, new, &new_tokens) else {
+    return whole();
+  };
+  let shares_word = common
+    .iter()
+    .any(|(i, _)| byte_class(old[old_tokens[*i].0]) == ByteClass::Word);
+  if !shares_word {
+    return if old == new { Vec::new() } else { whole() };
+  }
+  let mut regions = Vec::new();
+  let (mut old_index, mut new_index) = (0, 0);
+  // A sentinel past the last tokens closes the trailing region
+  for (i, j) in common
+    .into_iter()
+    .chain(std::iter::once((old_tokens.len(), new_tokens.len())))
+  {
+    if i > old_index || j > new_index {
+      let old_start = old_tokens.get(old_index).map_or(old.len(), |token| token.0);
+      let old_end = old_tokens.get(i).map_or(old.len(), |token| token.0);
+      let new_start = new_tokens.get(new_index).map_or(new.len(), |token| token.0);
+      let new_end = new_tokens.get(j).map_or(new.len(), |token| token.0);
+      regions.push(DiffRegion {
+        old_start: old_start as u32,
+        old_len: (old_end - old_start) as u32,
+        new_start: new_start as u32,
+        new_len: (new_end - new_start) as u32,
+      });
+    }
+    old_index = i + 1;
+    new_index = j + 1;
+  }
+  regions
+}
@@ -0,0 +1,179 @@
+use std::path::Path;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{diff::Diff, error::IntoNapiError, index::Index, repo::Repository};
+
+#[napi]
+#[repr(u32)]
+/// Options for matching a `Pathspec`, which may be combined with `|`.
+pub enum PathspecFlags {
+  /// Use the default pathspec matching configuration.
+  Default = 0,
+  /// Force matching to ignore case, otherwise matching will use native case
+  /// sensitivity of the platform filesystem.
+  /// 1 << 0
+  IgnoreCase = 1,
+  /// Force case sensitive matches, otherwise match will use the native case
+  /// sensitivity of the platform filesystem.
+  /// 1 << 1
+  UseCase = 2,
+  /// Disable glob patterns and just use simple string comparison for
+  /// matching.
+  /// 1 << 2
+  NoGlob = 4,
+  /// Fail with a `NotFound` error if no matches are found. By default no
+  /// matches is a success.
+  /// 1 << 3
+  NoMatchError = 8,
+  /// Track which patterns matched which files so that the patterns that did
+  /// not match any files can be listed by `PathspecMatchList.failedEntries`.
+  /// 1 << 4
+  FindFailures = 16,
+  /// Don't keep the matching filenames, to just test if there were any
+  /// matches at all or in combination with `FindFailures` to validate a
+  /// pathspec.
+  /// 1 << 5
+  FailuresOnly = 32,
+}
+
+fn to_git2_flags(flags: Option<u32>) -> git2::PathspecFlags {
+  flags
+    .map(git2::PathspecFlags::from_bits_truncate)
+    .unwrap_or_default()
+}
+
+#[napi]
+/// A compiled list of pathspecs, the glob patterns used by git to select
+/// files, e.g. `src/*.rs`.
+pub struct Pathspec {
+  pub(crate) inner: git2::Pathspec,
+}
+
+#[napi]
+impl Pathspec {
+  #[napi(constructor)]
+  /// Compile the given pathspecs.
+  pub fn new(patterns: Vec<String>) -> Result<Self> {
+    Ok(Self {
+      inner: git2::Pathspec::new(&patterns).convert("Compile pathspec failed")?,
+    })
+  }
+
+  #[napi]
+  /// Try to match a path against this pathspec.
+  pub fn matches_path(
+    &self,
+    path: String,
+    #[napi(ts_arg_type = "PathspecFlags | number")] flags: Option<u32>,
+  ) -> bool {
+    self
+      .inner
+      .matches_path(Path::new(&path), to_git2_flags(flags))
+  }
+
+  #[napi]
+  /// Match this pathspec against the working directory of a repository.
+  ///
+  /// Ignored files are not matched, unless they are already in the index.
+  pub fn match_workdir(
+    &self,
+    env: Env,
+    self_ref: Reference<Pathspec>,
+    repo: &Repository,
+    #[napi(ts_arg_type = "PathspecFlags | number")] flags: Option<u32>,
+  ) -> Result<PathspecMatchList> {
+    Ok(PathspecMatchList {
+      inner: self_ref.share_with(env, |pathspec| {
+        pathspec
+          .inner
+          .match_workdir(&repo.inner, to_git2_flags(flags))
+          .convert("Match pathspec against the working directory failed")
+      })?,
+      position: 0,
+    })
+  }
+
+  #[napi]
+  /// Match this pathspec against the entries of an index.
+  pub fn match_index(
+    &self,
+    env: Env,
+    self_ref: Reference<Pathspec>,
+    index: &Index,
+    #[napi(ts_arg_type = "PathspecFlags | number")] flags: Option<u32>,
+  ) -> Result<PathspecMatchList> {
+    Ok(PathspecMatchList {
+      inner: self_ref.share_with(env, |pathspec| {
+        pathspec
+          .inner
+          .match_index(&index.inner, to_git2_flags(flags))
+          .convert("Match pathspec against the index failed")
+      })?,
+      position: 0,
+    })
+  }
+
+  #[napi]
+  /// Match this pathspec against the deltas of a diff, yielding the new path
+  /// of each matching delta.
+  pub fn match_diff(
+    &self,
+    env: Env,
+    self_ref: Reference<Pathspec>,
+    diff: &Diff,
+    #[napi(ts_arg_type = "PathspecFlags | number")] flags: Option<u32>,
+  ) -> Result<PathspecMatchList> {
+    Ok(PathspecMatchList {
+      inner: self_ref.share_with(env, |pathspec| {
+        pathspec
+          .inner
+          .match_diff(&diff.inner, to_git2_flags(flags))
+          .convert("Match pathspec against the diff failed")
+      })?,
+      position: 0,
+    })
+  }
+}
+
+#[napi(iterator)]
+/// The paths matched by a `Pathspec`.
+pub struct PathspecMatchList {
+  pub(crate) inner: SharedReference<Pathspec, git2::PathspecMatchList<'static>>,
+  pub(crate) position: usize,
+}
+
+#[napi]
+impl Generator for PathspecMatchList {
+  type Yield = String;
+  type Return = ();
+  type Next = ();
+
+  fn next(&mut self, _value: Option<Self::Next>) -> Option<Self::Yield> {
+    let path = match self.inner.entry(self.position) {
+      Some(path) => String::from_utf8_lossy(path).into_owned(),
+      None => {
+        let delta = self.inner.diff_entry(self.position)?;
+        let path = delta.new_file().path_bytes()?;
+        String::from_utf8_lossy(path).into_owned()
+      }
+    };
+    self.position += 1;
+    Some(path)
+  }
+}
+
+#[napi]
+impl PathspecMatchList {
+  #[napi]
+  /// The pathspecs which did not match any file, requires the
+  /// `FindFailures` flag.
+  pub fn failed_entries(&self) -> Vec<String> {
+    self
+      .inner
+      .failed_entries()
+      .map(|entry| String::from_utf8_lossy(entry).into_owned())
+      .collect()
+  }
+}
@@ -0,0 +1,159 @@
+use std::path::Path;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+  commit::CommitSummary,
+  diff::{Diff, DiffOptions},
+  error::IntoNapiError,
+  repo::Repository,
+  stats::{HandleKind, OpenHandle},
+  tree::{Tree, TreeEntry, TreeParent, TreeWalkMode},
+};
+
+#[napi]
+/// A read-only view of a repository pinned at a commit, see
+/// `Repository.pinnedView`.
+///
+/// The view only holds the id of the commit, so moving references or
+/// creating commits does not change what it reads, and it never blocks
+/// writers.
+pub struct PinnedView {
+  pub(crate) repo: Reference<Repository>,
+  pub(crate) oid: git2::Oid,
+}
+
+#[napi]
+impl PinnedView {
+  #[napi]
+  /// The commit the view is pinned at.
+  pub fn commit_id(&self) -> String {
+    self.oid.to_string()
+  }
+
+  #[napi]
+  /// Read the content of the file at `path` in the pinned commit.
+  ///
+  /// Returns `null` if the commit has no file at `path`.
+  pub fn file_at_revision(&self, path: String) -> Result<Option<Buffer>> {
+    let tree = self.tree_inner()?;
+    let Ok(entry) = tree.get_path(Path::new(&path)) else {
+      return Ok(None);
+    };
+    if entry.kind() != Some(git2::ObjectType::Blob) {
+      return Ok(None);
+    }
+    let blob = self
+      .repo
+      .inner
+      .find_blob(entry.id())
+      .convert(format!("Read the file [{path}] failed"))?;
+    Ok(Some(blob.content().to_vec().into()))
+  }
+
+  #[napi]
+  /// List the paths of the files of the pinned commit, like `git ls-files`
+  /// on a clean checkout of it.
+  pub fn ls_files(&self) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    self
+      .tree_inner()?
+      .walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(git2::ObjectType::Blob) {
+          files.push(format!(
+            "{root}{}",
+            String::from_utf8_lossy(entry.name_bytes())
+          ));
+        }
+        git2::TreeWalkResult::Ok
+      })
+      .convert("Walk the tree failed")?;
+    Ok(files)
+  }
+
+  #[napi]
+  /// Create a diff between the tree of `oldCommitish` and the one of the
+  /// pinned commit, like `Repository.diffCommits`.
+  pub fn diff_commits(
+    &self,
+    env: Env,
+    old_commitish: String,
+    options: Option<DiffOptions>,
+  ) -> Result<Diff> {
+    let mut diff_options = options
+      .as_ref()
+      .map(DiffOptions::to_git2)
+      .unwrap_or_default();
+    let oid = self.oid;
+    let inner = self.repo.clone(env)?.share_with(env, |repo| {
+      let old_tree = repo
+        .inner
+        .revparse_single(&old_commitish)
+        .and_then(|object| object.peel_to_tree())
+        .convert(format!("Resolve [{old_commitish}] to a tree failed"))?;
+      let new_tree = repo
+        .inner
+        .find_commit(oid)
+        .and_then(|commit| commit.tree())
+        .convert(format!("Get the tree of [{oid}] failed"))?;
+      repo
+        .inner
+        .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut diff_options))
+        .convert_without_message()
+    })?;
+    Diff::new(env, inner, &self.repo, options)
+  }
+
+  #[napi]
+  /// List the commits reachable from the pinned commit but not from `since`,
+  /// newest first, like `git log <since>..<commit>`.
+  pub fn commit_range(&self, since: String) -> Result<Vec<CommitSummary>> {
+    let since = self
+      .repo
+      .inner
+      .revparse_single(&since)
+      .and_then(|object| object.peel_to_commit())
+      .convert(format!("Resolve [{since}] to a commit failed"))?;
+    self.repo.commit_range(since.id(), self.oid)
+  }
+
+  #[napi]
+  /// Get the tree of the pinned commit.
+  pub fn tree(&self, env: Env) -> Result<Tree> {
+    let oid = self.oid;
+    Ok(Tree {
+      inner: TreeParent::Repository(self.repo.clone(env)?.share_with(env, |repo| {
+        repo
+          .inner
+          .find_commit(oid)
+          .and_then(|commit| commit.tree())
+          .convert(format!("Get the tree of [{oid}] failed"))
+      })?),
+      _handle: Some(OpenHandle::new(&self.repo.handles, HandleKind::Tree)),
+    })
+  }
+
+  #[napi]
+  /// Traverse the tree of the pinned commit like `Tree.walk`.
+  pub fn tree_walk(
+    &self,
+    env: Env,
+    mode: TreeWalkMode,
+    #[napi(ts_arg_type = "(root: string, entry: TreeEntry) => number | undefined | null")]
+    callback: FunctionRef<(String, TreeEntry), Option<i32>>,
+  ) -> Result<()> {
+    self.tree(env)?.walk(env, mode, callback)
+  }
+}
+
+impl PinnedView {
+  fn tree_inner(&self) -> Result<git2::Tree<'_>> {
+    self
+      .repo
+      .inner
+      .find_commit(self.oid)
+      .and_then(|commit| commit.tree())
+      .convert(format!("Get the tree of [{}] failed", self.oid))
+  }
+}
@@ -0,0 +1,290 @@
+use std::borrow::Cow;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+  error::IntoNapiError,
+  reference::RefUpdate,
+  repo::{commit_id, Repository},
+  signature::Signature,
+};
+
+#[napi(object)]
+#[derive(Default)]
+pub struct RebaseOptions {
+  /// Tell other clients that the rebase is quiet, which is only used for
+  /// compatibility with the `rebase-merge` state of git.
+  pub quiet: Option<bool>,
+  /// Perform the rebase in memory, without touching the working directory,
+  /// the index or the state of the repository.
+  pub inmemory: Option<bool>,
+  /// Copy the notes of the rebased commits to their rewritten commits, from
+  /// this notes reference.
+  ///
+  /// Not set to use the `notes.rewriteRef` configuration.
+  pub rewrite_notes_ref: Option<String>,
+}
+
+impl RebaseOptions {
+  pub(crate) fn to_git2(&self) -> git2::RebaseOptions<'static> {
+    let mut options = git2::RebaseOptions::new();
+    if let Some(quiet) = self.quiet {
+      options.quiet(quiet);
+    }
+    if let Some(inmemory) = self.inmemory {
+      options.inmemory(inmemory);
+    }
+    if let Some(rewrite_notes_ref) = &self.rewrite_notes_ref {
+      options.rewrite_notes_ref(rewrite_notes_ref);
+    }
+    options
+  }
+}
+
+#[napi]
+/// A rebase operation
+///
+/// Describes a single instruction/operation to be performed during the
+/// rebase.
+pub enum RebaseOperationType {
+  /// The given commit is to be cherry-picked. The client should commit the
+  /// changes and continue if there are no conflicts.
+  Pick,
+  /// The given commit is to be cherry-picked, but the client should prompt
+  /// the user to provide an updated commit message.
+  Reword,
+  /// The given commit is to be cherry-picked, but the client should stop to
+  /// allow the user to edit the changes before committing them.
+  Edit,
+  /// The given commit is to be squashed into the previous commit. The commit
+  /// message will be merged with the previous message.
+  Squash,
+  /// The given commit is to be squashed into the previous commit. The commit
+  /// message from this commit will be discarded.
+  Fixup,
+  /// No commit will be cherry-picked. The client should run the given command
+  /// and (if successful) continue.
+  Exec,
+}
+
+impl From<git2::RebaseOperationType> for RebaseOperationType {
+  fn from(value: git2::RebaseOperationType) -> Self {
+    match value {
+      git2::RebaseOperationType::Pick => RebaseOperationType::Pick,
+      git2::RebaseOperationType::Reword => RebaseOperationType::Reword,
+      git2::RebaseOperationType::Edit => RebaseOperationType::Edit,
+      git2::RebaseOperationType::Squash => RebaseOperationType::Squash,
+      git2::RebaseOperationType::Fixup => RebaseOperationType::Fixup,
+      git2::RebaseOperationType::Exec => RebaseOperationType::Exec,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct RebaseOperation {
+  pub kind: RebaseOperationType,
+  /// The commit to be cherry-picked, all zeros for an `Exec` operation.
+  pub id: String,
+  /// The command to run for an `Exec` operation, empty otherwise.
+  pub exec: String,
+}
+
+impl From<git2::RebaseOperation<'_>> for RebaseOperation {
+  fn from(operation: git2::RebaseOperation<'_>) -> Self {
+    let kind = operation.kind().unwrap_or(git2::RebaseOperationType::Pick);
+    Self {
+      id: operation.id().to_string(),
+      // `exec` panics for the operations without a command
+      exec: match kind {
+        git2::RebaseOperationType::Exec => operation.exec().unwrap_or_default().to_owned(),
+        _ => String::new(),
+      },
+      kind: kind.into(),
+    }
+  }
+}
+
+#[napi]
+/// Representation of a rebase, see `Repository.rebaseInit`.
+pub struct Rebase {
+  pub(crate) inner: SharedReference<Repository, git2::Rebase<'static>>,
+  /// Whether the rebase leaves the references alone, see
+  /// `RebaseOptions.inmemory`.
+  pub(crate) inmemory: bool,
+}
+
+#[napi]
+impl Rebase {
+  #[napi]
+  /// Gets the count of rebase operations that are to be applied.
+  pub fn len(&self) -> u32 {
+    self.inner.len() as u32
+  }
+
+  #[napi]
+  /// Whether the rebase has no operation to apply.
+  pub fn is_empty(&self) -> bool {
+    self.inner.len() == 0
+  }
+
+  #[napi]
+  /// Gets the original `HEAD` ref name for merge rebases.
+  pub fn orig_head_name(&self) -> Option<String> {
+    self.inner.orig_head_name().map(ToOwned::to_owned)
+  }
+
+  #[napi]
+  /// Gets the original HEAD id for merge rebases.
+  pub fn orig_head_id(&self) -> Option<String> {
+    self.inner.orig_head_id().map(|oid| oid.to_string())
+  }
+
+  #[napi]
+  /// Gets the index of the rebase operation that is currently being applied.
+  ///
+  /// Returns `null` if no operation was applied yet.
+  pub fn operation_current(&mut self) -> Option<u32> {
+    self.inner.operation_current().map(|index| index as u32)
+  }
+
+  #[napi]
+  /// Performs the next rebase operation and returns the information about it.
+  ///
+  /// If the operation is one that applies a patch (which is any operation
+  /// except `Exec`) then the patch will be applied and the index and working
+  /// directory will be updated with the changes. If there are conflicts, you
+  /// will need to address those before committing the changes.
+  ///
+  /// Returns `null` once every operation was applied.
+  #[allow(clippy::should_implement_trait)]
+  pub fn next(&mut self) -> Result<Option<RebaseOperation>> {
+    self
+      .inner
+      .next()
+      .transpose()
+      .map(|operation| operation.map(RebaseOperation::from))
+      .convert("Apply the next rebase operation failed")
+  }
+
+  #[napi]
+  /// Commits the current patch. You must have resolved any conflicts that
+  /// were introduced during the patch application from the `next`
+  /// invocation.
+  ///
+  /// The author and the message of the original commit are kept when not
+  /// given.
+  pub fn commit(
+    &mut self,
+    env: Env,
+    author: Option<&Signature>,
+    committer: &Signature,
+    message: Option<String>,
+  ) -> Result<String> {
+    let repo = self.inner.clone_owner(env)?;
+    if !self.inmemory && repo.ref_update_guard.is_some() {
+      if let Some(oid) = self.next_commit_id(&repo, author, committer, message.as_deref())? {
+        let (refname, old_oid) = repo.resolve_update_ref("HEAD")?;
+        repo.check_ref_update(&env, RefUpdate::new(&refname, old_oid, Some(oid)))?;
+      }
+    }
+    self
+      .inner
+      .commit(
+        author.map(|author| author.as_ref()),
+        committer.as_ref(),
+        message.as_deref(),
+      )
+      .map(|oid| oid.to_string())
+      .convert("Commit the rebase operation failed")
+  }
+
+  #[napi]
+  /// Aborts a rebase that is currently in progress, resetting the repository
+  /// and working directory to their state before rebase began.
+  pub fn abort(&mut self) -> Result<()> {
+    self.inner.abort().convert("Abort rebase failed")
+  }
+
+  #[napi]
+  /// Finishes a rebase that is currently in progress once all patches have
+  /// been applied.
+  pub fn finish(&mut self, env: Env, signature: Option<&Signature>) -> Result<()> {
+    let repo = self.inner.clone_owner(env)?;
+    // A rebase started from a detached `HEAD` leaves the branches alone
+    let branch = self
+      .inner
+      .orig_head_name()
+      .filter(|name| name.starts_with("refs/"));
+    if let (false, Some(branch), Some(_)) = (self.inmemory, branch, &repo.ref_update_guard) {
+      let head = repo
+        .inner
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .convert("Resolve HEAD failed")?;
+      repo.check_ref_update(
+        &env,
+        RefUpdate::new(branch, self.inner.orig_head_id(), Some(head.id())),
+      )?;
+    }
+    self
+      .inner
+      .finish(signature.map(|signature| signature.as_ref()))
+      .convert("Finish rebase failed")
+  }
+
+  /// Compute the id of the commit `commit` creates for the current operation,
+  /// `None` when there is none or creating it fails anyway.
+  fn next_commit_id(
+    &mut self,
+    repo: &Repository,
+    author: Option<&Signature>,
+    committer: &Signature,
+    message: Option<&str>,
+  ) -> Result<Option<git2::Oid>> {
+    let Some(operation) = self
+      .inner
+      .operation_current()
+      .and_then(|current| self.inner.nth(current))
+    else {
+      return Ok(None);
+    };
+    let current = repo
+      .inner
+      .find_commit(operation.id())
+      .convert("Find the commit of the rebase operation failed")?;
+    let mut index = repo.inner.index().convert("Get the index failed")?;
+    if index.has_conflicts() {
+      return Ok(None);
+    }
+    let parent = repo
+      .inner
+      .head()
+      .and_then(|head| head.peel_to_commit())
+      .convert("Resolve HEAD failed")?;
+    let tree = index
+      .write_tree()
+      .and_then(|oid| repo.inner.find_tree(oid))
+      .convert("Write the index failed")?;
+    if tree.id() == parent.tree_id() {
+      return Ok(None);
+    }
+    let (message_encoding, message) = match message {
+      Some(message) => (None, Cow::Borrowed(message)),
+      None => (
+        current.message_encoding(),
+        String::from_utf8_lossy(current.message_bytes()),
+      ),
+    };
+    let oid = commit_id(
+      &repo.inner,
+      author.map_or(&current.author(), |author| author.as_ref()),
+      committer.as_ref(),
+      message_encoding,
+      &message,
+      &tree,
+      &[&parent],
+    )?;
+    Ok(Some(oid))
+  }
+}
@@ -1,9 +1,17 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use crate::error::IntoNapiError;
+use crate::blob::{Blob, BlobParent};
+use crate::commit::{Commit, CommitInner};
+use crate::error::{coded_error_with_properties, IntoNapiError, NotNullError};
+use crate::object::{GitObject, ObjectParent, ObjectType};
+use crate::stats::{HandleKind, OpenHandle};
+use crate::tag::{Tag, TagInner};
 use crate::tree::{Tree, TreeParent};
 
+/// The code of the error thrown when a reference moved since it was read.
+const MODIFIED: &str = "Modified";
+
 #[napi]
 pub struct Reference {
   pub(crate) inner:
@@ -23,6 +31,66 @@ pub enum ReferenceType {
   Unknown,
 }
 
+#[napi(string_enum = "lowercase")]
+/// The kind of change a ref update makes to a reference.
+pub enum RefUpdateKind {
+  /// The reference does not exist yet.
+  Create,
+  /// The reference exists and is moved to another target.
+  Update,
+  /// The reference is removed.
+  Delete,
+}
+
+#[napi(object)]
+/// A reference write about to be performed, as seen by the guard installed
+/// with `Repository.setRefUpdateGuard`.
+pub struct RefUpdate {
+  pub refname: String,
+  /// The current target of the reference, `null` if it does not exist yet.
+  pub old_oid: Option<String>,
+  /// The new target of the reference, `null` if it is deleted.
+  pub new_oid: Option<String>,
+  pub kind: RefUpdateKind,
+}
+
+impl RefUpdate {
+  pub(crate) fn new(refname: &str, old_oid: Option<git2::Oid>, new_oid: Option<git2::Oid>) -> Self {
+    let kind = match (old_oid, new_oid) {
+      (_, None) => RefUpdateKind::Delete,
+      (None, Some(_)) => RefUpdateKind::Create,
+      (Some(_), Some(_)) => RefUpdateKind::Update,
+    };
+    Self {
+      refname: refname.to_owned(),
+      old_oid: old_oid.map(|oid| oid.to_string()),
+      new_oid: new_oid.map(|oid| oid.to_string()),
+      kind,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct BranchRecoveryOptions {
+  /// The namespace the deleted references are kept in.
+  ///
+  /// Defaults to `refs/deleted`.
+  pub namespace: Option<String>,
+}
+
+#[napi(object)]
+/// A deleted reference kept by `Repository.enableBranchRecovery`.
+pub struct DeletedBranch {
+  /// The name of the reference keeping the deleted target, to pass to
+  /// `Repository.restoreDeletedBranch`.
+  pub entry: String,
+  /// The name of the deleted branch, or the full name of other deleted
+  /// references.
+  pub name: String,
+  pub oid: String,
+  pub deleted_at_ms: i64,
+}
+
 #[napi]
 impl Reference {
   #[napi]
@@ -127,10 +195,76 @@ impl Reference {
     env: Env,
     self_ref: napi::bindgen_prelude::Reference<Reference>,
   ) -> Result<Tree> {
+    let handle = OpenHandle::new(&self.inner.clone_owner(env)?.handles, HandleKind::Tree);
     Ok(Tree {
       inner: TreeParent::Reference(self_ref.share_with(env, |reference| {
         reference.inner.peel_to_tree().convert_without_message()
       })?),
+      _handle: Some(handle),
+    })
+  }
+
+  #[napi]
+  /// Peel a reference to a commit
+  ///
+  /// This method recursively peels the reference, through annotated tags,
+  /// until it reaches a commit.
+  pub fn peel_to_commit(&self, env: Env) -> Result<Commit> {
+    Ok(Commit {
+      inner: CommitInner::Repository(self.inner.clone_owner(env)?.share_with(env, |_| {
+        self
+          .inner
+          .peel_to_commit()
+          .convert("Peel reference to commit failed")
+      })?),
+    })
+  }
+
+  #[napi]
+  /// Peel a reference to a blob
+  ///
+  /// This method recursively peels the reference until it reaches
+  /// a blob.
+  pub fn peel_to_blob(&self, env: Env) -> Result<Blob> {
+    Ok(Blob {
+      inner: BlobParent::Repository(self.inner.clone_owner(env)?.share_with(env, |_| {
+        self
+          .inner
+          .peel_to_blob()
+          .convert("Peel reference to blob failed")
+      })?),
+    })
+  }
+
+  #[napi]
+  /// Peel a reference to an annotated tag
+  ///
+  /// This method recursively peels the reference until it reaches
+  /// a tag.
+  pub fn peel_to_tag(&self, env: Env) -> Result<Tag> {
+    Ok(Tag {
+      inner: TagInner::Repository(self.inner.clone_owner(env)?.share_with(env, |_| {
+        self
+          .inner
+          .peel_to_tag()
+          .convert("Peel reference to tag failed")
+      })?),
+    })
+  }
+
+  #[napi]
+  /// Peel a reference to an object of the given type
+  ///
+  /// This method recursively peels the reference until it reaches an object
+  /// of the given type, or any object which is not a tag for `Any`.
+  pub fn peel(&self, env: Env, kind: ObjectType) -> Result<GitObject> {
+    Ok(GitObject {
+      inner: ObjectParent::Repository(self.inner.clone_owner(env)?.share_with(env, |_| {
+        self
+          .inner
+          .peel(kind.into())
+          .convert("Peel reference failed")
+      })?),
     })
   }
 
@@ -159,6 +293,41 @@ impl Reference {
     Ok(Self { inner: shared })
   }
 
+  #[napi]
+  /// Delete an existing reference.
+  ///
+  /// This method works for both direct and symbolic references. The
+  /// reference will be immediately removed on disk.
+  ///
+  /// Deleting the branch checked out in the repository fails.
+  pub fn delete(&mut self, env: Env) -> Result<()> {
+    let repo = self.inner.clone_owner(env)?;
+    let name = self
+      .inner
+      .name()
+      .expect_not_null("Reference name is not valid utf-8".to_owned())?
+      .to_owned();
+    if self.inner.is_branch()
+      && repo
+        .inner
+        .head()
+        .ok()
+        .and_then(|head| head.name().map(str::to_owned))
+        == Some(name.clone())
+    {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Cannot delete branch [{name}] as it is the current HEAD of the repository"),
+      ));
+    }
+    repo.check_ref_update(&env, RefUpdate::new(&name, self.inner.target(), None))?;
+    repo.record_deletion(&env, &name, self.inner.target())?;
+    self
+      .inner
+      .delete()
+      .convert(format!("Delete reference [{name}] failed"))
+  }
+
   #[napi]
   /// Rename an existing reference.
   ///
@@ -166,16 +335,114 @@ impl Reference {
   ///
   /// If the force flag is not enabled, and there's already a reference with
   /// the given name, the renaming will fail.
+  ///
+  /// If `expectedTarget` is given, the reference is read again right before
+  /// renaming it, and the rename fails with a `Modified` error if it no longer
+  /// points to `expectedTarget`. The error carries the current target of the
+  /// reference in its `actual` property.
   pub fn rename(
     &mut self,
     env: Env,
     new_name: String,
     force: bool,
     msg: String,
-  ) -> Result<Reference> {
+    expected_target: Option<String>,
+  ) -> Result<ReferenceRename> {
+    let repo = self.inner.clone_owner(env)?;
+    let old_name = self
+      .inner
+      .name()
+      .expect_not_null("Reference name is not valid utf-8".to_owned())?
+      .to_owned();
+    let current = repo
+      .inner
+      .find_reference(&old_name)
+      .convert(format!("Find reference [{old_name}] failed"))?;
+    let target = current.target();
+    if let Some(expected) = expected_target {
+      let expected = git2::Oid::from_str(&expected).convert(format!("Invalid oid [{expected}]"))?;
+      if target != Some(expected) {
+        let actual = target.map(|oid| oid.to_string()).unwrap_or_default();
+        return Err(coded_error_with_properties(
+          &env,
+          MODIFIED,
+          format!("Reference [{old_name}] points to [{actual}], expected [{expected}]"),
+          &[("actual", &actual)],
+        ));
+      }
+    }
+    if repo.ref_update_guard.is_some() {
+      let existing = repo.inner.refname_to_id(&new_name).ok();
+      repo.check_ref_update(&env, RefUpdate::new(&old_name, target, None))?;
+      repo.check_ref_update(&env, RefUpdate::new(&new_name, existing, target))?;
+    }
     let inner = self.inner.clone(env)?.share_with(env, |r| {
       r.rename(&new_name, force, &msg).convert_without_message()
     })?;
-    Ok(Self { inner })
+    Ok(ReferenceRename {
+      reference: Self { inner },
+      old_name,
+      new_name,
+      target: target.map(|oid| oid.to_string()),
+    })
+  }
+}
+
+#[napi(object, object_from_js = false)]
+/// The outcome of `Reference.rename`, for audit logs.
+pub struct ReferenceRename {
+  /// The renamed reference.
+  pub reference: Reference,
+  pub old_name: String,
+  pub new_name: String,
+  /// The object the reference pointed to when it was renamed.
+  ///
+  /// Not set for symbolic references.
+  pub target: Option<String>,
+}
+
+#[napi(iterator)]
+/// An iterator over the references of a repository.
+pub struct References {
+  pub(crate) inner: SharedReference<crate::repo::Repository, git2::References<'static>>,
+  pub(crate) env: Env,
+}
+
+#[napi]
+impl Generator for References {
+  type Yield = Reference;
+  type Return = ();
+  type Next = ();
+
+  fn next(&mut self, _value: Option<Self::Next>) -> Option<Self::Yield> {
+    let reference = self.inner.next()?.ok()?;
+    Some(Reference {
+      inner: self
+        .inner
+        .clone(self.env)
+        .and_then(|references| references.share_with(self.env, |_| Ok(reference)))
+        .ok()?,
+    })
+  }
+}
+
+#[napi(iterator)]
+/// An iterator over the names of the references of a repository.
+pub struct ReferenceNames {
+  pub(crate) inner: SharedReference<crate::repo::Repository, git2::References<'static>>,
+}
+
+#[napi]
+impl Generator for ReferenceNames {
+  type Yield = String;
+  type Return = ();
+  type Next = ();
+
+  fn next(&mut self, _value: Option<Self::Next>) -> Option<Self::Yield> {
+    self
+      .inner
+      .next()?
+      .ok()
+      .map(|reference| String::from_utf8_lossy(reference.name_bytes()).into_owned())
   }
 }
@@ -1,21 +1,65 @@
-use std::path::PathBuf;
-use std::sync::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use napi::{bindgen_prelude::*, JsString};
+use napi::{bindgen_prelude::*, JsObject, JsString};
 use napi_derive::napi;
 use once_cell::sync::Lazy;
 
-use crate::commit::{Commit, CommitInner};
-use crate::diff::Diff;
-use crate::error::{IntoNapiError, NotNullError};
-use crate::object::{GitObject, ObjectParent};
-use crate::reference;
-use crate::remote::Remote;
-use crate::rev_walk::RevWalk;
-use crate::signature::Signature;
-use crate::tag::Tag;
+use crate::annotated_commit::AnnotatedCommit;
+use crate::apply::{ApplyLocation, ApplyOptions};
+use crate::binary::{BinaryDetection, BinaryDetectionOptions};
+use crate::blob::{Blob, BlobParent, BlobWriter};
+use crate::branch::{Branch, BranchType};
+use crate::checkout::CheckoutOptions;
+use crate::cherry_pick::{
+  CherryPickRangeOptions, CherryPickRangeResult, CherryPickStop, CherryPickedCommit,
+};
+use crate::commit::{Commit, CommitInner, CommitSummary};
+use crate::commit_signature::SignatureSummary;
+use crate::config::Config;
+use crate::conventional_commit::{
+  validate_commit_message, CommitMessageValidation, ConventionalCommit, ConventionalCommitsOptions,
+  ValidateCommitMessageOptions,
+};
+use crate::describe::{DescribeOptions, DescribeResult};
+use crate::diff::{Diff, DiffOptions};
+use crate::error::{coded_error, IntoNapiError, NotNullError};
+use crate::index::Index;
+use crate::mailmap::Mailmap;
+use crate::maintenance::{MaintenanceReportOptions, MaintenanceReportTask};
+use crate::merge::{
+  apply_merge_attributes, MergeFileInput, MergeFileOptions, MergeFileResult, MergeOptions,
+  MergeSides,
+};
+use crate::note::{self, Note, Notes};
+use crate::object::{GitObject, ObjectParent, ObjectType};
+use crate::odb::{Odb, RawObject};
+use crate::pack_analysis::PackAnalysisTask;
+use crate::pack_builder::PackBuilder;
+use crate::pinned_view::PinnedView;
+use crate::rebase::{Rebase, RebaseOptions};
+use crate::reference::{self, BranchRecoveryOptions, DeletedBranch, RefUpdate};
+use crate::remote::{FetchHeadEntry, FetchOptions, Remote, RemoteFetchResult};
+use crate::repo_builder::{CloneTask, RepoBuilder};
+use crate::resumable_fetch::{
+  new_token, staging_namespace, staging_refspec, FetchIntoOdbOptions, FetchedRef, ResumableFetch,
+};
+use crate::rev_walk::{LogOptions, LogTask, RevWalk};
+use crate::safe_path;
+use crate::signature::{Signature, SignatureInner};
+use crate::stats::{HandleCounts, HandleKind, OpenHandle, OpenHandles};
+use crate::status::{self, DirectoryStatus, StatusByDirectoryOptions};
+use crate::subtree_split::{SubtreeSplitOptions, SubtreeSplitTask};
+use crate::tag::{Tag, TagInner, TagListEntry, TagListOptions};
+use crate::timeline::{self, TimelineEvent, TimelineOptions, TimelineTask};
+use crate::transport::TransportInfo;
 use crate::tree::{Tree, TreeEntry, TreeParent};
-use crate::util::path_to_javascript_string;
+use crate::tree_builder::TreeBuilder;
+use crate::util::{path_to_javascript_string, CancellationToken};
+use crate::worktree::{Worktree, WorktreeAddOptions};
 
 static INIT_GIT_CONFIG: Lazy<Result<()>> = Lazy::new(|| {
   // Handle the `failed to stat '/root/.gitconfig'; class=Config (7)` Error
@@ -40,6 +84,24 @@ static INIT_GIT_CONFIG: Lazy<Result<()>> = Lazy::new(|| {
   Ok(())
 });
 
+/// The error code of operations which need the upstream branch of a branch
+/// which has none configured.
+const NO_UPSTREAM: &str = "NoUpstream";
+
+/// The error code of operations which need the commit of `HEAD` in a
+/// repository whose current branch has no commit yet.
+const UNBORN_HEAD: &str = "UnbornHead";
+
+/// The error code of `stageHunks` and `unstageHunks` when the file changed
+/// since its hunks were listed.
+const STALE_HUNKS: &str = "StaleHunks";
+
+/// The namespace deleted references are kept in by `enableBranchRecovery`.
+const DEFAULT_RECOVERY_NAMESPACE: &str = "refs/deleted";
+
+/// Symbolic references followed when resolving the reference a commit updates.
+const MAX_SYMREF_DEPTH: usize = 5;
+
 #[napi]
 pub enum RepositoryState {
   Clean,
@@ -104,6 +166,7 @@ impl From<RepositoryOpenFlags> for git2::RepositoryOpenFlags {
 pub struct GitDateTask {
   repo: RwLock<napi::bindgen_prelude::Reference<Repository>>,
   filepath: String,
+  cancellation: CancellationToken,
 }
 
 unsafe impl Send for GitDateTask {}
@@ -114,28 +177,108 @@ impl Task for GitDateTask {
   type JsValue = i64;
 
   fn compute(&mut self) -> napi::Result<Self::Output> {
-    get_file_modified_date(
-      &(**self
+    let date = get_file_modified_date(
+      &self
         .repo
         .read()
-        .map_err(|err| napi::Error::new(Status::GenericFailure, format!("{err}")))?)
-      .inner,
+        .map_err(|err| napi::Error::new(Status::GenericFailure, format!("{err}")))?
+        .inner,
       &self.filepath,
-    )
-    .convert_without_message()
-    .and_then(|value| {
+      &self.cancellation,
+    );
+    self.cancellation.check()?;
+    date.convert_without_message().and_then(|value| {
       value.expect_not_null(format!("Failed to get commit for [{}]", &self.filepath))
     })
   }
 
-  fn resolve(&mut self, _env: napi::Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+  fn resolve(&mut self, env: napi::Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    self.cancellation.detach(env)?;
+    Ok(output)
+  }
+
+  fn reject(&mut self, env: napi::Env, err: napi::Error) -> napi::Result<Self::JsValue> {
+    self.cancellation.reject(env, err)
+  }
+}
+
+pub struct GitDateBatchTask {
+  repo: RwLock<napi::bindgen_prelude::Reference<Repository>>,
+  filepaths: Vec<String>,
+  cancellation: CancellationToken,
+}
+
+unsafe impl Send for GitDateBatchTask {}
+
+#[napi]
+impl Task for GitDateBatchTask {
+  type Output = Vec<Option<i64>>;
+  type JsValue = Vec<Option<i64>>;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    let dates = get_files_modified_date(
+      &self
+        .repo
+        .read()
+        .map_err(|err| napi::Error::new(Status::GenericFailure, format!("{err}")))?
+        .inner,
+      &self.filepaths,
+      &self.cancellation,
+    );
+    self.cancellation.check()?;
+    dates.convert_without_message()
+  }
+
+  fn resolve(&mut self, env: napi::Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    self.cancellation.detach(env)?;
     Ok(output)
   }
+
+  fn reject(&mut self, env: napi::Env, err: napi::Error) -> napi::Result<Self::JsValue> {
+    self.cancellation.reject(env, err)
+  }
+}
+
+/// Whether libgit2 was asked to fsync the files it writes in the git
+/// directories, which it does not report back.
+static FSYNC_GITDIR: AtomicBool = AtomicBool::new(false);
+
+#[napi(object)]
+pub struct FsyncPolicyOptions {
+  /// Fsync the loose objects written to this repository, through
+  /// `core.fsyncObjectFiles`.
+  ///
+  /// libgit2 reads it when the object database is loaded, so it applies to
+  /// the repositories opened afterwards.
+  pub objects: Option<bool>,
+  /// Fsync the references written to any repository.
+  ///
+  /// libgit2 only has a process-global switch for it, so this affects all the
+  /// repositories of the process.
+  pub refs: Option<bool>,
+  /// Fsync the index of this repository when writing the one returned by
+  /// `Repository.index`.
+  pub index: Option<bool>,
+}
+
+#[napi(object)]
+/// The effective fsync settings of a repository.
+pub struct FsyncPolicy {
+  pub objects: bool,
+  pub refs: bool,
+  pub index: bool,
 }
 
 #[napi]
 pub struct Repository {
   pub(crate) inner: git2::Repository,
+  pub(crate) ref_update_guard: Option<FunctionRef<RefUpdate, Either<bool, String>>>,
+  pub(crate) binary_detection: Option<BinaryDetection>,
+  /// The namespace the targets of deleted references are copied into, see
+  /// `enableBranchRecovery`.
+  pub(crate) branch_recovery: Option<String>,
+  /// The number of live wrappers handed out, see `openHandles`.
+  pub(crate) handles: Arc<HandleCounts>,
 }
 
 #[napi]
@@ -150,6 +293,10 @@ impl Repository {
           format!("Failed to open git repo: [{p}], reason: {err}",),
         )
       })?,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
     })
   }
 
@@ -189,6 +336,10 @@ impl Repository {
     Ok(Self {
       inner: git2::Repository::open_ext(path, flags.into(), ceiling_dirs)
         .convert("Failed to open git repo")?,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
     })
   }
 
@@ -202,6 +353,10 @@ impl Repository {
     Ok(Self {
       inner: git2::Repository::discover(&path)
         .convert(format!("Discover git repo from [{path}] failed"))?,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
     })
   }
 
@@ -212,6 +367,10 @@ impl Repository {
   pub fn init_bare(path: String) -> Result<Self> {
     Ok(Self {
       inner: git2::Repository::init_bare(path).convert("Failed to init bare repo")?,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
     })
   }
 
@@ -223,9 +382,31 @@ impl Repository {
   pub fn clone(url: String, path: String) -> Result<Self> {
     Ok(Self {
       inner: git2::Repository::clone(&url, path).convert("Failed to clone repo")?,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
     })
   }
 
+  #[napi(
+    ts_args_type = "url: string, path: string, signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<Repository>"
+  )]
+  /// Clone a remote repository on the libuv thread pool.
+  ///
+  /// Aborting the `signal` interrupts the transfer and rejects the returned
+  /// `Promise` with an `Aborted` error.
+  pub fn clone_async(
+    env: Env,
+    url: String,
+    path: String,
+    signal: Option<JsObject>,
+  ) -> Result<AsyncTask<CloneTask>> {
+    let builder = RepoBuilder::into_reference(RepoBuilder::new(), env)?;
+    builder.clone_async(env, builder.clone(env)?, url, path, signal)
+  }
+
   #[napi(factory)]
   /// Clone a remote repository, initialize and update its submodules
   /// recursively.
@@ -235,6 +416,10 @@ impl Repository {
     Ok(Self {
       inner: git2::Repository::clone_recurse(&url, path)
         .convert("Failed to clone repo recursively")?,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
     })
   }
 
@@ -251,6 +436,10 @@ impl Repository {
           format!("Failed to open git repo: [{git_dir}], reason: {err}",),
         )
       })?,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
     })
   }
 
@@ -268,611 +457,3303 @@ impl Repository {
   }
 
   #[napi]
-  /// Tests whether this repository is a shallow clone.
-  pub fn is_shallow(&self) -> Result<bool> {
-    Ok(self.inner.is_shallow())
+  /// Get the commit `HEAD` points to.
+  ///
+  /// Throws an error with the `UnbornHead` code if the current branch has no
+  /// commit yet.
+  pub fn head_commit(&self, self_ref: Reference<Repository>, env: Env) -> Result<Commit> {
+    Ok(Commit {
+      inner: CommitInner::Repository(
+        self_ref.share_with(env, |repo| repo.resolve_head_commit(&env))?,
+      ),
+    })
   }
 
   #[napi]
-  /// Tests whether this repository is empty.
-  pub fn is_empty(&self) -> Result<bool> {
-    self.inner.is_empty().convert_without_message()
+  /// Get the tree of the commit `HEAD` points to.
+  ///
+  /// Throws an error with the `UnbornHead` code if the current branch has no
+  /// commit yet.
+  pub fn head_tree(&self, self_ref: Reference<Repository>, env: Env) -> Result<Tree> {
+    Ok(Tree {
+      inner: TreeParent::Repository(self_ref.share_with(env, |repo| {
+        repo
+          .resolve_head_commit(&env)?
+          .tree()
+          .convert("Get the tree of HEAD failed")
+      })?),
+      _handle: Some(OpenHandle::new(&self.handles, HandleKind::Tree)),
+    })
   }
 
   #[napi]
-  /// Tests whether this repository is a worktree.
-  pub fn is_worktree(&self) -> Result<bool> {
-    Ok(self.inner.is_worktree())
+  /// Pin a read-only view of the repository at the commit of `rev`, `HEAD` if
+  /// not set, resolved once so that the reads of the view stay consistent
+  /// while references move.
+  pub fn pinned_view(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    rev: Option<String>,
+  ) -> Result<PinnedView> {
+    let oid = match &rev {
+      Some(rev) => self
+        .inner
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .convert(format!("Resolve [{rev}] to a commit failed"))?,
+      None => self.resolve_head_commit(&env)?,
+    }
+    .id();
+    Ok(PinnedView {
+      repo: self_ref,
+      oid,
+    })
   }
 
   #[napi]
-  /// Returns the path to the `.git` folder for normal repositories or the
-  /// repository itself for bare repositories.
-  pub fn path(&self, env: Env) -> Result<JsString> {
-    path_to_javascript_string(&env, self.inner.path())
+  /// Create a new direct reference.
+  ///
+  /// This function will return an error if a reference already exists with
+  /// the given name unless force is true, in which case it will be
+  /// overwritten.
+  pub fn create_reference(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    name: String,
+    oid: String,
+    force: bool,
+    log_message: String,
+  ) -> Result<reference::Reference> {
+    let oid = git2::Oid::from_str(&oid).convert(format!("Invalid OID [{oid}]"))?;
+    if self.ref_update_guard.is_some() {
+      let existing = self.inner.refname_to_id(&name).ok();
+      self.check_ref_update(&env, RefUpdate::new(&name, existing, Some(oid)))?;
+    }
+    Ok(reference::Reference {
+      inner: self_ref.share_with(env, |repo| {
+        repo
+          .inner
+          .reference(&name, oid, force, &log_message)
+          .convert(format!("Create reference [{name}] failed"))
+      })?,
+    })
   }
 
   #[napi]
-  /// Returns the current state of this repository
-  pub fn state(&self) -> Result<RepositoryState> {
-    Ok(self.inner.state().into())
+  /// Create a new symbolic reference.
+  ///
+  /// This function will return an error if a reference already exists with
+  /// the given name unless force is true, in which case it will be
+  /// overwritten.
+  pub fn create_symbolic_reference(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    name: String,
+    target: String,
+    force: bool,
+    log_message: String,
+  ) -> Result<reference::Reference> {
+    if self.ref_update_guard.is_some() {
+      let existing = self.inner.refname_to_id(&name).ok();
+      let new = self.inner.refname_to_id(&target).ok();
+      self.check_ref_update(&env, RefUpdate::new(&name, existing, new))?;
+    }
+    Ok(reference::Reference {
+      inner: self_ref.share_with(env, |repo| {
+        repo
+          .inner
+          .reference_symbolic(&name, &target, force, &log_message)
+          .convert(format!("Create symbolic reference [{name}] failed"))
+      })?,
+    })
   }
 
   #[napi]
-  /// Get the path of the working directory for this repository.
-  ///
-  /// If this repository is bare, then `None` is returned.
-  pub fn workdir(&self, env: Env) -> Option<JsString> {
-    self
-      .inner
-      .workdir()
-      .and_then(|path| path_to_javascript_string(&env, path).ok())
+  /// Create an iterator over all of the references in this repository.
+  pub fn references(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+  ) -> Result<reference::References> {
+    Ok(reference::References {
+      inner: self_ref.share_with(env, |repo| {
+        repo.inner.references().convert("List references failed")
+      })?,
+      env,
+    })
   }
 
   #[napi]
-  /// Set the path to the working directory for this repository.
-  ///
-  /// If `update_link` is true, create/update the gitlink file in the workdir
-  /// and set config "core.worktree" (if workdir is not the parent of the .git
-  /// directory).
-  pub fn set_workdir(&self, path: String, update_gitlink: bool) -> Result<()> {
-    self
-      .inner
-      .set_workdir(PathBuf::from(path).as_path(), update_gitlink)
-      .convert_without_message()?;
-    Ok(())
+  /// Create an iterator over the references in this repository whose names
+  /// match the given glob pattern, e.g. `refs/tags/*`.
+  pub fn references_glob(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    pattern: String,
+  ) -> Result<reference::References> {
+    Ok(reference::References {
+      inner: self_ref.share_with(env, |repo| {
+        repo
+          .inner
+          .references_glob(&pattern)
+          .convert(format!("List references matching [{pattern}] failed"))
+      })?,
+      env,
+    })
   }
 
   #[napi]
-  /// Get the currently active namespace for this repository.
+  /// Create an iterator over the names of the references in this repository,
+  /// optionally only those matching the given glob pattern.
   ///
-  /// If there is no namespace, or the namespace is not a valid utf8 string,
-  /// `None` is returned.
-  pub fn namespace(&self) -> Option<String> {
-    self.inner.namespace().map(|n| n.to_owned())
+  /// Cheaper than `references` when only the names are needed.
+  pub fn reference_names(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    pattern: Option<String>,
+  ) -> Result<reference::ReferenceNames> {
+    Ok(reference::ReferenceNames {
+      inner: self_ref.share_with(env, |repo| match &pattern {
+        Some(pattern) => repo
+          .inner
+          .references_glob(pattern)
+          .convert(format!("List references matching [{pattern}] failed")),
+        None => repo.inner.references().convert("List references failed"),
+      })?,
+    })
   }
 
   #[napi]
-  /// Set the active namespace for this repository.
-  pub fn set_namespace(&self, namespace: String) -> Result<()> {
-    self
+  /// List the names of the references in this repository matching the given
+  /// glob pattern, e.g. `refs/remotes/origin/*`.
+  ///
+  /// Unlike `referenceNames`, the names are collected up front.
+  pub fn reference_names_glob(&self, glob: String) -> Result<Vec<String>> {
+    let mut references = self
       .inner
-      .set_namespace(&namespace)
-      .convert_without_message()?;
-    Ok(())
-  }
-
-  #[napi]
-  /// Remove the active namespace for this repository.
-  pub fn remove_namespace(&self) -> Result<()> {
-    self.inner.remove_namespace().convert_without_message()?;
-    Ok(())
+      .references_glob(&glob)
+      .convert(format!("List references matching [{glob}] failed"))?;
+    references
+      .names()
+      .map(|name| name.map(ToOwned::to_owned))
+      .collect::<std::result::Result<Vec<_>, _>>()
+      .convert(format!("List references matching [{glob}] failed"))
   }
 
   #[napi]
-  /// Retrieves the Git merge message.
-  /// Remember to remove the message when finished.
-  pub fn message(&self) -> Result<String> {
-    self
-      .inner
-      .message()
-      .convert("Failed to get Git merge message")
+  /// Lookup a branch by its name in a repository.
+  pub fn find_branch(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    name: String,
+    branch_type: BranchType,
+  ) -> Option<Branch> {
+    Some(Branch {
+      inner: self_ref
+        .share_with(env, |repo| {
+          repo
+            .inner
+            .find_branch(&name, branch_type.into())
+            .convert(format!("Find branch [{name}] failed"))
+        })
+        .ok()?,
+    })
   }
 
   #[napi]
-  /// Remove the Git merge message.
-  pub fn remove_message(&self) -> Result<()> {
-    self
-      .inner
-      .remove_message()
-      .convert("Remove the Git merge message failed")
+  /// Retrieve the name of the upstream branch of a local branch, e.g.
+  /// `refs/remotes/origin/main` for `refs/heads/main`.
+  ///
+  /// Returns `null` if the branch has no upstream branch, or if `refname` is
+  /// `HEAD` and HEAD is detached.
+  pub fn branch_upstream_name(&self, refname: String) -> Result<Option<String>> {
+    let Some(refname) = self.resolve_branch_refname(refname) else {
+      return Ok(None);
+    };
+    not_found_as_none(self.inner.branch_upstream_name(&refname))
+      .map(|name| name.and_then(|name| name.as_str().map(ToOwned::to_owned)))
+      .convert(format!("Find the upstream branch of [{refname}] failed"))
   }
 
   #[napi]
-  /// List all remotes for a given repository
-  pub fn remotes(&self) -> Result<Vec<String>> {
-    self
-      .inner
-      .remotes()
-      .map(|remotes| {
-        remotes
-          .into_iter()
-          .flatten()
-          .map(|name| name.to_owned())
-          .collect()
-      })
-      .convert("Fetch remotes failed")
+  /// Retrieve the name of the remote of a remote-tracking branch, e.g.
+  /// `origin` for `refs/remotes/origin/main`.
+  ///
+  /// Returns `null` if `refname` is not a remote-tracking branch.
+  pub fn branch_remote_name(&self, refname: String) -> Result<Option<String>> {
+    if !refname.starts_with("refs/remotes/") {
+      return Ok(None);
+    }
+    not_found_as_none(self.inner.branch_remote_name(&refname))
+      .map(|name| name.and_then(|name| name.as_str().map(ToOwned::to_owned)))
+      .convert(format!("Find the remote of [{refname}] failed"))
   }
 
   #[napi]
-  /// Get the information for a particular remote
-  pub fn find_remote(
+  /// Lookup a reference by its full name, e.g. `refs/heads/main`.
+  pub fn find_reference(
     &self,
-    self_ref: Reference<Repository>,
     env: Env,
+    self_ref: Reference<Repository>,
     name: String,
-  ) -> Option<Remote> {
-    Some(Remote {
+  ) -> Option<reference::Reference> {
+    Some(reference::Reference {
       inner: self_ref
-        .share_with(env, move |repo| {
+        .share_with(env, |repo| {
           repo
             .inner
-            .find_remote(&name)
-            .convert(format!("Failed to get remote [{}]", &name))
+            .find_reference(&name)
+            .convert(format!("Find reference [{name}] failed"))
         })
         .ok()?,
     })
   }
 
   #[napi]
-  /// Add a remote with the default fetch refspec to the repository's
-  /// configuration.
-  pub fn remote(
-    &mut self,
+  /// Lookup a reference by its shorthand name, e.g. `main` for
+  /// `refs/heads/main`, following the precedence rules of git.
+  pub fn resolve_reference_from_short_name(
+    &self,
     env: Env,
-    this: Reference<Repository>,
-    name: String,
-    url: String,
-  ) -> Result<Remote> {
-    Ok(Remote {
-      inner: this.share_with(env, move |repo| {
+    self_ref: Reference<Repository>,
+    shorthand: String,
+  ) -> Result<reference::Reference> {
+    Ok(reference::Reference {
+      inner: self_ref.share_with(env, |repo| {
         repo
           .inner
-          .remote(&name, &url)
-          .convert(format!("Failed to add remote [{}]", &name))
+          .resolve_reference_from_short_name(&shorthand)
+          .convert(format!("Resolve reference [{shorthand}] failed"))
       })?,
     })
   }
 
   #[napi]
-  /// Add a remote with the provided fetch refspec to the repository's
-  /// configuration.
-  pub fn remote_with_fetch(
-    &mut self,
+  /// Count the changed files of the working directory and the index by
+  /// directory, e.g. to decorate the folders of a file tree.
+  ///
+  /// Untracked files are counted but ignored files are not, and renames are
+  /// not detected.
+  pub fn status_by_directory(
+    &self,
+    options: Option<StatusByDirectoryOptions>,
+  ) -> Result<Vec<DirectoryStatus>> {
+    let (depth, pathspec) = options
+      .map(|options| (options.depth, options.pathspec))
+      .unwrap_or_default();
+    let mut status_options = git2::StatusOptions::new();
+    status_options
+      .include_untracked(true)
+      .recurse_untracked_dirs(true)
+      .include_ignored(false);
+    for pathspec in pathspec.iter().flatten() {
+      status_options.pathspec(pathspec);
+    }
+    let statuses = self
+      .inner
+      .statuses(Some(&mut status_options))
+      .convert("Read statuses failed")?;
+    Ok(status::aggregate(&statuses, depth))
+  }
+
+  #[napi]
+  /// Initializes a rebase operation to rebase the changes in `branch` relative
+  /// to `upstream` onto another branch.
+  ///
+  /// The branches are revspecs, usually branch names. `branch` defaults to
+  /// `HEAD`, all the commits reachable from `branch` are rebased when
+  /// `upstream` is not given, and `onto` defaults to `upstream`.
+  pub fn rebase_init(
+    &self,
     env: Env,
-    this: Reference<Repository>,
-    name: String,
-    url: String,
-    refspect: String,
-  ) -> Result<Remote> {
-    Ok(Remote {
-      inner: this.share_with(env, move |repo| {
+    self_ref: Reference<Repository>,
+    branch: Option<String>,
+    upstream: Option<String>,
+    onto: Option<String>,
+    options: Option<RebaseOptions>,
+  ) -> Result<Rebase> {
+    let branch = branch
+      .map(|branch| self.annotated_commit(&branch))
+      .transpose()?;
+    let upstream = upstream
+      .map(|upstream| self.annotated_commit(&upstream))
+      .transpose()?;
+    let onto = onto.map(|onto| self.annotated_commit(&onto)).transpose()?;
+    let options = options.unwrap_or_default();
+    let inmemory = options.inmemory.unwrap_or(false);
+    let mut options = options.to_git2();
+    Ok(Rebase {
+      inner: self_ref.share_with(env, |repo| {
         repo
           .inner
-          .remote_with_fetch(&name, &url, &refspect)
-          .convert("Failed to add remote")
+          .rebase(
+            branch.as_ref(),
+            upstream.as_ref(),
+            onto.as_ref(),
+            Some(&mut options),
+          )
+          .convert("Initialize rebase failed")
       })?,
+      inmemory,
     })
   }
 
   #[napi]
-  /// Create an anonymous remote
-  ///
-  /// Create a remote with the given URL and refspec in memory. You can use
-  /// this when you have a URL instead of a remote's name. Note that anonymous
-  /// remotes cannot be converted to persisted remotes.
-  pub fn remote_anonymous(
+  /// Opens an existing rebase that was previously started by either an
+  /// invocation of `rebaseInit` or by another client.
+  pub fn rebase_open(
     &self,
     env: Env,
-    this: Reference<Repository>,
-    url: String,
-  ) -> Result<Remote> {
-    Ok(Remote {
-      inner: this.share_with(env, move |repo| {
+    self_ref: Reference<Repository>,
+    options: Option<RebaseOptions>,
+  ) -> Result<Rebase> {
+    let mut options = options.unwrap_or_default().to_git2();
+    Ok(Rebase {
+      inner: self_ref.share_with(env, |repo| {
         repo
           .inner
-          .remote_anonymous(&url)
-          .convert("Failed to create anonymous remote")
+          .open_rebase(Some(&mut options))
+          .convert("Open rebase failed")
       })?,
+      inmemory: false,
     })
   }
 
   #[napi]
-  /// Give a remote a new name
+  /// Tests whether this repository is a shallow clone.
+  pub fn is_shallow(&self) -> Result<bool> {
+    Ok(self.inner.is_shallow())
+  }
+
+  #[napi]
+  /// Fetch the whole history of a shallow repository from `remoteName` with
+  /// its configured refspecs, like `git fetch --unshallow`.
   ///
-  /// All remote-tracking branches and configuration settings for the remote
-  /// are updated.
+  /// The `depth` of `fetchOptions` is ignored.
+  pub fn unshallow(&self, remote_name: String, fetch_options: Option<&FetchOptions>) -> Result<()> {
+    let mut remote = self
+      .inner
+      .find_remote(&remote_name)
+      .convert(format!("Failed to get remote [{remote_name}]"))?;
+    let mut fetch_options = fetch_options.cloned().unwrap_or_else(FetchOptions::new);
+    fetch_options.unshallow();
+    let (mut options, callback_error) = fetch_options.build(remote.url());
+    callback_error.check(
+      remote
+        .fetch::<&str>(&[], Some(&mut options), None)
+        .convert(format!("Unshallow from remote [{remote_name}] failed")),
+    )
+  }
+
+  #[napi]
+  /// Fetch `refspecs` from `url` into the object database without updating
+  /// the references they name, so that an interrupted fetch can be resumed.
   ///
-  /// A temporary in-memory remote cannot be given a name with this method.
+  /// Each refspec is fetched in its own pack, and its tips are kept in a
+  /// staging namespace of the repository. Resuming with the returned
+  /// `resumeToken` skips the refspecs whose tips were fetched already, and
+  /// negotiates the fetched tips as haves for the others. Pass the complete result to `finalizeFetched` to write the
+  /// references.
   ///
-  /// No loaded instances of the remote with the old name will change their
-  /// name or their list of refspecs.
+  /// Tags are not followed automatically.
+  pub fn fetch_into_odb(
+    &self,
+    url: String,
+    refspecs: Vec<String>,
+    options: Option<FetchIntoOdbOptions>,
+    fetch_options: Option<&FetchOptions>,
+  ) -> Result<ResumableFetch> {
+    let token = options
+      .and_then(|options| options.resume_token)
+      .unwrap_or_else(new_token);
+    let namespace = staging_namespace(&token)?;
+    let mut remote = self
+      .inner
+      .remote_anonymous(&url)
+      .convert(format!("Create remote for [{url}] failed"))?;
+    let (callbacks, proxy, callback_error) = fetch_options
+      .map(|options| options.build_connect(Some(&url)))
+      .unwrap_or_default();
+    let heads = callback_error.check(
+      remote
+        .connect_auth(git2::Direction::Fetch, callbacks, proxy)
+        .and_then(|connection| {
+          Ok(
+            connection
+              .list()?
+              .iter()
+              .map(|head| (head.name().to_owned(), head.oid()))
+              .collect::<HashMap<_, _>>(),
+          )
+        })
+        .convert(format!("Connect to [{url}] failed")),
+    )?;
+    let mut received_objects = 0;
+    let mut error = None;
+    for refspec in &refspecs {
+      let staged = staging_refspec(&namespace, refspec)?;
+      // The tips fetched by a previous attempt are not fetched again
+      if let Some((src, dst)) = refspec.trim_start_matches('+').split_once(':') {
+        let fetched = self.inner.refname_to_id(&format!("{namespace}{dst}")).ok();
+        if fetched.is_some() && heads.get(src).copied() == fetched {
+          continue;
+        }
+      }
+      let (mut options, callback_error) = fetch_options
+        .map(|options| options.build(Some(&url)))
+        .unwrap_or_default();
+      options
+        .update_fetchhead(false)
+        .download_tags(git2::AutotagOption::None);
+      let result = callback_error.check(
+        remote
+          .fetch(&[staged], Some(&mut options), None)
+          .convert_without_message(),
+      );
+      received_objects += remote.stats().received_objects() as u32;
+      if let Err(err) = result {
+        error = Some(err.reason);
+        break;
+      }
+    }
+    let refs = self
+      .inner
+      .references_glob(&format!("{namespace}*"))
+      .convert("List staged references failed")?
+      .filter_map(|reference| {
+        let reference = reference.ok()?;
+        Some(FetchedRef {
+          refname: reference.name()?.strip_prefix(&namespace)?.to_owned(),
+          oid: reference.target()?.to_string(),
+        })
+      })
+      .collect();
+    Ok(ResumableFetch {
+      resume_token: token,
+      complete: error.is_none(),
+      refs,
+      received_objects,
+      error,
+      transport: TransportInfo::of_url(&url),
+    })
+  }
+
+  #[napi]
+  /// Write the references fetched by a complete `fetchIntoOdb`, and drop its
+  /// staging namespace.
+  pub fn finalize_fetched(&self, env: Env, fetch: ResumableFetch) -> Result<()> {
+    let namespace = staging_namespace(&fetch.resume_token)?;
+    if !fetch.complete {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Fetch [{}] is not complete", fetch.resume_token),
+      ));
+    }
+    let odb = self
+      .inner
+      .odb()
+      .convert("Get the object database of Repository failed")?;
+    for fetched in &fetch.refs {
+      let oid =
+        git2::Oid::from_str(&fetched.oid).convert(format!("Invalid OID [{}]", fetched.oid))?;
+      let staged = self
+        .inner
+        .refname_to_id(&format!("{namespace}{}", fetched.refname));
+      if staged.ok() != Some(oid) || !odb.exists(oid) {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!(
+            "[{}] was not fetched into [{}]",
+            fetched.oid, fetched.refname
+          ),
+        ));
+      }
+      let existing = self.inner.refname_to_id(&fetched.refname).ok();
+      self.check_ref_update(&env, RefUpdate::new(&fetched.refname, existing, Some(oid)))?;
+    }
+    for fetched in &fetch.refs {
+      let oid = git2::Oid::from_str(&fetched.oid).convert_without_message()?;
+      self
+        .inner
+        .reference(
+          &fetched.refname,
+          oid,
+          true,
+          "fetch: finalize fetched objects",
+        )
+        .convert(format!("Update reference [{}] failed", fetched.refname))?;
+    }
+    let staged = self
+      .inner
+      .references_glob(&format!("{namespace}*"))
+      .convert("List staged references failed")?
+      .collect::<std::result::Result<Vec<_>, _>>()
+      .convert("List staged references failed")?;
+    for reference in &staged {
+      let refname = reference
+        .name()
+        .expect_not_null("Reference name is not valid utf-8".to_owned())?;
+      self.check_ref_update(&env, RefUpdate::new(refname, reference.target(), None))?;
+    }
+    for mut reference in staged {
+      reference
+        .delete()
+        .convert("Delete staged reference failed")?;
+    }
+    Ok(())
+  }
+
+  #[napi]
+  /// Write `entries` to `FETCH_HEAD` in the format of git, replacing its
+  /// content, e.g. after fetching objects through a custom transport.
+  pub fn write_fetch_head(&self, entries: Vec<FetchHeadEntry>) -> Result<()> {
+    let mut content = String::new();
+    for entry in &entries {
+      git2::Oid::from_str(&entry.oid).convert(format!("Invalid oid [{}]", entry.oid))?;
+      content.push_str(&entry.to_line());
+    }
+    std::fs::write(self.inner.path().join("FETCH_HEAD"), content).map_err(|err| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Write FETCH_HEAD failed: {err}"),
+      )
+    })
+  }
+
+  #[napi]
+  /// Delete `FETCH_HEAD`, if it exists.
+  pub fn clear_fetch_head(&self) -> Result<()> {
+    match std::fs::remove_file(self.inner.path().join("FETCH_HEAD")) {
+      Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(Error::new(
+        Status::GenericFailure,
+        format!("Delete FETCH_HEAD failed: {err}"),
+      )),
+      _ => Ok(()),
+    }
+  }
+
+  #[napi]
+  /// Call `callback` with each entry of `FETCH_HEAD`, returning `false` from
+  /// it stops the iteration.
+  pub fn fetchhead_foreach(
+    &self,
+    env: Env,
+    #[napi(ts_arg_type = "(entry: FetchHeadEntry) => boolean | undefined | null")]
+    callback: FunctionRef<FetchHeadEntry, Option<bool>>,
+  ) -> Result<()> {
+    let mut callback_error = None;
+    let mut stopped = false;
+    let result = self
+      .inner
+      .fetchhead_foreach(|refname, remote_url, oid, is_merge| {
+        let entry = FetchHeadEntry {
+          oid: oid.to_string(),
+          is_merge,
+          refname: refname.to_owned(),
+          remote_url: String::from_utf8_lossy(remote_url).into_owned(),
+        };
+        match callback.borrow_back(&env).and_then(|cb| cb.call(entry)) {
+          Ok(next) => {
+            stopped = next == Some(false);
+            !stopped
+          }
+          Err(err) => {
+            callback_error = Some(err);
+            false
+          }
+        }
+      });
+    if let Some(err) = callback_error {
+      return Err(err);
+    }
+    match result {
+      Err(_) if stopped => Ok(()),
+      result => result.convert("Read FETCH_HEAD failed"),
+    }
+  }
+
+  #[napi]
+  /// Tests whether this repository is empty.
+  pub fn is_empty(&self) -> Result<bool> {
+    self.inner.is_empty().convert_without_message()
+  }
+
+  #[napi]
+  /// Tests whether this repository is a worktree.
+  pub fn is_worktree(&self) -> Result<bool> {
+    Ok(self.inner.is_worktree())
+  }
+
+  #[napi]
+  /// Returns the path to the `.git` folder for normal repositories or the
+  /// repository itself for bare repositories.
+  pub fn path(&self, env: Env) -> Result<JsString> {
+    path_to_javascript_string(&env, self.inner.path())
+  }
+
+  #[napi]
+  /// Returns the path to the index file of the repository.
+  pub fn index_path(&self, env: Env) -> Result<JsString> {
+    let index = self
+      .inner
+      .index()
+      .convert("Get the index of Repository failed")?;
+    let path = index
+      .path()
+      .expect_not_null("The index of Repository has no file".to_owned())?;
+    path_to_javascript_string(&env, path)
+  }
+
+  #[napi]
+  /// Returns the path to the common directory of the repository, shared by
+  /// its linked worktrees.
   ///
-  /// The returned array of strings is a list of the non-default refspecs
-  /// which cannot be renamed and are returned for further processing by the
-  /// caller.
-  pub fn remote_rename(&self, name: String, new_name: String) -> Result<Vec<String>> {
+  /// It is the same as `path` for a repository which is not a linked
+  /// worktree.
+  pub fn common_path(&self, env: Env) -> Result<JsString> {
+    path_to_javascript_string(&env, self.inner.commondir())
+  }
+
+  #[napi]
+  /// Returns the current state of this repository
+  pub fn state(&self) -> Result<RepositoryState> {
+    Ok(self.inner.state().into())
+  }
+
+  #[napi]
+  /// Get the path of the working directory for this repository.
+  ///
+  /// If this repository is bare, then `None` is returned.
+  pub fn workdir(&self, env: Env) -> Option<JsString> {
+    self
+      .inner
+      .workdir()
+      .and_then(|path| path_to_javascript_string(&env, path).ok())
+  }
+
+  #[napi]
+  /// Set the path to the working directory for this repository.
+  ///
+  /// If `update_link` is true, create/update the gitlink file in the workdir
+  /// and set config "core.worktree" (if workdir is not the parent of the .git
+  /// directory).
+  pub fn set_workdir(&self, path: String, update_gitlink: bool) -> Result<()> {
+    self
+      .inner
+      .set_workdir(PathBuf::from(path).as_path(), update_gitlink)
+      .convert_without_message()?;
+    Ok(())
+  }
+
+  #[napi]
+  /// Get the currently active namespace for this repository.
+  ///
+  /// If there is no namespace, or the namespace is not a valid utf8 string,
+  /// `None` is returned.
+  pub fn namespace(&self) -> Option<String> {
+    self.inner.namespace().map(|n| n.to_owned())
+  }
+
+  #[napi]
+  /// Set the active namespace for this repository.
+  pub fn set_namespace(&self, namespace: String) -> Result<()> {
+    self
+      .inner
+      .set_namespace(&namespace)
+      .convert_without_message()?;
+    Ok(())
+  }
+
+  #[napi]
+  /// Remove the active namespace for this repository.
+  pub fn remove_namespace(&self) -> Result<()> {
+    self.inner.remove_namespace().convert_without_message()?;
+    Ok(())
+  }
+
+  #[napi]
+  /// Retrieves the Git merge message.
+  /// Remember to remove the message when finished.
+  pub fn message(&self) -> Result<String> {
+    self
+      .inner
+      .message()
+      .convert("Failed to get Git merge message")
+  }
+
+  #[napi]
+  /// Remove the Git merge message.
+  pub fn remove_message(&self) -> Result<()> {
+    self
+      .inner
+      .remove_message()
+      .convert("Remove the Git merge message failed")
+  }
+
+  #[napi]
+  /// List all remotes for a given repository
+  pub fn remotes(&self) -> Result<Vec<String>> {
+    self
+      .inner
+      .remotes()
+      .map(|remotes| {
+        remotes
+          .into_iter()
+          .flatten()
+          .map(|name| name.to_owned())
+          .collect()
+      })
+      .convert("Fetch remotes failed")
+  }
+
+  #[napi(
+    ts_args_type = "fetchOptions?: ((remote: string) => FetchOptions) | undefined | null, prune?: boolean | undefined | null"
+  )]
+  /// Fetch every remote with its default refspecs, like `git fetch --all`.
+  ///
+  /// `fetchOptions` is called with the name of each remote to create the
+  /// options of its fetch, so every remote gets fresh callbacks. A remote
+  /// failing to fetch doesn't stop the others from being fetched, its
+  /// failure is reported in its result instead.
+  pub fn fetch_all(
+    &self,
+    fetch_options: Option<Function<String, ClassInstance<FetchOptions>>>,
+    prune: Option<bool>,
+  ) -> Result<Vec<RemoteFetchResult>> {
+    let fetch = |name: &str| -> Result<git2::Progress<'static>> {
+      let mut remote = self
+        .inner
+        .find_remote(name)
+        .convert(format!("Failed to get remote [{name}]"))?;
+      let (mut options, callback_error) = match &fetch_options {
+        Some(factory) => factory.call(name.to_owned())?.build(remote.url()),
+        None => Default::default(),
+      };
+      if let Some(prune) = prune {
+        options.prune(if prune {
+          git2::FetchPrune::On
+        } else {
+          git2::FetchPrune::Off
+        });
+      }
+      callback_error.check(
+        remote
+          .fetch::<&str>(&[], Some(&mut options), None)
+          .convert_without_message(),
+      )?;
+      Ok(remote.stats().to_owned())
+    };
     Ok(
       self
-        .inner
-        .remote_rename(&name, &new_name)
-        .convert(format!("Failed to rename remote [{}]", &name))?
+        .remotes()?
         .into_iter()
-        .flatten()
-        .map(|s| s.to_owned())
-        .collect::<Vec<_>>(),
+        .map(|name| {
+          let transport = self
+            .inner
+            .find_remote(&name)
+            .ok()
+            .and_then(|remote| remote.url().map(TransportInfo::of_url));
+          match fetch(&name) {
+            Ok(stats) => RemoteFetchResult {
+              remote: name,
+              ok: true,
+              error: None,
+              received_objects: stats.received_objects() as u32,
+              received_bytes: stats.received_bytes() as i64,
+              transport,
+            },
+            Err(err) => RemoteFetchResult {
+              remote: name,
+              ok: false,
+              error: Some(err.reason),
+              received_objects: 0,
+              received_bytes: 0,
+              transport,
+            },
+          }
+        })
+        .collect(),
     )
   }
 
   #[napi]
-  /// Delete an existing persisted remote.
-  ///
-  /// All remote-tracking branches and configuration settings for the remote
-  /// will be removed.
-  pub fn remote_delete(&self, name: String) -> Result<&Self> {
-    self.inner.remote_delete(&name).convert_without_message()?;
-    Ok(self)
+  /// Get the information for a particular remote
+  pub fn find_remote(
+    &self,
+    self_ref: Reference<Repository>,
+    env: Env,
+    name: String,
+  ) -> Option<Remote> {
+    Some(Remote {
+      inner: self_ref
+        .share_with(env, move |repo| {
+          repo
+            .inner
+            .find_remote(&name)
+            .convert(format!("Failed to get remote [{}]", &name))
+        })
+        .ok()?,
+      _handle: OpenHandle::new(&self.handles, HandleKind::Remote),
+    })
+  }
+
+  #[napi]
+  /// Add a remote with the default fetch refspec to the repository's
+  /// configuration.
+  pub fn remote(
+    &mut self,
+    env: Env,
+    this: Reference<Repository>,
+    name: String,
+    url: String,
+  ) -> Result<Remote> {
+    Ok(Remote {
+      inner: this.share_with(env, move |repo| {
+        repo
+          .inner
+          .remote(&name, &url)
+          .convert(format!("Failed to add remote [{}]", &name))
+      })?,
+      _handle: OpenHandle::new(&self.handles, HandleKind::Remote),
+    })
+  }
+
+  #[napi]
+  /// Add a remote with the provided fetch refspec to the repository's
+  /// configuration.
+  pub fn remote_with_fetch(
+    &mut self,
+    env: Env,
+    this: Reference<Repository>,
+    name: String,
+    url: String,
+    refspect: String,
+  ) -> Result<Remote> {
+    Ok(Remote {
+      inner: this.share_with(env, move |repo| {
+        repo
+          .inner
+          .remote_with_fetch(&name, &url, &refspect)
+          .convert("Failed to add remote")
+      })?,
+      _handle: OpenHandle::new(&self.handles, HandleKind::Remote),
+    })
+  }
+
+  #[napi]
+  /// Create an anonymous remote
+  ///
+  /// Create a remote with the given URL and refspec in memory. You can use
+  /// this when you have a URL instead of a remote's name. Note that anonymous
+  /// remotes cannot be converted to persisted remotes.
+  pub fn remote_anonymous(
+    &self,
+    env: Env,
+    this: Reference<Repository>,
+    url: String,
+  ) -> Result<Remote> {
+    Ok(Remote {
+      inner: this.share_with(env, move |repo| {
+        repo
+          .inner
+          .remote_anonymous(&url)
+          .convert("Failed to create anonymous remote")
+      })?,
+      _handle: OpenHandle::new(&self.handles, HandleKind::Remote),
+    })
+  }
+
+  #[napi]
+  /// Give a remote a new name
+  ///
+  /// All remote-tracking branches and configuration settings for the remote
+  /// are updated.
+  ///
+  /// A temporary in-memory remote cannot be given a name with this method.
+  ///
+  /// No loaded instances of the remote with the old name will change their
+  /// name or their list of refspecs.
+  ///
+  /// The returned array of strings is a list of the non-default refspecs
+  /// which cannot be renamed and are returned for further processing by the
+  /// caller.
+  pub fn remote_rename(&self, name: String, new_name: String) -> Result<Vec<String>> {
+    Ok(
+      self
+        .inner
+        .remote_rename(&name, &new_name)
+        .convert(format!("Failed to rename remote [{}]", &name))?
+        .into_iter()
+        .flatten()
+        .map(|s| s.to_owned())
+        .collect::<Vec<_>>(),
+    )
+  }
+
+  #[napi]
+  /// Delete an existing persisted remote.
+  ///
+  /// All remote-tracking branches and configuration settings for the remote
+  /// will be removed.
+  pub fn remote_delete(&self, name: String) -> Result<&Self> {
+    self.inner.remote_delete(&name).convert_without_message()?;
+    Ok(self)
+  }
+
+  #[napi]
+  /// Add a fetch refspec to the remote's configuration
+  ///
+  /// Add the given refspec to the fetch list in the configuration. No loaded
+  pub fn remote_add_fetch(&self, name: String, refspec: String) -> Result<&Self> {
+    self
+      .inner
+      .remote_add_fetch(&name, &refspec)
+      .convert_without_message()?;
+    Ok(self)
+  }
+
+  #[napi]
+  /// Add a push refspec to the remote's configuration.
+  ///
+  /// Add the given refspec to the push list in the configuration. No
+  /// loaded remote instances will be affected.
+  pub fn remote_add_push(&self, name: String, refspec: String) -> Result<&Self> {
+    self
+      .inner
+      .remote_add_push(&name, &refspec)
+      .convert_without_message()?;
+    Ok(self)
+  }
+
+  #[napi]
+  /// Add a push refspec to the remote's configuration.
+  ///
+  /// Add the given refspec to the push list in the configuration. No
+  /// loaded remote instances will be affected.
+  pub fn remote_set_url(&self, name: String, url: String) -> Result<&Self> {
+    self
+      .inner
+      .remote_set_url(&name, &url)
+      .convert_without_message()?;
+    Ok(self)
+  }
+
+  #[napi]
+  /// Set the remote's URL for pushing in the configuration.
+  ///
+  /// Remote objects already in memory will not be affected. This assumes
+  /// the common case of a single-url remote and will otherwise return an
+  /// error.
+  ///
+  /// `None` indicates that it should be cleared.
+  pub fn remote_set_pushurl(&self, name: String, url: Option<String>) -> Result<&Self> {
+    self
+      .inner
+      .remote_set_pushurl(&name, url.as_deref())
+      .convert_without_message()?;
+    Ok(self)
+  }
+
+  #[napi]
+  /// Lookup a reference to one of the objects in a repository.
+  pub fn find_tree(&self, oid: String, self_ref: Reference<Repository>, env: Env) -> Option<Tree> {
+    Some(Tree {
+      inner: TreeParent::Repository(
+        self_ref
+          .share_with(env, |repo| {
+            repo
+              .inner
+              .find_tree(git2::Oid::from_str(oid.as_str()).convert(format!("Invalid OID [{oid}]"))?)
+              .convert(format!("Find tree from OID [{oid}] failed"))
+          })
+          .ok()?,
+      ),
+      _handle: Some(OpenHandle::new(&self.handles, HandleKind::Tree)),
+    })
+  }
+
+  #[napi]
+  /// Lookup a reference to one of the trees in the repository by prefix hash.
+  pub fn find_tree_by_prefix(
+    &self,
+    prefix: String,
+    self_ref: Reference<Repository>,
+    env: Env,
+  ) -> Option<Tree> {
+    Some(Tree {
+      inner: TreeParent::Repository(
+        self_ref
+          .share_with(env, |repo| {
+            repo
+              .inner
+              .find_object_by_prefix(&prefix, Some(git2::ObjectType::Tree))
+              .and_then(|object| {
+                object
+                  .into_tree()
+                  .map_err(|_| git2::Error::from_str("Object is not a tree"))
+              })
+              .convert(format!("Find tree from OID [{prefix}] failed"))
+          })
+          .ok()?,
+      ),
+      _handle: Some(OpenHandle::new(&self.handles, HandleKind::Tree)),
+    })
+  }
+
+  #[napi]
+  /// Lookup a reference to one of the blobs in the repository by prefix hash.
+  pub fn find_blob_by_prefix(
+    &self,
+    prefix: String,
+    self_ref: Reference<Repository>,
+    env: Env,
+  ) -> Option<Blob> {
+    Some(Blob {
+      inner: BlobParent::Repository(
+        self_ref
+          .share_with(env, |repo| {
+            repo
+              .inner
+              .find_object_by_prefix(&prefix, Some(git2::ObjectType::Blob))
+              .and_then(|object| {
+                object
+                  .into_blob()
+                  .map_err(|_| git2::Error::from_str("Object is not a blob"))
+              })
+              .convert(format!("Find blob from OID [{prefix}] failed"))
+          })
+          .ok()?,
+      ),
+    })
+  }
+
+  #[napi]
+  /// Lookup a reference to one of the objects in the repository by prefix
+  /// hash, of any type unless `kind` is given.
+  pub fn find_object_by_prefix(
+    &self,
+    prefix: String,
+    kind: Option<ObjectType>,
+    self_ref: Reference<Repository>,
+    env: Env,
+  ) -> Option<GitObject> {
+    Some(GitObject {
+      inner: ObjectParent::Repository(
+        self_ref
+          .share_with(env, |repo| {
+            repo
+              .inner
+              .find_object_by_prefix(&prefix, kind.map(|kind| kind.into()))
+              .convert(format!("Find object from OID [{prefix}] failed"))
+          })
+          .ok()?,
+      ),
+    })
+  }
+
+  #[napi]
+  pub fn find_commit(
+    &self,
+    oid: String,
+    this_ref: Reference<Repository>,
+    env: Env,
+  ) -> Option<Commit> {
+    let commit = this_ref
+      .share_with(env, |repo| {
+        repo
+          .inner
+          .find_commit_by_prefix(&oid)
+          .convert(format!("Find commit from OID [{oid}] failed"))
+      })
+      .ok()?;
+    Some(Commit {
+      inner: CommitInner::Repository(commit),
+    })
+  }
+
+  #[napi]
+  /// Write an in-memory buffer to the object database as a blob.
+  ///
+  /// Returns the OID of the written blob.
+  pub fn blob(&self, data: Buffer) -> Result<String> {
+    self
+      .inner
+      .blob(&data)
+      .map(|oid| oid.to_string())
+      .convert("Write blob failed")
+  }
+
+  #[napi]
+  /// Read a file from the filesystem and write its content to the object
+  /// database as a blob, like `git add` does.
+  ///
+  /// A relative `path` is resolved from the working directory. The filters of
+  /// the files of the working directory, e.g. the CRLF conversion and the
+  /// `clean` filters, are applied to the content.
+  ///
+  /// Returns the OID of the written blob.
+  pub fn blob_path(&self, path: String) -> Result<String> {
+    let full_path = match self.inner.workdir() {
+      Some(workdir) => workdir.join(&path),
+      None => PathBuf::from(&path),
+    };
+    self
+      .inner
+      .blob_path(&full_path)
+      .map(|oid| oid.to_string())
+      .convert(format!("Write blob from [{path}] failed"))
+  }
+
+  #[napi]
+  /// Create a stream to write a blob in chunks, without holding its whole
+  /// content in memory.
+  ///
+  /// The filters of `hintPath`, a path relative to the working directory, are
+  /// applied to the content. Not set, no filter is applied.
+  pub fn blob_writer(
+    &self,
+    env: Env,
+    this: Reference<Repository>,
+    hint_path: Option<String>,
+  ) -> Result<BlobWriter> {
+    Ok(BlobWriter {
+      inner: this.share_with(env, |repo| {
+        repo
+          .inner
+          .blob_writer(hint_path.as_deref().map(Path::new))
+          .map(Some)
+          .convert("Create blob writer failed")
+      })?,
+    })
+  }
+
+  #[napi]
+  /// Create a `TreeBuilder` to write a new tree, starting from the entries of
+  /// `baseTree` if set.
+  pub fn treebuilder(
+    &self,
+    env: Env,
+    this: Reference<Repository>,
+    base_tree: Option<&Tree>,
+  ) -> Result<TreeBuilder> {
+    Ok(TreeBuilder {
+      inner: this.share_with(env, |repo| {
+        repo
+          .inner
+          .treebuilder(base_tree.map(|tree| tree.inner()))
+          .convert("Create tree builder failed")
+      })?,
+    })
+  }
+
+  #[napi]
+  /// Lookup a blob object from the repository.
+  pub fn find_blob(&self, env: Env, this: Reference<Repository>, oid: String) -> Result<Blob> {
+    Ok(Blob {
+      inner: BlobParent::Repository(this.share_with(env, |repo| {
+        repo
+          .inner
+          .find_blob(git2::Oid::from_str(oid.as_str()).convert(format!("Invalid OID [{oid}]"))?)
+          .convert(format!("Find blob from OID [{oid}] failed"))
+      })?),
+    })
+  }
+
+  #[napi]
+  /// Lookup the blob at `path` in the tree of the commit `commitish`
+  /// resolves to, e.g. a branch name, a tag or an OID.
+  pub fn get_blob_at(
+    &self,
+    env: Env,
+    this: Reference<Repository>,
+    commitish: String,
+    path: String,
+  ) -> Result<Blob> {
+    Ok(Blob {
+      inner: BlobParent::Repository(this.share_with(env, |repo| {
+        let tree = repo
+          .inner
+          .revparse_single(&commitish)
+          .and_then(|object| object.peel_to_tree())
+          .convert(format!("Resolve [{commitish}] failed"))?;
+        tree
+          .get_path(Path::new(&path))
+          .and_then(|entry| entry.to_object(&repo.inner))
+          .and_then(|object| object.peel_to_blob())
+          .convert(format!("Find blob [{path}] at [{commitish}] failed"))
+      })?),
+    })
+  }
+
+  #[napi]
+  /// Write the files of the tree `treeish` resolves to in `directory`, like
+  /// extracting its `git archive`, without touching the index nor `HEAD`.
+  ///
+  /// Every path is checked like `checkSafePath` before anything is written,
+  /// throwing an `UnsafePath` error naming the first unsafe entry. Existing
+  /// files are not overwritten and the symbolic links are created last, so
+  /// that no file is written through them. Submodules are skipped.
+  ///
+  /// Returns the number of files written.
+  pub fn safe_path_checkout(&self, env: Env, treeish: String, directory: String) -> Result<u32> {
+    let tree = self
+      .inner
+      .revparse_single(&treeish)
+      .and_then(|object| object.peel_to_tree())
+      .convert(format!("Resolve [{treeish}] failed"))?;
+    let mut files = Vec::new();
+    let mut trees = vec![(Vec::new(), tree)];
+    while let Some((prefix, tree)) = trees.pop() {
+      for entry in tree.iter() {
+        let mut path = prefix.clone();
+        path.extend_from_slice(entry.name_bytes());
+        safe_path::check_path(&path)
+          .map_err(|reason| safe_path::unsafe_path_error(&env, &path, reason))?;
+        match entry.kind() {
+          Some(git2::ObjectType::Tree) => {
+            let tree = self
+              .inner
+              .find_tree(entry.id())
+              .convert(format!("Find tree [{}] failed", entry.id()))?;
+            path.push(b'/');
+            trees.push((path, tree));
+          }
+          Some(git2::ObjectType::Blob) => files.push((path, entry.id(), entry.filemode())),
+          _ => {}
+        }
+      }
+    }
+    files.sort_by_key(|(_, _, mode)| *mode == i32::from(git2::FileMode::Link));
+    let directory = Path::new(&directory);
+    for (path, oid, mode) in &files {
+      // Checked to be valid UTF-8 above
+      let path = directory.join(String::from_utf8_lossy(path).as_ref());
+      let blob = self
+        .inner
+        .find_blob(*oid)
+        .convert(format!("Find blob [{oid}] failed"))?;
+      write_checkout_file(&path, blob.content(), *mode).map_err(|err| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Write [{}] failed: {err}", path.display()),
+        )
+      })?;
+    }
+    Ok(files.len() as u32)
+  }
+
+  #[napi]
+  /// Create a new tag in the repository from an object
+  ///
+  /// A new reference will also be created pointing to this tag object. If
+  /// `force` is true and a reference already exists with the given name,
+  /// it'll be replaced.
+  ///
+  /// The message will not be cleaned up.
+  ///
+  /// The tag name will be checked for validity. You must avoid the characters
+  /// '~', '^', ':', ' \ ', '?', '[', and '*', and the sequences ".." and " @
+  /// {" which have special meaning to revparse.
+  pub fn tag(
+    &self,
+    env: Env,
+    name: String,
+    target: &GitObject,
+    tagger: &Signature,
+    message: String,
+    force: bool,
+  ) -> Result<String> {
+    if self.ref_update_guard.is_some() {
+      let oid = tag_id(&name, &target.inner, &tagger.inner, &message)?;
+      self.check_tag_update(&env, &name, Some(oid))?;
+    }
+    self
+      .inner
+      .tag(&name, &target.inner, &tagger.inner, &message, force)
+      .map(|o| o.to_string())
+      .convert("Failed to create tag")
+  }
+
+  #[napi]
+  /// Create a new tag in the repository from an object without creating a reference.
+  ///
+  /// The message will not be cleaned up.
+  ///
+  /// The tag name will be checked for validity. You must avoid the characters
+  /// '~', '^', ':', ' \ ', '?', '[', and '*', and the sequences ".." and " @
+  /// {" which have special meaning to revparse.
+  pub fn tag_annotation_create(
+    &self,
+    name: String,
+    target: &GitObject,
+    tagger: &Signature,
+    message: String,
+  ) -> Result<String> {
+    self
+      .inner
+      .tag_annotation_create(&name, &target.inner, &tagger.inner, &message)
+      .map(|o| o.to_string())
+      .convert("Failed to create tag annotation")
+  }
+
+  #[napi]
+  /// Create a new lightweight tag pointing at a target object
+  ///
+  /// A new direct reference will be created pointing to this target object.
+  /// If force is true and a reference already exists with the given name,
+  /// it'll be replaced.
+  pub fn tag_lightweight(
+    &self,
+    env: Env,
+    name: String,
+    target: &GitObject,
+    force: bool,
+  ) -> Result<String> {
+    self.check_tag_update(&env, &name, Some(target.inner.id()))?;
+    self
+      .inner
+      .tag_lightweight(&name, &target.inner, force)
+      .map(|o| o.to_string())
+      .convert("Failed to create lightweight tag")
+  }
+
+  #[napi]
+  /// Lookup a tag object from the repository.
+  pub fn find_tag(&self, env: Env, this: Reference<Repository>, oid: String) -> Result<Tag> {
+    Ok(Tag {
+      inner: TagInner::Repository(this.share_with(env, |repo| {
+        repo
+          .inner
+          .find_tag(git2::Oid::from_str(oid.as_str()).convert(format!("Invalid OID [{oid}]"))?)
+          .convert(format!("Find tag from OID [{oid}] failed"))
+      })?),
+    })
+  }
+
+  #[napi]
+  /// Lookup a tag object by prefix hash from the repository.
+  pub fn find_tag_by_prefix(
+    &self,
+    env: Env,
+    this: Reference<Repository>,
+    prefix_hash: String,
+  ) -> Result<Tag> {
+    Ok(Tag {
+      inner: TagInner::Repository(this.share_with(env, |repo| {
+        repo
+          .inner
+          .find_tag_by_prefix(&prefix_hash)
+          .convert(format!("Find tag from OID [{prefix_hash}] failed"))
+      })?),
+    })
+  }
+
+  #[napi]
+  /// Delete an existing tag reference.
+  ///
+  /// The tag name will be checked for validity, see `tag` for some rules
+  /// about valid names.
+  pub fn tag_delete(&self, env: Env, name: String) -> Result<()> {
+    self.check_tag_update(&env, &name, None)?;
+    let refname = format!("refs/tags/{name}");
+    self.record_deletion(&env, &refname, self.inner.refname_to_id(&refname).ok())?;
+    self.inner.tag_delete(&name).convert_without_message()?;
+    Ok(())
+  }
+
+  #[napi]
+  /// Get a list with all the tags in the repository.
+  ///
+  /// An optional fnmatch pattern can also be specified.
+  pub fn tag_names(&self, pattern: Option<String>) -> Result<Vec<String>> {
+    self
+      .inner
+      .tag_names(pattern.as_deref())
+      .convert("Failed to get tag names")
+      .map(|tags| {
+        tags
+          .into_iter()
+          .filter_map(|s| s.map(|s| s.to_owned()))
+          .collect()
+      })
+  }
+
+  #[napi]
+  /// List the tags of the repository along with their targets, optionally
+  /// only those whose name matches the given fnmatch pattern.
+  ///
+  /// The references are listed in a single pass, only reading the headers of
+  /// the objects which are not tags.
+  pub fn tag_list(
+    &self,
+    pattern: Option<String>,
+    options: Option<TagListOptions>,
+  ) -> Result<Vec<TagListEntry>> {
+    let raw_names = options.and_then(|o| o.raw_names).unwrap_or(false);
+    let glob = format!("refs/tags/{}", pattern.as_deref().unwrap_or("*"));
+    let odb = self
+      .inner
+      .odb()
+      .convert("Get the odb of Repository failed")?;
+    let mut tags = Vec::new();
+    for reference in self
+      .inner
+      .references_glob(&glob)
+      .convert(format!("List tags matching [{glob}] failed"))?
+    {
+      let reference = reference.convert("List tags failed")?;
+      let Some(oid) = reference
+        .target()
+        .or_else(|| reference.resolve().ok()?.target())
+      else {
+        continue;
+      };
+      let name = &reference.name_bytes()["refs/tags/".len()..];
+      let (_, kind) = odb
+        .read_header(oid)
+        .convert(format!("Read the header of object [{oid}] failed"))?;
+      let target_oid = if kind == git2::ObjectType::Tag {
+        self
+          .inner
+          .find_object(oid, Some(git2::ObjectType::Tag))
+          .and_then(|tag| tag.peel(git2::ObjectType::Any))
+          .convert(format!("Peel tag [{oid}] failed"))?
+          .id()
+      } else {
+        oid
+      };
+      tags.push(TagListEntry {
+        name: String::from_utf8_lossy(name).into_owned(),
+        name_bytes: raw_names.then(|| name.to_vec().into()),
+        oid: oid.to_string(),
+        target_oid: target_oid.to_string(),
+        is_annotated: kind == git2::ObjectType::Tag,
+      });
+    }
+    Ok(tags)
+  }
+
+  #[napi]
+  /// iterate over all tags calling `cb` on each.
+  /// the callback is provided the tag id and name
+  pub fn tag_foreach(&self, cb: Function<(String, Buffer), bool>) -> Result<()> {
+    self
+      .inner
+      .tag_foreach(|oid, name| {
+        let oid = oid.to_string();
+        let name = name.to_vec();
+        cb.call((oid, name.into())).unwrap_or(false)
+      })
+      .convert_without_message()
+  }
+
+  #[napi]
+  /// Create a diff between a tree and the working directory.
+  ///
+  /// The tree you provide will be used for the "old_file" side of the delta,
+  /// and the working directory will be used for the "new_file" side.
+  ///
+  /// This is not the same as `git diff <treeish>` or `git diff-index
+  /// <treeish>`.  Those commands use information from the index, whereas this
+  /// function strictly returns the differences between the tree and the files
+  /// in the working directory, regardless of the state of the index.  Use
+  /// `tree_to_workdir_with_index` to emulate those commands.
+  ///
+  /// To see difference between this and `tree_to_workdir_with_index`,
+  /// consider the example of a staged file deletion where the file has then
+  /// been put back into the working dir and further modified.  The
+  /// tree-to-workdir diff for that file is 'modified', but `git diff` would
+  /// show status 'deleted' since there is a staged delete.
+  ///
+  /// If `None` is passed for `tree`, then an empty tree is used.
+  pub fn diff_tree_to_workdir(
+    &self,
+    env: Env,
+    self_reference: Reference<Repository>,
+    old_tree: Option<&Tree>,
+    options: Option<DiffOptions>,
+  ) -> Result<Diff> {
+    let mut diff_options = options
+      .as_ref()
+      .map(DiffOptions::to_git2)
+      .unwrap_or_default();
+    let inner = self_reference.share_with(env, |repo| {
+      repo
+        .inner
+        .diff_tree_to_workdir(old_tree.map(|t| t.inner()), Some(&mut diff_options))
+        .convert_without_message()
+    })?;
+    Diff::new(env, inner, self, options)
+  }
+
+  #[napi]
+  /// Create a diff between a tree and the working directory using index data
+  /// to account for staged deletes, tracked files, etc.
+  ///
+  /// This emulates `git diff <tree>` by diffing the tree to the index and
+  /// the index to the working directory and blending the results into a
+  /// single diff that includes staged deleted, etc.
+  pub fn diff_tree_to_workdir_with_index(
+    &self,
+    env: Env,
+    self_reference: Reference<Repository>,
+    old_tree: Option<&Tree>,
+    options: Option<DiffOptions>,
+  ) -> Result<Diff> {
+    let mut diff_options = options
+      .as_ref()
+      .map(DiffOptions::to_git2)
+      .unwrap_or_default();
+    let inner = self_reference.share_with(env, |repo| {
+      repo
+        .inner
+        .diff_tree_to_workdir_with_index(old_tree.map(|t| t.inner()), Some(&mut diff_options))
+        .convert_without_message()
+    })?;
+    Diff::new(env, inner, self, options)
+  }
+
+  #[napi]
+  /// Create a diff with the difference between two tree objects.
+  ///
+  /// This is equivalent to `git diff <old-tree> <new-tree>`.
+  ///
+  /// If `None` is passed for either tree, then an empty tree is used.
+  pub fn diff_tree_to_tree(
+    &self,
+    env: Env,
+    self_reference: Reference<Repository>,
+    old_tree: Option<&Tree>,
+    new_tree: Option<&Tree>,
+    options: Option<DiffOptions>,
+  ) -> Result<Diff> {
+    let mut diff_options = options
+      .as_ref()
+      .map(DiffOptions::to_git2)
+      .unwrap_or_default();
+    let inner = self_reference.share_with(env, |repo| {
+      repo
+        .inner
+        .diff_tree_to_tree(
+          old_tree.map(|t| t.inner()),
+          new_tree.map(|t| t.inner()),
+          Some(&mut diff_options),
+        )
+        .convert_without_message()
+    })?;
+    Diff::new(env, inner, self, options)
+  }
+
+  #[napi]
+  /// Create a diff between the repository index and the workdir directory.
+  ///
+  /// This matches the `git diff` command.
+  ///
+  /// If `None` is passed for `index`, then the index of the repository is
+  /// used.
+  pub fn diff_index_to_workdir(
+    &self,
+    env: Env,
+    self_reference: Reference<Repository>,
+    index: Option<&Index>,
+    options: Option<DiffOptions>,
+  ) -> Result<Diff> {
+    let mut diff_options = options
+      .as_ref()
+      .map(DiffOptions::to_git2)
+      .unwrap_or_default();
+    let inner = self_reference.share_with(env, |repo| {
+      repo
+        .inner
+        .diff_index_to_workdir(index.map(|i| &i.inner), Some(&mut diff_options))
+        .convert_without_message()
+    })?;
+    Diff::new(env, inner, self, options)
+  }
+
+  #[napi]
+  /// Create a diff between a tree and repository index.
+  ///
+  /// This is equivalent to `git diff --cached <treeish>` or if you pass the
+  /// HEAD tree, then like `git diff --cached`.
+  ///
+  /// If `None` is passed for `tree`, then an empty tree is used, and if
+  /// `None` is passed for `index`, then the index of the repository is used.
+  pub fn diff_tree_to_index(
+    &self,
+    env: Env,
+    self_reference: Reference<Repository>,
+    old_tree: Option<&Tree>,
+    index: Option<&Index>,
+    options: Option<DiffOptions>,
+  ) -> Result<Diff> {
+    let mut diff_options = options
+      .as_ref()
+      .map(DiffOptions::to_git2)
+      .unwrap_or_default();
+    let inner = self_reference.share_with(env, |repo| {
+      repo
+        .inner
+        .diff_tree_to_index(
+          old_tree.map(|t| t.inner()),
+          index.map(|i| &i.inner),
+          Some(&mut diff_options),
+        )
+        .convert_without_message()
+    })?;
+    Diff::new(env, inner, self, options)
+  }
+
+  #[napi]
+  /// Create a diff between the trees of two revisions, e.g. `main` and
+  /// `HEAD~2`, like `git diff <old> <new>`.
+  ///
+  /// Both revisions are resolved with `revparse_single` and peeled to their
+  /// tree, so commits, tags and trees are accepted.
+  pub fn diff_commits(
+    &self,
+    env: Env,
+    self_reference: Reference<Repository>,
+    old_commitish: String,
+    new_commitish: String,
+    options: Option<DiffOptions>,
+  ) -> Result<Diff> {
+    let mut diff_options = options
+      .as_ref()
+      .map(DiffOptions::to_git2)
+      .unwrap_or_default();
+    let inner = self_reference.share_with(env, |repo| {
+      let resolve = |spec: &str| {
+        repo
+          .inner
+          .revparse_single(spec)
+          .and_then(|object| object.peel_to_tree())
+          .convert(format!("Resolve [{spec}] to a tree failed"))
+      };
+      let old_tree = resolve(&old_commitish)?;
+      let new_tree = resolve(&new_commitish)?;
+      repo
+        .inner
+        .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut diff_options))
+        .convert_without_message()
+    })?;
+    Diff::new(env, inner, self, options)
+  }
+
+  #[napi]
+  /// Apply a Diff to the given repo, making changes directly in the working
+  /// directory, the index, or both.
+  pub fn apply(
+    &self,
+    diff: &Diff,
+    location: ApplyLocation,
+    options: Option<&mut ApplyOptions>,
+  ) -> Result<()> {
+    let mut default = ApplyOptions::new();
+    options
+      .unwrap_or(&mut default)
+      .apply("Apply diff failed", |options| {
+        self
+          .inner
+          .apply(&diff.inner, location.into(), Some(options))
+      })
+  }
+
+  #[napi]
+  /// Stage the hunks at `hunkIndexes` of the unstaged changes of the file at
+  /// `path`, leaving the working directory untouched, like `git add -p`.
+  ///
+  /// The hunks are numbered like the ones of the `Patch` of the file in
+  /// `diffIndexToWorkdir`. `expectedOid` is the id of the working directory
+  /// side the hunks were listed from, e.g. `DiffDelta.newFile().id()`,
+  /// failing with `StaleHunks` if the file changed since.
+  pub fn stage_hunks(
+    &self,
+    env: Env,
+    path: String,
+    hunk_indexes: Vec<u32>,
+    expected_oid: Option<String>,
+  ) -> Result<()> {
+    let mut options = git2::DiffOptions::new();
+    options.pathspec(&path).disable_pathspec_match(true);
+    let diff = self
+      .inner
+      .diff_index_to_workdir(None, Some(&mut options))
+      .convert(format!("Diff [{path}] to the working directory failed"))?;
+    self.apply_hunks(&env, &diff, &path, &hunk_indexes, expected_oid, false)
+  }
+
+  #[napi]
+  /// Unstage the hunks at `hunkIndexes` of the staged changes of the file at
+  /// `path`, applying them in reverse to the index, like `git reset -p`.
+  ///
+  /// The hunks are numbered like the ones of the `Patch` of the file in
+  /// `diffTreeToIndex` from the tree of `HEAD`. `expectedOid` is the id of
+  /// the index side the hunks were listed from, failing with `StaleHunks` if
+  /// the file was staged since.
+  pub fn unstage_hunks(
+    &self,
+    env: Env,
+    path: String,
+    hunk_indexes: Vec<u32>,
+    expected_oid: Option<String>,
+  ) -> Result<()> {
+    // An unborn `HEAD` has everything staged against the empty tree
+    let head = match self.inner.head() {
+      Ok(head) => Some(
+        head
+          .peel_to_tree()
+          .convert("Resolve the tree of HEAD failed")?,
+      ),
+      Err(err) if err.code() == git2::ErrorCode::UnbornBranch => None,
+      Err(err) => return Err(err).convert("Get the HEAD of Repository failed"),
+    };
+    let mut options = git2::DiffOptions::new();
+    options
+      .pathspec(&path)
+      .disable_pathspec_match(true)
+      .reverse(true);
+    let diff = self
+      .inner
+      .diff_tree_to_index(head.as_ref(), None, Some(&mut options))
+      .convert(format!("Diff [{path}] to the index failed"))?;
+    self.apply_hunks(&env, &diff, &path, &hunk_indexes, expected_oid, true)
+  }
+
+  #[napi]
+  /// Apply a Diff to the provided tree, and return the resulting Index.
+  pub fn apply_to_tree(
+    &self,
+    tree: &Tree,
+    diff: &Diff,
+    options: Option<&mut ApplyOptions>,
+  ) -> Result<Index> {
+    let mut default = ApplyOptions::new();
+    let index = options
+      .unwrap_or(&mut default)
+      .apply("Apply diff to tree failed", |options| {
+        self
+          .inner
+          .apply_to_tree(tree.inner(), &diff.inner, Some(options))
+      })?;
+    Ok(index.into())
+  }
+
+  #[napi]
+  pub fn tree_entry_to_object(
+    &self,
+    tree_entry: &TreeEntry,
+    this_ref: Reference<Repository>,
+    env: Env,
+  ) -> Result<GitObject> {
+    Ok(GitObject {
+      inner: ObjectParent::Repository(this_ref.share_with(env, |repo| {
+        tree_entry
+          .inner
+          .to_object(&repo.inner)
+          .convert_without_message()
+      })?),
+    })
+  }
+
+  #[napi]
+  /// Create new commit in the repository
+  ///
+  /// If the `update_ref` is not `None`, name of the reference that will be
+  /// updated to point to this commit. If the reference is not direct, it will
+  /// be resolved to a direct reference. Use "HEAD" to update the HEAD of the
+  /// current branch and make it point to this commit. If the reference
+  /// doesn't exist yet, it will be created. If it does exist, the first
+  /// parent must be the tip of this branch.
+  pub fn commit(
+    &self,
+    env: Env,
+    update_ref: Option<String>,
+    author: &Signature,
+    committer: &Signature,
+    message: String,
+    tree: &Tree,
+  ) -> Result<String> {
+    self.create_commit(&env, update_ref, author, committer, &message, tree, &[])
+  }
+
+  #[napi]
+  /// Create new commit in the repository on top of `parents`, the OIDs of the
+  /// parent commits.
+  ///
+  /// Merge commits are created by passing several parents. `update_ref`
+  /// behaves as in `commit`, the first parent must be the tip of the updated
+  /// branch if it exists.
+  #[allow(clippy::too_many_arguments)]
+  pub fn commit_with_parents(
+    &self,
+    env: Env,
+    update_ref: Option<String>,
+    author: &Signature,
+    committer: &Signature,
+    message: String,
+    tree: &Tree,
+    parents: Vec<String>,
+  ) -> Result<String> {
+    let parents = parents
+      .iter()
+      .map(|parent| {
+        git2::Oid::from_str(parent)
+          .and_then(|oid| self.inner.find_commit(oid))
+          .convert(format!("Find parent commit [{parent}] failed"))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    self.create_commit(
+      &env,
+      update_ref,
+      author,
+      committer,
+      &message,
+      tree,
+      &parents.iter().collect::<Vec<_>>(),
+    )
+  }
+
+  #[napi]
+  /// Create a commit object from `commitContent`, the content of an unsigned
+  /// commit, signed with `signature`, e.g. an ASCII-armored GPG or SSH
+  /// signature of `commitContent`.
+  ///
+  /// The signature is stored in the `signatureField` header, `gpgsig` by
+  /// default. No reference is updated, returns the OID of the new commit.
+  pub fn commit_signed(
+    &self,
+    commit_content: String,
+    signature: String,
+    signature_field: Option<String>,
+  ) -> Result<String> {
+    self
+      .inner
+      .commit_signed(&commit_content, &signature, signature_field.as_deref())
+      .map(|oid| oid.to_string())
+      .convert("Create signed commit failed")
+  }
+
+  #[napi]
+  /// Get the Index file for this repository.
+  ///
+  /// If a custom index has not been set, the default index for the
+  /// repository will be returned (the one located in .git/index).
+  pub fn index(&self) -> Result<Index> {
+    let mut index: Index = self
+      .inner
+      .index()
+      .convert("Get the index of Repository failed")?
+      .into();
+    index.fsync = self.fsync_policy()?.index;
+    Ok(index)
+  }
+
+  #[napi]
+  /// Lookup the commit `id` as an annotated commit.
+  pub fn find_annotated_commit(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    id: String,
+  ) -> Result<AnnotatedCommit> {
+    let oid = git2::Oid::from_str(&id).convert(format!("Invalid OID [{id}]"))?;
+    Ok(AnnotatedCommit {
+      inner: self_ref.share_with(env, |repo| {
+        repo
+          .inner
+          .find_annotated_commit(oid)
+          .convert(format!("Find annotated commit from OID [{id}] failed"))
+      })?,
+      refname: None,
+    })
+  }
+
+  #[napi]
+  /// Lookup the commit `reference` points to as an annotated commit,
+  /// recording the name of the reference.
+  pub fn annotated_commit_from_ref(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    reference: &reference::Reference,
+  ) -> Result<AnnotatedCommit> {
+    Ok(AnnotatedCommit {
+      inner: self_ref.share_with(env, |repo| {
+        repo
+          .inner
+          .reference_to_annotated_commit(&reference.inner)
+          .convert("Resolve the reference to an annotated commit failed")
+      })?,
+      refname: Some(String::from_utf8_lossy(reference.inner.name_bytes()).into_owned()),
+    })
+  }
+
+  #[napi]
+  /// Lookup the commit `id` as an annotated commit fetched from the branch
+  /// `branchName` of `remoteUrl`, like the entries of `FETCH_HEAD`.
+  pub fn annotated_commit_from_fetchhead(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    branch_name: String,
+    remote_url: String,
+    id: String,
+  ) -> Result<AnnotatedCommit> {
+    let oid = git2::Oid::from_str(&id).convert(format!("Invalid OID [{id}]"))?;
+    Ok(AnnotatedCommit {
+      inner: self_ref.share_with(env, |repo| {
+        repo
+          .inner
+          .annotated_commit_from_fetchhead(&branch_name, &remote_url, &oid)
+          .convert(format!("Find annotated commit from OID [{id}] failed"))
+      })?,
+      refname: Some(branch_name),
+    })
+  }
+
+  #[napi]
+  /// Merges the given commit(s) into HEAD, writing the results into the
+  /// working directory. Any changes are staged for commit and any conflicts
+  /// are written to the index. Callers should inspect the repository's index
+  /// after this completes, resolve any conflicts and prepare a commit.
+  ///
+  /// `annotated_commits` are the OIDs of the commits to merge.
+  ///
+  /// For compatibility with git, the repository is put into a merging state.
+  /// Once the commit is done (or if the user wishes to abort), you should
+  /// clear this state by calling `cleanupState()`.
+  pub fn merge(
+    &self,
+    annotated_commits: Vec<String>,
+    merge_opts: Option<&mut MergeOptions>,
+    checkout_opts: Option<&mut CheckoutOptions>,
+  ) -> Result<()> {
+    let annotated_commits = annotated_commits
+      .iter()
+      .map(|oid| {
+        let id = git2::Oid::from_str(oid).convert(format!("Invalid OID [{oid}]"))?;
+        self
+          .inner
+          .find_annotated_commit(id)
+          .convert(format!("Find annotated commit from OID [{oid}] failed"))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    let annotated_commits = annotated_commits.iter().collect::<Vec<_>>();
+    self
+      .inner
+      .merge(
+        annotated_commits.as_slice(),
+        merge_opts.map(|o| &mut o.inner),
+        checkout_opts.map(|o| &mut o.inner),
+      )
+      .convert("Merge failed")
+  }
+
+  #[napi]
+  /// Merge two commits, producing an index that reflects the result of
+  /// the merge. The index may be written as-is to the working directory or
+  /// checked out. If the index is to be converted to a tree, the caller
+  /// should resolve any conflicts that arose as part of the merge.
+  ///
+  /// The `merge` attribute of the merged paths is honored: paths using the
+  /// built-in `ours` driver are resolved to our side, `union` keeps the lines
+  /// of both sides, `binary` keeps our side conflicted, and paths using a
+  /// custom driver are reported by `Index.mergeConflicts` so that the driver
+  /// can be run by the caller.
+  pub fn merge_commits(
+    &self,
+    our_commit: &Commit,
+    their_commit: &Commit,
+    opts: Option<&MergeOptions>,
+  ) -> Result<Index> {
+    let mut index = self
+      .inner
+      .merge_commits(
+        &our_commit.inner,
+        &their_commit.inner,
+        opts.map(|o| &o.inner),
+      )
+      .convert("Merge commits failed")?;
+    let ancestor = self
+      .inner
+      .merge_base(our_commit.inner.id(), their_commit.inner.id())
+      .and_then(|base| self.inner.find_commit(base))
+      .and_then(|base| base.tree())
+      .ok();
+    let (ours, theirs) = our_commit
+      .inner
+      .tree()
+      .and_then(|ours| Ok((ours, their_commit.inner.tree()?)))
+      .convert("Find the merged trees failed")?;
+    let sides = MergeSides {
+      ancestor: ancestor.as_ref(),
+      ours: &ours,
+      theirs: &theirs,
+    };
+    let merge_drivers = apply_merge_attributes(&self.inner, &mut index, sides)
+      .convert("Apply merge attributes failed")?;
+    Ok(Index {
+      inner: index,
+      merge_drivers,
+      fsync: false,
+    })
+  }
+
+  #[napi]
+  /// Merge `theirTree` into `ourTree` in memory, producing an index that
+  /// reflects the result of the merge, without touching the index or the
+  /// working directory of the repository.
+  ///
+  /// The conflicts are recorded as conflict entries of the returned index.
+  /// Without an `ancestorTree`, both sides are merged as if they were added
+  /// to an empty tree. The `merge` attribute of conflicted paths is honored
+  /// like by `mergeCommits`.
+  pub fn merge_trees(
+    &self,
+    ancestor_tree: Option<&Tree>,
+    our_tree: &Tree,
+    their_tree: &Tree,
+    opts: Option<&MergeOptions>,
+  ) -> Result<Index> {
+    let empty_tree;
+    let ancestor_tree = match ancestor_tree {
+      Some(tree) => tree.inner(),
+      None => {
+        // libgit2 knows the empty tree without it being written
+        empty_tree = git2::Oid::hash_object(git2::ObjectType::Tree, b"")
+          .and_then(|oid| self.inner.find_tree(oid))
+          .convert("Find the empty tree failed")?;
+        &empty_tree
+      }
+    };
+    let mut index = self
+      .inner
+      .merge_trees(
+        ancestor_tree,
+        our_tree.inner(),
+        their_tree.inner(),
+        opts.map(|o| &o.inner),
+      )
+      .convert("Merge trees failed")?;
+    let sides = MergeSides {
+      ancestor: Some(ancestor_tree),
+      ours: our_tree.inner(),
+      theirs: their_tree.inner(),
+    };
+    let merge_drivers = apply_merge_attributes(&self.inner, &mut index, sides)
+      .convert("Apply merge attributes failed")?;
+    Ok(Index {
+      inner: index,
+      merge_drivers,
+      fsync: false,
+    })
+  }
+
+  #[napi]
+  /// Merge the contents of a single file, `theirs` into `ours` from their
+  /// common `ancestor`, like `git merge-file`.
+  ///
+  /// The contents are written to the object database as blobs.
+  pub fn merge_file(
+    &self,
+    ancestor: MergeFileInput,
+    ours: MergeFileInput,
+    theirs: MergeFileInput,
+    opts: Option<&mut MergeFileOptions>,
+  ) -> Result<MergeFileResult> {
+    let [ancestor, ours, theirs] = [ancestor, ours, theirs].map(|input| {
+      let path = input.path.unwrap_or_default().into_bytes();
+      self
+        .inner
+        .blob(&input.content)
+        .map(|id| git2::IndexEntry {
+          ctime: git2::IndexTime::new(0, 0),
+          mtime: git2::IndexTime::new(0, 0),
+          dev: 0,
+          ino: 0,
+          mode: input.mode,
+          uid: 0,
+          gid: 0,
+          file_size: input.content.len() as u32,
+          id,
+          flags: path
+            .len()
+            .min(libgit2_sys::GIT_INDEX_ENTRY_NAMEMASK as usize) as u16,
+          flags_extended: 0,
+          path,
+        })
+        .convert("Write blob failed")
+    });
+    let result = self
+      .inner
+      .merge_file_from_index(&ancestor?, &ours?, &theirs?, opts.map(|o| &mut o.inner))
+      .convert("Merge file failed")?;
+    Ok(MergeFileResult {
+      automergeable: result.is_automergeable(),
+      path: result
+        .path_bytes()
+        .filter(|path| !path.is_empty())
+        .map(|path| String::from_utf8_lossy(path).into_owned()),
+      mode: result.mode(),
+      content: result.content().to_vec().into(),
+    })
+  }
+
+  #[napi]
+  /// Cherry-pick the commits of `range` onto the branch `ontoBranch`, oldest
+  /// first, without touching the index or the working directory.
+  ///
+  /// `range` is either a single revision or a `from..to` range. Each commit is
+  /// applied in memory onto the commit picked before it, and the branch is
+  /// moved to the last picked commit. When a commit conflicts, picking stops
+  /// and the commit is reported by `stoppedAt`, the later commits of the range
+  /// are not picked.
+  ///
+  /// Without `stopOnConflict`, a conflict leaves the branch untouched: the
+  /// commits picked before it are still listed by `applied`, but they are
+  /// only written to the object database, reachable from no reference, and
+  /// are removed by the next garbage collection unless referenced. With
+  /// `stopOnConflict`, the branch is at the last commit of `applied`.
+  pub fn cherry_pick_range(
+    &self,
+    env: Env,
+    range: String,
+    onto_branch: String,
+    options: CherryPickRangeOptions,
+  ) -> Result<CherryPickRangeResult> {
+    let record_origin = options.record_origin.unwrap_or(false);
+    let stop_on_conflict = options.stop_on_conflict.unwrap_or(false);
+    let committer: &git2::Signature = &options.committer.inner;
+    let refname = format!("refs/heads/{onto_branch}");
+    let start = self
+      .inner
+      .refname_to_id(&refname)
+      .convert(format!("Find branch [{onto_branch}] failed"))?;
+    let sources = if range.contains("..") {
+      let mut rev_walk = self.inner.revwalk().convert_without_message()?;
+      rev_walk
+        .set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+        .convert_without_message()?;
+      rev_walk
+        .push_range(&range)
+        .convert(format!("Invalid range [{range}]"))?;
+      rev_walk
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .convert(format!("Walk range [{range}] failed"))?
+    } else {
+      vec![self
+        .inner
+        .revparse_single(&range)
+        .and_then(|object| object.peel_to_commit())
+        .convert(format!("Invalid range [{range}]"))?
+        .id()]
+    };
+    let mut tip = self
+      .inner
+      .find_commit(start)
+      .convert(format!("Find commit [{start}] failed"))?;
+    let mut applied = Vec::new();
+    let mut stopped_at = None;
+    for source in sources {
+      let commit = self
+        .inner
+        .find_commit(source)
+        .convert(format!("Find commit [{source}] failed"))?;
+      if commit.parent_count() > 1 {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Cannot cherry-pick merge commit [{source}]"),
+        ));
+      }
+      let mut index = self
+        .inner
+        .cherrypick_commit(&commit, &tip, 0, None)
+        .convert(format!("Cherry-pick commit [{source}] failed"))?;
+      let (ancestor, ours, theirs) = commit
+        .parent(0)
+        .and_then(|parent| parent.tree())
+        .and_then(|ancestor| Ok((ancestor, tip.tree()?, commit.tree()?)))
+        .convert("Find the cherry-picked trees failed")?;
+      let sides = MergeSides {
+        ancestor: Some(&ancestor),
+        ours: &ours,
+        theirs: &theirs,
+      };
+      apply_merge_attributes(&self.inner, &mut index, sides)
+        .convert("Apply merge attributes failed")?;
+      if index.has_conflicts() {
+        let conflicts = index
+          .conflicts()
+          .convert("Read index conflicts failed")?
+          .filter_map(|conflict| {
+            let conflict = conflict.ok()?;
+            let entry = conflict.our.or(conflict.their).or(conflict.ancestor)?;
+            Some(String::from_utf8_lossy(&entry.path).into_owned())
+          })
+          .collect();
+        stopped_at = Some(CherryPickStop {
+          source_oid: source.to_string(),
+          conflicts,
+        });
+        break;
+      }
+      let tree_id = index
+        .write_tree_to(&self.inner)
+        .convert("Write cherry-picked tree failed")?;
+      let tree = self
+        .inner
+        .find_tree(tree_id)
+        .convert(format!("Find tree [{tree_id}] failed"))?;
+      let mut message = String::from_utf8_lossy(commit.message_raw_bytes()).into_owned();
+      if record_origin {
+        if !message.ends_with('\n') {
+          message.push('\n');
+        }
+        message.push_str(&format!("\n(cherry picked from commit {source})\n"));
+      }
+      let new_oid = self
+        .inner
+        .commit(None, &commit.author(), committer, &message, &tree, &[&tip])
+        .convert(format!("Commit cherry-picked commit [{source}] failed"))?;
+      if stop_on_conflict {
+        self.move_branch(
+          &env,
+          &refname,
+          tip.id(),
+          new_oid,
+          &format!("cherry-pick: {source}"),
+        )?;
+      }
+      tip = self
+        .inner
+        .find_commit(new_oid)
+        .convert(format!("Find commit [{new_oid}] failed"))?;
+      applied.push(CherryPickedCommit {
+        source_oid: source.to_string(),
+        new_oid: new_oid.to_string(),
+      });
+    }
+    if !stop_on_conflict && stopped_at.is_none() && tip.id() != start {
+      self.move_branch(
+        &env,
+        &refname,
+        start,
+        tip.id(),
+        &format!("cherry-pick: {range}"),
+      )?;
+    }
+    Ok(CherryPickRangeResult {
+      applied,
+      stopped_at,
+    })
+  }
+
+  #[napi]
+  /// Remove all the metadata associated with an ongoing command like
+  /// merge, revert, cherry-pick, etc. For example: MERGE_HEAD, MERGE_MSG,
+  /// etc.
+  pub fn cleanup_state(&self) -> Result<()> {
+    self
+      .inner
+      .cleanup_state()
+      .convert("Cleanup repository state failed")
+  }
+
+  #[napi]
+  /// Describes a commit
+  ///
+  /// Performs a describe operation on the current commit and the worktree.
+  /// After performing a describe on HEAD, a status is run and description is
+  /// considered to be dirty if there are uncommitted changes.
+  pub fn describe(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    opts: Option<&DescribeOptions>,
+  ) -> Result<DescribeResult> {
+    let default_opts = git2::DescribeOptions::new();
+    let opts = opts.map(|o| &o.inner).unwrap_or(&default_opts);
+    Ok(DescribeResult {
+      inner: self_ref.share_with(env, |repo| {
+        repo.inner.describe(opts).convert("Describe failed")
+      })?,
+    })
+  }
+
+  #[napi(ts_args_type = "guard: ((update: RefUpdate) => boolean | string) | undefined | null")]
+  /// Install a guard consulted synchronously before this binding writes a
+  /// reference, e.g. when creating or deleting tags, committing with
+  /// `updateRef` or renaming references.
+  ///
+  /// The guard is called once for each reference an operation writes.
+  /// Returning `false` or a string rejects the write, the string being used as
+  /// the error message. Pass `null` to remove the guard.
+  ///
+  /// References updated by libgit2 itself during a fetch or a push are not
+  /// guarded.
+  pub fn set_ref_update_guard(
+    &mut self,
+    guard: Option<FunctionRef<RefUpdate, Either<bool, String>>>,
+  ) {
+    self.ref_update_guard = guard;
+  }
+
+  #[napi]
+  /// Keep the targets of the references deleted through this repository, so
+  /// that an accidentally deleted branch can be restored.
+  ///
+  /// Before a reference is deleted, its target is copied into
+  /// `refs/deleted/<branch>@<epoch ms>`, see `recoverDeletedBranches`.
+  pub fn enable_branch_recovery(&mut self, options: Option<BranchRecoveryOptions>) -> Result<()> {
+    let namespace = options
+      .and_then(|options| options.namespace)
+      .unwrap_or_else(|| DEFAULT_RECOVERY_NAMESPACE.to_owned());
+    let namespace = format!("{}/", namespace.trim_end_matches('/'));
+    if !namespace.starts_with("refs/") {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Branch recovery namespace [{namespace}] must be under refs/"),
+      ));
+    }
+    self.branch_recovery = Some(namespace);
+    Ok(())
+  }
+
+  #[napi]
+  /// List the deleted references kept by `enableBranchRecovery`, oldest
+  /// first.
+  pub fn recover_deleted_branches(&self) -> Result<Vec<DeletedBranch>> {
+    let Some(namespace) = &self.branch_recovery else {
+      return Ok(Vec::new());
+    };
+    let mut deleted = self
+      .inner
+      .references_glob(&format!("{namespace}*"))
+      .convert("List deleted branches failed")?
+      .filter_map(|reference| {
+        let reference = reference.ok()?;
+        let entry = reference.name()?;
+        let (name, deleted_at_ms) = entry.strip_prefix(namespace.as_str())?.rsplit_once('@')?;
+        Some(DeletedBranch {
+          entry: entry.to_owned(),
+          name: name.to_owned(),
+          oid: reference.target()?.to_string(),
+          deleted_at_ms: deleted_at_ms.parse().ok()?,
+        })
+      })
+      .collect::<Vec<_>>();
+    deleted.sort_by_key(|deleted| deleted.deleted_at_ms);
+    Ok(deleted)
+  }
+
+  #[napi]
+  /// Restore a reference listed by `recoverDeletedBranches` under its
+  /// original name, or as the branch `asBranch`, and drop it from the
+  /// deleted references.
+  ///
+  /// Fails if the reference to restore already exists.
+  pub fn restore_deleted_branch(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    entry_name: String,
+    as_branch: Option<String>,
+  ) -> Result<reference::Reference> {
+    let deleted = self
+      .recover_deleted_branches()?
+      .into_iter()
+      .find(|deleted| deleted.entry == entry_name)
+      .ok_or_else(|| {
+        Error::new(
+          Status::InvalidArg,
+          format!("[{entry_name}] is not a deleted branch"),
+        )
+      })?;
+    let refname = match as_branch {
+      Some(branch) => format!("refs/heads/{branch}"),
+      None if deleted.name.starts_with("refs/") => deleted.name,
+      None => format!("refs/heads/{}", deleted.name),
+    };
+    let oid =
+      git2::Oid::from_str(&deleted.oid).convert(format!("Invalid OID [{}]", deleted.oid))?;
+    self.check_ref_update(&env, RefUpdate::new(&refname, None, Some(oid)))?;
+    self.check_ref_update(&env, RefUpdate::new(&entry_name, Some(oid), None))?;
+    let restored = self_ref.share_with(env, |repo| {
+      repo
+        .inner
+        .reference(&refname, oid, false, &format!("restore: {entry_name}"))
+        .convert(format!("Restore reference [{refname}] failed"))
+    })?;
+    self
+      .inner
+      .find_reference(&entry_name)
+      .and_then(|mut entry| entry.delete())
+      .convert(format!("Delete [{entry_name}] failed"))?;
+    Ok(reference::Reference { inner: restored })
+  }
+
+  #[napi]
+  /// Count the revwalks, diffs, trees and remotes of this repository which
+  /// are still alive in JavaScript, to track down leaks.
+  pub fn open_handles(&self) -> OpenHandles {
+    self.handles.snapshot()
+  }
+
+  #[napi]
+  /// Set how this repository decides whether a file is binary.
+  ///
+  /// The policy is used by the diffs of this repository instead of the
+  /// heuristics of libgit2, to classify the deltas, to choose the deltas the
+  /// `textconv` option converts and to render them with `Diff.toBuffer`.
+  pub fn set_binary_detection(&mut self, options: BinaryDetectionOptions) {
+    self.binary_detection = Some(options.into());
+  }
+
+  #[napi]
+  /// Whether the file at `path` is binary according to the binary detection
+  /// policy of this repository, or to the default one if it was not set.
+  ///
+  /// Without `content`, only the attributes and the extension of the path are
+  /// considered.
+  pub fn is_binary_path(&self, path: String, content: Option<Buffer>) -> Result<bool> {
+    let default = BinaryDetection::default();
+    self
+      .binary_detection
+      .as_ref()
+      .unwrap_or(&default)
+      .is_binary(&self.inner, &path, content.as_deref())
+      .convert("Detect binary file failed")
   }
 
   #[napi]
-  /// Add a fetch refspec to the remote's configuration
+  /// Whether the file at `path`, relative to the working directory, would be
+  /// ignored by the `.gitignore` files, the exclude files and the rules added
+  /// with `addIgnoreRule`.
   ///
-  /// Add the given refspec to the fetch list in the configuration. No loaded
-  pub fn remote_add_fetch(&self, name: String, refspec: String) -> Result<&Self> {
+  /// The path does not need to exist.
+  pub fn is_path_ignored(&self, path: String) -> Result<bool> {
     self
       .inner
-      .remote_add_fetch(&name, &refspec)
-      .convert_without_message()?;
-    Ok(self)
+      .is_path_ignored(&path)
+      .convert(format!("Check whether [{path}] is ignored failed"))
   }
 
   #[napi]
-  /// Add a push refspec to the remote's configuration.
+  /// Add ignore rules, in the `.gitignore` format with one rule per line, to
+  /// this repository object.
   ///
-  /// Add the given refspec to the push list in the configuration. No
-  /// loaded remote instances will be affected.
-  pub fn remote_add_push(&self, name: String, refspec: String) -> Result<&Self> {
+  /// The rules are kept in memory only, until `clearIgnoreRules` is called.
+  pub fn add_ignore_rule(&self, rules: String) -> Result<()> {
     self
       .inner
-      .remote_add_push(&name, &refspec)
-      .convert_without_message()?;
-    Ok(self)
+      .add_ignore_rule(&rules)
+      .convert("Add ignore rules failed")
   }
 
   #[napi]
-  /// Add a push refspec to the remote's configuration.
+  /// Clear the ignore rules added with `addIgnoreRule`.
   ///
-  /// Add the given refspec to the push list in the configuration. No
-  /// loaded remote instances will be affected.
-  pub fn remote_set_url(&self, name: String, url: String) -> Result<&Self> {
+  /// The rules of the `.gitignore` and exclude files are kept.
+  pub fn clear_ignore_rules(&self) -> Result<()> {
     self
       .inner
-      .remote_set_url(&name, &url)
-      .convert_without_message()?;
-    Ok(self)
+      .clear_ignore_rules()
+      .convert("Clear ignore rules failed")
   }
 
   #[napi]
-  /// Set the remote's URL for pushing in the configuration.
+  /// Get the value of the gitattribute `name` for the file at `path`.
   ///
-  /// Remote objects already in memory will not be affected. This assumes
-  /// the common case of a single-url remote and will otherwise return an
-  /// error.
+  /// Set attributes are reported as `set` and unset ones, e.g. `-diff`, as
+  /// `unset`, like `git check-attr` does.
   ///
-  /// `None` indicates that it should be cleared.
-  pub fn remote_set_pushurl(&self, name: String, url: Option<String>) -> Result<&Self> {
-    self
+  /// Returns `null` if the attribute is unspecified.
+  pub fn check_attribute(&self, path: String, name: String) -> Result<Option<String>> {
+    let value = self
       .inner
-      .remote_set_pushurl(&name, url.as_deref())
-      .convert_without_message()?;
-    Ok(self)
+      .get_attr(Path::new(&path), &name, git2::AttrCheckFlags::default())
+      .convert(format!("Get the attribute [{name}] of [{path}] failed"))?;
+    Ok(match git2::AttrValue::from_string(value) {
+      git2::AttrValue::True => Some("set".to_owned()),
+      git2::AttrValue::False => Some("unset".to_owned()),
+      git2::AttrValue::String(value) => Some(value.to_owned()),
+      git2::AttrValue::Bytes(value) => Some(String::from_utf8_lossy(value).into_owned()),
+      git2::AttrValue::Unspecified => None,
+    })
   }
 
   #[napi]
-  /// Lookup a reference to one of the objects in a repository.
-  pub fn find_tree(&self, oid: String, self_ref: Reference<Repository>, env: Env) -> Option<Tree> {
-    Some(Tree {
-      inner: TreeParent::Repository(
-        self_ref
-          .share_with(env, |repo| {
-            repo
-              .inner
-              .find_tree(git2::Oid::from_str(oid.as_str()).convert(format!("Invalid OID [{oid}]"))?)
-              .convert(format!("Find tree from OID [{oid}] failed"))
-          })
-          .ok()?,
-      ),
-    })
+  /// Get the default notes reference for this repository
+  pub fn note_default_ref(&self) -> Result<String> {
+    self
+      .inner
+      .note_default_ref()
+      .convert("Get the default notes reference failed")
   }
 
   #[napi]
-  pub fn find_commit(
+  /// Read the note for an object.
+  ///
+  /// The `notes_ref` argument is the canonical name of the reference to use,
+  /// defaulting to "refs/notes/commits".
+  ///
+  /// The id specified is the Oid of the git object to read the note from.
+  pub fn note(
     &self,
-    oid: String,
-    this_ref: Reference<Repository>,
     env: Env,
-  ) -> Option<Commit> {
-    let commit = this_ref
-      .share_with(env, |repo| {
+    self_ref: Reference<Repository>,
+    notes_ref: Option<String>,
+    id: String,
+  ) -> Result<Note> {
+    let oid = git2::Oid::from_str(&id).convert(format!("Invalid OID [{id}]"))?;
+    Ok(Note {
+      inner: self_ref.share_with(env, |repo| {
         repo
           .inner
-          .find_commit_by_prefix(&oid)
-          .convert(format!("Find commit from OID [{oid}] failed"))
-      })
-      .ok()?;
-    Some(Commit {
-      inner: CommitInner::Repository(commit),
+          .find_note(notes_ref.as_deref(), oid)
+          .convert(format!("Find note for [{id}] failed"))
+      })?,
     })
   }
 
   #[napi]
-  /// Create a new tag in the repository from an object
-  ///
-  /// A new reference will also be created pointing to this tag object. If
-  /// `force` is true and a reference already exists with the given name,
-  /// it'll be replaced.
+  /// Add a note for an object
   ///
-  /// The message will not be cleaned up.
+  /// The `notes_ref` argument is the canonical name of the reference to use,
+  /// defaulting to "refs/notes/commits". If `force` is specified then
+  /// previous notes are overwritten.
   ///
-  /// The tag name will be checked for validity. You must avoid the characters
-  /// '~', '^', ':', ' \ ', '?', '[', and '*', and the sequences ".." and " @
-  /// {" which have special meaning to revparse.
-  pub fn tag(
+  /// Returns the id of the note blob.
+  #[allow(clippy::too_many_arguments)]
+  pub fn note_create(
     &self,
-    name: String,
-    target: &GitObject,
-    tagger: &Signature,
-    message: String,
+    env: Env,
+    notes_ref: Option<String>,
+    author: &Signature,
+    committer: &Signature,
+    id: String,
+    note: String,
     force: bool,
   ) -> Result<String> {
+    let oid = git2::Oid::from_str(&id).convert(format!("Invalid OID [{id}]"))?;
+    self.check_notes_update(
+      &env,
+      notes_ref.as_deref(),
+      author,
+      committer,
+      oid,
+      Some(&note),
+      force,
+    )?;
     self
       .inner
-      .tag(&name, &*target.inner, &*tagger.inner, &message, force)
-      .map(|o| o.to_string())
-      .convert("Failed to create tag")
+      .note(
+        &author.inner,
+        &committer.inner,
+        notes_ref.as_deref(),
+        oid,
+        &note,
+        force,
+      )
+      .map(|oid| oid.to_string())
+      .convert(format!("Create note for [{id}] failed"))
   }
 
   #[napi]
-  /// Create a new tag in the repository from an object without creating a reference.
+  /// Remove the note for an object
   ///
-  /// The message will not be cleaned up.
+  /// The `notes_ref` argument is the canonical name of the reference to use,
+  /// defaulting to "refs/notes/commits".
   ///
-  /// The tag name will be checked for validity. You must avoid the characters
-  /// '~', '^', ':', ' \ ', '?', '[', and '*', and the sequences ".." and " @
-  /// {" which have special meaning to revparse.
-  pub fn tag_annotation_create(
+  /// The id specified is the Oid of the git object to remove the note from.
+  pub fn note_delete(
     &self,
-    name: String,
-    target: &GitObject,
-    tagger: &Signature,
-    message: String,
-  ) -> Result<String> {
+    env: Env,
+    notes_ref: Option<String>,
+    author: &Signature,
+    committer: &Signature,
+    id: String,
+  ) -> Result<()> {
+    let oid = git2::Oid::from_str(&id).convert(format!("Invalid OID [{id}]"))?;
+    self.check_notes_update(
+      &env,
+      notes_ref.as_deref(),
+      author,
+      committer,
+      oid,
+      None,
+      false,
+    )?;
     self
       .inner
-      .tag_annotation_create(&name, &*target.inner, &*tagger.inner, &message)
-      .map(|o| o.to_string())
-      .convert("Failed to create tag annotation")
+      .note_delete(oid, notes_ref.as_deref(), &author.inner, &committer.inner)
+      .convert(format!("Delete note for [{id}] failed"))
   }
 
   #[napi]
-  /// Create a new lightweight tag pointing at a target object
+  /// Creates a new iterator for notes in this repository.
   ///
-  /// A new direct reference will be created pointing to this target object.
-  /// If force is true and a reference already exists with the given name,
-  /// it'll be replaced.
-  pub fn tag_lightweight(&self, name: String, target: &GitObject, force: bool) -> Result<String> {
-    self
+  /// The `notes_ref` argument is the canonical name of the reference to use,
+  /// defaulting to "refs/notes/commits".
+  pub fn notes(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    notes_ref: Option<String>,
+  ) -> Result<Notes> {
+    Ok(Notes {
+      inner: self_ref.share_with(env, |repo| {
+        repo
+          .inner
+          .notes(notes_ref.as_deref())
+          .convert("Create notes iterator failed")
+      })?,
+    })
+  }
+
+  #[napi]
+  /// Set which writes are flushed to disk with fsync, to protect repositories
+  /// on network filesystems against torn writes.
+  ///
+  /// The settings are also written to `core.fsync` so that git honors them.
+  /// Fields which are not set keep their current value.
+  pub fn set_fsync_policy(&self, options: FsyncPolicyOptions) -> Result<()> {
+    let current = self.fsync_policy()?;
+    let policy = FsyncPolicy {
+      objects: options.objects.unwrap_or(current.objects),
+      refs: options.refs.unwrap_or(current.refs),
+      index: options.index.unwrap_or(current.index),
+    };
+    if let Some(refs) = options.refs {
+      let result = unsafe {
+        libgit2_sys::git_libgit2_opts(libgit2_sys::GIT_OPT_ENABLE_FSYNC_GITDIR as _, refs as i32)
+      };
+      if result < 0 {
+        return Err(git2::Error::last_error(result)).convert("Set fsync option failed");
+      }
+      FSYNC_GITDIR.store(refs, Ordering::Relaxed);
+    }
+    let mut config = self
       .inner
-      .tag_lightweight(&name, &*target.inner, force)
-      .map(|o| o.to_string())
-      .convert("Failed to create lightweight tag")
+      .config()
+      .convert("Get the config of Repository failed")?;
+    if let Some(objects) = options.objects {
+      config
+        .set_bool("core.fsyncObjectFiles", objects)
+        .convert("Set config [core.fsyncObjectFiles] failed")?;
+    }
+    let components = [
+      (policy.objects, "loose-object"),
+      (policy.refs, "reference"),
+      (policy.index, "index"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, component)| enabled.then_some(component))
+    .collect::<Vec<_>>();
+    let result = if components.is_empty() {
+      match config.remove("core.fsync") {
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(()),
+        result => result,
+      }
+    } else {
+      config.set_str("core.fsync", &components.join(","))
+    };
+    result.convert("Set config [core.fsync] failed")
   }
 
   #[napi]
-  /// Lookup a tag object from the repository.
-  pub fn find_tag(&self, env: Env, this: Reference<Repository>, oid: String) -> Result<Tag> {
-    Ok(Tag {
-      inner: this.share_with(env, |repo| {
+  /// Read back the effective fsync settings of this repository, see
+  /// `setFsyncPolicy`.
+  pub fn fsync_policy(&self) -> Result<FsyncPolicy> {
+    let config = self
+      .inner
+      .config()
+      .convert("Get the config of Repository failed")?;
+    let fsync_object_files = config.get_bool("core.fsyncObjectFiles").unwrap_or(false);
+    let gitdir = FSYNC_GITDIR.load(Ordering::Relaxed);
+    let index = config
+      .get_string("core.fsync")
+      .map(|components| {
+        components
+          .split(',')
+          .any(|component| matches!(component.trim(), "index" | "committed" | "all"))
+      })
+      .unwrap_or(false);
+    Ok(FsyncPolicy {
+      objects: fsync_object_files || gitdir,
+      refs: fsync_object_files || gitdir,
+      index,
+    })
+  }
+
+  #[napi]
+  /// Get the configuration file for this repository.
+  ///
+  /// If a configuration file has not been set, the default config set for the
+  /// repository will be returned, including global and system configurations
+  /// (if they are available).
+  pub fn config(&self) -> Result<Config> {
+    Ok(Config {
+      inner: self
+        .inner
+        .config()
+        .convert("Get the config of Repository failed")?,
+    })
+  }
+
+  #[napi]
+  /// Get the mailmap of this repository, read from the `.mailmap` file of
+  /// the working directory, or of the tree of `HEAD` in bare repositories,
+  /// and from the `mailmap.file` and `mailmap.blob` configurations.
+  pub fn mailmap(&self) -> Result<Mailmap> {
+    Ok(Mailmap {
+      inner: self
+        .inner
+        .mailmap()
+        .convert("Get the mailmap of Repository failed")?,
+    })
+  }
+
+  #[napi]
+  /// Create a new action signature with default user and now timestamp.
+  ///
+  /// This looks up the `user.name` and `user.email` from the configuration
+  /// and uses the current time as the timestamp, and creates a new signature
+  /// based on that information. It will return `NotFound` if either the
+  /// `user.name` or `user.email` are not set.
+  pub fn signature(&self) -> Result<Signature> {
+    Ok(Signature {
+      inner: SignatureInner::Signature(
+        self
+          .inner
+          .signature()
+          .convert("Get the default signature failed")?,
+      ),
+    })
+  }
+
+  #[napi]
+  /// Get the object database for this repository
+  pub fn odb(&self, env: Env, self_ref: Reference<Repository>) -> Result<Odb> {
+    Ok(Odb {
+      inner: self_ref.share_with(env, |repo| {
         repo
           .inner
-          .find_tag(git2::Oid::from_str(oid.as_str()).convert(format!("Invalid OID [{oid}]"))?)
-          .convert(format!("Find tag from OID [{oid}] failed"))
+          .odb()
+          .convert("Get the object database of Repository failed")
       })?,
     })
   }
 
   #[napi]
-  /// Lookup a tag object by prefix hash from the repository.
-  pub fn find_tag_by_prefix(
-    &self,
-    env: Env,
-    this: Reference<Repository>,
-    prefix_hash: String,
-  ) -> Result<Tag> {
-    Ok(Tag {
-      inner: this.share_with(env, |repo| {
+  /// Read the type and the content of an object as stored in the object
+  /// database, without parsing it.
+  pub fn read_raw_object(&self, oid: String) -> Result<RawObject> {
+    let oid = git2::Oid::from_str(&oid).convert(format!("Invalid OID [{oid}]"))?;
+    let odb = self
+      .inner
+      .odb()
+      .convert("Get the object database of Repository failed")?;
+    let object = odb
+      .read(oid)
+      .convert(format!("Read object [{oid}] from odb failed"))?;
+    Ok(RawObject {
+      kind: object.kind().into(),
+      data: object.data().to_vec().into(),
+    })
+  }
+
+  #[napi]
+  /// Write the content of an object to the object database as is, without
+  /// normalizing it.
+  ///
+  /// Returns the OID of the written object, which is checked to be the hash
+  /// of its content.
+  pub fn write_raw_object(&self, kind: ObjectType, data: Buffer) -> Result<String> {
+    if matches!(kind, ObjectType::Any) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Cannot write an object of type Any".to_owned(),
+      ));
+    }
+    let expected =
+      git2::Oid::hash_object(kind.into(), data.as_ref()).convert("Hash object failed")?;
+    let oid = self
+      .inner
+      .odb()
+      .convert("Get the object database of Repository failed")?
+      .write(kind.into(), data.as_ref())
+      .convert("Write object to odb failed")?;
+    if oid != expected {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Written object [{oid}] does not match the hash of its content [{expected}]"),
+      ));
+    }
+    Ok(oid.to_string())
+  }
+
+  #[napi]
+  /// Create a builder of packfiles, e.g. to send the objects of a range of
+  /// commits to another repository.
+  pub fn packbuilder(&self, this_ref: Reference<Repository>, env: Env) -> Result<PackBuilder> {
+    Ok(PackBuilder {
+      inner: this_ref.share_with(env, |repo| {
         repo
           .inner
-          .find_tag_by_prefix(&prefix_hash)
-          .convert(format!("Find tag from OID [{prefix_hash}] failed"))
+          .packbuilder()
+          .convert("Create the packbuilder failed")
       })?,
     })
   }
 
   #[napi]
-  /// Delete an existing tag reference.
+  /// Create a revwalk that can be used to traverse the commit graph.
+  pub fn rev_walk(&self, this_ref: Reference<Repository>, env: Env) -> Result<RevWalk> {
+    Ok(RevWalk {
+      inner: this_ref.share_with(env, |repo| repo.inner.revwalk().convert_without_message())?,
+      _handle: Some(OpenHandle::new(&self.handles, HandleKind::RevWalk)),
+    })
+  }
+
+  #[napi(
+    ts_args_type = "options?: LogOptions | undefined | null, signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<Array<CommitSummary>>"
+  )]
+  /// Walk the commits selected by `options` and summarize them on the libuv
+  /// thread pool, e.g. to list a long history without reading each commit
+  /// from JavaScript.
   ///
-  /// The tag name will be checked for validity, see `tag` for some rules
-  /// about valid names.
-  pub fn tag_delete(&self, name: String) -> Result<()> {
-    self.inner.tag_delete(&name).convert_without_message()?;
-    Ok(())
+  /// Aborting the `signal` rejects the returned `Promise` with an `Aborted`
+  /// error.
+  pub fn log(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    options: Option<LogOptions>,
+    signal: Option<JsObject>,
+  ) -> Result<AsyncTask<LogTask>> {
+    Ok(AsyncTask::new(LogTask {
+      repo: self_ref,
+      options: options.unwrap_or_default(),
+      cancellation: CancellationToken::from_signal(&env, signal)?,
+    }))
   }
 
   #[napi]
-  /// Get a list with all the tags in the repository.
+  /// Parse the messages of the commits in `range` according to the
+  /// Conventional Commits specification, e.g. to generate release notes.
   ///
-  /// An optional fnmatch pattern can also be specified.
-  pub fn tag_names(&self, pattern: Option<String>) -> Result<Vec<String>> {
-    self
-      .inner
-      .tag_names(pattern.as_deref())
-      .convert("Failed to get tag names")
-      .map(|tags| {
-        tags
-          .into_iter()
-          .filter_map(|s| s.map(|s| s.to_owned()))
-          .collect()
+  /// `range` is either a revision, listing all its ancestors, or a
+  /// `from..to` range. Commits are listed from the newest to the oldest.
+  pub fn conventional_commits(
+    &self,
+    range: String,
+    options: Option<ConventionalCommitsOptions>,
+  ) -> Result<Vec<ConventionalCommit>> {
+    let include_body = options.and_then(|o| o.include_body).unwrap_or(false);
+    let mut rev_walk = self.inner.revwalk().convert_without_message()?;
+    rev_walk
+      .set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
+      .convert_without_message()?;
+    if range.contains("..") {
+      rev_walk.push_range(&range)
+    } else {
+      self
+        .inner
+        .revparse_single(&range)
+        .and_then(|object| object.peel_to_commit())
+        .and_then(|commit| rev_walk.push(commit.id()))
+    }
+    .convert(format!("Invalid range [{range}]"))?;
+    rev_walk
+      .map(|oid| {
+        let commit = self
+          .inner
+          .find_commit(oid.convert_without_message()?)
+          .convert_without_message()?;
+        Ok(ConventionalCommit::parse(
+          commit.id().to_string(),
+          &String::from_utf8_lossy(commit.message_bytes()),
+          include_body,
+        ))
       })
+      .collect()
   }
 
   #[napi]
-  /// iterate over all tags calling `cb` on each.
-  /// the callback is provided the tag id and name
-  pub fn tag_foreach(&self, cb: Function<(String, Buffer), bool>) -> Result<()> {
-    self
-      .inner
-      .tag_foreach(|oid, name| {
-        let oid = oid.to_string();
-        let name = name.to_vec();
-        cb.call((oid, name.into())).unwrap_or(false)
-      })
-      .convert_without_message()
+  /// Check a commit message against the `rules` of `options`, e.g. before
+  /// creating a commit, using the parser of `conventionalCommits` for the
+  /// `requireConventional` rule.
+  ///
+  /// Throws an `InvalidArg` error if `subjectRegex` is not a valid regular
+  /// expression.
+  pub fn validate_commit_message(
+    &self,
+    message: String,
+    options: Option<ValidateCommitMessageOptions>,
+  ) -> Result<CommitMessageValidation> {
+    let rules = options.and_then(|o| o.rules).unwrap_or_default();
+    validate_commit_message(&message, &rules)
   }
 
   #[napi]
-  /// Create a diff between a tree and the working directory.
-  ///
-  /// The tree you provide will be used for the "old_file" side of the delta,
-  /// and the working directory will be used for the "new_file" side.
-  ///
-  /// This is not the same as `git diff <treeish>` or `git diff-index
-  /// <treeish>`.  Those commands use information from the index, whereas this
-  /// function strictly returns the differences between the tree and the files
-  /// in the working directory, regardless of the state of the index.  Use
-  /// `tree_to_workdir_with_index` to emulate those commands.
+  /// Summarize the signatures of the commits in `range`, telling whether
+  /// they are signed, in which format and by which key, without verifying
+  /// them.
   ///
-  /// To see difference between this and `tree_to_workdir_with_index`,
-  /// consider the example of a staged file deletion where the file has then
-  /// been put back into the working dir and further modified.  The
-  /// tree-to-workdir diff for that file is 'modified', but `git diff` would
-  /// show status 'deleted' since there is a staged delete.
+  /// `range` is either a revision, listing all its ancestors, or a
+  /// `from..to` range. Commits are listed from the newest to the oldest.
+  pub fn signature_summaries(&self, range: String) -> Result<Vec<SignatureSummary>> {
+    let mut rev_walk = self.inner.revwalk().convert_without_message()?;
+    rev_walk
+      .set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
+      .convert_without_message()?;
+    if range.contains("..") {
+      rev_walk.push_range(&range)
+    } else {
+      self
+        .inner
+        .revparse_single(&range)
+        .and_then(|object| object.peel_to_commit())
+        .and_then(|commit| rev_walk.push(commit.id()))
+    }
+    .convert(format!("Invalid range [{range}]"))?;
+    rev_walk
+      .map(|oid| {
+        let commit = self
+          .inner
+          .find_commit(oid.convert_without_message()?)
+          .convert_without_message()?;
+        let signature = ["gpgsig", "gpgsig-sha256"]
+          .into_iter()
+          .find_map(|field| commit.header_field_bytes(field).ok());
+        Ok(SignatureSummary::parse(
+          commit.id().to_string(),
+          signature.as_deref(),
+        ))
+      })
+      .collect()
+  }
+
+  #[napi]
+  /// List the commits of `branchName` which are not on its upstream branch,
+  /// that is the commits a push would publish.
   ///
-  /// If `None` is passed for `tree`, then an empty tree is used.
-  pub fn diff_tree_to_workdir(
+  /// Defaults to the branch `HEAD` points to. Fails with the `NoUpstream`
+  /// error code if the branch has no upstream branch configured.
+  pub fn outgoing_commits(
     &self,
     env: Env,
-    self_reference: Reference<Repository>,
-    old_tree: Option<&Tree>,
-  ) -> Result<Diff> {
-    let mut diff_options = git2::DiffOptions::default();
-    Ok(Diff {
-      inner: self_reference.share_with(env, |repo| {
-        repo
-          .inner
-          .diff_tree_to_workdir(old_tree.map(|t| t.inner()), Some(&mut diff_options))
-          .convert_without_message()
-      })?,
-    })
+    branch_name: Option<String>,
+  ) -> Result<Vec<CommitSummary>> {
+    let (local, upstream) = self.resolve_upstream(&env, branch_name)?;
+    self.commit_range(upstream, local)
   }
 
   #[napi]
-  /// Create a diff between a tree and the working directory using index data
-  /// to account for staged deletes, tracked files, etc.
+  /// List the commits of the upstream branch of `branchName` which are not on
+  /// `branchName`, that is the commits fetched but not yet merged.
   ///
-  /// This emulates `git diff <tree>` by diffing the tree to the index and
-  /// the index to the working directory and blending the results into a
-  /// single diff that includes staged deleted, etc.
-  pub fn diff_tree_to_workdir_with_index(
+  /// Defaults to the branch `HEAD` points to. Fails with the `NoUpstream`
+  /// error code if the branch has no upstream branch configured.
+  pub fn incoming_commits(
     &self,
     env: Env,
-    self_reference: Reference<Repository>,
-    old_tree: Option<&Tree>,
-  ) -> Result<Diff> {
-    let mut diff_options = git2::DiffOptions::default();
-    Ok(Diff {
-      inner: self_reference.share_with(env, |repo| {
-        repo
-          .inner
-          .diff_tree_to_workdir_with_index(old_tree.map(|t| t.inner()), Some(&mut diff_options))
-          .convert_without_message()
-      })?,
-    })
+    branch_name: Option<String>,
+  ) -> Result<Vec<CommitSummary>> {
+    let (local, upstream) = self.resolve_upstream(&env, branch_name)?;
+    self.commit_range(local, upstream)
   }
 
-  #[napi]
-  pub fn tree_entry_to_object(
+  #[napi(
+    ts_args_type = "filepaths: Array<string>, signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<Array<number | null>>"
+  )]
+  /// Get the latest modified dates of `filepaths` on the libuv thread pool,
+  /// walking the history only once.
+  ///
+  /// The dates are returned in the order of `filepaths`, `null` for the paths
+  /// which are not found in the history.
+  ///
+  /// Aborting the `signal` stops the history walk and rejects the returned
+  /// `Promise` with an `Aborted` error.
+  pub fn get_files_latest_modified_date_async(
     &self,
-    tree_entry: &TreeEntry,
-    this_ref: Reference<Repository>,
     env: Env,
-  ) -> Result<GitObject> {
-    Ok(GitObject {
-      inner: ObjectParent::Repository(this_ref.share_with(env, |repo| {
-        tree_entry
-          .inner
-          .to_object(&repo.inner)
-          .convert_without_message()
-      })?),
-    })
+    self_ref: Reference<Repository>,
+    filepaths: Vec<String>,
+    signal: Option<JsObject>,
+  ) -> Result<AsyncTask<GitDateBatchTask>> {
+    Ok(AsyncTask::new(GitDateBatchTask {
+      repo: RwLock::new(self_ref),
+      filepaths,
+      cancellation: CancellationToken::from_signal(&env, signal)?,
+    }))
   }
 
   #[napi]
-  /// Create new commit in the repository
+  /// Lists all the worktrees for the repository
+  pub fn worktrees(&self) -> Result<Vec<String>> {
+    Ok(
+      self
+        .inner
+        .worktrees()
+        .convert("List worktrees failed")?
+        .iter()
+        .flatten()
+        .map(|name| name.to_owned())
+        .collect(),
+    )
+  }
+
+  #[napi]
+  /// Opens a worktree by name for the given repository
   ///
-  /// If the `update_ref` is not `None`, name of the reference that will be
-  /// updated to point to this commit. If the reference is not direct, it will
-  /// be resolved to a direct reference. Use "HEAD" to update the HEAD of the
-  /// current branch and make it point to this commit. If the reference
-  /// doesn't exist yet, it will be created. If it does exist, the first
-  /// parent must be the tip of this branch.
-  pub fn commit(
-    &self,
-    update_ref: Option<String>,
-    author: &Signature,
-    committer: &Signature,
-    message: String,
-    tree: &Tree,
-  ) -> Result<String> {
-    self
-      .inner
-      .commit(
-        update_ref.as_deref(),
-        author.as_ref(),
-        committer.as_ref(),
-        message.as_str(),
-        tree.as_ref(),
-        &[],
-      )
-      .convert_without_message()
-      .map(|oid| oid.to_string())
+  /// This can open any worktree that the worktrees method returns.
+  pub fn find_worktree(&self, name: String) -> Result<Worktree> {
+    Ok(Worktree {
+      inner: self
+        .inner
+        .find_worktree(&name)
+        .convert(format!("Find worktree [{name}] failed"))?,
+    })
   }
 
   #[napi]
-  /// Create a revwalk that can be used to traverse the commit graph.
-  pub fn rev_walk(&self, this_ref: Reference<Repository>, env: Env) -> Result<RevWalk> {
-    Ok(RevWalk {
-      inner: this_ref.share_with(env, |repo| repo.inner.revwalk().convert_without_message())?,
+  /// Creates a new worktree for the repository
+  ///
+  /// Without a `reference`, the branch `name` is created from `HEAD` unless
+  /// `checkoutExisting` is set and it already exists.
+  pub fn worktree_add(
+    &self,
+    env: Env,
+    name: String,
+    path: String,
+    opts: Option<&WorktreeAddOptions>,
+  ) -> Result<Worktree> {
+    let reference = opts
+      .and_then(|o| o.reference.as_deref())
+      .map(|refname| {
+        self
+          .inner
+          .find_reference(refname)
+          .convert(format!("Find reference [{refname}] failed"))
+      })
+      .transpose()?;
+    let checkout_existing = opts.is_some_and(|o| o.checkout_existing);
+    if reference.is_none()
+      && self.ref_update_guard.is_some()
+      && !(checkout_existing
+        && self
+          .inner
+          .find_branch(&name, git2::BranchType::Local)
+          .is_ok())
+    {
+      let head = self.inner.head().convert("Resolve HEAD failed")?;
+      self.check_ref_update(
+        &env,
+        RefUpdate::new(&format!("refs/heads/{name}"), None, head.target()),
+      )?;
+    }
+    let mut add_options = git2::WorktreeAddOptions::new();
+    if let Some(opts) = opts {
+      add_options.lock(opts.lock);
+      add_options.checkout_existing(opts.checkout_existing);
+    }
+    add_options.reference(reference.as_ref());
+    Ok(Worktree {
+      inner: self
+        .inner
+        .worktree(&name, Path::new(&path), Some(&add_options))
+        .convert(format!("Add worktree [{name}] failed"))?,
     })
   }
 
   #[napi]
   pub fn get_file_latest_modified_date(&self, filepath: String) -> Result<i64> {
-    get_file_modified_date(&self.inner, &filepath)
+    get_file_modified_date(&self.inner, &filepath, &CancellationToken::default())
       .convert_without_message()
       .and_then(|value| value.expect_not_null(format!("Failed to get commit for [{filepath}]")))
   }
 
-  #[napi]
+  #[napi(
+    ts_args_type = "filepath: string, signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<number>"
+  )]
+  /// Get the latest modified date of `filepath` on the libuv thread pool.
+  ///
+  /// Aborting the `signal` stops the history walk and rejects the returned
+  /// `Promise` with an `Aborted` error.
   pub fn get_file_latest_modified_date_async(
     &self,
+    env: Env,
     self_ref: Reference<Repository>,
     filepath: String,
-    signal: Option<AbortSignal>,
+    signal: Option<JsObject>,
   ) -> Result<AsyncTask<GitDateTask>> {
-    Ok(AsyncTask::with_optional_signal(
-      GitDateTask {
-        repo: RwLock::new(self_ref),
-        filepath,
-      },
-      signal,
-    ))
+    Ok(AsyncTask::new(GitDateTask {
+      repo: RwLock::new(self_ref),
+      filepath,
+      cancellation: CancellationToken::from_signal(&env, signal)?,
+    }))
+  }
+
+  #[napi(
+    ts_args_type = "signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<Array<PackAnalysis>>"
+  )]
+  /// Analyze the pack files of the repository on the libuv thread pool,
+  /// reading the header of every packed object.
+  ///
+  /// The packs of the alternates are not analyzed.
+  ///
+  /// Aborting the `signal` rejects the returned `Promise` with an `Aborted`
+  /// error.
+  pub fn pack_analysis(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    signal: Option<JsObject>,
+  ) -> Result<AsyncTask<PackAnalysisTask>> {
+    Ok(AsyncTask::new(PackAnalysisTask {
+      repo: self_ref,
+      cancellation: CancellationToken::from_signal(&env, signal)?,
+    }))
+  }
+
+  #[napi(
+    ts_args_type = "options?: MaintenanceReportOptions | undefined | null, signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<MaintenanceReport>"
+  )]
+  /// Count the loose objects, the packs and the loose references of the
+  /// repository on the libuv thread pool and recommend the maintenance tasks
+  /// their numbers call for.
+  ///
+  /// Aborting the `signal` rejects the returned `Promise` with an `Aborted`
+  /// error.
+  pub fn maintenance_report(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    options: Option<MaintenanceReportOptions>,
+    signal: Option<JsObject>,
+  ) -> Result<AsyncTask<MaintenanceReportTask>> {
+    Ok(AsyncTask::new(MaintenanceReportTask {
+      repo: self_ref,
+      options: options.unwrap_or_default(),
+      cancellation: CancellationToken::from_signal(&env, signal)?,
+    }))
+  }
+
+  #[napi(
+    ts_args_type = "prefix: string, options?: SubtreeSplitOptions | undefined | null, signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<SubtreeSplit>"
+  )]
+  /// Rewrite the history of the directory `prefix` into new commits whose
+  /// tree is the one of the directory, like `git subtree split -P <prefix>`,
+  /// on the libuv thread pool.
+  ///
+  /// The authors, committers and messages are kept, and the commits which
+  /// leave the directory unchanged are skipped. Merges are only kept when
+  /// they join rewritten histories which diverged, so the non-merge commits
+  /// of the split history are the ones `git log --no-merges -- <prefix>`
+  /// lists.
+  ///
+  /// Aborting the `signal` rejects the returned `Promise` with an `Aborted`
+  /// error.
+  pub fn subtree_split(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    prefix: String,
+    options: Option<SubtreeSplitOptions>,
+    signal: Option<JsObject>,
+  ) -> Result<AsyncTask<SubtreeSplitTask>> {
+    Ok(AsyncTask::new(SubtreeSplitTask::new(
+      env,
+      self_ref,
+      prefix,
+      options,
+      CancellationToken::from_signal(&env, signal)?,
+    )?))
+  }
+
+  #[napi]
+  /// List the commits reachable from `HEAD`, the creations of the annotated
+  /// tags and the movements of the references recorded in their reflogs,
+  /// from the newest to the oldest, e.g. to render an activity feed.
+  ///
+  /// The reflog entries recording a commit listed as a commit event are
+  /// skipped, as is the reflog of `HEAD` which mirrors the one of the
+  /// branches.
+  pub fn timeline(&self, options: Option<TimelineOptions>) -> Result<Vec<TimelineEvent>> {
+    timeline::timeline(
+      &self.inner,
+      &options.unwrap_or_default(),
+      &CancellationToken::default(),
+    )
+  }
+
+  #[napi(
+    ts_args_type = "options?: TimelineOptions | undefined | null, signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<Array<TimelineEvent>>"
+  )]
+  /// List the events of `timeline` on the libuv thread pool.
+  ///
+  /// Aborting the `signal` rejects the returned `Promise` with an `Aborted`
+  /// error.
+  pub fn timeline_async(
+    &self,
+    env: Env,
+    self_ref: Reference<Repository>,
+    options: Option<TimelineOptions>,
+    signal: Option<JsObject>,
+  ) -> Result<AsyncTask<TimelineTask>> {
+    Ok(AsyncTask::new(TimelineTask {
+      repo: self_ref,
+      options: options.unwrap_or_default(),
+      cancellation: CancellationToken::from_signal(&env, signal)?,
+    }))
+  }
+}
+
+/// Write a new file at `path`, creating its parent directories, as a symbolic
+/// link to `content` if `mode` is the mode of links and the platform has them.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn write_checkout_file(path: &Path, content: &[u8], mode: i32) -> std::io::Result<()> {
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  #[cfg(unix)]
+  if mode == i32::from(git2::FileMode::Link) {
+    use std::os::unix::ffi::OsStrExt;
+
+    return std::os::unix::fs::symlink(std::ffi::OsStr::from_bytes(content), path);
+  }
+  let mut options = std::fs::OpenOptions::new();
+  options.write(true).create_new(true);
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let executable = mode == i32::from(git2::FileMode::BlobExecutable);
+    options.mode(if executable { 0o755 } else { 0o644 });
   }
+  std::io::Write::write_all(&mut options.open(path)?, content)
 }
 
 fn get_file_modified_date(
   repo: &git2::Repository,
   filepath: &str,
+  cancellation: &CancellationToken,
 ) -> std::result::Result<Option<i64>, git2::Error> {
   let mut diff_options = git2::DiffOptions::new();
   diff_options.disable_pathspec_match(false);
@@ -884,6 +3765,7 @@ fn get_file_modified_date(
   Ok(
     rev_walk
       .by_ref()
+      .take_while(|_| !cancellation.is_cancelled())
       .filter_map(|oid| oid.ok())
       .find_map(|oid| {
         let commit = repo.find_commit(oid).ok()?;
@@ -916,3 +3798,475 @@ fn get_file_modified_date(
       }),
   )
 }
+
+impl Repository {
+  /// Consult the guard installed with `setRefUpdateGuard`, if any.
+  pub(crate) fn check_ref_update(&self, env: &Env, update: RefUpdate) -> Result<()> {
+    let Some(guard) = &self.ref_update_guard else {
+      return Ok(());
+    };
+    let refname = update.refname.clone();
+    match guard.borrow_back(env)?.call(update)? {
+      Either::A(true) => Ok(()),
+      Either::A(false) => Err(Error::new(
+        Status::GenericFailure,
+        format!("Update of reference [{refname}] rejected by the ref update guard"),
+      )),
+      Either::B(message) => Err(Error::new(Status::GenericFailure, message)),
+    }
+  }
+
+  /// Copy the target of a reference about to be deleted into the namespace of
+  /// `enableBranchRecovery`, if it is enabled.
+  pub(crate) fn record_deletion(
+    &self,
+    env: &Env,
+    refname: &str,
+    target: Option<git2::Oid>,
+  ) -> Result<()> {
+    let (Some(namespace), Some(target)) = (&self.branch_recovery, target) else {
+      return Ok(());
+    };
+    let name = refname.strip_prefix("refs/heads/").unwrap_or(refname);
+    let deleted_at_ms = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_millis())
+      .unwrap_or_default();
+    let entry = format!("{namespace}{name}@{deleted_at_ms}");
+    self.check_ref_update(env, RefUpdate::new(&entry, None, Some(target)))?;
+    self
+      .inner
+      .reference(&entry, target, false, &format!("delete: keep {refname}"))
+      .convert(format!("Keep the target of [{refname}] failed"))?;
+    Ok(())
+  }
+
+  /// Resolve `HEAD` to the branch it points to, `None` if it is detached.
+  fn resolve_branch_refname(&self, refname: String) -> Option<String> {
+    if refname != "HEAD" {
+      return Some(refname);
+    }
+    let head = self.inner.find_reference("HEAD").ok()?;
+    head.symbolic_target().map(ToOwned::to_owned)
+  }
+
+  /// Resolve a revspec to an annotated commit, keeping the reference it names
+  /// if any so that operations like rebase can update it.
+  fn annotated_commit(&self, spec: &str) -> Result<git2::AnnotatedCommit<'_>> {
+    if let Ok(reference) = self.inner.resolve_reference_from_short_name(spec) {
+      return self
+        .inner
+        .reference_to_annotated_commit(&reference)
+        .convert(format!("Resolve [{spec}] failed"));
+    }
+    let commit = self
+      .inner
+      .revparse_single(spec)
+      .and_then(|object| object.peel_to_commit())
+      .convert(format!("Resolve [{spec}] failed"))?;
+    self
+      .inner
+      .find_annotated_commit(commit.id())
+      .convert(format!("Resolve [{spec}] failed"))
+  }
+
+  /// Apply the hunks at `hunk_indexes` of the single file `diff` to the index,
+  /// checking that the listed side, the old one if `reversed`, is still
+  /// `expected_oid`.
+  fn apply_hunks(
+    &self,
+    env: &Env,
+    diff: &git2::Diff,
+    path: &str,
+    hunk_indexes: &[u32],
+    expected_oid: Option<String>,
+    reversed: bool,
+  ) -> Result<()> {
+    let patch =
+      git2::Patch::from_diff(diff, 0).convert(format!("Compute the patch of [{path}] failed"))?;
+    if let Some(expected_oid) = expected_oid {
+      let listed = patch.as_ref().map(|patch| {
+        let delta = patch.delta();
+        if reversed {
+          delta.old_file().id()
+        } else {
+          delta.new_file().id()
+        }
+      });
+      if listed.is_none_or(|listed| listed.to_string() != expected_oid) {
+        return Err(coded_error(
+          env,
+          STALE_HUNKS,
+          format!("[{path}] changed since its hunks were listed"),
+        ));
+      }
+    }
+    let hunks = patch.as_ref().map_or(0, |patch| patch.num_hunks());
+    if let Some(index) = hunk_indexes.iter().find(|index| **index as usize >= hunks) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("[{path}] has no hunk at index {index}, it has {hunks} hunks"),
+      ));
+    }
+    if hunk_indexes.is_empty() {
+      return Ok(());
+    }
+    let selected = hunk_indexes.iter().copied().collect::<HashSet<_>>();
+    let mut hunk_index = 0;
+    let mut options = git2::ApplyOptions::new();
+    options.hunk_callback(|_| {
+      let apply = selected.contains(&hunk_index);
+      hunk_index += 1;
+      apply
+    });
+    self
+      .inner
+      .apply(diff, git2::ApplyLocation::Index, Some(&mut options))
+      .convert(format!("Apply the hunks of [{path}] to the index failed"))
+  }
+
+  /// The commit `HEAD` points to, failing with `UnbornHead` if there is none.
+  fn resolve_head_commit(&self, env: &Env) -> Result<git2::Commit<'_>> {
+    match self.inner.head() {
+      Ok(head) => head
+        .peel_to_commit()
+        .convert("Resolve the commit of HEAD failed"),
+      Err(err) if err.code() == git2::ErrorCode::UnbornBranch => Err(coded_error(
+        env,
+        UNBORN_HEAD,
+        "HEAD points to a branch without commits",
+      )),
+      Err(err) => Err(err).convert("Get the HEAD of Repository failed"),
+    }
+  }
+
+  /// Move `refname` from `old` to `new` through the ref update guard,
+  /// failing if it was moved concurrently.
+  fn move_branch(
+    &self,
+    env: &Env,
+    refname: &str,
+    old: git2::Oid,
+    new: git2::Oid,
+    log_message: &str,
+  ) -> Result<()> {
+    self.check_ref_update(env, RefUpdate::new(refname, Some(old), Some(new)))?;
+    self
+      .inner
+      .reference_matching(refname, new, true, old, log_message)
+      .convert(format!("Update reference [{refname}] failed"))?;
+    Ok(())
+  }
+
+  fn check_tag_update(&self, env: &Env, name: &str, new_oid: Option<git2::Oid>) -> Result<()> {
+    if self.ref_update_guard.is_none() {
+      return Ok(());
+    }
+    let refname = format!("refs/tags/{name}");
+    let old_oid = self.inner.refname_to_id(&refname).ok();
+    self.check_ref_update(env, RefUpdate::new(&refname, old_oid, new_oid))
+  }
+
+  /// Consult the ref update guard about the notes commit `noteCreate` or
+  /// `noteDelete` writes to `notes_ref`.
+  #[allow(clippy::too_many_arguments)]
+  fn check_notes_update(
+    &self,
+    env: &Env,
+    notes_ref: Option<&str>,
+    author: &Signature,
+    committer: &Signature,
+    target: git2::Oid,
+    note: Option<&str>,
+    force: bool,
+  ) -> Result<()> {
+    if self.ref_update_guard.is_none() {
+      return Ok(());
+    }
+    let refname = match notes_ref {
+      Some(notes_ref) => notes_ref.to_owned(),
+      None => self
+        .inner
+        .note_default_ref()
+        .convert("Get the default notes reference failed")?,
+    };
+    let old_oid = self.inner.refname_to_id(&refname).ok();
+    let new_oid = note::notes_commit_id(
+      &self.inner,
+      old_oid,
+      author.as_ref(),
+      committer.as_ref(),
+      target,
+      note,
+      force,
+    )?;
+    match new_oid {
+      Some(new_oid) => self.check_ref_update(env, RefUpdate::new(&refname, old_oid, Some(new_oid))),
+      None => Ok(()),
+    }
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn create_commit(
+    &self,
+    env: &Env,
+    update_ref: Option<String>,
+    author: &Signature,
+    committer: &Signature,
+    message: &str,
+    tree: &Tree,
+    parents: &[&git2::Commit],
+  ) -> Result<String> {
+    if let (Some(update_ref), Some(_)) = (&update_ref, &self.ref_update_guard) {
+      let oid = commit_id(
+        &self.inner,
+        author.as_ref(),
+        committer.as_ref(),
+        None,
+        message,
+        tree.as_ref(),
+        parents,
+      )?;
+      let (refname, old_oid) = self.resolve_update_ref(update_ref)?;
+      self.check_ref_update(env, RefUpdate::new(&refname, old_oid, Some(oid)))?;
+    }
+    self
+      .inner
+      .commit(
+        update_ref.as_deref(),
+        author.as_ref(),
+        committer.as_ref(),
+        message,
+        tree.as_ref(),
+        parents,
+      )
+      .convert_without_message()
+      .map(|oid| oid.to_string())
+  }
+
+  /// Resolve the tips of a local branch and of its upstream branch.
+  fn resolve_upstream(
+    &self,
+    env: &Env,
+    branch_name: Option<String>,
+  ) -> Result<(git2::Oid, git2::Oid)> {
+    let branch = match branch_name {
+      Some(name) => self
+        .inner
+        .find_branch(&name, git2::BranchType::Local)
+        .convert(format!("Find branch [{name}] failed"))?,
+      None => {
+        let head = self.inner.head().convert("Resolve HEAD failed")?;
+        if !head.is_branch() {
+          return Err(Error::new(
+            Status::GenericFailure,
+            "HEAD does not point to a branch".to_owned(),
+          ));
+        }
+        git2::Branch::wrap(head)
+      }
+    };
+    let name = String::from_utf8_lossy(branch.name_bytes().convert_without_message()?).into_owned();
+    let upstream = match branch.upstream() {
+      Ok(upstream) => upstream,
+      Err(err) if err.code() == git2::ErrorCode::NotFound => {
+        return Err(coded_error(
+          env,
+          NO_UPSTREAM,
+          format!("Branch [{name}] has no upstream branch"),
+        ))
+      }
+      Err(err) => return Err(err).convert(format!("Resolve upstream of branch [{name}] failed")),
+    };
+    let local = branch
+      .get()
+      .target()
+      .expect_not_null(format!("Branch [{name}] has no target"))?;
+    let upstream = upstream
+      .get()
+      .target()
+      .expect_not_null(format!("Upstream of branch [{name}] has no target"))?;
+    Ok((local, upstream))
+  }
+
+  /// List the commits reachable from `to` but not from `from`, newest first.
+  pub(crate) fn commit_range(&self, from: git2::Oid, to: git2::Oid) -> Result<Vec<CommitSummary>> {
+    let mut rev_walk = self.inner.revwalk().convert_without_message()?;
+    rev_walk
+      .set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
+      .convert_without_message()?;
+    rev_walk.push(to).convert_without_message()?;
+    rev_walk.hide(from).convert_without_message()?;
+    rev_walk
+      .map(|oid| {
+        let commit = self
+          .inner
+          .find_commit(oid.convert_without_message()?)
+          .convert_without_message()?;
+        Ok(CommitSummary::from(&commit))
+      })
+      .collect()
+  }
+
+  /// Resolve the reference updated by a commit through symbolic references
+  /// such as `HEAD`, returning its name and current target.
+  ///
+  /// Like libgit2, gives up after [`MAX_SYMREF_DEPTH`] symbolic references.
+  pub(crate) fn resolve_update_ref(&self, update_ref: &str) -> Result<(String, Option<git2::Oid>)> {
+    let mut chain = vec![update_ref.to_owned()];
+    loop {
+      let refname = chain.last().expect("chain starts with update_ref");
+      let target = match self.inner.find_reference(refname) {
+        Ok(reference) => match reference.symbolic_target() {
+          Some(target) => target.to_owned(),
+          None => return Ok((refname.clone(), reference.target())),
+        },
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok((refname.clone(), None)),
+        Err(err) => return Err(err).convert(format!("Find reference [{refname}] failed")),
+      };
+      let cycle = chain.contains(&target);
+      chain.push(target);
+      if cycle || chain.len() - 1 > MAX_SYMREF_DEPTH {
+        let kind = if cycle { "cycle" } else { "chain too deep" };
+        return Err(Error::new(
+          Status::GenericFailure,
+          format!("Symbolic reference {kind}: [{}]", chain.join(" -> ")),
+        ));
+      }
+    }
+  }
+}
+
+/// Compute the id of a commit without writing it, e.g. to consult the ref
+/// update guard before creating it.
+pub(crate) fn commit_id(
+  repo: &git2::Repository,
+  author: &git2::Signature,
+  committer: &git2::Signature,
+  message_encoding: Option<&str>,
+  message: &str,
+  tree: &git2::Tree,
+  parents: &[&git2::Commit],
+) -> Result<git2::Oid> {
+  let mut buf = repo
+    .commit_create_buffer(author, committer, message, tree, parents)
+    .convert_without_message()?
+    .to_vec();
+  // `commit_create_buffer` takes no encoding, libgit2 writes it as the last
+  // header before the message
+  if let Some(encoding) = message_encoding {
+    let end = buf
+      .windows(2)
+      .position(|window| window == b"\n\n")
+      .map_or(buf.len(), |position| position + 1);
+    buf.splice(end..end, format!("encoding {encoding}\n").into_bytes());
+  }
+  git2::Oid::hash_object(git2::ObjectType::Commit, &buf).convert_without_message()
+}
+
+/// Compute the id of the annotated tag `tag` writes, without writing it.
+fn tag_id(
+  name: &str,
+  target: &git2::Object,
+  tagger: &git2::Signature,
+  message: &str,
+) -> Result<git2::Oid> {
+  let kind = target
+    .kind()
+    .expect_not_null(format!("Object [{}] has no type", target.id()))?;
+  let when = tagger.when();
+  let offset = when.offset_minutes();
+  let sign = if offset < 0 || when.sign() == '-' {
+    '-'
+  } else {
+    '+'
+  };
+  let mut buf = format!("object {}\ntype {kind}\ntag {name}\ntagger ", target.id()).into_bytes();
+  buf.extend_from_slice(tagger.name_bytes());
+  buf.extend_from_slice(b" <");
+  buf.extend_from_slice(tagger.email_bytes());
+  buf.extend_from_slice(
+    format!(
+      "> {} {sign}{:02}{:02}\n\n{message}",
+      when.seconds() as u32,
+      offset.abs() / 60,
+      offset.abs() % 60
+    )
+    .as_bytes(),
+  );
+  git2::Oid::hash_object(git2::ObjectType::Tag, &buf).convert_without_message()
+}
+
+fn get_files_modified_date(
+  repo: &git2::Repository,
+  filepaths: &[String],
+  cancellation: &CancellationToken,
+) -> std::result::Result<Vec<Option<i64>>, git2::Error> {
+  let mut pending: HashSet<PathBuf> = filepaths.iter().map(PathBuf::from).collect();
+  let mut found: HashMap<PathBuf, i64> = HashMap::with_capacity(pending.len());
+  let mut rev_walk = repo.revwalk()?;
+  rev_walk.push_head()?;
+  rev_walk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)?;
+  for oid in rev_walk {
+    if pending.is_empty() || cancellation.is_cancelled() {
+      break;
+    }
+    let commit = repo.find_commit(oid?)?;
+    let time = commit.time().seconds() * 1000;
+    match commit.parent_count() {
+      // commit with parent
+      1 => {
+        let mut diff_options = git2::DiffOptions::new();
+        diff_options.disable_pathspec_match(true);
+        for path in &pending {
+          diff_options.pathspec(path);
+        }
+        let diff = repo.diff_tree_to_tree(
+          Some(&commit.parent(0)?.tree()?),
+          Some(&commit.tree()?),
+          Some(&mut diff_options),
+        )?;
+        for delta in diff.deltas() {
+          for path in [delta.old_file().path(), delta.new_file().path()]
+            .into_iter()
+            .flatten()
+          {
+            if let Some(path) = pending.take(path) {
+              found.insert(path, time);
+            }
+          }
+        }
+      }
+      // root commit
+      0 => {
+        let tree = commit.tree()?;
+        pending.retain(|path| {
+          if tree.get_path(path).is_ok() {
+            found.insert(path.clone(), time);
+            return false;
+          }
+          true
+        });
+      }
+      // ignore merge commits
+      _ => {}
+    }
+  }
+  Ok(
+    filepaths
+      .iter()
+      .map(|path| found.get(Path::new(path)).copied())
+      .collect(),
+  )
+}
+
+/// Map a `NotFound` error to `None`.
+fn not_found_as_none<T>(
+  result: std::result::Result<T, git2::Error>,
+) -> std::result::Result<Option<T>, git2::Error> {
+  match result {
+    Ok(value) => Ok(Some(value)),
+    Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+    Err(err) => Err(err),
+  }
+}
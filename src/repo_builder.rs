@@ -1,13 +1,19 @@
-use std::{mem, path::Path};
+use std::path::Path;
 
-use napi::bindgen_prelude::*;
+use napi::{bindgen_prelude::*, JsObject};
 use napi_derive::napi;
 
-use crate::{error::IntoNapiError, remote::FetchOptions, repo::Repository};
+use crate::{
+  error::IntoNapiError,
+  remote::FetchOptions,
+  repo::Repository,
+  util::{CallbackError, CancellationToken},
+};
 
 #[napi]
 pub struct RepoBuilder {
   builder: git2::build::RepoBuilder<'static>,
+  fetch_options: Option<FetchOptions>,
 }
 
 #[napi]
@@ -83,6 +89,7 @@ impl RepoBuilder {
   pub fn new() -> Self {
     Self {
       builder: Default::default(),
+      fetch_options: None,
     }
   }
 
@@ -117,29 +124,115 @@ impl RepoBuilder {
   #[napi]
   /// Options which control the fetch, including callbacks.
   ///
-  /// The callbacks are used for reporting fetch progress, and for acquiring
-  /// credentials in the event they are needed.
-  pub fn fetch_options(&mut self, fetch_options: &mut FetchOptions) -> Result<&Self> {
-    if fetch_options.used {
-      return Err(Error::new(
-        Status::GenericFailure,
-        "FetchOptions has been used, please create a new one",
-      ));
-    }
-    let mut opt = git2::FetchOptions::default();
-    mem::swap(&mut fetch_options.inner, &mut opt);
-    fetch_options.used = true;
-    self.builder.fetch_options(opt);
-    Ok(self)
+  /// The callbacks are used for reporting fetch progress, for acquiring
+  /// credentials in the event they are needed, and for checking the
+  /// certificate of the remote, e.g. to trust a self-signed certificate.
+  ///
+  /// The options can be reused, a failed clone can be retried by calling
+  /// `clone` again.
+  pub fn fetch_options(&mut self, fetch_options: &FetchOptions) -> &Self {
+    self.fetch_options = Some(fetch_options.clone());
+    self
   }
 
   #[napi]
   pub fn clone(&mut self, url: String, path: String) -> Result<Repository> {
+    let (options, callback_error) = self
+      .fetch_options
+      .as_ref()
+      .map(|o| o.build(Some(&url)))
+      .unwrap_or_default();
+    self.builder.fetch_options(options);
+    let result = self
+      .builder
+      .clone(&url, Path::new(&path))
+      .convert("Clone failed");
+    Ok(Repository {
+      inner: callback_error.check(result)?,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
+    })
+  }
+
+  #[napi(
+    ts_args_type = "url: string, path: string, signal?: AbortSignal | undefined | null",
+    ts_return_type = "Promise<Repository>"
+  )]
+  /// Clone on the libuv thread pool.
+  ///
+  /// Callbacks set on the `RemoteCallbacks` of the fetch options are called
+  /// back on the JavaScript main thread while the clone is running.
+  ///
+  /// Aborting the `signal` interrupts the transfer and rejects the returned
+  /// `Promise` with an `Aborted` error.
+  pub fn clone_async(
+    &self,
+    env: Env,
+    self_ref: Reference<RepoBuilder>,
+    url: String,
+    path: String,
+    signal: Option<JsObject>,
+  ) -> Result<AsyncTask<CloneTask>> {
+    let cancellation = CancellationToken::from_signal(&env, signal)?;
+    let (options, callback_error) = self
+      .fetch_options
+      .clone()
+      .unwrap_or_else(FetchOptions::new)
+      .build_cancellable(Some(&url), Some(&cancellation));
+    Ok(AsyncTask::new(CloneTask {
+      builder: self_ref,
+      url,
+      path,
+      options: Some(options),
+      callback_error,
+      cancellation,
+    }))
+  }
+}
+
+pub struct CloneTask {
+  builder: Reference<RepoBuilder>,
+  url: String,
+  path: String,
+  options: Option<git2::FetchOptions<'static>>,
+  callback_error: CallbackError,
+  cancellation: CancellationToken,
+}
+
+unsafe impl Send for CloneTask {}
+
+#[napi]
+impl Task for CloneTask {
+  type Output = git2::Repository;
+  type JsValue = Repository;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    self.cancellation.check()?;
+    if let Some(options) = self.options.take() {
+      self.builder.builder.fetch_options(options);
+    }
+    let result = self
+      .builder
+      .builder
+      .clone(&self.url, Path::new(&self.path))
+      .convert("Clone failed");
+    self.callback_error.check(result)
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    self.cancellation.detach(env)?;
     Ok(Repository {
-      inner: self
-        .builder
-        .clone(&url, Path::new(&path))
-        .convert("Clone failed")?,
+      inner: output,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
     })
   }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    self.cancellation.reject(env, err)
+  }
 }
@@ -0,0 +1,182 @@
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+  time::{Duration, Instant, SystemTime},
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::repo::Repository;
+
+const DEFAULT_MAX_OPEN: u32 = 64;
+
+#[napi(object)]
+pub struct RepositoryPoolOptions {
+  /// The maximum number of repositories kept open, the least recently used
+  /// repository is closed once it is exceeded.
+  ///
+  /// Defaults to 64.
+  pub max_open: Option<u32>,
+  /// Close repositories which have not been used for this many milliseconds.
+  ///
+  /// Idle repositories are never closed by default.
+  pub idle_timeout_ms: Option<u32>,
+}
+
+struct PoolEntry {
+  repo: Reference<Repository>,
+  modified: Option<Vec<Option<SystemTime>>>,
+  last_used: Instant,
+}
+
+#[napi]
+/// A cache of open repositories, for processes working with many
+/// repositories.
+///
+/// Repositories are keyed by their canonical path. The pool holds at most
+/// `maxOpen` of them, closing the least recently used one first, and reopens a
+/// repository whose git directory, index, branches or tags were modified since
+/// it was opened.
+///
+/// A repository closed by the pool stays usable by JavaScript code still
+/// holding it, its file descriptors are released once it is garbage
+/// collected.
+pub struct RepositoryPool {
+  entries: HashMap<PathBuf, PoolEntry>,
+  max_open: usize,
+  idle_timeout: Option<Duration>,
+}
+
+#[napi]
+impl RepositoryPool {
+  #[napi(constructor)]
+  pub fn new(options: Option<RepositoryPoolOptions>) -> Result<Self> {
+    let max_open = options
+      .as_ref()
+      .and_then(|o| o.max_open)
+      .unwrap_or(DEFAULT_MAX_OPEN);
+    if max_open == 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "maxOpen must be greater than 0".to_owned(),
+      ));
+    }
+    Ok(Self {
+      entries: HashMap::new(),
+      max_open: max_open as usize,
+      idle_timeout: options
+        .and_then(|o| o.idle_timeout_ms)
+        .map(|ms| Duration::from_millis(ms as u64)),
+    })
+  }
+
+  #[napi(getter)]
+  /// The number of repositories currently held open by the pool.
+  pub fn size(&self) -> u32 {
+    self.entries.len() as u32
+  }
+
+  #[napi]
+  /// Get the repository at `path`, opening it if it is not in the pool.
+  pub fn get(&mut self, env: Env, path: String) -> Result<Reference<Repository>> {
+    let key = fs::canonicalize(&path).map_err(|err| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to open git repo: [{path}], reason: {err}"),
+      )
+    })?;
+    self.close_idle();
+    let now = Instant::now();
+    if let Some(entry) = self.entries.get_mut(&key) {
+      if entry.modified.is_some() && entry.modified == modified_times(&entry.repo) {
+        entry.last_used = now;
+        return entry.repo.clone(env);
+      }
+      self.entries.remove(&key);
+    }
+    let repo = Repository::into_reference(Repository::new(path)?, env)?;
+    let entry = PoolEntry {
+      modified: modified_times(&repo),
+      repo: repo.clone(env)?,
+      last_used: now,
+    };
+    self.entries.insert(key, entry);
+    while self.entries.len() > self.max_open {
+      let least_recently_used = self
+        .entries
+        .iter()
+        .min_by_key(|(_, entry)| entry.last_used)
+        .map(|(key, _)| key.clone());
+      if let Some(key) = least_recently_used {
+        self.entries.remove(&key);
+      }
+    }
+    Ok(repo)
+  }
+
+  #[napi(
+    ts_generic_types = "T",
+    ts_args_type = "path: string, callback: (repo: Repository) => T",
+    ts_return_type = "T"
+  )]
+  /// Call `callback` with the repository at `path`, returning its result.
+  pub fn with(
+    &mut self,
+    env: Env,
+    path: String,
+    callback: Function<Reference<Repository>, Unknown>,
+  ) -> Result<Unknown> {
+    callback.call(self.get(env, path)?)
+  }
+
+  #[napi]
+  /// Close the repositories which have been idle for longer than
+  /// `idleTimeoutMs`.
+  ///
+  /// This also happens on every `get`.
+  pub fn close_idle(&mut self) {
+    if let Some(idle_timeout) = self.idle_timeout {
+      let now = Instant::now();
+      self
+        .entries
+        .retain(|_, entry| now.duration_since(entry.last_used) < idle_timeout);
+    }
+  }
+
+  #[napi]
+  /// Close all the repositories held by the pool.
+  pub fn clear(&mut self) {
+    self.entries.clear();
+  }
+}
+
+/// The modification times of the git directory, `HEAD`, the index,
+/// `packed-refs` and the directories of the branches and tags, which are
+/// updated in place without touching the git directory itself, e.g. by a
+/// push.
+///
+/// Only these paths are checked to keep `get` cheap, so the references in
+/// nested directories, e.g. `refs/heads/feature/a` or the remote-tracking
+/// branches, are not noticed until one of these paths changes too, e.g. once
+/// they are packed.
+fn modified_times(repo: &Repository) -> Option<Vec<Option<SystemTime>>> {
+  let modified = |path: &Path| {
+    fs::metadata(path)
+      .and_then(|metadata| metadata.modified())
+      .ok()
+  };
+  let git_dir = repo.inner.path();
+  let common_dir = repo.inner.commondir();
+  let mut times = vec![
+    Some(modified(git_dir)?),
+    modified(&git_dir.join("HEAD")),
+    modified(&git_dir.join("index")),
+    modified(&common_dir.join("packed-refs")),
+  ];
+  for dir in ["refs", "refs/heads", "refs/tags"] {
+    times.push(modified(&common_dir.join(dir)));
+  }
+  Some(times)
+}
@@ -0,0 +1,78 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::transport::TransportInfo;
+
+/// The namespace of the references holding the tips fetched by
+/// `Repository.fetchIntoOdb` until they are finalized.
+const STAGING_PREFIX: &str = "refs/fetch-staging/";
+
+#[napi(object)]
+pub struct FetchIntoOdbOptions {
+  /// The `resumeToken` of a previous attempt, whose fetched objects are not
+  /// transferred again.
+  pub resume_token: Option<String>,
+}
+
+#[napi(object)]
+pub struct FetchedRef {
+  pub refname: String,
+  pub oid: String,
+}
+
+#[napi(object)]
+/// The outcome of `Repository.fetchIntoOdb`.
+pub struct ResumableFetch {
+  /// Pass it to `fetchIntoOdb` to resume an incomplete fetch.
+  pub resume_token: String,
+  /// Whether every refspec was fetched.
+  pub complete: bool,
+  /// The references fetched so far and their targets, which
+  /// `Repository.finalizeFetched` writes.
+  pub refs: Vec<FetchedRef>,
+  /// The number of objects received by this attempt.
+  pub received_objects: u32,
+  /// The error which interrupted the fetch.
+  ///
+  /// Not set if it is complete.
+  pub error: Option<String>,
+  /// How the remote was reached.
+  pub transport: TransportInfo,
+}
+
+/// A token naming the staging namespace of a new fetch.
+pub(crate) fn new_token() -> String {
+  let nanos = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_nanos())
+    .unwrap_or_default();
+  format!("{:x}{:x}", nanos, std::process::id())
+}
+
+/// The prefix of the staged references of the fetch named by `token`.
+pub(crate) fn staging_namespace(token: &str) -> Result<String> {
+  if token.is_empty() || !token.chars().all(|c| c.is_ascii_hexdigit()) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid resume token [{token}]"),
+    ));
+  }
+  Ok(format!("{STAGING_PREFIX}{token}/"))
+}
+
+/// Rewrite `refspec` to write its destination in the staging `namespace`.
+pub(crate) fn staging_refspec(namespace: &str, refspec: &str) -> Result<String> {
+  let (src, dst) = refspec
+    .trim_start_matches('+')
+    .split_once(':')
+    .filter(|(_, dst)| dst.starts_with("refs/"))
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Refspec [{refspec}] needs a destination under refs/"),
+      )
+    })?;
+  Ok(format!("+{src}:{namespace}{dst}"))
+}
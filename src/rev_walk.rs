@@ -1,7 +1,10 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use crate::{error::IntoNapiError, repo::Repository};
+use crate::{
+  branch::BranchType, commit::CommitSummary, error::IntoNapiError, repo::Repository,
+  stats::OpenHandle, util::CancellationToken,
+};
 
 #[napi]
 /// Orderings that may be specified for Revwalk iteration.
@@ -44,9 +47,105 @@ impl From<Sort> for git2::Sort {
   }
 }
 
+#[napi(object)]
+#[derive(Default)]
+/// The configuration of the revwalk of `Repository.log`.
+pub struct LogOptions {
+  /// The revisions to walk from, either single revisions or `from..to`
+  /// ranges.
+  ///
+  /// Not set to walk from `HEAD`.
+  pub revs: Option<Vec<String>>,
+  /// The revisions whose ancestors are not listed.
+  pub hide: Option<Vec<String>>,
+  /// The sorting modes, combined.
+  ///
+  /// Not set to use the default sorting of libgit2.
+  pub sorting: Option<Vec<Sort>>,
+  /// Only follow the first parent of merges, like `git log --first-parent`.
+  pub first_parent: Option<bool>,
+  /// List at most this many commits.
+  pub limit: Option<u32>,
+}
+
+pub struct LogTask {
+  pub(crate) repo: Reference<Repository>,
+  pub(crate) options: LogOptions,
+  pub(crate) cancellation: CancellationToken,
+}
+
+unsafe impl Send for LogTask {}
+
+#[napi]
+impl Task for LogTask {
+  type Output = Vec<CommitSummary>;
+  type JsValue = Vec<CommitSummary>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let repo = &self.repo.inner;
+    let mut rev_walk = repo.revwalk().convert_without_message()?;
+    if let Some(sorting) = &self.options.sorting {
+      let sorting = sorting
+        .iter()
+        .fold(git2::Sort::NONE, |sorting, sort| sorting | (*sort).into());
+      rev_walk.set_sorting(sorting).convert_without_message()?;
+    }
+    if self.options.first_parent.unwrap_or(false) {
+      rev_walk.simplify_first_parent().convert_without_message()?;
+    }
+    match &self.options.revs {
+      Some(revs) => {
+        for rev in revs {
+          if rev.contains("..") {
+            rev_walk.push_range(rev)
+          } else {
+            repo
+              .revparse_single(rev)
+              .and_then(|object| object.peel_to_commit())
+              .and_then(|commit| rev_walk.push(commit.id()))
+          }
+          .convert(format!("Push [{rev}] failed"))?;
+        }
+      }
+      None => rev_walk.push_head().convert("Push HEAD failed")?,
+    }
+    for rev in self.options.hide.iter().flatten() {
+      repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .and_then(|commit| rev_walk.hide(commit.id()))
+        .convert(format!("Hide [{rev}] failed"))?;
+    }
+    let limit = self
+      .options
+      .limit
+      .map_or(usize::MAX, |limit| limit as usize);
+    let mut commits = Vec::new();
+    for oid in rev_walk.take(limit) {
+      self.cancellation.check()?;
+      let commit = repo
+        .find_commit(oid.convert("Walk the commits failed")?)
+        .convert_without_message()?;
+      commits.push(CommitSummary::from(&commit));
+    }
+    Ok(commits)
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    self.cancellation.detach(env)?;
+    Ok(output)
+  }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    self.cancellation.reject(env, err)
+  }
+}
+
 #[napi(iterator)]
 pub struct RevWalk {
   pub(crate) inner: SharedReference<Repository, git2::Revwalk<'static>>,
+  /// Released once the iteration completes.
+  pub(crate) _handle: Option<OpenHandle>,
 }
 
 #[napi]
@@ -56,10 +155,14 @@ impl Generator for RevWalk {
   type Next = ();
 
   fn next(&mut self, _value: Option<Self::Next>) -> Option<Self::Yield> {
-    self
+    let next = self
       .inner
       .next()
-      .and_then(|s| s.ok().map(|oid| oid.to_string()))
+      .and_then(|s| s.ok().map(|oid| oid.to_string()));
+    if next.is_none() {
+      self._handle = None;
+    }
+    next
   }
 }
 
@@ -147,6 +250,16 @@ impl RevWalk {
     Ok(self)
   }
 
+  #[napi]
+  /// Push `to` and hide `from`, like `push_range` with `<from>..<to>`.
+  pub fn push_between(&mut self, from: String, to: String) -> Result<&Self> {
+    self
+      .inner
+      .push_range(&format!("{from}..{to}"))
+      .convert(format!("Invalid range [{from}..{to}]"))?;
+    Ok(self)
+  }
+
   #[napi]
   /// Push the OID pointed to by a reference
   ///
@@ -197,4 +310,50 @@ impl RevWalk {
     self.inner.hide_ref(&reference).convert_without_message()?;
     Ok(self)
   }
+
+  #[napi]
+  /// Hide the commits reachable from the branches of `branch_type`.
+  pub fn hide_refs_of_type(&mut self, env: Env, branch_type: BranchType) -> Result<&Self> {
+    let repo = self.inner.clone_owner(env)?;
+    let branches = repo
+      .inner
+      .branches(Some(branch_type.into()))
+      .convert("List branches failed")?;
+    for branch in branches {
+      let (branch, _) = branch.convert_without_message()?;
+      let Some(refname) = branch.get().name() else {
+        continue;
+      };
+      self
+        .inner
+        .hide_ref(refname)
+        .convert(format!("Hide reference [{refname}] failed"))?;
+    }
+    Ok(self)
+  }
+
+  #[napi]
+  /// Drain the revwalk, returning the number of commits it yields.
+  pub fn count_commits(&mut self) -> Result<u32> {
+    let mut count = 0;
+    for oid in self.inner.by_ref() {
+      oid.convert("Walk the commits failed")?;
+      count += 1;
+    }
+    self._handle = None;
+    Ok(count)
+  }
+
+  #[napi]
+  /// Drain the revwalk, returning the ids of the commits it yields.
+  pub fn collect_oids(&mut self) -> Result<Vec<String>> {
+    let oids = self
+      .inner
+      .by_ref()
+      .map(|oid| oid.map(|oid| oid.to_string()))
+      .collect::<std::result::Result<Vec<_>, _>>()
+      .convert("Walk the commits failed")?;
+    self._handle = None;
+    Ok(oids)
+  }
 }
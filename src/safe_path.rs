@@ -0,0 +1,107 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::error::coded_error_with_properties;
+
+/// The `code` of the error rejecting a path which is unsafe to write.
+pub(crate) const UNSAFE_PATH: &str = "UnsafePath";
+
+/// The names of the devices of Windows, reserved in every directory whatever
+/// their extension.
+const RESERVED_NAMES: &[&str] = &[
+  "CON", "PRN", "AUX", "NUL", "CONIN$", "CONOUT$", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6",
+  "COM7", "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// The characters Windows does not allow in file names.
+const RESERVED_CHARS: &[char] = &['<', '>', ':', '"', '|', '?', '*', '\\'];
+
+/// The code points HFS+ ignores when comparing file names, which make e.g.
+/// `.g\u{200c}it` name the `.git` directory.
+fn is_hfs_ignorable(c: char) -> bool {
+  matches!(
+    c,
+    '\u{200c}'..='\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{206a}'..='\u{206f}' | '\u{feff}'
+  )
+}
+
+/// Check that `path`, a `/` separated path of a tree entry, can be written
+/// below a destination directory without escaping it nor writing in a `.git`
+/// directory.
+///
+/// Like the `core.protectNTFS` and `core.protectHFS` defaults of git, the
+/// names Windows and macOS would resolve to another file are rejected on
+/// every platform, as the materialized files may be copied there.
+///
+/// Returns the reason the path is rejected.
+pub(crate) fn check_path(path: &[u8]) -> std::result::Result<(), &'static str> {
+  let path = std::str::from_utf8(path).map_err(|_| "it is not valid UTF-8")?;
+  if path.is_empty() {
+    return Err("it is empty");
+  }
+  if path.starts_with('/') || path.starts_with('\\') {
+    return Err("it is absolute");
+  }
+  for component in path.split('/') {
+    check_component(component)?;
+  }
+  Ok(())
+}
+
+fn check_component(component: &str) -> std::result::Result<(), &'static str> {
+  match component {
+    "" => return Err("it has an empty component"),
+    "." | ".." => return Err("it has a `.` or `..` component"),
+    _ => {}
+  }
+  if component.chars().any(|c| c.is_control()) {
+    return Err("it has a control character");
+  }
+  if component.contains(RESERVED_CHARS) {
+    return Err("it has a character reserved by Windows");
+  }
+  if component.ends_with(['.', ' ']) {
+    return Err("it has a component ending with a dot or a space");
+  }
+  let name = component
+    .chars()
+    .filter(|c| !is_hfs_ignorable(*c))
+    .collect::<String>();
+  if name.eq_ignore_ascii_case(".git") || name.eq_ignore_ascii_case("git~1") {
+    return Err("it writes in a .git directory");
+  }
+  let stem = name.split('.').next().unwrap_or_default().trim_end();
+  if RESERVED_NAMES
+    .iter()
+    .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+  {
+    return Err("it names a device reserved by Windows");
+  }
+  Ok(())
+}
+
+/// The `UnsafePath` error rejecting `path`, whose `path` property names it.
+pub(crate) fn unsafe_path_error(env: &Env, path: &[u8], reason: &str) -> Error {
+  let path = String::from_utf8_lossy(path);
+  coded_error_with_properties(
+    env,
+    UNSAFE_PATH,
+    format!("Path [{path}] is unsafe to write, {reason}"),
+    &[("path", &path)],
+  )
+}
+
+#[napi]
+/// Check that `path`, the `/` separated path of a tree entry, is safe to write
+/// below a destination directory.
+///
+/// Absolute paths, `.` and `..` components and paths in a `.git` directory are
+/// rejected, along with the names which Windows or macOS would resolve to one
+/// of them or to a device, e.g. `.GIT.`, `git~1` or `aux.txt`, whatever the
+/// current platform.
+///
+/// Throws an error with the `UnsafePath` code and the rejected `path` if it is
+/// unsafe.
+pub fn check_safe_path(env: Env, path: String) -> Result<()> {
+  check_path(path.as_bytes()).map_err(|reason| unsafe_path_error(&env, path.as_bytes(), reason))
+}
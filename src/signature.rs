@@ -3,11 +3,12 @@ use std::ops::Deref;
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use crate::{commit::Commit, error::IntoNapiError};
+use crate::{commit::Commit, config::Config, error::IntoNapiError, tag::Tag};
 
 pub(crate) enum SignatureInner {
   Signature(git2::Signature<'static>),
   FromCommit(SharedReference<Commit, git2::Signature<'static>>),
+  FromTag(SharedReference<Tag, git2::Signature<'static>>),
 }
 
 impl Deref for SignatureInner {
@@ -17,10 +18,20 @@ impl Deref for SignatureInner {
     match self {
       SignatureInner::Signature(parent) => parent,
       SignatureInner::FromCommit(parent) => parent,
+      SignatureInner::FromTag(parent) => parent,
     }
   }
 }
 
+#[napi(object)]
+/// The time of a signature along with the time zone it was made in.
+pub struct SignatureTime {
+  /// Seconds since the Unix epoch.
+  pub seconds: i64,
+  /// The time zone offset from UTC, in minutes.
+  pub offset_minutes: i32,
+}
+
 #[napi]
 /// A Signature is used to indicate authorship of various actions throughout the
 /// library.
@@ -49,17 +60,46 @@ impl Signature {
     })
   }
 
+  #[napi(factory)]
+  /// Create a new action signature with a timestamp of 'now' from the
+  /// `user.name` and `user.email` of `config`.
+  ///
+  /// Returns error if either of them is not set.
+  pub fn from_config(config: &Config) -> Result<Self> {
+    let name = config
+      .inner
+      .get_string("user.name")
+      .convert("Get config [user.name] failed")?;
+    let email = config
+      .inner
+      .get_string("user.email")
+      .convert("Get config [user.email] failed")?;
+    Self::now(name, email)
+  }
+
   #[napi(constructor)]
   /// Create a new action signature.
   ///
-  /// The `time` specified is in seconds since the epoch, and the `offset` is
-  /// the time zone offset in minutes.
+  /// The `time` specified is in seconds since the epoch, in UTC. Use
+  /// `withOffset` to create a signature in another time zone.
   ///
   /// Returns error if either `name` or `email` contain angle brackets.
   pub fn new(name: String, email: String, time: i64) -> Result<Self> {
+    Self::with_offset(name, email, time, 0)
+  }
+
+  #[napi(factory)]
+  /// Create a new action signature in the time zone `offsetMinutes` minutes
+  /// away from UTC, e.g. `120` for UTC+02:00.
+  ///
+  /// The `time` specified is in seconds since the epoch.
+  ///
+  /// Returns error if either `name` or `email` contain angle brackets.
+  pub fn with_offset(name: String, email: String, time: i64, offset_minutes: i32) -> Result<Self> {
     Ok(Signature {
       inner: SignatureInner::Signature(
-        git2::Signature::new(&name, &email, &git2::Time::new(time, 0)).convert_without_message()?,
+        git2::Signature::new(&name, &email, &git2::Time::new(time, offset_minutes))
+          .convert_without_message()?,
       ),
     })
   }
@@ -85,6 +125,22 @@ impl Signature {
   pub fn when(&self) -> i64 {
     self.inner.when().seconds()
   }
+
+  #[napi]
+  /// Return the time zone offset from UTC of the signature, in minutes.
+  pub fn offset(&self) -> i32 {
+    self.inner.when().offset_minutes()
+  }
+
+  #[napi]
+  /// Return the time of the signature along with its time zone offset.
+  pub fn when_with_offset(&self) -> SignatureTime {
+    let when = self.inner.when();
+    SignatureTime {
+      seconds: when.seconds(),
+      offset_minutes: when.offset_minutes(),
+    }
+  }
 }
 
 impl<'a> AsRef<git2::Signature<'a>> for Signature {
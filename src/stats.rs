@@ -0,0 +1,127 @@
+use std::sync::{
+  atomic::{AtomicU32, Ordering},
+  Arc,
+};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::error::IntoNapiError;
+
+#[napi(object)]
+/// The memory libgit2 holds across every repository of the process.
+pub struct GitMemoryStats {
+  /// The size of the objects held by the object caches.
+  pub cached_objects_bytes: i64,
+  /// The size the object caches are allowed to hold, see
+  /// `git_libgit2_opts(GIT_OPT_SET_CACHE_MAX_SIZE)`.
+  pub cached_objects_limit_bytes: i64,
+  /// The size of the pack files libgit2 may map in memory before unmapping
+  /// the least recently used windows.
+  pub mwindow_mapped_limit_bytes: i64,
+}
+
+#[napi]
+/// Read the memory usage counters of libgit2.
+///
+/// libgit2 does not expose the number of cached objects nor the size of the
+/// pack windows currently mapped, only their limit.
+pub fn git_memory_stats() -> Result<GitMemoryStats> {
+  let mut current: isize = 0;
+  let mut allowed: isize = 0;
+  // SAFETY: `GIT_OPT_GET_CACHED_MEMORY` writes two `ssize_t` to the pointers.
+  let code = unsafe {
+    libgit2_sys::git_libgit2_opts(
+      libgit2_sys::GIT_OPT_GET_CACHED_MEMORY as _,
+      &mut current as *mut isize,
+      &mut allowed as *mut isize,
+    )
+  };
+  if code < 0 {
+    return Err(Error::new(
+      Status::GenericFailure,
+      "Read the cached memory of libgit2 failed".to_owned(),
+    ));
+  }
+  // SAFETY: only reads a global option.
+  let mapped_limit = unsafe { git2::opts::get_mwindow_mapped_limit() }
+    .convert("Read the mapped memory limit of libgit2 failed")?;
+  Ok(GitMemoryStats {
+    cached_objects_bytes: current as i64,
+    cached_objects_limit_bytes: allowed as i64,
+    mwindow_mapped_limit_bytes: mapped_limit as i64,
+  })
+}
+
+#[napi(object)]
+/// The objects of a repository handed out to JavaScript which are still
+/// alive, see `Repository.openHandles`.
+pub struct OpenHandles {
+  /// Revwalks whose iteration did not complete.
+  pub revwalks: u32,
+  pub diffs: u32,
+  pub trees: u32,
+  pub remotes: u32,
+}
+
+#[derive(Default)]
+pub(crate) struct HandleCounts {
+  revwalks: AtomicU32,
+  diffs: AtomicU32,
+  trees: AtomicU32,
+  remotes: AtomicU32,
+}
+
+impl HandleCounts {
+  pub(crate) fn snapshot(&self) -> OpenHandles {
+    OpenHandles {
+      revwalks: self.revwalks.load(Ordering::Relaxed),
+      diffs: self.diffs.load(Ordering::Relaxed),
+      trees: self.trees.load(Ordering::Relaxed),
+      remotes: self.remotes.load(Ordering::Relaxed),
+    }
+  }
+
+  fn counter(&self, kind: HandleKind) -> &AtomicU32 {
+    match kind {
+      HandleKind::RevWalk => &self.revwalks,
+      HandleKind::Diff => &self.diffs,
+      HandleKind::Tree => &self.trees,
+      HandleKind::Remote => &self.remotes,
+    }
+  }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum HandleKind {
+  RevWalk,
+  Diff,
+  Tree,
+  Remote,
+}
+
+/// Counts a wrapper in the `OpenHandles` of its repository until it is
+/// dropped.
+pub(crate) struct OpenHandle {
+  counts: Arc<HandleCounts>,
+  kind: HandleKind,
+}
+
+impl OpenHandle {
+  pub(crate) fn new(counts: &Arc<HandleCounts>, kind: HandleKind) -> Self {
+    counts.counter(kind).fetch_add(1, Ordering::Relaxed);
+    Self {
+      counts: counts.clone(),
+      kind,
+    }
+  }
+}
+
+impl Drop for OpenHandle {
+  fn drop(&mut self) {
+    self
+      .counts
+      .counter(self.kind)
+      .fetch_sub(1, Ordering::Relaxed);
+  }
+}
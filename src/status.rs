@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use napi_derive::napi;
+
+#[napi(object)]
+pub struct StatusByDirectoryOptions {
+  /// The number of leading path components of the directories the statuses
+  /// are aggregated into, e.g. `1` to aggregate everything under `src/` into
+  /// `src`.
+  ///
+  /// Not set to aggregate each file into its own directory.
+  pub depth: Option<u32>,
+  /// Only the files matching these pathspecs are counted.
+  ///
+  /// Not set to count every file.
+  pub pathspec: Option<Vec<String>>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+/// The number of changed files under a directory.
+pub struct DirectoryStatus {
+  /// The path of the directory relative to the working directory, `""` for
+  /// the files at the root.
+  pub dir: String,
+  /// Files with changes in the index.
+  pub staged: u32,
+  /// Tracked files with changes in the working directory.
+  pub unstaged: u32,
+  pub untracked: u32,
+  pub conflicted: u32,
+}
+
+/// The directory `path` is aggregated into, keeping at most `depth`
+/// components of its parent directory.
+fn directory_of(path: &str, depth: Option<u32>) -> String {
+  let parent = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+  match depth {
+    Some(depth) => parent
+      .split('/')
+      .filter(|component| !component.is_empty())
+      .take(depth as usize)
+      .collect::<Vec<_>>()
+      .join("/"),
+    None => parent.to_owned(),
+  }
+}
+
+/// Aggregate the entries of a status run into their directories, sorted by
+/// path.
+pub(crate) fn aggregate(statuses: &git2::Statuses, depth: Option<u32>) -> Vec<DirectoryStatus> {
+  let mut directories = BTreeMap::<String, DirectoryStatus>::new();
+  for entry in statuses.iter() {
+    let path = String::from_utf8_lossy(entry.path_bytes());
+    let dir = directory_of(&path, depth);
+    let directory = directories
+      .entry(dir.clone())
+      .or_insert_with(|| DirectoryStatus {
+        dir,
+        ..Default::default()
+      });
+    let status = entry.status();
+    if status.is_conflicted() {
+      directory.conflicted += 1;
+      continue;
+    }
+    if status.is_wt_new() {
+      directory.untracked += 1;
+      continue;
+    }
+    if status.intersects(
+      git2::Status::INDEX_NEW
+        | git2::Status::INDEX_MODIFIED
+        | git2::Status::INDEX_DELETED
+        | git2::Status::INDEX_RENAMED
+        | git2::Status::INDEX_TYPECHANGE,
+    ) {
+      directory.staged += 1;
+    }
+    if status.intersects(
+      git2::Status::WT_MODIFIED
+        | git2::Status::WT_DELETED
+        | git2::Status::WT_RENAMED
+        | git2::Status::WT_TYPECHANGE,
+    ) {
+      directory.unstaged += 1;
+    }
+  }
+  directories.into_values().collect()
+}
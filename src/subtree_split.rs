@@ -0,0 +1,221 @@
+use std::{collections::HashMap, path::Path};
+
+use napi::{bindgen_prelude::*, JsUnknown};
+use napi_derive::napi;
+
+use crate::{
+  error::IntoNapiError,
+  repo::Repository,
+  util::{CancellationToken, JsCallback},
+};
+
+/// How many commits are rewritten between two calls of the progress callback.
+const PROGRESS_INTERVAL: u32 = 100;
+
+#[napi(object, object_to_js = false)]
+#[derive(Default)]
+pub struct SubtreeSplitOptions {
+  /// The revision whose history is split.
+  ///
+  /// Not set to split the history of `HEAD`.
+  pub rev: Option<String>,
+  /// A commit of a previously split history, e.g. the one the subdirectory
+  /// was added from with `git subtree add`, whose commits are reused as is.
+  ///
+  /// Not set to rewrite the whole history.
+  pub onto: Option<String>,
+  /// Called periodically with the number of commits processed and the total
+  /// number of commits to process.
+  #[napi(ts_type = "(processed: number, total: number) => void")]
+  pub progress: Option<FunctionRef<(u32, u32), JsUnknown>>,
+}
+
+#[napi(object)]
+/// The result of `Repository.subtreeSplit`.
+pub struct SubtreeSplit {
+  /// The commit the history of `rev` is split into.
+  pub head_oid: String,
+  /// The number of commits written for the split history.
+  pub mapped: u32,
+}
+
+pub struct SubtreeSplitTask {
+  pub(crate) repo: Reference<Repository>,
+  pub(crate) prefix: String,
+  pub(crate) rev: Option<String>,
+  pub(crate) onto: Option<String>,
+  pub(crate) progress: Option<JsCallback<(u32, u32), JsUnknown>>,
+  pub(crate) cancellation: CancellationToken,
+}
+
+unsafe impl Send for SubtreeSplitTask {}
+
+impl SubtreeSplitTask {
+  pub(crate) fn new(
+    env: Env,
+    repo: Reference<Repository>,
+    prefix: String,
+    options: Option<SubtreeSplitOptions>,
+    cancellation: CancellationToken,
+  ) -> Result<Self> {
+    let options = options.unwrap_or_default();
+    Ok(Self {
+      repo,
+      prefix,
+      rev: options.rev,
+      onto: options.onto,
+      progress: options
+        .progress
+        .map(|progress| JsCallback::new(env, progress))
+        .transpose()?,
+      cancellation,
+    })
+  }
+
+  fn report(&self, processed: u32, total: u32) -> Result<()> {
+    match &self.progress {
+      Some(progress) => progress.call((processed, total), |_| Ok(())),
+      None => Ok(()),
+    }
+  }
+}
+
+#[napi]
+impl Task for SubtreeSplitTask {
+  type Output = SubtreeSplit;
+  type JsValue = SubtreeSplit;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let repo = &self.repo.inner;
+    let prefix = Path::new(self.prefix.trim_end_matches('/'));
+    let rev = self.rev.as_deref().unwrap_or("HEAD");
+    let head = repo
+      .revparse_single(rev)
+      .and_then(|object| object.peel_to_commit())
+      .convert(format!("Resolve [{rev}] to a commit failed"))?;
+    // The commits the original ones are rewritten to, `None` if the
+    // subdirectory is missing from them and from their ancestors
+    let mut mapped = HashMap::<git2::Oid, Option<git2::Oid>>::new();
+    let mut rev_walk = repo.revwalk().convert_without_message()?;
+    rev_walk
+      .set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+      .convert_without_message()?;
+    rev_walk.push(head.id()).convert_without_message()?;
+    if let Some(onto) = &self.onto {
+      let onto = repo
+        .revparse_single(onto)
+        .and_then(|object| object.peel_to_commit())
+        .convert(format!("Resolve [{onto}] to a commit failed"))?;
+      let mut onto_walk = repo.revwalk().convert_without_message()?;
+      onto_walk.push(onto.id()).convert_without_message()?;
+      for oid in onto_walk {
+        let oid = oid.convert("Walk the commits of onto failed")?;
+        mapped.insert(oid, Some(oid));
+      }
+      rev_walk.hide(onto.id()).convert_without_message()?;
+    }
+    let commits = rev_walk
+      .collect::<std::result::Result<Vec<_>, _>>()
+      .convert("Walk the commits failed")?;
+    let total = commits.len() as u32;
+    let mut written = 0;
+    for (processed, oid) in commits.into_iter().enumerate() {
+      self.cancellation.check()?;
+      if (processed as u32).is_multiple_of(PROGRESS_INTERVAL) {
+        self.report(processed as u32, total)?;
+      }
+      let commit = repo.find_commit(oid).convert_without_message()?;
+      let tree = commit
+        .tree()
+        .convert_without_message()?
+        .get_path(prefix)
+        .ok()
+        .filter(|entry| entry.kind() == Some(git2::ObjectType::Tree))
+        .map(|entry| entry.id());
+      let mut parents = Vec::new();
+      for parent in commit.parent_ids() {
+        if let Some(Some(parent)) = mapped.get(&parent) {
+          if !parents.contains(parent) {
+            parents.push(*parent);
+          }
+        }
+      }
+      // Drop the parents already reachable from another one, which the
+      // rewritten history would merge for nothing
+      let mut independent = Vec::with_capacity(parents.len());
+      for parent in &parents {
+        let mut redundant = false;
+        for other in &parents {
+          if other != parent
+            && repo
+              .graph_descendant_of(*other, *parent)
+              .convert_without_message()?
+          {
+            redundant = true;
+            break;
+          }
+        }
+        if !redundant {
+          independent.push(*parent);
+        }
+      }
+      let new_oid = match (tree, independent.as_slice()) {
+        (None, parents) => parents.first().copied(),
+        (Some(tree), [parent])
+          if repo
+            .find_commit(*parent)
+            .convert_without_message()?
+            .tree_id()
+            == tree =>
+        {
+          Some(*parent)
+        }
+        (Some(tree), parents) => {
+          let tree = repo.find_tree(tree).convert_without_message()?;
+          let parents = parents
+            .iter()
+            .map(|parent| repo.find_commit(*parent))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .convert_without_message()?;
+          let message = String::from_utf8_lossy(commit.message_raw_bytes());
+          let new_oid = repo
+            .commit(
+              None,
+              &commit.author(),
+              &commit.committer(),
+              &message,
+              &tree,
+              &parents.iter().collect::<Vec<_>>(),
+            )
+            .convert(format!("Rewrite commit [{oid}] failed"))?;
+          written += 1;
+          Some(new_oid)
+        }
+      };
+      mapped.insert(oid, new_oid);
+    }
+    self.report(total, total)?;
+    match mapped.get(&head.id()) {
+      Some(Some(head_oid)) => Ok(SubtreeSplit {
+        head_oid: head_oid.to_string(),
+        mapped: written,
+      }),
+      _ => Err(Error::new(
+        Status::InvalidArg,
+        format!(
+          "The history of [{rev}] never contains the directory [{}]",
+          self.prefix
+        ),
+      )),
+    }
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    self.cancellation.detach(env)?;
+    Ok(output)
+  }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    self.cancellation.reject(env, err)
+  }
+}
@@ -1,11 +1,33 @@
+use std::ops::Deref;
+
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use crate::{error::IntoNapiError, object::GitObject};
+use crate::{
+  error::IntoNapiError,
+  object::{GitObject, ObjectParent, ObjectType},
+  signature::{Signature, SignatureInner},
+};
+
+pub(crate) enum TagInner {
+  Repository(SharedReference<crate::repo::Repository, git2::Tag<'static>>),
+  GitObject(SharedReference<GitObject, git2::Tag<'static>>),
+}
+
+impl Deref for TagInner {
+  type Target = git2::Tag<'static>;
+
+  fn deref(&self) -> &git2::Tag<'static> {
+    match self {
+      TagInner::Repository(parent) => parent.deref(),
+      TagInner::GitObject(parent) => parent.deref(),
+    }
+  }
+}
 
 #[napi]
 pub struct Tag {
-  pub(crate) inner: SharedReference<crate::repo::Repository, git2::Tag<'static>>,
+  pub(crate) inner: TagInner,
 }
 
 #[napi]
@@ -54,6 +76,50 @@ impl Tag {
     self.inner.name_bytes().to_vec().into()
   }
 
+  #[napi]
+  /// Get the tagger (author) of a tag
+  ///
+  /// If the author is unspecified, then `null` is returned.
+  pub fn tagger(&self, env: Env, self_ref: Reference<Tag>) -> Result<Option<Signature>> {
+    if self.inner.tagger().is_none() {
+      return Ok(None);
+    }
+    let tagger = self_ref.share_with(env, |tag| {
+      tag
+        .inner
+        .tagger()
+        .ok_or_else(|| Error::from_reason("Tag has no tagger"))
+    })?;
+    Ok(Some(Signature {
+      inner: SignatureInner::FromTag(tagger),
+    }))
+  }
+
+  #[napi]
+  /// Get the tagged object of a tag
+  ///
+  /// This method performs a repository lookup for the given object and
+  /// returns it
+  pub fn target(&self, env: Env, self_ref: Reference<Tag>) -> Result<GitObject> {
+    Ok(GitObject {
+      inner: ObjectParent::Tag(self_ref.share_with(env, |tag| {
+        tag.inner.target().convert("Get the target of tag failed")
+      })?),
+    })
+  }
+
+  #[napi]
+  /// Get the OID of the tagged object of a tag
+  pub fn target_id(&self) -> String {
+    self.inner.target_id().to_string()
+  }
+
+  #[napi]
+  /// Get the ObjectType of the tagged object of a tag
+  pub fn target_type(&self) -> Option<ObjectType> {
+    self.inner.target_type().map(|kind| kind.into())
+  }
+
   #[napi]
   /// Recursively peel a tag until a non tag git_object is found
   pub fn peel(&self) -> Result<GitObject> {
@@ -63,3 +129,28 @@ impl Tag {
     })
   }
 }
+
+#[napi(object)]
+pub struct TagListOptions {
+  /// Also return the raw bytes of the names, which are not valid UTF-8 for
+  /// some tags.
+  pub raw_names: Option<bool>,
+}
+
+#[napi(object)]
+/// A tag listed by `Repository.tagList`.
+pub struct TagListEntry {
+  /// The name of the tag, without the `refs/tags/` prefix.
+  ///
+  /// The invalid UTF-8 sequences are replaced with `U+FFFD`.
+  pub name: String,
+  /// The raw bytes of `name`.
+  ///
+  /// Not set unless `rawNames` is.
+  pub name_bytes: Option<Buffer>,
+  /// The id the reference points at, the tag object of an annotated tag.
+  pub oid: String,
+  /// The object the tag points at, through any chain of tag objects.
+  pub target_oid: String,
+  pub is_annotated: bool,
+}
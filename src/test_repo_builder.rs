@@ -0,0 +1,265 @@
+use std::collections::{BTreeMap, HashMap};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{error::IntoNapiError, repo::Repository};
+
+/// The time of the first commit when none is given, 2000-01-01T00:00:00Z.
+const DEFAULT_TIMESTAMP: i64 = 946_684_800;
+/// The time elapsed between two commits when none is given, in seconds.
+const DEFAULT_TIMESTAMP_STEP: i64 = 60;
+const DEFAULT_BRANCH: &str = "main";
+
+#[napi(object)]
+pub struct TestRepoAuthor {
+  pub name: String,
+  pub email: String,
+}
+
+#[napi(object)]
+/// A commit created by `TestRepoBuilder`.
+pub struct TestRepoCommit {
+  pub message: String,
+  /// The content of the files changed by the commit, keyed by path relative
+  /// to the root of the repository, `null` deletes the path.
+  pub files: HashMap<String, Option<String>>,
+  /// Used as both the author and the committer.
+  ///
+  /// Defaults to `Test <test@example.com>`.
+  pub author: Option<TestRepoAuthor>,
+  /// The commit time, in seconds since the Unix epoch.
+  ///
+  /// Defaults to one minute after the previous commit, the first commit
+  /// being made at 2000-01-01T00:00:00Z.
+  pub timestamp: Option<i64>,
+}
+
+/// A change to a file, the path split into its components and the new
+/// content, `None` deleting the file.
+type FileChange<'a> = (Vec<&'a str>, Option<&'a str>);
+
+enum Step {
+  Commit(TestRepoCommit),
+  Branch(String),
+  Checkout(String),
+  Tag(String),
+}
+
+#[napi]
+/// A builder to create repositories with a given history, for tests and
+/// scaffolding.
+///
+/// Nothing is written until `build` is called, which creates all the objects
+/// without going through the index, so the same steps always produce the
+/// same commits.
+///
+/// # Example
+///
+/// ```js
+/// const repo = new TestRepoBuilder('/tmp/fixture')
+///   .commit({ message: 'init', files: { 'README.md': '# fixture\n' } })
+///   .branch('feature')
+///   .commit({ message: 'feature', files: { 'src/index.js': 'export {}\n' } })
+///   .tag('v1')
+///   .checkout('main')
+///   .commit({ message: 'remove readme', files: { 'README.md': null } })
+///   .build()
+/// ```
+pub struct TestRepoBuilder {
+  path: String,
+  steps: Vec<Step>,
+}
+
+#[napi]
+impl TestRepoBuilder {
+  #[napi(constructor)]
+  /// Creates a builder for a repository initialized at `path`, on the `main`
+  /// branch.
+  pub fn new(path: String) -> Self {
+    Self {
+      path,
+      steps: Vec::new(),
+    }
+  }
+
+  #[napi]
+  /// Commit changes to files on the current branch.
+  pub fn commit(&mut self, commit: TestRepoCommit) -> &Self {
+    self.steps.push(Step::Commit(commit));
+    self
+  }
+
+  #[napi]
+  /// Create a branch at the current commit and switch to it.
+  pub fn branch(&mut self, name: String) -> &Self {
+    self.steps.push(Step::Branch(name));
+    self
+  }
+
+  #[napi]
+  /// Switch to an existing branch.
+  pub fn checkout(&mut self, name: String) -> &Self {
+    self.steps.push(Step::Checkout(name));
+    self
+  }
+
+  #[napi]
+  /// Create a lightweight tag at the current commit.
+  pub fn tag(&mut self, name: String) -> &Self {
+    self.steps.push(Step::Tag(name));
+    self
+  }
+
+  #[napi]
+  /// Create the repository, ending with `HEAD` on the current branch and its
+  /// files checked out.
+  pub fn build(&self) -> Result<Repository> {
+    let repo = git2::Repository::init_opts(
+      &self.path,
+      git2::RepositoryInitOptions::new().initial_head(DEFAULT_BRANCH),
+    )
+    .convert(format!("Init repository [{}] failed", self.path))?;
+    let mut branch = DEFAULT_BRANCH.to_owned();
+    let mut timestamp = None;
+    for step in &self.steps {
+      match step {
+        Step::Commit(commit) => {
+          let time = commit
+            .timestamp
+            .unwrap_or(timestamp.map_or(DEFAULT_TIMESTAMP, |t| t + DEFAULT_TIMESTAMP_STEP));
+          timestamp = Some(time);
+          write_commit(&repo, &branch, commit, time)
+            .convert(format!("Create commit [{}] failed", commit.message))?;
+        }
+        Step::Branch(name) => {
+          let tip = branch_tip(&repo, &branch)?;
+          repo
+            .branch(name, &tip, false)
+            .convert(format!("Create branch [{name}] failed"))?;
+          branch = name.clone();
+        }
+        Step::Checkout(name) => {
+          repo
+            .find_branch(name, git2::BranchType::Local)
+            .convert(format!("Find branch [{name}] failed"))?;
+          branch = name.clone();
+        }
+        Step::Tag(name) => {
+          let tip = branch_tip(&repo, &branch)?;
+          repo
+            .tag_lightweight(name, tip.as_object(), false)
+            .convert(format!("Create tag [{name}] failed"))?;
+        }
+      }
+    }
+    repo
+      .set_head(&format!("refs/heads/{branch}"))
+      .convert(format!("Set HEAD to [{branch}] failed"))?;
+    if repo.head().is_ok() {
+      repo
+        .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .convert("Checkout HEAD failed")?;
+    }
+    Ok(Repository {
+      inner: repo,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
+    })
+  }
+}
+
+fn branch_tip<'repo>(repo: &'repo git2::Repository, branch: &str) -> Result<git2::Commit<'repo>> {
+  repo
+    .find_branch(branch, git2::BranchType::Local)
+    .and_then(|b| b.get().peel_to_commit())
+    .convert(format!("Branch [{branch}] has no commit"))
+}
+
+fn write_commit(
+  repo: &git2::Repository,
+  branch: &str,
+  commit: &TestRepoCommit,
+  time: i64,
+) -> std::result::Result<git2::Oid, git2::Error> {
+  let parent = repo
+    .find_branch(branch, git2::BranchType::Local)
+    .ok()
+    .map(|b| b.get().peel_to_commit())
+    .transpose()?;
+  let base = parent.as_ref().map(|p| p.tree()).transpose()?;
+  let changes = commit
+    .files
+    .iter()
+    .map(|(path, content)| (path.split('/').collect::<Vec<_>>(), content.as_deref()))
+    .collect::<Vec<_>>();
+  let tree = match write_tree(repo, base.as_ref(), &changes)? {
+    Some(oid) => oid,
+    None => repo.treebuilder(None)?.write()?,
+  };
+  let signature = match &commit.author {
+    Some(author) => git2::Signature::new(&author.name, &author.email, &git2::Time::new(time, 0))?,
+    None => git2::Signature::new("Test", "test@example.com", &git2::Time::new(time, 0))?,
+  };
+  repo.commit(
+    Some(&format!("refs/heads/{branch}")),
+    &signature,
+    &signature,
+    &commit.message,
+    &repo.find_tree(tree)?,
+    &parent.iter().collect::<Vec<_>>(),
+  )
+}
+
+/// Apply `changes` to `base`, returning `None` once the tree is empty.
+fn write_tree(
+  repo: &git2::Repository,
+  base: Option<&git2::Tree>,
+  changes: &[FileChange],
+) -> std::result::Result<Option<git2::Oid>, git2::Error> {
+  let mut builder = repo.treebuilder(base)?;
+  let mut subtrees: BTreeMap<&str, Vec<FileChange>> = BTreeMap::new();
+  for (components, content) in changes {
+    match components.as_slice() {
+      [name] => match content {
+        Some(content) => {
+          builder.insert(name, repo.blob(content.as_bytes())?, 0o100644)?;
+        }
+        None => {
+          if builder.get(name)?.is_some() {
+            builder.remove(name)?;
+          }
+        }
+      },
+      [dir, rest @ ..] => subtrees
+        .entry(dir)
+        .or_default()
+        .push((rest.to_vec(), *content)),
+      [] => {}
+    }
+  }
+  for (dir, changes) in subtrees {
+    let subtree = match builder.get(dir)? {
+      Some(entry) if entry.kind() == Some(git2::ObjectType::Tree) => {
+        Some(repo.find_tree(entry.id())?)
+      }
+      _ => None,
+    };
+    match write_tree(repo, subtree.as_ref(), &changes)? {
+      Some(oid) => {
+        builder.insert(dir, oid, 0o040000)?;
+      }
+      None => {
+        if builder.get(dir)?.is_some() {
+          builder.remove(dir)?;
+        }
+      }
+    }
+  }
+  if builder.is_empty() {
+    return Ok(None);
+  }
+  builder.write().map(Some)
+}
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{error::IntoNapiError, repo::Repository, util::CancellationToken};
+
+#[napi(string_enum = "lowercase")]
+#[derive(PartialEq, Eq)]
+/// The sources of the events listed by `Repository.timeline`.
+pub enum TimelineSource {
+  /// The commits reachable from `HEAD`.
+  Commits,
+  /// The creation of the annotated tags.
+  Tags,
+  /// The movements of the references recorded in their reflogs.
+  Reflog,
+}
+
+#[napi(string_enum = "lowercase")]
+pub enum TimelineEventKind {
+  Commit,
+  Tag,
+  Reflog,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct TimelineOptions {
+  /// Only list the events which happened at or after this time, in seconds
+  /// since the Unix epoch.
+  ///
+  /// Not set to list every event.
+  pub since_epoch: Option<i64>,
+  /// List at most this many events, the newest ones.
+  ///
+  /// Not set to list every event.
+  pub limit: Option<u32>,
+  /// The sources of the events.
+  ///
+  /// Not set to list the events of every source.
+  pub include: Option<Vec<TimelineSource>>,
+}
+
+#[napi(object)]
+/// An event listed by `Repository.timeline`.
+pub struct TimelineEvent {
+  pub kind: TimelineEventKind,
+  /// The time of the event in milliseconds since the Unix epoch, the
+  /// committer time of commits and the tagger time of tags.
+  pub time_ms: i64,
+  /// The branch `HEAD` points to for commits, the tag for tags and the moved
+  /// reference for reflog entries.
+  ///
+  /// Not set for the commits of a detached `HEAD`.
+  pub refname: Option<String>,
+  /// The commit, the tag object or the new target of the reference.
+  pub oid: String,
+  /// The summary of the commit, the first line of the tag message or the
+  /// reflog message.
+  pub title: String,
+  /// The author of commits, the tagger of tags and the committer of reflog
+  /// entries.
+  ///
+  /// Not set if the tag has no tagger.
+  pub actor_name: Option<String>,
+  pub actor_email: Option<String>,
+}
+
+impl TimelineEvent {
+  fn new(
+    kind: TimelineEventKind,
+    time: git2::Time,
+    refname: Option<String>,
+    oid: git2::Oid,
+    title: &[u8],
+    actor: Option<&git2::Signature>,
+  ) -> Self {
+    Self {
+      kind,
+      time_ms: time.seconds() * 1000,
+      refname,
+      oid: oid.to_string(),
+      title: String::from_utf8_lossy(title).into_owned(),
+      actor_name: actor.map(|actor| String::from_utf8_lossy(actor.name_bytes()).into_owned()),
+      actor_email: actor.map(|actor| String::from_utf8_lossy(actor.email_bytes()).into_owned()),
+    }
+  }
+}
+
+/// List the events of the sources selected by `options`, from the newest to
+/// the oldest.
+pub(crate) fn timeline(
+  repo: &git2::Repository,
+  options: &TimelineOptions,
+  cancellation: &CancellationToken,
+) -> Result<Vec<TimelineEvent>> {
+  let includes = |source: TimelineSource| {
+    options
+      .include
+      .as_ref()
+      .is_none_or(|include| include.contains(&source))
+  };
+  let since_ms = options.since_epoch.map(|since| since * 1000);
+  let is_recent = |time: git2::Time| since_ms.is_none_or(|since| time.seconds() * 1000 >= since);
+  let limit = options.limit.map(|limit| limit as usize);
+  let mut events = Vec::new();
+  let mut commits = HashSet::new();
+  if includes(TimelineSource::Commits) {
+    // An unborn `HEAD` has no commits
+    if let Ok(head) = repo.head() {
+      let refname = head
+        .is_branch()
+        .then(|| head.name().map(str::to_owned))
+        .flatten();
+      let head = head
+        .peel_to_commit()
+        .convert("Resolve the HEAD commit failed")?;
+      let mut rev_walk = repo.revwalk().convert_without_message()?;
+      rev_walk
+        .set_sorting(git2::Sort::TIME)
+        .convert_without_message()?;
+      rev_walk.push(head.id()).convert_without_message()?;
+      // Commits are sorted by time, so the older ones cannot be listed
+      for oid in rev_walk.take(limit.unwrap_or(usize::MAX)) {
+        cancellation.check()?;
+        let commit = repo
+          .find_commit(oid.convert_without_message()?)
+          .convert_without_message()?;
+        if !is_recent(commit.time()) {
+          break;
+        }
+        commits.insert(commit.id());
+        events.push(TimelineEvent::new(
+          TimelineEventKind::Commit,
+          commit.time(),
+          refname.clone(),
+          commit.id(),
+          commit.summary_bytes().unwrap_or_default(),
+          Some(&commit.author()),
+        ));
+      }
+    }
+  }
+  if includes(TimelineSource::Tags) {
+    for reference in repo
+      .references_glob("refs/tags/*")
+      .convert("List tags failed")?
+    {
+      cancellation.check()?;
+      let reference = reference.convert_without_message()?;
+      let Some(tag) = reference
+        .peel(git2::ObjectType::Tag)
+        .ok()
+        .and_then(|tag| tag.into_tag().ok())
+      else {
+        continue;
+      };
+      let Some(tagger) = tag.tagger() else {
+        continue;
+      };
+      if !is_recent(tagger.when()) {
+        continue;
+      }
+      let title = tag
+        .message_bytes()
+        .and_then(|message| message.split(|byte| *byte == b'\n').next())
+        .filter(|title| !title.is_empty())
+        .unwrap_or(tag.name_bytes());
+      events.push(TimelineEvent::new(
+        TimelineEventKind::Tag,
+        tagger.when(),
+        Some(String::from_utf8_lossy(reference.name_bytes()).into_owned()),
+        tag.id(),
+        title,
+        Some(&tagger),
+      ));
+    }
+  }
+  if includes(TimelineSource::Reflog) {
+    for reference in repo.references().convert("List references failed")? {
+      cancellation.check()?;
+      let reference = reference.convert_without_message()?;
+      let Some(refname) = reference.name() else {
+        continue;
+      };
+      let reflog = repo
+        .reflog(refname)
+        .convert(format!("Read the reflog of [{refname}] failed"))?;
+      for entry in reflog.iter() {
+        let committer = entry.committer();
+        let message = entry.message_bytes().unwrap_or_default();
+        // The commit events already list the updates made by committing
+        if !is_recent(committer.when())
+          || (message.starts_with(b"commit") && commits.contains(&entry.id_new()))
+        {
+          continue;
+        }
+        events.push(TimelineEvent::new(
+          TimelineEventKind::Reflog,
+          committer.when(),
+          Some(refname.to_owned()),
+          entry.id_new(),
+          message,
+          Some(&committer),
+        ));
+      }
+    }
+  }
+  events.sort_by_key(|event| std::cmp::Reverse(event.time_ms));
+  if let Some(limit) = limit {
+    events.truncate(limit);
+  }
+  Ok(events)
+}
+
+pub struct TimelineTask {
+  pub(crate) repo: Reference<Repository>,
+  pub(crate) options: TimelineOptions,
+  pub(crate) cancellation: CancellationToken,
+}
+
+unsafe impl Send for TimelineTask {}
+
+#[napi]
+impl Task for TimelineTask {
+  type Output = Vec<TimelineEvent>;
+  type JsValue = Vec<TimelineEvent>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    timeline(&self.repo.inner, &self.options, &self.cancellation)
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    self.cancellation.detach(env)?;
+    Ok(output)
+  }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    self.cancellation.reject(env, err)
+  }
+}
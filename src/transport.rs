@@ -0,0 +1,99 @@
+use std::ffi::CString;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+#[napi(string_enum = "lowercase")]
+/// The protocol used to talk to a remote.
+pub enum TransportProtocol {
+  Https,
+  Http,
+  Ssh,
+  Git,
+  /// A repository on the local filesystem, through a path or a `file://` URL.
+  File,
+}
+
+#[napi(object)]
+/// How the remote of a network operation was reached.
+pub struct TransportInfo {
+  pub protocol: TransportProtocol,
+}
+
+impl TransportInfo {
+  /// The transport libgit2 picks for `url`.
+  pub(crate) fn of_url(url: &str) -> Self {
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme);
+    let protocol = match scheme.map(str::to_ascii_lowercase).as_deref() {
+      Some("https") => TransportProtocol::Https,
+      Some("http") => TransportProtocol::Http,
+      Some("ssh" | "ssh+git" | "git+ssh") => TransportProtocol::Ssh,
+      Some("git") => TransportProtocol::Git,
+      Some(_) => TransportProtocol::File,
+      // The scp-like syntax `[user@]host:path`, a path being local if it
+      // has a `/` before the first `:`, or is a Windows drive
+      None => match url.split_once(':') {
+        Some((host, _)) if !host.contains('/') && host.len() > 1 => TransportProtocol::Ssh,
+        _ => TransportProtocol::File,
+      },
+    };
+    Self { protocol }
+  }
+}
+
+#[napi]
+/// Set the comment of the User-Agent header sent to HTTP(S) remotes, which
+/// reads `git/2.0 (<userAgent>)`.
+///
+/// It is process-global, shared by every repository, and must be set before
+/// the first network operation.
+pub fn set_user_agent(user_agent: String) -> Result<()> {
+  let user_agent = CString::new(user_agent)
+    .map_err(|err| Error::new(Status::InvalidArg, format!("Invalid user agent: {err}")))?;
+  libgit2_sys::init();
+  // SAFETY: `GIT_OPT_SET_USER_AGENT` copies the string.
+  let code = unsafe {
+    libgit2_sys::git_libgit2_opts(
+      libgit2_sys::GIT_OPT_SET_USER_AGENT as _,
+      user_agent.as_ptr(),
+    )
+  };
+  if code < 0 {
+    return Err(Error::new(
+      Status::GenericFailure,
+      "Set the user agent of libgit2 failed".to_owned(),
+    ));
+  }
+  Ok(())
+}
+
+#[napi]
+/// Get the comment of the User-Agent header, `libgit2 <version>` unless set
+/// by `setUserAgent`.
+pub fn get_user_agent() -> Result<String> {
+  let mut buf = libgit2_sys::git_buf {
+    ptr: std::ptr::null_mut(),
+    reserved: 0,
+    size: 0,
+  };
+  libgit2_sys::init();
+  // SAFETY: `GIT_OPT_GET_USER_AGENT` fills the buffer, disposed below.
+  let code =
+    unsafe { libgit2_sys::git_libgit2_opts(libgit2_sys::GIT_OPT_GET_USER_AGENT as _, &mut buf) };
+  let agent = if code >= 0 && !buf.ptr.is_null() {
+    // SAFETY: the buffer holds `size` bytes.
+    let bytes = unsafe { std::slice::from_raw_parts(buf.ptr as *const u8, buf.size) };
+    String::from_utf8_lossy(bytes).into_owned()
+  } else {
+    String::new()
+  };
+  // SAFETY: the buffer was filled by libgit2.
+  unsafe { libgit2_sys::git_buf_dispose(&mut buf) };
+  if code < 0 {
+    return Err(Error::new(
+      Status::GenericFailure,
+      "Get the user agent of libgit2 failed".to_owned(),
+    ));
+  }
+  Ok(agent)
+}
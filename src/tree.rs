@@ -2,34 +2,71 @@ use std::ops::Deref;
 use std::path::Path;
 
 use napi::bindgen_prelude::{
-  Env, Error, Generator, Reference, Result, SharedReference, Uint8Array,
+  Env, Error, FunctionRef, Generator, Reference, Result, SharedReference, Uint8Array,
 };
 use napi_derive::napi;
 
 use crate::{
+  deltas::FileMode,
   error::IntoNapiError,
-  object::{GitObject, ObjectParent},
+  object::{GitObject, ObjectParent, ObjectType},
   repo::Repository,
+  stats::OpenHandle,
+  util::CallbackError,
 };
 
 pub(crate) enum TreeParent {
   Repository(SharedReference<crate::repo::Repository, git2::Tree<'static>>),
   Reference(SharedReference<crate::reference::Reference, git2::Tree<'static>>),
   Commit(SharedReference<crate::commit::Commit, git2::Tree<'static>>),
+  GitObject(SharedReference<GitObject, git2::Tree<'static>>),
+}
+
+#[napi]
+/// The order in which `Tree.walk` visits the entries of nested trees.
+pub enum TreeWalkMode {
+  /// Visit a tree before its entries.
+  PreOrder,
+  /// Visit a tree after its entries.
+  PostOrder,
+}
+
+impl From<TreeWalkMode> for git2::TreeWalkMode {
+  fn from(value: TreeWalkMode) -> Self {
+    match value {
+      TreeWalkMode::PreOrder => git2::TreeWalkMode::PreOrder,
+      TreeWalkMode::PostOrder => git2::TreeWalkMode::PostOrder,
+    }
+  }
+}
+
+#[napi(object)]
+/// An entry of a tree or of its subtrees, see `Tree.entriesRecursive`.
+pub struct TreeEntryInfo {
+  /// The `/` separated path of the entry relative to the tree.
+  pub path: String,
+  pub oid: String,
+  pub filemode: FileMode,
+  #[napi(js_name = "type")]
+  pub kind: ObjectType,
 }
 
 #[napi]
 pub struct Tree {
   pub(crate) inner: TreeParent,
+  /// Not set for the trees of commits which do not belong to a repository,
+  /// and for the trees downcast from a `GitObject`.
+  pub(crate) _handle: Option<OpenHandle>,
 }
 
 #[napi]
 impl Tree {
-  pub(crate) fn inner(&self) -> &git2::Tree {
+  pub(crate) fn inner(&self) -> &git2::Tree<'_> {
     match &self.inner {
       TreeParent::Repository(parent) => parent,
       TreeParent::Reference(parent) => parent,
       TreeParent::Commit(parent) => parent,
+      TreeParent::GitObject(parent) => parent,
     }
   }
 
@@ -59,6 +96,62 @@ impl Tree {
     })
   }
 
+  #[napi]
+  /// Traverse the entries of the tree and of its subtrees, calling `callback`
+  /// with the path of the parent tree, ending with a `/` unless empty, and
+  /// each entry.
+  ///
+  /// Returning `1` from the callback skips the subtree of the entry in
+  /// pre-order, and a negative value stops the walk.
+  pub fn walk(
+    &self,
+    env: Env,
+    mode: TreeWalkMode,
+    #[napi(ts_arg_type = "(root: string, entry: TreeEntry) => number | undefined | null")]
+    callback: FunctionRef<(String, TreeEntry), Option<i32>>,
+  ) -> Result<()> {
+    let callback_error = CallbackError::default();
+    let mut aborted = false;
+    let result = self.inner().walk(mode.into(), |root, entry| {
+      let entry = TreeEntry {
+        inner: TreeEntryInner::Owned(entry.to_owned()),
+      };
+      let result = callback
+        .borrow_back(&env)
+        .and_then(|cb| cb.call((root.to_owned(), entry)))
+        .map(Option::unwrap_or_default)
+        .unwrap_or_else(|err| {
+          callback_error.set(err);
+          -1
+        });
+      aborted = result < 0;
+      result
+    });
+    callback_error.check(match result {
+      Err(_) if aborted => Ok(()),
+      result => result.convert("Walk the tree failed"),
+    })
+  }
+
+  #[napi]
+  /// List the entries of the tree and of its subtrees, in pre-order.
+  pub fn entries_recursive(&self) -> Result<Vec<TreeEntryInfo>> {
+    let mut entries = Vec::new();
+    self
+      .inner()
+      .walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+        entries.push(TreeEntryInfo {
+          path: format!("{root}{}", String::from_utf8_lossy(entry.name_bytes())),
+          oid: entry.id().to_string(),
+          filemode: entry.filemode().into(),
+          kind: entry.kind().map_or(ObjectType::Any, Into::into),
+        });
+        git2::TreeWalkResult::Ok
+      })
+      .convert("Walk the tree failed")?;
+    Ok(entries)
+  }
+
   #[napi]
   /// Lookup a tree entry by SHA value
   pub fn get_id(&self, this_ref: Reference<Tree>, env: Env, id: String) -> Option<TreeEntry> {
@@ -136,6 +229,7 @@ impl<'a> AsRef<git2::Tree<'a>> for Tree {
       TreeParent::Repository(ref parent) => parent.deref(),
       TreeParent::Reference(ref parent) => parent.deref(),
       TreeParent::Commit(ref parent) => parent.deref(),
+      TreeParent::GitObject(ref parent) => parent.deref(),
     }
   }
 }
@@ -189,6 +283,8 @@ impl TreeEntry {
 
   #[napi]
   /// Get the name of a tree entry
+  ///
+  /// Throws if the name is not valid UTF-8, see `nameBytes`.
   pub fn name(&self) -> Result<&str> {
     self
       .inner
@@ -0,0 +1,83 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{
+  deltas::FileMode,
+  error::IntoNapiError,
+  repo::Repository,
+  tree::{TreeEntry, TreeEntryInner},
+};
+
+#[napi]
+/// Constructor for in-memory trees, see `Repository.treebuilder`.
+pub struct TreeBuilder {
+  pub(crate) inner: SharedReference<Repository, git2::TreeBuilder<'static>>,
+}
+
+#[napi]
+impl TreeBuilder {
+  #[napi]
+  /// Add or update an entry of the tree.
+  ///
+  /// The object `oid` points to is not required to exist, but `filemode`
+  /// must be one of `Blob`, `BlobExecutable`, `Tree`, `Link` or `Commit`.
+  pub fn insert(&mut self, name: String, oid: String, filemode: FileMode) -> Result<()> {
+    let oid = git2::Oid::from_str(&oid).convert(format!("Invalid OID [{oid}]"))?;
+    self
+      .inner
+      .insert(&name, oid, filemode.into())
+      .convert(format!("Insert [{name}] in tree failed"))?;
+    Ok(())
+  }
+
+  #[napi]
+  /// Remove an entry from the tree.
+  pub fn remove(&mut self, name: String) -> Result<()> {
+    self
+      .inner
+      .remove(&name)
+      .convert(format!("Remove [{name}] from tree failed"))
+  }
+
+  #[napi]
+  /// Get an entry of the tree by its filename.
+  pub fn get(&self, name: String) -> Result<Option<TreeEntry>> {
+    let entry = self
+      .inner
+      .get(&name)
+      .convert(format!("Get [{name}] from tree failed"))?;
+    Ok(entry.map(|entry| TreeEntry {
+      inner: TreeEntryInner::Owned(entry.to_owned()),
+    }))
+  }
+
+  #[napi]
+  /// Get the number of entries of the tree.
+  pub fn len(&self) -> u32 {
+    self.inner.len() as u32
+  }
+
+  #[napi]
+  /// Return `true` if there is no entry.
+  pub fn is_empty(&self) -> bool {
+    self.inner.is_empty()
+  }
+
+  #[napi]
+  /// Remove every entry of the tree.
+  pub fn clear(&mut self) -> Result<()> {
+    self.inner.clear().convert("Clear tree failed")
+  }
+
+  #[napi]
+  /// Write the tree to the object database.
+  ///
+  /// Returns the OID of the written tree.
+  pub fn write(&self) -> Result<String> {
+    self
+      .inner
+      .write()
+      .map(|oid| oid.to_string())
+      .convert("Write tree failed")
+  }
+}
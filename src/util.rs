@@ -1,6 +1,19 @@
-use std::path::Path;
+use std::{
+  path::Path,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc, Arc, Mutex,
+  },
+  thread::{self, ThreadId},
+};
 
-use napi::{Env, JsString, Result};
+use napi::{
+  bindgen_prelude::{FromNapiValue, FunctionRef, JsValuesTupleIntoVec},
+  threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode},
+  Env, Error, JsFunction, JsObject, JsString, Ref, Result, Status,
+};
+
+use crate::error::coded_error;
 
 pub(crate) fn path_to_javascript_string(env: &Env, p: &Path) -> Result<JsString> {
   #[cfg(unix)]
@@ -17,3 +30,221 @@ pub(crate) fn path_to_javascript_string(env: &Env, p: &Path) -> Result<JsString>
     env.create_string_utf16(path_buf.as_slice())
   }
 }
+
+/// Marks a value which is only ever touched on the JavaScript main thread as
+/// transferable, so it can be carried through a libuv worker thread.
+pub(crate) struct AssertSend<T>(pub(crate) T);
+
+unsafe impl<T> Send for AssertSend<T> {}
+unsafe impl<T> Sync for AssertSend<T> {}
+
+type MainThreadJob = Box<dyn FnOnce(Env) + Send>;
+
+/// A JavaScript callback which can be invoked from the JavaScript main thread
+/// as well as from the libuv thread pool while an `AsyncTask` is computing.
+///
+/// Calls made off the main thread are marshaled back through a threadsafe
+/// function, and the calling thread blocks until the callback returned.
+pub(crate) struct JsCallback<Args: JsValuesTupleIntoVec, Return: FromNapiValue> {
+  env: Env,
+  main_thread: ThreadId,
+  callback: Arc<AssertSend<FunctionRef<Args, Return>>>,
+  tsfn: ThreadsafeFunction<MainThreadJob, ErrorStrategy::Fatal>,
+}
+
+// SAFETY: `env` and `callback` are only used when called on `main_thread`,
+// other threads go through the threadsafe function.
+unsafe impl<Args: JsValuesTupleIntoVec, Return: FromNapiValue> Send for JsCallback<Args, Return> {}
+unsafe impl<Args: JsValuesTupleIntoVec, Return: FromNapiValue> Sync for JsCallback<Args, Return> {}
+
+impl<Args, Return> JsCallback<Args, Return>
+where
+  Args: JsValuesTupleIntoVec + Send + 'static,
+  Return: FromNapiValue + 'static,
+{
+  pub(crate) fn new(env: Env, callback: FunctionRef<Args, Return>) -> Result<Self> {
+    let noop = env.create_function_from_closure("noop", |_| Ok(()))?;
+    let mut tsfn = noop.create_threadsafe_function(0, |ctx| {
+      let job: MainThreadJob = ctx.value;
+      job(ctx.env);
+      Ok(Vec::<()>::new())
+    })?;
+    // Pending callbacks must not keep the Node.js event loop alive on their own
+    tsfn.unref(&env)?;
+    Ok(Self {
+      env,
+      main_thread: thread::current().id(),
+      callback: Arc::new(AssertSend(callback)),
+      tsfn,
+    })
+  }
+
+  /// Call the JavaScript callback and convert its return value with `map` on
+  /// the JavaScript main thread.
+  pub(crate) fn call<T, F>(&self, args: Args, map: F) -> Result<T>
+  where
+    T: Send + 'static,
+    F: FnOnce(Return) -> Result<T> + Send + 'static,
+  {
+    if thread::current().id() == self.main_thread {
+      return self
+        .callback
+        .0
+        .borrow_back(&self.env)
+        .and_then(|cb| cb.call(args))
+        .and_then(map);
+    }
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let callback = self.callback.clone();
+    let status = self.tsfn.call(
+      Box::new(move |env: Env| {
+        let result = callback
+          .0
+          .borrow_back(&env)
+          .and_then(|cb| cb.call(args))
+          .and_then(map);
+        // The receiver only goes away if the worker thread is gone
+        let _ = sender.send(result);
+      }),
+      ThreadsafeFunctionCallMode::Blocking,
+    );
+    if status != Status::Ok {
+      return Err(Error::new(
+        status,
+        "Schedule JavaScript callback on the main thread failed".to_owned(),
+      ));
+    }
+    receiver.recv().map_err(|_| {
+      Error::new(
+        Status::Cancelled,
+        "JavaScript callback was dropped before being called".to_owned(),
+      )
+    })?
+  }
+}
+
+/// Keeps the first error thrown by a JavaScript callback during an
+/// operation, so that it can be surfaced instead of the generic error libgit2
+/// reports for the failed callback.
+#[derive(Clone, Default)]
+pub(crate) struct CallbackError(Arc<Mutex<Option<Error>>>);
+
+impl CallbackError {
+  pub(crate) fn set(&self, err: Error) {
+    if let Ok(mut slot) = self.0.lock() {
+      slot.get_or_insert(err);
+    }
+  }
+
+  /// Prefer the error thrown by a callback over the result of the operation.
+  pub(crate) fn check<T>(&self, result: Result<T>) -> Result<T> {
+    match self.0.lock().ok().and_then(|mut slot| slot.take()) {
+      Some(err) => Err(err),
+      None => result,
+    }
+  }
+}
+
+/// The `code` of the error rejecting an async task aborted through its
+/// `AbortSignal`.
+pub(crate) const ABORTED: &str = "Aborted";
+
+/// A flag raised once the `AbortSignal` of an async task is aborted.
+///
+/// Tasks check it between units of work while computing on the libuv thread
+/// pool, and reject with an `Aborted` error once it is cancelled.
+#[derive(Clone, Default)]
+pub(crate) struct CancellationToken {
+  cancelled: Arc<AtomicBool>,
+  listener: Arc<Mutex<Option<AbortListener>>>,
+}
+
+/// The `abort` listener added to the `AbortSignal` of a task, kept to be
+/// removed once the task settles so that the signal does not keep the task
+/// alive.
+struct AbortListener {
+  signal: Ref<()>,
+  listener: Ref<()>,
+}
+
+impl CancellationToken {
+  /// Create a token cancelled by `signal`.
+  ///
+  /// The task owning the token must `detach` it once it settles.
+  pub(crate) fn from_signal(env: &Env, signal: Option<JsObject>) -> Result<Self> {
+    let token = Self::default();
+    let Some(signal) = signal else {
+      return Ok(token);
+    };
+    if signal.get_named_property::<bool>("aborted")? {
+      token.cancel();
+      return Ok(token);
+    }
+    let cancel = token.clone();
+    let listener = env.create_function_from_closure("onabort", move |_| {
+      cancel.cancel();
+      Ok(())
+    })?;
+    let abort_listener = AbortListener {
+      signal: env.create_reference(&signal)?,
+      listener: env.create_reference(&listener)?,
+    };
+    let add_event_listener: JsFunction = signal.get_named_property("addEventListener")?;
+    add_event_listener.call(
+      Some(&signal),
+      &[
+        env.create_string("abort")?.into_unknown(),
+        listener.into_unknown(),
+      ],
+    )?;
+    *token.listener.lock().unwrap() = Some(abort_listener);
+    Ok(token)
+  }
+
+  /// Remove the `abort` listener from the `AbortSignal` of the token, to be
+  /// called once the task settles.
+  pub(crate) fn detach(&self, env: Env) -> Result<()> {
+    let Some(mut abort_listener) = self.listener.lock().unwrap().take() else {
+      return Ok(());
+    };
+    let signal: JsObject = env.get_reference_value(&abort_listener.signal)?;
+    let listener: JsFunction = env.get_reference_value(&abort_listener.listener)?;
+    let remove_event_listener: JsFunction = signal.get_named_property("removeEventListener")?;
+    let removed = remove_event_listener.call(
+      Some(&signal),
+      &[
+        env.create_string("abort")?.into_unknown(),
+        listener.into_unknown(),
+      ],
+    );
+    abort_listener.signal.unref(env)?;
+    abort_listener.listener.unref(env)?;
+    removed.map(|_| ())
+  }
+
+  pub(crate) fn cancel(&self) {
+    self.cancelled.store(true, Ordering::Relaxed);
+  }
+
+  pub(crate) fn is_cancelled(&self) -> bool {
+    self.cancelled.load(Ordering::Relaxed)
+  }
+
+  /// Stop the computation of a task once the token is cancelled.
+  pub(crate) fn check(&self) -> Result<()> {
+    if self.is_cancelled() {
+      return Err(Error::new(Status::Cancelled, ABORTED.to_owned()));
+    }
+    Ok(())
+  }
+
+  /// Reject a task with an `Aborted` error if the token was cancelled, to be
+  /// called from `Task::reject`. The token is detached from its signal.
+  pub(crate) fn reject<T>(&self, env: Env, err: Error) -> Result<T> {
+    self.detach(env)?;
+    if !self.is_cancelled() {
+      return Err(err);
+    }
+    Err(coded_error(&env, ABORTED, "The operation was aborted"))
+  }
+}
@@ -0,0 +1,50 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// The level of the exported API, bumped whenever it changes incompatibly.
+///
+/// `api-level.mjs` stamps it into the generated `index.js` and `index.d.ts`.
+const API_LEVEL: u32 = 1;
+
+#[napi(object)]
+/// The versions of the native binding, see `nativeVersion`.
+pub struct NativeVersion {
+  /// The version of the package the binding was built for.
+  #[napi(js_name = "crate")]
+  pub package: String,
+  /// The version of the bundled libgit2, e.g. `1.9.0`.
+  pub libgit2: String,
+  /// The level of the exported API, bumped whenever it changes incompatibly.
+  pub api_level: u32,
+}
+
+#[napi]
+/// Get the versions of the loaded native binding, e.g. to detect several
+/// copies of the package being installed.
+pub fn native_version() -> NativeVersion {
+  let (major, minor, patch) = git2::Version::get().libgit2_version();
+  NativeVersion {
+    package: env!("SIMPLE_GIT_PACKAGE_VERSION").to_owned(),
+    libgit2: format!("{major}.{minor}.{patch}"),
+    api_level: API_LEVEL,
+  }
+}
+
+#[napi]
+/// Check that the loaded native binding implements the API level
+/// `expected`, which the JavaScript or TypeScript side was built against.
+///
+/// `index.js` calls it when loaded, with the level stamped into it at build
+/// time. Throws an error naming both versions otherwise.
+pub fn assert_api_level(expected: u32) -> Result<()> {
+  if expected == API_LEVEL {
+    return Ok(());
+  }
+  Err(Error::new(
+    Status::GenericFailure,
+    format!(
+      "The native binding {} implements the API level {API_LEVEL} but the level {expected} is expected, several versions of @napi-rs/simple-git may be installed",
+      env!("SIMPLE_GIT_PACKAGE_VERSION"),
+    ),
+  ))
+}
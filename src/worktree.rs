@@ -0,0 +1,205 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{error::IntoNapiError, reference::Reference, repo::Repository};
+
+#[napi]
+/// An owned git worktree
+///
+/// This structure corresponds to a `git_worktree` in libgit2.
+pub struct Worktree {
+  pub(crate) inner: git2::Worktree,
+}
+
+#[napi]
+impl Worktree {
+  #[napi]
+  /// Retrieves the name of the worktree
+  ///
+  /// This is the name that can be passed to `findWorktree` to reopen the
+  /// worktree. This is also the name that would appear in the list returned
+  /// by `worktrees`.
+  pub fn name(&self) -> Option<String> {
+    self.inner.name().map(|s| s.to_owned())
+  }
+
+  #[napi]
+  /// Retrieves the path to the worktree
+  ///
+  /// This is the path to the top-level of the source and not the path to the
+  /// .git file within the worktree. This path can be passed to `new
+  /// Repository`.
+  pub fn path(&self) -> Option<String> {
+    self.inner.path().to_str().map(|s| s.to_owned())
+  }
+
+  #[napi]
+  /// Checks if the worktree is locked
+  pub fn is_locked(&self) -> Result<bool> {
+    Ok(!matches!(
+      self
+        .inner
+        .is_locked()
+        .convert("Check worktree lock failed")?,
+      git2::WorktreeLockStatus::Unlocked
+    ))
+  }
+
+  #[napi]
+  /// Returns the reason the worktree was locked with, if it is locked.
+  pub fn lock_reason(&self) -> Result<Option<String>> {
+    Ok(
+      match self
+        .inner
+        .is_locked()
+        .convert("Check worktree lock failed")?
+      {
+        git2::WorktreeLockStatus::Locked(reason) => reason,
+        git2::WorktreeLockStatus::Unlocked => None,
+      },
+    )
+  }
+
+  #[napi]
+  /// Checks if the worktree is prunable with the given options
+  pub fn is_prunable(&self, opts: Option<&mut WorktreePruneOptions>) -> Result<bool> {
+    self
+      .inner
+      .is_prunable(opts.map(|o| &mut o.inner))
+      .convert("Check worktree prunable failed")
+  }
+
+  #[napi]
+  /// Locks the worktree
+  pub fn lock(&self, reason: Option<String>) -> Result<()> {
+    self
+      .inner
+      .lock(reason.as_deref())
+      .convert("Lock worktree failed")
+  }
+
+  #[napi]
+  /// Unlocks the worktree
+  pub fn unlock(&self) -> Result<()> {
+    self.inner.unlock().convert("Unlock worktree failed")
+  }
+
+  #[napi]
+  /// Prunes the worktree
+  pub fn prune(&self, opts: Option<&mut WorktreePruneOptions>) -> Result<()> {
+    self
+      .inner
+      .prune(opts.map(|o| &mut o.inner))
+      .convert("Prune worktree failed")
+  }
+
+  #[napi]
+  /// Opens the repository checked out in this worktree.
+  pub fn open_repository(&self) -> Result<Repository> {
+    Ok(Repository {
+      inner: git2::Repository::open_from_worktree(&self.inner)
+        .convert("Open worktree repository failed")?,
+      ref_update_guard: None,
+      binary_detection: None,
+      branch_recovery: None,
+      handles: Default::default(),
+    })
+  }
+}
+
+#[napi]
+/// Options which can be used to configure how a worktree is initialized
+pub struct WorktreeAddOptions {
+  pub(crate) lock: bool,
+  pub(crate) checkout_existing: bool,
+  /// The name of the reference to use for the new worktree HEAD.
+  pub(crate) reference: Option<String>,
+}
+
+#[napi]
+impl WorktreeAddOptions {
+  #[napi(constructor)]
+  #[allow(clippy::new_without_default)]
+  /// Creates a default set of add options.
+  ///
+  /// By default this will not lock the worktree
+  pub fn new() -> WorktreeAddOptions {
+    WorktreeAddOptions {
+      lock: false,
+      checkout_existing: false,
+      reference: None,
+    }
+  }
+
+  #[napi]
+  /// If enabled, this will cause the newly added worktree to be locked
+  pub fn lock(&mut self, enabled: bool) -> &Self {
+    self.lock = enabled;
+    self
+  }
+
+  #[napi]
+  /// If enabled, this will checkout the existing branch matching the worktree
+  /// name.
+  pub fn checkout_existing(&mut self, enabled: bool) -> &Self {
+    self.checkout_existing = enabled;
+    self
+  }
+
+  #[napi]
+  /// reference to use for the new worktree HEAD
+  pub fn reference(&mut self, reference: &Reference) -> &Self {
+    self.reference = reference.inner.name().map(|s| s.to_owned());
+    self
+  }
+}
+
+#[napi]
+/// Options to configure how worktree pruning is performed
+pub struct WorktreePruneOptions {
+  pub(crate) inner: git2::WorktreePruneOptions,
+}
+
+#[napi]
+impl WorktreePruneOptions {
+  #[napi(constructor)]
+  #[allow(clippy::new_without_default)]
+  /// Creates a default set of pruning options
+  ///
+  /// By defaults this will prune only worktrees that are no longer valid
+  /// unlocked and not checked out
+  pub fn new() -> WorktreePruneOptions {
+    WorktreePruneOptions {
+      inner: git2::WorktreePruneOptions::new(),
+    }
+  }
+
+  #[napi]
+  /// Controls whether valid (still existing on the filesystem) worktrees
+  /// will be pruned
+  ///
+  /// Defaults to false
+  pub fn valid(&mut self, valid: bool) -> &Self {
+    self.inner.valid(valid);
+    self
+  }
+
+  #[napi]
+  /// Controls whether locked worktrees will be pruned
+  ///
+  /// Defaults to false
+  pub fn locked(&mut self, locked: bool) -> &Self {
+    self.inner.locked(locked);
+    self
+  }
+
+  #[napi]
+  /// Controls whether the actual working tree on the filesystem is recursively
+  /// removed
+  ///
+  /// Defaults to false
+  pub fn working_tree(&mut self, working_tree: bool) -> &Self {
+    self.inner.working_tree(working_tree);
+    self
+  }
+}